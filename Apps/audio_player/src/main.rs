@@ -1,8 +1,11 @@
+mod playback;
+
 use std::fs::{self, File};
 use std::io::{BufWriter};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use rayon::prelude::*;
 use std::sync::atomic::{AtomicU64, Ordering};
 use hound::WavReader;
@@ -12,6 +15,10 @@ use mp4ameta::Tag as Mp4Tag;
 use eframe::egui;
 use egui::Align;
 use egui_extras::TableBuilder;
+use playback::{format_position, spawn_audio_thread, Command, State, Status, TICK};
+
+/// How long an error stays in the corner, unless it's dismissed.
+const ERROR_DURATION: Duration = Duration::from_secs(5);
 
 struct Audio {
     path: String,
@@ -29,18 +36,111 @@ struct Audio {
 
 struct AudioPlayer {
     audio_list: Vec<Audio>,
+    /// The row clicked last, which "Play" plays.
+    selected: Option<usize>,
+    /// Kept up to date by the audio thread.
+    status: Arc<Mutex<Status>>,
+    commands: Sender<Command>,
+    /// Why the last file couldn't be played, and since when that's shown.
+    error: Option<(String, Instant)>,
 }
 
 impl AudioPlayer {
     fn new() -> Self {
+        let status = Arc::new(Mutex::new(Status::default()));
         Self {
             audio_list: Vec::new(),
+            selected: None,
+            commands: spawn_audio_thread(Arc::clone(&status)),
+            status,
+            error: None,
+        }
+    }
+
+    fn send(&self, command: Command) {
+        // The audio thread only ends when the app does.
+        self.commands.send(command).ok();
+    }
+
+    fn play(&mut self, index: usize) {
+        if let Some(audio) = self.audio_list.get(index) {
+            self.selected = Some(index);
+            self.send(Command::Play(PathBuf::from(&audio.path)));
+        }
+    }
+
+    /// Play, Pause and Stop, and what's playing.
+    fn controls_ui(&mut self, ui: &mut egui::Ui, status: &Status) {
+        ui.horizontal(|ui| {
+            match status.state {
+                State::Playing => {
+                    if ui.button("⏸ Pause").clicked() {
+                        self.send(Command::Pause);
+                    }
+                }
+                State::Paused => {
+                    if ui.button("▶ Play").clicked() {
+                        self.send(Command::Resume);
+                    }
+                }
+                State::Stopped => {
+                    if ui.add_enabled(self.selected.is_some(), egui::Button::new("▶ Play")).clicked() {
+                        if let Some(index) = self.selected {
+                            self.play(index);
+                        }
+                    }
+                }
+            }
+            if ui.add_enabled(status.state != State::Stopped, egui::Button::new("⏹ Stop")).clicked() {
+                self.send(Command::Stop);
+            }
+
+            if let Some(path) = &status.path {
+                let title = self.audio_list.iter()
+                    .find(|audio| Path::new(&audio.path) == path)
+                    .map_or_else(|| path.display().to_string(), |audio| audio.title.clone());
+                ui.label(title);
+                let duration = status.duration.map_or("?".to_string(), format_position);
+                ui.weak(format!("{} / {}", format_position(status.position), duration));
+            }
+        });
+    }
+
+    /// The last error in the bottom right corner, for [`ERROR_DURATION`].
+    fn error_ui(&mut self, ctx: &egui::Context) {
+        let Some((error, since)) = &self.error else {
+            return;
+        };
+        let remaining = ERROR_DURATION.saturating_sub(since.elapsed());
+        if remaining.is_zero() {
+            self.error = None;
+            return;
+        }
+        ctx.request_repaint_after(remaining);
+
+        let mut dismissed = false;
+        egui::Area::new(egui::Id::new("error"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -40.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_max_width(300.0);
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::RED, "✖");
+                        ui.label(error);
+                        dismissed = ui.small_button("🗙").on_hover_text("Dismiss").clicked();
+                    });
+                });
+            });
+        if dismissed {
+            self.error = None;
         }
     }
 
     fn update_audio_list(&mut self) {
         let home_dir = dirs::home_dir().expect("Unable to find home directory");
         let start_time = Instant::now();
+        self.selected = None;
 
         let count = Arc::new(AtomicU64::new(0));
         let csv_writer = Arc::new(Mutex::new(
@@ -213,6 +313,24 @@ fn find_audio_files(dir: &Path, csv_writer: Arc<Mutex<csv::Writer<BufWriter<File
 
 impl eframe::App for AudioPlayer {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let status = {
+            let mut status = self.status.lock().unwrap();
+            if let Some(error) = status.error.take() {
+                self.error = Some((error, Instant::now()));
+            }
+            status.clone()
+        };
+        if status.state == State::Playing {
+            ctx.request_repaint_after(TICK);
+        }
+
+        egui::TopBottomPanel::bottom("playback").show(ctx, |ui| {
+            self.controls_ui(ui, &status);
+        });
+        self.error_ui(ctx);
+
+        let mut clicked = None;
+        let mut double_clicked = None;
         egui::CentralPanel::default().show(ctx, |ui| {
             if ui.button("Load Audio Files").clicked() {
                 self.update_audio_list();
@@ -236,6 +354,7 @@ impl eframe::App for AudioPlayer {
                     .column(egui_extras::Column::initial(0.0).at_least(0.0))
                     .column(egui_extras::Column::initial(0.0).at_least(0.0))
                     .min_scrolled_height(0.0)
+                    .sense(egui::Sense::click())
                     .header(20.0, |mut header| {
                         header.col(|ui| { ui.strong("Type"); });
                         header.col(|ui| { ui.strong("Title"); });
@@ -249,10 +368,18 @@ impl eframe::App for AudioPlayer {
                         header.col(|ui| { ui.strong("Bits/Per Sample"); });
                     })
                     .body(|mut body| {
-                        for audio in &self.audio_list {
+                        for (index, audio) in self.audio_list.iter().enumerate() {
+                            let playing = status.path.as_deref() == Some(Path::new(&audio.path));
                             body.row(20.0, |mut row| {
+                                row.set_selected(playing || self.selected == Some(index));
                                 row.col(|ui| { ui.label(&audio.audio_type); });
-                                row.col(|ui| { ui.label(&audio.title); });
+                                row.col(|ui| {
+                                    if playing {
+                                        ui.strong(format!("🔊 {}", audio.title));
+                                    } else {
+                                        ui.label(&audio.title);
+                                    }
+                                });
                                 row.col(|ui| { ui.label(&audio.artist); });
                                 row.col(|ui| { ui.label(&audio.album); });
                                 row.col(|ui| { ui.label(&audio.year); });
@@ -261,11 +388,24 @@ impl eframe::App for AudioPlayer {
                                 row.col(|ui| { ui.label(&audio.sample_rate); });
                                 row.col(|ui| { ui.label(&audio.channels); });
                                 row.col(|ui| { ui.label(&audio.bits_per_sample); });
+
+                                let response = row.response();
+                                if response.double_clicked() {
+                                    double_clicked = Some(index);
+                                } else if response.clicked() {
+                                    clicked = Some(index);
+                                }
                             });
                         }
                     });
             }
         });
+
+        if let Some(index) = double_clicked {
+            self.play(index);
+        } else if clicked.is_some() {
+            self.selected = clicked;
+        }
     }
 }
 
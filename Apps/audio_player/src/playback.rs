@@ -0,0 +1,388 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+
+/// How often the audio thread updates the [`Status`] while nothing is asked of it.
+pub const TICK: Duration = Duration::from_millis(100);
+
+/// What the audio thread is asked to do.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Stops what's playing, and plays the file at the path from the start.
+    Play(PathBuf),
+    Pause,
+    Resume,
+    Stop,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum State {
+    #[default]
+    Stopped,
+    Playing,
+    Paused,
+}
+
+/// What's playing, kept up to date by the audio thread for the UI to show.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Status {
+    /// The file playing or paused.
+    pub path: Option<PathBuf>,
+    pub state: State,
+    pub position: Duration,
+    /// Unknown for some formats until they've been played through.
+    pub duration: Option<Duration>,
+    /// Why the last file couldn't be played, for the UI to take and show once.
+    pub error: Option<String>,
+}
+
+/// A file being played, like a [`Sink`] with its decoder in it.
+pub trait Track {
+    fn play(&self);
+    fn pause(&self);
+    fn stop(&self);
+    fn position(&self) -> Duration;
+    fn duration(&self) -> Option<Duration>;
+    /// Has it played to the end?
+    fn is_finished(&self) -> bool;
+}
+
+/// Plays one [`Track`] at a time, as [`Command`]s say, keeping the [`Status`] in step.
+pub struct Playback<T> {
+    track: Option<T>,
+    status: Arc<Mutex<Status>>,
+}
+
+impl<T: Track> Playback<T> {
+    pub fn new(status: Arc<Mutex<Status>>) -> Self {
+        Self {
+            track: None,
+            status,
+        }
+    }
+
+    /// Does what `command` says; `open` makes a [`Track`] of a file to play.
+    ///
+    /// Whatever was playing is stopped before the next file is opened, so two never play
+    /// over each other. A file that can't be played leaves nothing playing, and says why.
+    pub fn handle(&mut self, command: Command, open: impl FnOnce(&Path) -> Result<T, String>) {
+        match command {
+            Command::Play(path) => {
+                if let Some(track) = self.track.take() {
+                    track.stop();
+                }
+                // Opened without holding the status, which the UI reads every frame.
+                let status = match open(&path) {
+                    Ok(track) => {
+                        track.play();
+                        let duration = track.duration();
+                        self.track = Some(track);
+                        Status {
+                            path: Some(path),
+                            state: State::Playing,
+                            duration,
+                            ..Status::default()
+                        }
+                    }
+                    Err(err) => Status {
+                        error: Some(err),
+                        ..Status::default()
+                    },
+                };
+                set_keeping_error(&mut self.status.lock().unwrap(), status);
+            }
+            Command::Pause => {
+                if let Some(track) = &self.track {
+                    track.pause();
+                    self.status.lock().unwrap().state = State::Paused;
+                }
+            }
+            Command::Resume => {
+                if let Some(track) = &self.track {
+                    track.play();
+                    self.status.lock().unwrap().state = State::Playing;
+                }
+            }
+            Command::Stop => {
+                if let Some(track) = self.track.take() {
+                    track.stop();
+                }
+                set_keeping_error(&mut self.status.lock().unwrap(), Status::default());
+            }
+        }
+    }
+
+    /// Updates the position, and stops once the track has played to the end.
+    pub fn tick(&mut self) {
+        let Some(track) = &self.track else {
+            return;
+        };
+        let mut status = self.status.lock().unwrap();
+        if track.is_finished() {
+            self.track = None;
+            set_keeping_error(&mut status, Status::default());
+        } else {
+            status.position = track.position();
+        }
+    }
+}
+
+/// Replaces `status` with `new`, keeping an error the UI hasn't taken yet unless `new` has
+/// one of its own.
+fn set_keeping_error(status: &mut Status, new: Status) {
+    let error = new.error.or_else(|| status.error.take());
+    *status = Status { error, ..new };
+}
+
+/// Like "3:07", or "1:02:03" from an hour on.
+pub fn format_position(position: Duration) -> String {
+    let seconds = position.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// A file played by rodio.
+pub struct RodioTrack {
+    sink: Sink,
+    duration: Option<Duration>,
+}
+
+impl RodioTrack {
+    /// Decodes the file at `path` into a new sink of `output`, paused.
+    pub fn open(output: &OutputStreamHandle, path: &Path) -> Result<Self, String> {
+        let file =
+            File::open(path).map_err(|err| format!("Can't open {}: {err}", path.display()))?;
+        let decoder = Decoder::new(BufReader::new(file))
+            .map_err(|err| format!("Can't play {}: {err}", path.display()))?;
+        let duration = decoder.total_duration();
+        let sink =
+            Sink::try_new(output).map_err(|err| format!("Can't play {}: {err}", path.display()))?;
+        sink.pause();
+        sink.append(decoder);
+        Ok(Self { sink, duration })
+    }
+}
+
+impl Track for RodioTrack {
+    fn play(&self) {
+        self.sink.play();
+    }
+
+    fn pause(&self) {
+        self.sink.pause();
+    }
+
+    fn stop(&self) {
+        self.sink.stop();
+    }
+
+    fn position(&self) -> Duration {
+        self.sink.get_pos()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    fn is_finished(&self) -> bool {
+        self.sink.empty()
+    }
+}
+
+/// Starts the thread that plays audio, which does what's sent to it and keeps `status` up
+/// to date. It ends when the sender is dropped.
+///
+/// The output is opened on that thread, since rodio's can't be sent between threads.
+pub fn spawn_audio_thread(status: Arc<Mutex<Status>>) -> Sender<Command> {
+    let (sender, commands) = mpsc::channel();
+    thread::spawn(move || {
+        let output = OutputStream::try_default();
+        let mut playback = Playback::new(status);
+        loop {
+            match commands.recv_timeout(TICK) {
+                Ok(command) => playback.handle(command, |path| match &output {
+                    Ok((_stream, handle)) => RodioTrack::open(handle, path),
+                    Err(err) => Err(format!("No audio output: {err}")),
+                }),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            playback.tick();
+        }
+    });
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// What's been done to a [`MockTrack`], which the test keeps after the track is dropped.
+    #[derive(Debug, Default)]
+    struct Calls {
+        playing: Cell<bool>,
+        stopped: Cell<bool>,
+        position: Cell<Duration>,
+        finished: Cell<bool>,
+    }
+
+    struct MockTrack(Rc<Calls>);
+
+    impl Track for MockTrack {
+        fn play(&self) {
+            self.0.playing.set(true);
+        }
+
+        fn pause(&self) {
+            self.0.playing.set(false);
+        }
+
+        fn stop(&self) {
+            self.0.playing.set(false);
+            self.0.stopped.set(true);
+        }
+
+        fn position(&self) -> Duration {
+            self.0.position.get()
+        }
+
+        fn duration(&self) -> Option<Duration> {
+            Some(Duration::from_secs(180))
+        }
+
+        fn is_finished(&self) -> bool {
+            self.0.finished.get()
+        }
+    }
+
+    fn playback() -> (Playback<MockTrack>, Arc<Mutex<Status>>) {
+        let status = Arc::new(Mutex::new(Status::default()));
+        (Playback::new(Arc::clone(&status)), status)
+    }
+
+    /// Plays `path` with a new mock track, whose calls are returned.
+    fn play(playback: &mut Playback<MockTrack>, path: &str) -> Rc<Calls> {
+        let calls = Rc::new(Calls::default());
+        let track = MockTrack(Rc::clone(&calls));
+        playback.handle(Command::Play(PathBuf::from(path)), |_| Ok(track));
+        calls
+    }
+
+    #[test]
+    fn playing_pausing_and_resuming() {
+        let (mut playback, status) = playback();
+        let calls = play(&mut playback, "song.mp3");
+        assert!(calls.playing.get());
+        assert_eq!(
+            *status.lock().unwrap(),
+            Status {
+                path: Some(PathBuf::from("song.mp3")),
+                state: State::Playing,
+                duration: Some(Duration::from_secs(180)),
+                ..Status::default()
+            }
+        );
+
+        playback.handle(Command::Pause, |_| unreachable!());
+        assert!(!calls.playing.get());
+        assert_eq!(status.lock().unwrap().state, State::Paused);
+
+        playback.handle(Command::Resume, |_| unreachable!());
+        assert!(calls.playing.get());
+        assert_eq!(status.lock().unwrap().state, State::Playing);
+
+        playback.handle(Command::Stop, |_| unreachable!());
+        assert!(calls.stopped.get());
+        assert_eq!(*status.lock().unwrap(), Status::default());
+    }
+
+    #[test]
+    fn switching_tracks_stops_the_previous_one() {
+        let (mut playback, status) = playback();
+        let first = play(&mut playback, "first.wav");
+        let second = play(&mut playback, "second.wav");
+        assert!(first.stopped.get());
+        assert!(!first.playing.get());
+        assert!(second.playing.get());
+        assert_eq!(
+            status.lock().unwrap().path,
+            Some(PathBuf::from("second.wav"))
+        );
+    }
+
+    #[test]
+    fn files_that_cant_be_played_say_why() {
+        let (mut playback, status) = playback();
+        let first = play(&mut playback, "first.wav");
+        playback.handle(Command::Play(PathBuf::from("broken.mp3")), |_| {
+            Err("Can't play broken.mp3: unrecognized format".to_owned())
+        });
+        assert!(first.stopped.get());
+        let status = status.lock().unwrap().clone();
+        assert_eq!(status.state, State::Stopped);
+        assert_eq!(status.path, None);
+        assert_eq!(
+            status.error.as_deref(),
+            Some("Can't play broken.mp3: unrecognized format")
+        );
+
+        // Nothing to pause or resume.
+        playback.handle(Command::Pause, |_| unreachable!());
+        playback.handle(Command::Resume, |_| unreachable!());
+        playback.tick();
+    }
+
+    #[test]
+    fn errors_wait_for_the_ui() {
+        let (mut playback, status) = playback();
+        playback.handle(Command::Play(PathBuf::from("broken.mp3")), |_| {
+            Err("Can't play broken.mp3".to_owned())
+        });
+        playback.handle(Command::Stop, |_| unreachable!());
+        let calls = play(&mut playback, "song.mp3");
+        calls.finished.set(true);
+        playback.tick();
+        assert_eq!(
+            status.lock().unwrap().error.take().as_deref(),
+            Some("Can't play broken.mp3")
+        );
+
+        playback.handle(Command::Stop, |_| unreachable!());
+        assert_eq!(*status.lock().unwrap(), Status::default());
+    }
+
+    #[test]
+    fn ticks_follow_the_track_to_its_end() {
+        let (mut playback, status) = playback();
+        let calls = play(&mut playback, "song.mp3");
+        calls.position.set(Duration::from_secs(42));
+        playback.tick();
+        assert_eq!(status.lock().unwrap().position, Duration::from_secs(42));
+
+        calls.finished.set(true);
+        playback.tick();
+        assert_eq!(*status.lock().unwrap(), Status::default());
+        // It's over, so there's nothing left to pause.
+        playback.handle(Command::Pause, |_| unreachable!());
+        assert_eq!(status.lock().unwrap().state, State::Stopped);
+    }
+
+    #[test]
+    fn positions_are_minutes_and_seconds() {
+        assert_eq!(format_position(Duration::ZERO), "0:00");
+        assert_eq!(format_position(Duration::from_millis(187_900)), "3:07");
+        assert_eq!(format_position(Duration::from_secs(3723)), "1:02:03");
+    }
+}
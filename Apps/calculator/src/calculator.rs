@@ -4,6 +4,9 @@ pub struct Keypad {
     pub cursor_pos: usize,
     pub cursor_right: bool,
     pub cursor_left: bool,
+    /// `cursor_pos` was just moved past inserted text, so the text field's cursor should be
+    /// put there rather than read back.
+    pub cursor_moved: bool,
     pub done: bool,
 }
 
@@ -13,6 +16,7 @@ impl Keypad {
             cursor_pos: 0,
             cursor_right: false,
             cursor_left: false,
+            cursor_moved: false,
             done: false,
         }
     }
@@ -85,26 +89,64 @@ impl Keypad {
                 if ui.add_sized(size_1x1, Button::new("➗")).clicked() {
                     self.insert_text(input, "/");
                 }
+                if ui.add_sized(size_1x1, Button::new("π")).clicked() {
+                    self.insert_constant(input, "pi");
+                }
+                if ui.add_sized(size_1x1, Button::new("e")).clicked() {
+                    self.insert_constant(input, "e");
+                }
             });
             ui.add_space(5.0);
         });
     }
 
-    fn insert_text(&mut self, input: &mut String, text: &str) {
-        let pos = self.cursor_pos.min(input.len());
-        if pos <= input.len() {
-            input.insert_str(pos, text);
-            self.cursor_pos = (pos + text.len()).min(input.len());
-            self.cursor_right = true;
-            self.cursor_left = false;
+    /// Inserts the first line of `text` with anything in it, e.g. from the clipboard, at the
+    /// cursor. The rest is dropped: the input is a single line.
+    pub fn paste(&mut self, input: &mut String, text: &str) {
+        let line = text.lines().map(str::trim).find(|line| !line.is_empty());
+        self.insert_text(input, line.unwrap_or_default());
+    }
+
+    /// Inserts the constant `name`, like `pi`, at the cursor, with a `*` on either side where
+    /// it would otherwise run into a number or name: `2` then π is `2*pi`, not `2pi`, and
+    /// π then `e` is `pi*e`, not the unknown `pie`.
+    fn insert_constant(&mut self, input: &mut String, name: &str) {
+        let pos = self.cursor_pos.min(input.chars().count());
+        let before = pos
+            .checked_sub(1)
+            .and_then(|before| input.chars().nth(before));
+        let after = input.chars().nth(pos);
+
+        let mut text = String::new();
+        if before.is_some_and(|c| c.is_alphanumeric() || c == ')' || c == '.') {
+            text.push('*');
+        }
+        text.push_str(name);
+        if after.is_some_and(|c| c.is_alphanumeric() || c == '(' || c == '.') {
+            text.push('*');
         }
+        self.insert_text(input, &text);
+    }
+
+    /// Inserts `text` at `cursor_pos`, which counts chars, and moves the cursor past it.
+    fn insert_text(&mut self, input: &mut String, text: &str) {
+        let pos = self.cursor_pos.min(input.chars().count());
+        let byte = input
+            .char_indices()
+            .nth(pos)
+            .map_or(input.len(), |(byte, _)| byte);
+        input.insert_str(byte, text);
+        self.cursor_pos = pos + text.chars().count();
+        self.cursor_moved = true;
+        self.cursor_right = false;
+        self.cursor_left = false;
     }
 
+    /// Removes the char before `cursor_pos`, which counts chars.
     fn remove_char(&mut self, input: &mut String) {
-        if self.cursor_pos > 0 {
-            let pos = self.cursor_pos - 1;
-            if pos < input.len() {
-                input.remove(pos);
+        if let Some(pos) = self.cursor_pos.checked_sub(1) {
+            if let Some((byte, _)) = input.char_indices().nth(pos) {
+                input.remove(byte);
                 self.cursor_pos = pos;
                 self.cursor_right = false;
                 self.cursor_left = true;
@@ -112,3 +154,123 @@ impl Keypad {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypad_at(cursor_pos: usize) -> Keypad {
+        Keypad {
+            cursor_pos,
+            ..Keypad::new()
+        }
+    }
+
+    #[test]
+    fn pasted_text_goes_in_at_the_cursor() {
+        let mut input = "2*()".to_owned();
+        let mut keypad = keypad_at(3);
+        keypad.paste(&mut input, "sqrt(16) + 1");
+        assert_eq!(input, "2*(sqrt(16) + 1)");
+        assert_eq!(keypad.cursor_pos, 15);
+        assert!(keypad.cursor_moved);
+
+        // Then typing goes on after what was pasted.
+        keypad.insert_text(&mut input, "0");
+        assert_eq!(input, "2*(sqrt(16) + 10)");
+        assert_eq!(keypad.cursor_pos, 16);
+    }
+
+    #[test]
+    fn pasting_at_either_end() {
+        let mut input = "+ 1".to_owned();
+        let mut keypad = keypad_at(0);
+        keypad.paste(&mut input, "41 ");
+        assert_eq!(input, "41+ 1");
+        assert_eq!(keypad.cursor_pos, 2);
+
+        let mut keypad = keypad_at(99);
+        keypad.paste(&mut input, "00");
+        assert_eq!(input, "41+ 100");
+        assert_eq!(keypad.cursor_pos, 7);
+    }
+
+    #[test]
+    fn only_the_first_line_is_pasted() {
+        let mut input = String::new();
+        let mut keypad = keypad_at(0);
+        keypad.paste(&mut input, "\n  1 + 2\r\n3 + 4\n");
+        assert_eq!(input, "1 + 2");
+        assert_eq!(keypad.cursor_pos, 5);
+
+        keypad.paste(&mut input, "\n\n");
+        assert_eq!(input, "1 + 2");
+    }
+
+    #[test]
+    fn constants_are_multiplied_by_a_number_before_them() {
+        let mut input = "2".to_owned();
+        let mut keypad = keypad_at(1);
+        keypad.insert_constant(&mut input, "pi");
+        assert_eq!(input, "2*pi");
+        assert_eq!(keypad.cursor_pos, 4);
+        assert!(keypad.cursor_moved);
+
+        keypad.insert_constant(&mut input, "e");
+        assert_eq!(input, "2*pi*e");
+        assert_eq!(keypad.cursor_pos, 6);
+    }
+
+    #[test]
+    fn constants_after_operators_stand_alone() {
+        let mut input = String::new();
+        let mut keypad = keypad_at(0);
+        keypad.insert_constant(&mut input, "pi");
+        assert_eq!(input, "pi");
+        assert_eq!(keypad.cursor_pos, 2);
+
+        keypad.insert_text(&mut input, "/(");
+        keypad.insert_constant(&mut input, "e");
+        assert_eq!(input, "pi/(e");
+        assert_eq!(keypad.cursor_pos, 5);
+    }
+
+    #[test]
+    fn constants_before_a_number_are_multiplied_by_it() {
+        let mut input = "3 + 4".to_owned();
+        let mut keypad = keypad_at(4);
+        keypad.insert_constant(&mut input, "pi");
+        assert_eq!(input, "3 + pi*4");
+        assert_eq!(keypad.cursor_pos, 7);
+    }
+
+    #[test]
+    fn the_cursor_counts_chars() {
+        let mut input = "π*".to_owned();
+        let mut keypad = keypad_at(2);
+        keypad.paste(&mut input, "2²");
+        assert_eq!(input, "π*2²");
+        assert_eq!(keypad.cursor_pos, 4);
+
+        let mut keypad = keypad_at(1);
+        keypad.paste(&mut input, "·");
+        assert_eq!(input, "π·*2²");
+    }
+
+    #[test]
+    fn removing_takes_the_whole_char_before_the_cursor() {
+        let mut input = "π²+1".to_owned();
+        let mut keypad = keypad_at(2);
+        keypad.remove_char(&mut input);
+        assert_eq!(input, "π+1");
+        assert_eq!(keypad.cursor_pos, 1);
+
+        keypad.remove_char(&mut input);
+        assert_eq!(input, "+1");
+        assert_eq!(keypad.cursor_pos, 0);
+
+        // Nothing before the cursor.
+        keypad.remove_char(&mut input);
+        assert_eq!(input, "+1");
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use fend_core::Context;
+
+/// Where the parentheses of an input stop matching up, see [`check_parentheses`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unbalanced {
+    /// The `(` at this char index is never closed.
+    Unclosed(usize),
+    /// The `)` at this char index closes nothing.
+    Unopened(usize),
+}
+
+impl fmt::Display for Unbalanced {
+    // Positions count from 1 for people.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Unclosed(position) => write!(
+                f,
+                "unbalanced parentheses: '(' at {} isn't closed",
+                position + 1
+            ),
+            Self::Unopened(position) => write!(
+                f,
+                "unbalanced parentheses: ')' at {} has no '('",
+                position + 1
+            ),
+        }
+    }
+}
+
+/// Checks that every `(` in `input` is closed by a `)` and every `)` closes one, ignoring
+/// any in double-quoted strings. Cheap enough to run on every frame, unlike evaluating.
+///
+/// Of several unclosed parentheses, the innermost is reported.
+pub fn check_parentheses(input: &str) -> Result<(), Unbalanced> {
+    let mut open = Vec::new();
+    let mut in_string = false;
+    for (position, c) in input.chars().enumerate() {
+        match c {
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '(' => open.push(position),
+            ')' => {
+                if open.pop().is_none() {
+                    return Err(Unbalanced::Unopened(position));
+                }
+            }
+            _ => {}
+        }
+    }
+    match open.pop() {
+        Some(position) => Err(Unbalanced::Unclosed(position)),
+        None => Ok(()),
+    }
+}
+
+/// Evaluates `input` with each of `vars` bound to its value, as if they had been assigned
+/// first, like `x = 41`. The result is what the calculator shows, e.g. "42".
+///
+/// Names must be identifiers (letters, digits and `_`, not starting with a digit), and values
+/// finite, so a binding can't turn into some other expression. Identifiers that aren't bound
+/// here or built in, like units and `pi`, are an error.
+pub fn evaluate_expression_with_vars(
+    input: &str,
+    vars: &HashMap<String, f64>,
+) -> Result<String, String> {
+    let mut context = Context::new();
+    for (name, value) in vars {
+        if !is_identifier(name) {
+            return Err(format!("'{name}' isn't a valid variable name"));
+        }
+        if !value.is_finite() {
+            return Err(format!("{name} must be a finite number, not {value}"));
+        }
+        fend_core::evaluate(&format!("{name} = ({value})"), &mut context)?;
+    }
+    let result = fend_core::evaluate(input, &mut context)?;
+    Ok(result.get_main_result().to_owned())
+}
+
+/// Puts a `,` between each group of three digits of the integer part of `s`, e.g.
+/// "-1234567.89" becomes "-1,234,567.89", whatever the locale.
+///
+/// Only plain decimal numbers are grouped. Anything else fend may answer with, like
+/// "5 kg", "1/3" or "approx. 3.14", is returned as it is.
+pub fn group_thousands(s: &str) -> String {
+    let unsigned = s.strip_prefix('-').unwrap_or(s);
+    let (integer, fraction) = match unsigned.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (unsigned, None),
+    };
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits(integer) || fraction.is_some_and(|fraction| !is_digits(fraction)) {
+        return s.to_owned();
+    }
+
+    let mut grouped = String::with_capacity(s.len() + integer.len() / 3);
+    if s.starts_with('-') {
+        grouped.push('-');
+    }
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        grouped.push('.');
+        grouped.push_str(fraction);
+    }
+    grouped
+}
+
+/// What base integer results are shown in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Base {
+    #[default]
+    Dec,
+    Hex,
+    Bin,
+    Oct,
+}
+
+impl Base {
+    pub const ALL: [Self; 4] = [Self::Dec, Self::Hex, Self::Bin, Self::Oct];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Dec => "Dec",
+            Self::Hex => "Hex",
+            Self::Bin => "Bin",
+            Self::Oct => "Oct",
+        }
+    }
+}
+
+/// Why [`in_base`] left a result alone.
+pub const NOT_AN_INTEGER: &str = "Only whole numbers can be shown in another base";
+
+/// The result `s` in `base`, prefixed like Rust literals: "255" is "0xff" in hex, "0b11111111"
+/// in binary and "0o377" in octal. In decimal, it's [`group_thousands`], whatever base fend
+/// answered in.
+///
+/// Other bases only apply to integers, so anything else, like "1.5" or "5 kg", is `None`.
+pub fn in_base(s: &str, base: Base) -> Option<String> {
+    let (sign, digits) = match s.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", s),
+    };
+    // fend answers in the base of what it was given, like "0xff" for "0xf0 + 15".
+    let (radix, digits) = [("0x", 16), ("0b", 2), ("0o", 8)]
+        .into_iter()
+        .find_map(|(prefix, radix)| Some((radix, digits.strip_prefix(prefix)?)))
+        .unwrap_or((10, digits));
+    // Parsed as digits only, so "+1" and " 1" aren't integers either.
+    let integer = Some(digits)
+        .filter(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_digit(radix)))
+        .and_then(|digits| u128::from_str_radix(digits, radix).ok());
+    match (base, integer) {
+        (Base::Dec, _) => Some(group_thousands(s)),
+        (_, None) => None,
+        (Base::Hex, Some(n)) => Some(format!("{sign}{n:#x}")),
+        (Base::Bin, Some(n)) => Some(format!("{sign}{n:#b}")),
+        (Base::Oct, Some(n)) => Some(format!("{sign}{n:#o}")),
+    }
+}
+
+/// Whether `name` can be a variable: letters, digits and `_`, not starting with a digit.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(vars: &[(&str, f64)]) -> HashMap<String, f64> {
+        vars.iter()
+            .map(|&(name, value)| (name.to_owned(), value))
+            .collect()
+    }
+
+    #[test]
+    fn balanced_parentheses_pass() {
+        for input in [
+            "",
+            "1 + 2",
+            "2*(3+4)",
+            "((1)) * (2 - (3))",
+            "sin(pi / 2)",
+            "\"(\" + 1",
+        ] {
+            assert_eq!(check_parentheses(input), Ok(()), "{input}");
+        }
+    }
+
+    #[test]
+    fn unclosed_parentheses_are_found() {
+        assert_eq!(check_parentheses("2*(3+"), Err(Unbalanced::Unclosed(2)));
+        assert_eq!(check_parentheses("((1)"), Err(Unbalanced::Unclosed(0)));
+        assert_eq!(check_parentheses("(1 + (2"), Err(Unbalanced::Unclosed(5)));
+        // Counted in chars, not bytes.
+        assert_eq!(check_parentheses("π*(2"), Err(Unbalanced::Unclosed(2)));
+        assert_eq!(
+            check_parentheses("2*(3+").unwrap_err().to_string(),
+            "unbalanced parentheses: '(' at 3 isn't closed"
+        );
+    }
+
+    #[test]
+    fn extra_closing_parentheses_are_found() {
+        assert_eq!(check_parentheses("1)"), Err(Unbalanced::Unopened(1)));
+        assert_eq!(check_parentheses("(1))+(2"), Err(Unbalanced::Unopened(3)));
+        assert_eq!(check_parentheses(")("), Err(Unbalanced::Unopened(0)));
+        assert_eq!(
+            check_parentheses("1)").unwrap_err().to_string(),
+            "unbalanced parentheses: ')' at 2 has no '('"
+        );
+    }
+
+    #[test]
+    fn integers_are_grouped_by_thousands() {
+        assert_eq!(group_thousands("1234567"), "1,234,567");
+        assert_eq!(group_thousands("1000"), "1,000");
+        assert_eq!(group_thousands("999"), "999");
+        assert_eq!(group_thousands("0"), "0");
+        assert_eq!(group_thousands("123456"), "123,456");
+    }
+
+    #[test]
+    fn negative_numbers_are_grouped() {
+        assert_eq!(group_thousands("-1234567"), "-1,234,567");
+        assert_eq!(group_thousands("-123"), "-123");
+        assert_eq!(group_thousands("-1234.5"), "-1,234.5");
+    }
+
+    #[test]
+    fn only_the_integer_part_is_grouped() {
+        assert_eq!(group_thousands("1234567.89"), "1,234,567.89");
+        assert_eq!(group_thousands("0.123456"), "0.123456");
+        assert_eq!(group_thousands("12345.678901"), "12,345.678901");
+    }
+
+    #[test]
+    fn other_results_are_left_alone() {
+        for result in [
+            "",
+            "-",
+            "5 kg",
+            "1/3",
+            "approx. 3.1415926535",
+            "1.",
+            ".5",
+            "1e10",
+            "1,234",
+            "true",
+        ] {
+            assert_eq!(group_thousands(result), result);
+        }
+    }
+
+    #[test]
+    fn integers_are_shown_in_other_bases() {
+        assert_eq!(in_base("255", Base::Hex).as_deref(), Some("0xff"));
+        assert_eq!(in_base("255", Base::Bin).as_deref(), Some("0b11111111"));
+        assert_eq!(in_base("255", Base::Oct).as_deref(), Some("0o377"));
+        assert_eq!(in_base("0", Base::Hex).as_deref(), Some("0x0"));
+        assert_eq!(in_base("-255", Base::Hex).as_deref(), Some("-0xff"));
+        assert_eq!(in_base("0xff", Base::Oct).as_deref(), Some("0o377"));
+        assert_eq!(in_base("0b101", Base::Hex).as_deref(), Some("0x5"));
+        assert_eq!(
+            in_base("18446744073709551616", Base::Hex).as_deref(),
+            Some("0x10000000000000000")
+        );
+    }
+
+    #[test]
+    fn decimal_is_grouped() {
+        assert_eq!(in_base("1234567", Base::Dec).as_deref(), Some("1,234,567"));
+        assert_eq!(in_base("1.5", Base::Dec).as_deref(), Some("1.5"));
+        assert_eq!(in_base("5 kg", Base::Dec).as_deref(), Some("5 kg"));
+    }
+
+    #[test]
+    fn only_integers_change_base() {
+        for result in [
+            "1.5",
+            "-0.25",
+            "1/3",
+            "5 kg",
+            "approx. 3.14",
+            "",
+            "-",
+            "+1",
+            "true",
+            "0x",
+            "0xfg",
+            "0b12",
+        ] {
+            assert_eq!(in_base(result, Base::Hex), None, "{result}");
+        }
+        // Too big to convert.
+        assert_eq!(in_base(&"9".repeat(40), Base::Bin), None);
+    }
+
+    #[test]
+    fn results_of_fend_change_base() {
+        let result = evaluate_expression_with_vars("0xf0 + 15", &HashMap::new()).unwrap();
+        assert_eq!(in_base(&result, Base::Hex).as_deref(), Some("0xff"));
+        let result = evaluate_expression_with_vars("2^8 - 1", &HashMap::new()).unwrap();
+        assert_eq!(in_base(&result, Base::Bin).as_deref(), Some("0b11111111"));
+    }
+
+    #[test]
+    fn bound_variables_are_used() {
+        assert_eq!(
+            evaluate_expression_with_vars("x + 1", &vars(&[("x", 41.0)])),
+            Ok("42".to_owned())
+        );
+        assert_eq!(
+            evaluate_expression_with_vars(
+                "width * height",
+                &vars(&[("width", 2.5), ("height", -4.0)])
+            ),
+            Ok("-10".to_owned())
+        );
+        assert_eq!(
+            evaluate_expression_with_vars("1 + 1", &HashMap::new()),
+            Ok("2".to_owned())
+        );
+    }
+
+    #[test]
+    fn unbound_variables_are_an_error() {
+        let err = evaluate_expression_with_vars("x + 1", &HashMap::new()).unwrap_err();
+        assert!(err.contains("'x'"), "{err}");
+        let err = evaluate_expression_with_vars("x + y", &vars(&[("x", 1.0)])).unwrap_err();
+        assert!(err.contains("'y'"), "{err}");
+    }
+
+    #[test]
+    fn bindings_must_be_plain() {
+        for name in ["", "1x", "x y", "x = 2; y", "a+b"] {
+            assert!(
+                evaluate_expression_with_vars("1", &vars(&[(name, 1.0)])).is_err(),
+                "{name:?}"
+            );
+        }
+        assert!(evaluate_expression_with_vars("x", &vars(&[("x", f64::NAN)])).is_err());
+        assert!(evaluate_expression_with_vars("x", &vars(&[("x", f64::INFINITY)])).is_err());
+        assert_eq!(
+            evaluate_expression_with_vars("rate_2", &vars(&[("rate_2", 0.5)])),
+            Ok("0.5".to_owned())
+        );
+    }
+}
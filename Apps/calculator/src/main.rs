@@ -1,11 +1,15 @@
 mod calculator;
+mod evaluate;
 
 use eframe::egui;
 use calculator::Keypad;
+use std::collections::HashMap;
+use evaluate::{check_parentheses, evaluate_expression_with_vars, group_thousands, in_base, is_identifier, Base, NOT_AN_INTEGER};
 use eframe::egui::{Button, Color32, Key, Response, RichText, TextEdit, Ui};
 use egui_extras::TableBuilder;
-use fend_core;
-use fend_core::Context;
+
+/// [`eframe::Storage`] key of [`MyApp::dark_mode`].
+const DARK_MODE_KEY: &str = "dark_mode";
 
 fn main() -> eframe::Result {
     env_logger::init();
@@ -17,8 +21,12 @@ fn main() -> eframe::Result {
         "Custom Keypad App",
         options,
         Box::new(|cc| {
-            cc.egui_ctx.set_visuals(egui::Visuals::dark());
-            Ok(Box::<MyApp>::default())
+            let mut app = MyApp::default();
+            if let Some(dark_mode) = cc.storage.and_then(|storage| eframe::get_value(storage, DARK_MODE_KEY)) {
+                app.dark_mode = dark_mode;
+            }
+            cc.egui_ctx.set_visuals(egui::theme_visuals(app.dark_mode));
+            Ok(Box::new(app))
         }),
     )
 }
@@ -27,34 +35,62 @@ fn main() -> eframe::Result {
 struct Calculation {
     input: String,
     output: String,
+    /// What fend answered, shown in [`MyApp::base`] rather than as `output`.
+    result: Option<String>,
     done: bool,
+    /// The parentheses of `input` don't match up, as `output` says.
+    unbalanced: bool,
 }
 
 struct MyApp {
     input: String,
     keypad: Keypad,
     keypad_open: bool,
-    context: Context,
+    /// Named inputs, bound in every calculation.
+    vars: HashMap<String, f64>,
+    /// The name of the next variable, as it's being typed.
+    new_var: String,
     textedit: Option<Response>,
     calculations: Vec<Calculation>,
+    /// Evaluate what's pasted into the input right away.
+    evaluate_pasted: bool,
+    /// What base integer results are shown in.
+    base: Base,
+    /// Kept between sessions.
+    dark_mode: bool,
 }
 
 impl MyApp {
     fn done(&mut self) {
         if !self.input.is_empty() {
-            match fend_core::evaluate(&self.input, &mut self.context) {
-                Ok(evaluation) => {
+            if let Err(unbalanced) = check_parentheses(&self.input) {
+                self.calculations.push(Calculation {
+                    input: self.input.clone(),
+                    output: unbalanced.to_string(),
+                    result: None,
+                    done: true,
+                    unbalanced: true,
+                });
+                self.input.clear();
+                return;
+            }
+            match evaluate_expression_with_vars(&self.input, &self.vars) {
+                Ok(result) => {
                     self.calculations.push(Calculation {
                         input: self.input.clone(),
-                        output: evaluation.get_main_result().to_string(),
+                        output: group_thousands(&result),
+                        result: Some(result),
                         done: true,
+                        unbalanced: false,
                     });
                 }
                 Err(_) => {
                     self.calculations.push(Calculation {
                         input: self.input.clone(),
                         output: "Not Complete".to_string(),
+                        result: None,
                         done: true,
+                        unbalanced: false,
                     });
                 }
             }
@@ -62,6 +98,29 @@ impl MyApp {
             self.input.clear();
         }
     }
+
+    /// Inserts the first line of `text` at the cursor of the input, see [`Keypad::paste`].
+    fn paste(&mut self, ctx: &egui::Context, text: &str) {
+        let state = self
+            .textedit
+            .as_ref()
+            .and_then(|textedit| Some((textedit.id, TextEdit::load_state(ctx, textedit.id)?)));
+        self.keypad.cursor_pos = state
+            .as_ref()
+            .and_then(|(_, state)| state.cursor.char_range())
+            .map_or_else(|| self.input.chars().count(), |range| range.primary.index);
+        self.keypad.paste(&mut self.input, text);
+
+        if let Some((id, mut state)) = state {
+            let cursor = egui::text::CCursor::new(self.keypad.cursor_pos);
+            state.cursor.set_char_range(Some(egui::text::CCursorRange::one(cursor)));
+            state.store(ctx, id);
+            self.keypad.cursor_moved = false;
+        }
+        if self.evaluate_pasted {
+            self.done();
+        }
+    }
 }
 
 impl Default for MyApp {
@@ -72,13 +131,37 @@ impl Default for MyApp {
             keypad_open: false,
             calculations: vec![],
             textedit: None,
-            context: Context::new(),
+            vars: HashMap::new(),
+            new_var: String::new(),
+            evaluate_pasted: false,
+            base: Base::default(),
+            dark_mode: true,
         }
     }
 }
 
 impl eframe::App for MyApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, DARK_MODE_KEY, &self.dark_mode);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Taken before the input sees them, so that only the first line goes in, at the cursor.
+        let pasted: Vec<String> = ctx.input_mut(|i| {
+            let mut pasted = Vec::new();
+            i.events.retain(|event| match event {
+                egui::Event::Paste(text) => {
+                    pasted.push(text.clone());
+                    false
+                }
+                _ => true,
+            });
+            pasted
+        });
+        for text in pasted {
+            self.paste(ctx, &text);
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
 
             ui.group(|ui| {
@@ -89,15 +172,17 @@ impl eframe::App for MyApp {
                     .column(egui_extras::Column::initial(20.0))
                     .column(egui_extras::Column::initial(150.0))
                     .column(egui_extras::Column::initial(100.0))
-                    .max_scroll_height(50.0);
+                    .max_scroll_height(50.0)
+                    .stick_to_bottom(true);
 
-                let total_rows = self.calculations.len().max(9);
+                let total_rows = self.calculations.len().max(HISTORY_ROWS);
 
                 // Build the calculations table
                 calculations_table.body(|mut body| {
-                    for index in 0..total_rows {
+                    for row_index in 0..total_rows {
                         body.row(20.0, |mut row| {
-                            if let Some(calculation) = self.calculations.get(index) {
+                            if let Some(index) = history_index(row_index, self.calculations.len()) {
+                                let calculation = &self.calculations[index];
                                 row.col(|ui| {
                                     ui.label(RichText::new(format!("{}", index + 1)).color(Color32::LIGHT_BLUE));
                                 });
@@ -105,7 +190,14 @@ impl eframe::App for MyApp {
                                     ui.label(format!("{}", calculation.input.clone()));
                                 });
                                 row.col(|ui| {
-                                    ui.label(RichText::new(calculation.output.clone()).color(Color32::LIGHT_GREEN));
+                                    if calculation.unbalanced {
+                                        ui.label(RichText::new("⚠ unbalanced").color(Color32::LIGHT_RED))
+                                            .on_hover_text(&calculation.output);
+                                    } else if let Some(result) = &calculation.result {
+                                        result_label(ui, result, self.base, Color32::LIGHT_GREEN);
+                                    } else {
+                                        ui.label(RichText::new(calculation.output.clone()).color(Color32::LIGHT_GREEN));
+                                    }
                                 });
                             } else {
                                 // Empty rows
@@ -144,12 +236,31 @@ impl eframe::App for MyApp {
                                 self.textedit = Some(ui.add(TextEdit::singleline(&mut self.input).desired_width(150.0)));
                             });
                             row.col(|ui| {
-                                if let Ok(evaluation) = fend_core::evaluate(&self.input, &mut self.context) {
-                                    ui.label(RichText::new(evaluation.get_main_result()).color(Color32::GREEN));
+                                if let Err(unbalanced) = check_parentheses(&self.input) {
+                                    ui.label(RichText::new("⚠ unbalanced").color(Color32::LIGHT_RED))
+                                        .on_hover_text(unbalanced.to_string());
+                                } else if let Ok(result) = evaluate_expression_with_vars(&self.input, &self.vars) {
+                                    result_label(ui, &result, self.base, Color32::GREEN);
                                 }
                             });
                         });
                     });
+
+                    ui.horizontal(|ui| {
+                        if ui.button("📋 Paste").on_hover_text("Ctrl+V").clicked() {
+                            ctx.send_viewport_cmd(egui::ViewportCommand::RequestPaste);
+                        }
+                        ui.checkbox(&mut self.evaluate_pasted, "Evaluate on paste");
+                        egui::theme_toggle_button(ui, &mut self.dark_mode);
+                    });
+                    ui.horizontal(|ui| {
+                        for base in Base::ALL {
+                            ui.selectable_value(&mut self.base, base, base.label());
+                        }
+                    });
+                    ui.collapsing("Variables", |ui| {
+                        vars_ui(ui, &mut self.vars, &mut self.new_var);
+                    });
                 });
 
 
@@ -180,12 +291,13 @@ impl eframe::App for MyApp {
                     let textedit_id = self.textedit.clone().unwrap().id;
 
                     if let Some(mut state) = TextEdit::load_state(ctx, textedit_id) {
-                        if let Some(range) = state.cursor.char_range() {
+                        if let Some(range) = state.cursor.char_range().filter(|_| !self.keypad.cursor_moved) {
                             self.keypad.cursor_pos = range.primary.index;
                         }
+                        self.keypad.cursor_moved = false;
 
                         if self.keypad.cursor_right {
-                            self.keypad.cursor_pos = (self.keypad.cursor_pos + 1).min(self.input.len());
+                            self.keypad.cursor_pos = (self.keypad.cursor_pos + 1).min(self.input.chars().count());
                             self.keypad.cursor_right = false;
                         }
 
@@ -207,6 +319,53 @@ impl eframe::App for MyApp {
     }
 }
 
+/// The `result` of a calculation in `base`, or as it is with a note where it can't be.
+fn result_label(ui: &mut Ui, result: &str, base: Base, color: Color32) {
+    if let Some(text) = in_base(result, base) {
+        ui.label(RichText::new(text).color(color));
+    } else {
+        ui.label(RichText::new(group_thousands(result)).color(color));
+        ui.label(RichText::new("ⓘ").weak()).on_hover_text(NOT_AN_INTEGER);
+    }
+}
+
+/// Edits the named inputs: a value for each, and a name to add another.
+fn vars_ui(ui: &mut Ui, vars: &mut HashMap<String, f64>, new_var: &mut String) {
+    let mut names: Vec<String> = vars.keys().cloned().collect();
+    names.sort();
+    for name in names {
+        ui.horizontal(|ui| {
+            ui.label(&name);
+            if let Some(value) = vars.get_mut(&name) {
+                ui.add(egui::DragValue::new(value).speed(0.1));
+            }
+            if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                vars.remove(&name);
+            }
+        });
+    }
+    ui.horizontal(|ui| {
+        ui.add(TextEdit::singleline(new_var).hint_text("name").desired_width(80.0));
+        let name = new_var.trim();
+        let can_add = is_identifier(name) && !vars.contains_key(name);
+        if ui.add_enabled(can_add, Button::new("➕ Add")).clicked() {
+            vars.insert(name.to_owned(), 0.0);
+            new_var.clear();
+        }
+    });
+}
+
+/// The history shows at least this many rows, empty ones above the calculations.
+const HISTORY_ROWS: usize = 9;
+
+/// The calculation in row `row` of the history, or `None` for an empty row.
+///
+/// The empty rows come first, so the newest calculation is always the bottom row, which the
+/// history sticks to.
+fn history_index(row: usize, calculations: usize) -> Option<usize> {
+    (row + calculations).checked_sub(calculations.max(HISTORY_ROWS))
+}
+
 fn toggle_button(text: &str, toggle: &mut bool, ui: &mut Ui) {
     let color = if *toggle {
         ui.style().visuals.selection.bg_fill
@@ -218,3 +377,41 @@ fn toggle_button(text: &str, toggle: &mut bool, ui: &mut Ui) {
         *toggle = !*toggle;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_rows_come_before_the_calculations() {
+        let rows = |calculations: usize| (0..calculations.max(HISTORY_ROWS)).map(|row| history_index(row, calculations)).collect::<Vec<_>>();
+        assert_eq!(rows(0), [None; HISTORY_ROWS]);
+        assert_eq!(rows(2)[HISTORY_ROWS - 3..], [None, Some(0), Some(1)]);
+        assert_eq!(rows(12), (0..12).map(Some).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn evaluating_adds_a_calculation() {
+        let mut app = MyApp::default();
+        app.done();
+        assert!(app.calculations.is_empty());
+
+        app.input = "1 + 1".to_owned();
+        app.done();
+        assert_eq!(app.calculations.len(), 1);
+
+        // Errors are calculations too.
+        app.input = "(1 + 1".to_owned();
+        app.done();
+        assert!(app.calculations[1].unbalanced);
+    }
+
+    #[test]
+    fn variables_are_bound_in_calculations() {
+        let mut app = MyApp::default();
+        app.vars.insert("x".to_owned(), 41.0);
+        app.input = "x + 1".to_owned();
+        app.done();
+        assert_eq!(app.calculations[0].result.as_deref(), Some("42"));
+    }
+}
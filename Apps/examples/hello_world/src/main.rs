@@ -24,6 +24,7 @@ fn main() -> eframe::Result {
 struct MyApp {
     name: String,
     age: u32,
+    show_event_trace: bool,
 }
 
 impl Default for MyApp {
@@ -31,12 +32,18 @@ impl Default for MyApp {
         Self {
             name: "Arthur".to_owned(),
             age: 42,
+            show_event_trace: false,
         }
     }
 }
 
 impl eframe::App for MyApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        egui::Window::new("Event trace")
+            .open(&mut self.show_event_trace)
+            .default_height(200.0)
+            .show(ctx, |ui| event_trace_ui(ui, frame));
+
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("My egui Application");
             ui.horizontal(|ui| {
@@ -49,6 +56,7 @@ impl eframe::App for MyApp {
                 self.age += 1;
             }
             ui.label(format!("Hello '{}', age {}", self.name, self.age));
+            ui.toggle_value(&mut self.show_event_trace, "Event trace");
 
             ui.image(egui::include_image!(
                 "../../../crates/egui/assets/ferris.png"
@@ -56,3 +64,38 @@ impl eframe::App for MyApp {
         });
     }
 }
+
+/// Shows the events recently handled by the native event loop.
+fn event_trace_ui(ui: &mut egui::Ui, frame: &mut eframe::Frame) {
+    let mut tracing = frame.event_tracing();
+    ui.horizontal(|ui| {
+        if ui.checkbox(&mut tracing, "Record events").changed() {
+            frame.set_event_tracing(tracing);
+        }
+        if ui.button("Save to event_trace.txt").clicked() {
+            if let Err(err) = frame.write_event_trace(std::path::Path::new("event_trace.txt")) {
+                log::error!("Failed to write event trace: {err}");
+            }
+        }
+    });
+    ui.separator();
+
+    let trace = frame.event_trace();
+    let Some(start) = trace.first().map(|entry| entry.time) else {
+        ui.weak("No events recorded");
+        return;
+    };
+
+    egui::ScrollArea::vertical()
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            for entry in &trace {
+                ui.monospace(format!(
+                    "{:>9.3} ms  {:?}  {:?}",
+                    (entry.time - start).as_secs_f64() * 1000.0,
+                    entry.kind,
+                    entry.result,
+                ));
+            }
+        });
+}
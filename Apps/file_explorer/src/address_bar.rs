@@ -0,0 +1,292 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+use egui::{
+    popup_below_widget, Color32, Id, Key, Modifiers, PopupCloseBehavior, RichText, TextEdit, Ui,
+};
+
+use crate::archive::parse_archive_path;
+
+/// At most this many completions are offered.
+const MAX_COMPLETIONS: usize = 10;
+
+/// The breadcrumb row, when it has been switched to a text field (click it or press Ctrl+L).
+#[derive(Default)]
+pub struct AddressBar {
+    pub editing: bool,
+    pub text: String,
+    pub error: Option<String>,
+    completions: Vec<String>,
+    completions_for: String,
+}
+
+impl AddressBar {
+    /// Switches to editing, starting from `current_path`.
+    pub fn start_editing(&mut self, current_path: &str) {
+        self.editing = true;
+        self.text = current_path.to_owned();
+        self.error = None;
+        self.completions_for.clear();
+    }
+
+    pub fn stop_editing(&mut self) {
+        self.editing = false;
+        self.error = None;
+        self.completions.clear();
+    }
+
+    /// Shows the text field. Returns the folder to navigate to once Enter is pressed on a valid path.
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<PathBuf> {
+        let response = ui.add(
+            TextEdit::singleline(&mut self.text)
+                .desired_width(300.0)
+                .hint_text("Type a path"),
+        );
+        if !response.has_focus() && !response.lost_focus() {
+            response.request_focus();
+        }
+
+        if self.completions_for != self.text {
+            self.completions = complete(&self.text);
+            self.completions_for = self.text.clone();
+        }
+
+        let popup_id = Id::new("address_bar_completions");
+        if self.completions.is_empty() {
+            ui.memory_mut(|mem| {
+                if mem.is_popup_open(popup_id) {
+                    mem.close_popup();
+                }
+            });
+        } else {
+            ui.memory_mut(|mem| mem.open_popup(popup_id));
+        }
+
+        let mut completion = None;
+        popup_below_widget(
+            ui,
+            popup_id,
+            &response,
+            PopupCloseBehavior::IgnoreClicks,
+            |ui| {
+                ui.set_min_width(200.0);
+                for candidate in &self.completions {
+                    if ui.selectable_label(false, candidate).clicked() {
+                        completion = Some(candidate.clone());
+                    }
+                }
+            },
+        );
+        if ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Tab)) {
+            completion = self.completions.first().cloned();
+        }
+        let completed = completion.is_some();
+        if let Some(completion) = completion {
+            self.text = completion;
+            response.request_focus();
+        }
+
+        if let Some(error) = &self.error {
+            ui.label(RichText::new(error).color(Color32::RED));
+        }
+
+        // Clicking somewhere else (say, on the file list) gives up on the path, rather than
+        // taking the focus back every frame.
+        if ui.input(|i| i.key_pressed(Key::Escape))
+            || (!completed && response.clicked_elsewhere())
+        {
+            self.stop_editing();
+            return None;
+        }
+
+        if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+            match resolve_directory(&self.text) {
+                Ok(path) => {
+                    self.stop_editing();
+                    return Some(path);
+                }
+                Err(err) => {
+                    self.error = Some(err);
+                    response.request_focus();
+                }
+            }
+        }
+
+        None
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).map(PathBuf::from)
+}
+
+/// Turns what the user typed into a folder path (or one in an archive), checking that it exists.
+pub fn resolve_directory(input: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(normalize_input(
+        input,
+        home_dir().as_deref(),
+        cfg!(windows),
+    )?);
+    // Only the archive itself can be checked without reading it.
+    if let Some(archive) = parse_archive_path(&path.to_string_lossy()) {
+        return if archive.archive.is_file() {
+            Ok(archive.to_path())
+        } else {
+            Err(format!("No such archive: {}", archive.archive.display()))
+        };
+    }
+    match fs::metadata(&path) {
+        Ok(metadata) if metadata.is_dir() => Ok(path),
+        Ok(_) => Err(format!("Not a folder: {}", path.display())),
+        Err(err) => Err(format!("{}: {err}", path.display())),
+    }
+}
+
+/// Expands a leading `~` to `home` and drops trailing separators (except on a root),
+/// so that the result can be compared with and used as `current_path`.
+///
+/// On Windows both `/` and `\` are separators, and a bare drive letter (`C:`) means its root.
+pub fn normalize_input(input: &str, home: Option<&Path>, windows: bool) -> Result<String, String> {
+    let is_separator = |c: char| c == '/' || (windows && c == '\\');
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Type a path".to_owned());
+    }
+
+    let expanded = if let Some(rest) = input.strip_prefix('~') {
+        if rest.is_empty() || rest.starts_with(is_separator) {
+            let home = home.ok_or("Can't expand ~: no home folder")?;
+            format!("{}{rest}", home.to_string_lossy())
+        } else {
+            input.to_owned() // `~user` is not supported
+        }
+    } else {
+        input.to_owned()
+    };
+
+    if windows && is_drive(&expanded) {
+        return Ok(format!("{expanded}\\"));
+    }
+
+    let trimmed = expanded.trim_end_matches(is_separator);
+    if trimmed.is_empty() {
+        // Only separators: the root.
+        return Ok(expanded[..1].to_owned());
+    }
+    if windows && is_drive(trimmed) {
+        return Ok(format!("{trimmed}\\"));
+    }
+    Ok(trimmed.to_owned())
+}
+
+/// `C:`, `d:`…
+fn is_drive(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() == 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+/// Splits `input` into the folder to look in and the start of the name being typed.
+///
+/// `"/var/lo"` gives `("/var/", "lo")`, and `"/var/"` gives `("/var/", "")`.
+pub fn split_last_segment(input: &str, windows: bool) -> (&str, &str) {
+    let is_separator = |c: char| c == '/' || (windows && c == '\\');
+    match input.rfind(is_separator) {
+        Some(index) => input.split_at(index + 1),
+        None => ("", input),
+    }
+}
+
+/// Folders next to the last segment of `input` whose names start with it.
+fn complete(input: &str) -> Vec<String> {
+    let windows = cfg!(windows);
+    let (parent, prefix) = split_last_segment(input, windows);
+    if parent.is_empty() {
+        return Vec::new();
+    }
+    let Ok(folder) = normalize_input(parent, home_dir().as_deref(), windows) else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(folder) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map_or(false, |t| t.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(prefix) && name != prefix)
+        .collect();
+    names.sort();
+    names.truncate(MAX_COMPLETIONS);
+
+    names
+        .into_iter()
+        .map(|name| format!("{parent}{name}{}", std::path::MAIN_SEPARATOR))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unix(input: &str) -> Result<String, String> {
+        normalize_input(input, Some(Path::new("/home/me")), false)
+    }
+
+    fn windows(input: &str) -> Result<String, String> {
+        normalize_input(input, Some(Path::new("C:\\Users\\me")), true)
+    }
+
+    #[test]
+    fn normalizes_unix_paths() {
+        assert_eq!(unix("/var/log/nginx/"), Ok("/var/log/nginx".to_owned()));
+        assert_eq!(unix("  /var/log//  "), Ok("/var/log".to_owned()));
+        assert_eq!(unix("/"), Ok("/".to_owned()));
+        assert_eq!(unix("///"), Ok("/".to_owned()));
+        assert!(unix("").is_err());
+    }
+
+    #[test]
+    fn expands_tilde() {
+        assert_eq!(unix("~"), Ok("/home/me".to_owned()));
+        assert_eq!(unix("~/Downloads/"), Ok("/home/me/Downloads".to_owned()));
+        assert_eq!(unix("~other"), Ok("~other".to_owned()));
+        assert_eq!(
+            windows("~\\Downloads"),
+            Ok("C:\\Users\\me\\Downloads".to_owned())
+        );
+        assert!(normalize_input("~/x", None, false).is_err());
+    }
+
+    #[test]
+    fn normalizes_windows_drives() {
+        assert_eq!(windows("C:"), Ok("C:\\".to_owned()));
+        assert_eq!(windows("c:\\"), Ok("c:\\".to_owned()));
+        assert_eq!(windows("D:/"), Ok("D:\\".to_owned()));
+        assert_eq!(windows("C:\\Users\\"), Ok("C:\\Users".to_owned()));
+        assert_eq!(windows("C:/Users/me/"), Ok("C:/Users/me".to_owned()));
+    }
+
+    #[test]
+    fn backslash_is_not_a_separator_on_unix() {
+        assert_eq!(unix("/tmp/odd\\"), Ok("/tmp/odd\\".to_owned()));
+    }
+
+    #[test]
+    fn splits_last_segment() {
+        assert_eq!(split_last_segment("/var/lo", false), ("/var/", "lo"));
+        assert_eq!(split_last_segment("/var/", false), ("/var/", ""));
+        assert_eq!(split_last_segment("C:\\Us", true), ("C:\\", "Us"));
+        assert_eq!(split_last_segment("relative", false), ("", "relative"));
+    }
+
+    #[test]
+    fn resolve_rejects_missing_and_non_folders() {
+        assert!(resolve_directory("/definitely/not/a/real/folder").is_err());
+        let file = std::env::current_exe().unwrap();
+        assert!(resolve_directory(&file.to_string_lossy()).is_err());
+        let dir = std::env::temp_dir();
+        assert!(resolve_directory(&dir.to_string_lossy()).is_ok());
+    }
+}
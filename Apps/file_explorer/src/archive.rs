@@ -0,0 +1,696 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use zip::ZipArchive;
+
+use crate::file_type::{sniff, sniff_file, FileType, SNIFF_LEN};
+use crate::time_format::days_from_civil;
+
+/// Entries up to this size can be previewed, if they're text.
+pub const PREVIEW_LEN: u64 = 64 * 1024;
+
+/// Said wherever something would change an archive, which can only be browsed.
+pub const READ_ONLY: &str = "Archives are read-only: extract entries to change them";
+
+/// A location inside a zip archive, shown as a folder.
+///
+/// As a path it's the archive's path, a `!`, and the path inside it with `/` between the
+/// parts: `/path/to/a.zip!/inner/dir`, or `/path/to/a.zip!` for the top of the archive. So
+/// [`Path::parent`] goes up inside the archive and then out of it, which is all the
+/// breadcrumbs need.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchivePath {
+    /// The zip file itself.
+    pub archive: PathBuf,
+    /// Without a leading or trailing `/`; empty at the top of the archive.
+    pub inner: String,
+}
+
+impl ArchivePath {
+    /// The top of `archive`.
+    pub fn root(archive: &Path) -> Self {
+        Self {
+            archive: archive.to_owned(),
+            inner: String::new(),
+        }
+    }
+
+    /// The entry `name` in this folder of the archive.
+    pub fn join(&self, name: &str) -> Self {
+        let inner = if self.inner.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}/{name}", self.inner)
+        };
+        Self {
+            archive: self.archive.clone(),
+            inner,
+        }
+    }
+
+    /// The name of the entry, or `None` at the top of the archive.
+    pub fn name(&self) -> Option<&str> {
+        self.inner
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+    }
+
+    pub fn to_path(&self) -> PathBuf {
+        PathBuf::from(self.to_string())
+    }
+}
+
+impl fmt::Display for ArchivePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}!", self.archive.display())?;
+        if !self.inner.is_empty() {
+            write!(f, "/{}", self.inner)?;
+        }
+        Ok(())
+    }
+}
+
+/// The location inside an archive that `path` stands for, if it's one; see [`ArchivePath`].
+pub fn parse_archive_path(path: &str) -> Option<ArchivePath> {
+    parse(path, cfg!(windows))
+}
+
+/// [`parse_archive_path`], where `\` is a separator too on Windows.
+///
+/// The archive is the first `.zip` followed by a `!` and then a separator or nothing,
+/// so other names with a `!` in them are left alone. Inside the archive, empty parts and
+/// `.` are dropped, and `..` goes up (but not out of the archive).
+fn parse(path: &str, windows: bool) -> Option<ArchivePath> {
+    let is_separator = |c: char| c == '/' || (windows && c == '\\');
+    let (bang, rest) = path.match_indices('!').find_map(|(bang, _)| {
+        let rest = &path[bang + 1..];
+        let is_zip = path[..bang].to_lowercase().ends_with(".zip");
+        (is_zip && (rest.is_empty() || rest.starts_with(is_separator))).then_some((bang, rest))
+    })?;
+
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split(is_separator) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(ArchivePath {
+        archive: PathBuf::from(&path[..bang]),
+        inner: parts.join("/"),
+    })
+}
+
+/// Is the location at `path` still there to go back to: a folder, or somewhere in an
+/// archive that is?
+pub fn location_exists(path: &Path) -> bool {
+    match parse_archive_path(&path.to_string_lossy()) {
+        Some(archive) => archive.archive.is_file(),
+        None => path.is_dir(),
+    }
+}
+
+/// What a breadcrumb of a path ending in `name` says: "📦 a.zip" for the top of an archive.
+pub fn crumb_label(name: &str) -> String {
+    match name.strip_suffix('!') {
+        Some(archive) if archive.to_lowercase().ends_with(".zip") => format!("📦 {archive}"),
+        _ => name.to_owned(),
+    }
+}
+
+/// Is the file at `path` a zip archive, going by both its extension and its contents?
+pub fn is_zip(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+        && sniff_file(path).is_ok_and(|file_type| file_type == Some(FileType::Zip))
+}
+
+/// A file or folder in a folder of an archive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub is_dir: bool,
+    /// Uncompressed; for a folder, that of everything in it.
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// The entries of the folder at `path` in its archive, read from the archive's central
+/// directory without unpacking anything.
+pub fn list_archive(path: &ArchivePath) -> io::Result<Vec<ArchiveEntry>> {
+    let mut zip = open(&path.archive)?;
+    let mut entries = Vec::with_capacity(zip.len());
+    for index in 0..zip.len() {
+        let entry = zip.by_index_raw(index)?;
+        let time = entry.last_modified();
+        entries.push(ArchiveEntry {
+            name: entry.name().to_owned(),
+            is_dir: entry.is_dir(),
+            size: entry.size(),
+            modified: zip_time(
+                time.year(),
+                time.month(),
+                time.day(),
+                time.hour(),
+                time.minute(),
+                time.second(),
+            ),
+        });
+    }
+    children(entries, &path.inner)
+}
+
+fn open(archive: &Path) -> io::Result<ZipArchive<BufReader<fs::File>>> {
+    let file = fs::File::open(archive)?;
+    Ok(ZipArchive::new(BufReader::new(file))?)
+}
+
+/// The entries right in the folder `dir` of an archive, given all of its `entries` by their
+/// full names ("docs/notes.txt", with a trailing `/` for folders).
+///
+/// Archives needn't list folders on their own, so the ones only known from the files in
+/// them are made up. Folders come first, each sorted by name.
+fn children(entries: Vec<ArchiveEntry>, dir: &str) -> io::Result<Vec<ArchiveEntry>> {
+    let mut folders = BTreeMap::new();
+    let mut files = BTreeMap::new();
+    let mut found = dir.is_empty();
+    for entry in entries {
+        let name = normalize_name(&entry.name);
+        let Some(rest) = relative_to(&name, dir) else {
+            continue;
+        };
+        if rest.is_empty() {
+            if !entry.is_dir {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{dir} is a file, not a folder"),
+                ));
+            }
+            found = true;
+            continue;
+        }
+        found = true;
+        match rest.split_once('/') {
+            Some((folder, _)) => add_to_folder(&mut folders, folder, &entry, false),
+            None if entry.is_dir => add_to_folder(&mut folders, rest, &entry, true),
+            None => {
+                files.insert(
+                    rest.to_owned(),
+                    ArchiveEntry {
+                        name: rest.to_owned(),
+                        ..entry
+                    },
+                );
+            }
+        }
+    }
+    if !found {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("There's no {dir} in the archive"),
+        ));
+    }
+    Ok(folders.into_values().chain(files.into_values()).collect())
+}
+
+/// Counts `entry` into the folder `name`: as the folder itself if `is_folder`, or else as
+/// something inside it.
+fn add_to_folder(
+    folders: &mut BTreeMap<String, ArchiveEntry>,
+    name: &str,
+    entry: &ArchiveEntry,
+    is_folder: bool,
+) {
+    let folder = folders
+        .entry(name.to_owned())
+        .or_insert_with(|| ArchiveEntry {
+            name: name.to_owned(),
+            is_dir: true,
+            size: 0,
+            modified: None,
+        });
+    if is_folder {
+        folder.modified = entry.modified;
+    } else {
+        folder.size += entry.size;
+    }
+}
+
+/// The name of an entry without a leading `/` or `./`, or a trailing `/`, and with `/`
+/// between its parts even if the archive was made on Windows.
+fn normalize_name(name: &str) -> String {
+    let name = name.replace('\\', "/");
+    let name = name.trim_start_matches("./").trim_matches('/');
+    name.to_owned()
+}
+
+/// The rest of `name` inside the folder `dir` (empty if it is `dir`), if it's in there.
+fn relative_to<'a>(name: &'a str, dir: &str) -> Option<&'a str> {
+    if dir.is_empty() {
+        return Some(name);
+    }
+    match name.strip_prefix(dir)? {
+        "" => Some(""),
+        rest => rest.strip_prefix('/'),
+    }
+}
+
+/// When a zip entry was last modified. Zips keep the local time of whoever made them
+/// without saying which zone that was, so it's taken as UTC.
+fn zip_time(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Option<SystemTime> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+    let days = days_from_civil(i64::from(year), u32::from(month), u32::from(day));
+    let secs = days * 24 * 60 * 60
+        + i64::from(hour) * 60 * 60
+        + i64::from(minute) * 60
+        + i64::from(second.min(59));
+    Some(UNIX_EPOCH + Duration::from_secs(u64::try_from(secs).ok()?))
+}
+
+/// Unpacks the entry at `path`, with everything in it if it's a folder, into `dest_dir`.
+/// Returns where it went.
+///
+/// Nothing is overwritten, and entries whose names would lead out of `dest_dir` are skipped.
+pub fn extract(path: &ArchivePath, dest_dir: &Path) -> io::Result<PathBuf> {
+    let name = path.name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Pick an entry of the archive to extract",
+        )
+    })?;
+    let target = dest_dir.join(name);
+    if target.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", target.display()),
+        ));
+    }
+
+    let mut zip = open(&path.archive)?;
+    let mut found = false;
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index)?;
+        let name = normalize_name(entry.name());
+        let Some(rest) = relative_to(&name, &path.inner) else {
+            continue;
+        };
+        if entry.enclosed_name().is_none() || rest.split('/').any(|part| part == "..") {
+            continue;
+        }
+        found = true;
+        let out = rest
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .fold(target.clone(), |out, part| out.join(part));
+        if entry.is_dir() {
+            fs::create_dir_all(&out)?;
+            continue;
+        }
+        if let Some(parent) = out.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&out)?;
+        io::copy(&mut entry, &mut file)?;
+    }
+    if !found {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("There's no {} in the archive", path.inner),
+        ));
+    }
+    Ok(target)
+}
+
+/// The contents of the file at `path` in its archive, if it's text of at most
+/// [`PREVIEW_LEN`] bytes.
+pub fn preview(path: &ArchivePath) -> io::Result<String> {
+    let mut zip = open(&path.archive)?;
+    let index = file_index(&mut zip, &path.inner)?;
+    let mut entry = zip.by_index(index)?;
+    if entry.size() > PREVIEW_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Too big to preview: extract it to open it",
+        ));
+    }
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes)?;
+    let start = &bytes[..bytes.len().min(SNIFF_LEN as usize)];
+    if !bytes.is_empty() && sniff(start) != Some(FileType::Text) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Not text: extract it to open it",
+        ));
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Where in `zip` the file `name` is, with names compared the way they're listed: `./x` and
+/// `dir\x` are found as `x` and `dir/x`.
+fn file_index<R: Read + io::Seek>(zip: &mut ZipArchive<R>, name: &str) -> io::Result<usize> {
+    for index in 0..zip.len() {
+        let entry = zip.by_index_raw(index)?;
+        if !entry.is_dir() && normalize_name(entry.name()) == name {
+            return Ok(index);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("There's no {name} in the archive"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+    use std::io::Write as _;
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    fn archive_path(archive: &str, inner: &str) -> ArchivePath {
+        ArchivePath {
+            archive: PathBuf::from(archive),
+            inner: inner.to_owned(),
+        }
+    }
+
+    #[test]
+    fn paths_into_archives_are_parsed() {
+        let parse = |path| parse(path, false);
+        assert_eq!(
+            parse("/home/me/a.zip!"),
+            Some(archive_path("/home/me/a.zip", ""))
+        );
+        assert_eq!(
+            parse("/home/me/a.zip!/"),
+            Some(archive_path("/home/me/a.zip", ""))
+        );
+        assert_eq!(
+            parse("/home/me/a.zip!/docs/notes"),
+            Some(archive_path("/home/me/a.zip", "docs/notes"))
+        );
+        assert_eq!(
+            parse("/home/me/A.ZIP!/docs//./notes/"),
+            Some(archive_path("/home/me/A.ZIP", "docs/notes"))
+        );
+        assert_eq!(
+            parse("/home/me/a.zip!/docs/../../x"),
+            Some(archive_path("/home/me/a.zip", "x"))
+        );
+        assert_eq!(parse("a.zip!/x"), Some(archive_path("a.zip", "x")));
+    }
+
+    #[test]
+    fn other_paths_are_not_archives() {
+        let parse = |path| parse(path, false);
+        assert_eq!(parse("/home/me/a.zip"), None);
+        assert_eq!(parse("/home/me/a.zip/x"), None);
+        assert_eq!(parse("/home/me/wow!/a.txt"), None);
+        assert_eq!(parse("/home/me/a.tar!/x"), None);
+        assert_eq!(parse("/home/me/a.zip!x"), None);
+        assert_eq!(parse(""), None);
+        // The first archive is the one that's browsed; the name can have a '!' in it.
+        assert_eq!(
+            parse("/x/a.zip!b.zip!/c"),
+            Some(archive_path("/x/a.zip!b.zip", "c"))
+        );
+        assert_eq!(
+            parse("/x/a.zip!/b.zip!/c"),
+            Some(archive_path("/x/a.zip", "b.zip!/c"))
+        );
+    }
+
+    #[test]
+    fn backslashes_only_separate_on_windows() {
+        assert_eq!(
+            parse(r"C:\Users\me\a.zip!\docs\notes", true),
+            Some(archive_path(r"C:\Users\me\a.zip", "docs/notes"))
+        );
+        assert_eq!(
+            parse(r"C:\Users\me\a.zip!/docs\notes", true),
+            Some(archive_path(r"C:\Users\me\a.zip", "docs/notes"))
+        );
+        assert_eq!(parse(r"/home/me/a.zip!\docs", false), None);
+        assert_eq!(
+            parse(r"/home/me/a.zip!/do\cs", false),
+            Some(archive_path("/home/me/a.zip", r"do\cs"))
+        );
+    }
+
+    #[test]
+    fn paths_round_trip() {
+        let root = ArchivePath::root(Path::new("/home/me/a.zip"));
+        assert_eq!(root.to_string(), "/home/me/a.zip!");
+        assert_eq!(root.name(), None);
+
+        let notes = root.join("docs").join("notes");
+        assert_eq!(notes.to_string(), "/home/me/a.zip!/docs/notes");
+        assert_eq!(notes.name(), Some("notes"));
+
+        for path in [root, notes, archive_path("/x/a.zip!b.zip", "c d/é")] {
+            assert_eq!(parse(&path.to_string(), false), Some(path.clone()));
+            assert_eq!(
+                parse_archive_path(&path.to_path().to_string_lossy()),
+                Some(path)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn breadcrumbs_go_up_through_the_archive_and_out() {
+        let path = Path::new("/home/me/a.zip!/docs/notes");
+        let crumbs: Vec<_> = std::iter::successors(Some(path), |path| path.parent())
+            .map(|path| {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned());
+                let label = name.as_deref().map(crumb_label);
+                (label, parse_archive_path(&path.to_string_lossy()))
+            })
+            .collect();
+        assert_eq!(
+            crumbs,
+            [
+                (
+                    Some("notes".to_owned()),
+                    Some(archive_path("/home/me/a.zip", "docs/notes"))
+                ),
+                (
+                    Some("docs".to_owned()),
+                    Some(archive_path("/home/me/a.zip", "docs"))
+                ),
+                (
+                    Some("📦 a.zip".to_owned()),
+                    Some(archive_path("/home/me/a.zip", ""))
+                ),
+                (Some("me".to_owned()), None),
+                (Some("home".to_owned()), None),
+                (None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn crumb_labels() {
+        assert_eq!(crumb_label("a.zip!"), "📦 a.zip");
+        assert_eq!(crumb_label("Backup.ZIP!"), "📦 Backup.ZIP");
+        assert_eq!(crumb_label("wow!"), "wow!");
+        assert_eq!(crumb_label("docs"), "docs");
+    }
+
+    fn entry(name: &str, size: u64) -> ArchiveEntry {
+        ArchiveEntry {
+            name: name.to_owned(),
+            is_dir: name.ends_with('/'),
+            size,
+            modified: None,
+        }
+    }
+
+    fn listed(dir: &str) -> io::Result<Vec<(String, bool, u64)>> {
+        let entries = vec![
+            entry("docs/", 0),
+            entry("docs/a.txt", 1),
+            entry("docs/sub/b.txt", 2),
+            entry("top.txt", 4),
+            // No entry of its own for "implicit/" or "implicit/deep/".
+            entry("implicit/deep/c.txt", 8),
+            entry("./dotted.txt", 16),
+            entry("win\\d.txt", 32),
+        ];
+        let children = children(entries, dir)?;
+        Ok(children
+            .into_iter()
+            .map(|entry| (entry.name, entry.is_dir, entry.size))
+            .collect())
+    }
+
+    #[test]
+    fn lists_one_folder_of_an_archive() {
+        let row = |name: &str, is_dir, size| (name.to_owned(), is_dir, size);
+        assert_eq!(
+            listed("").unwrap(),
+            [
+                row("docs", true, 3),
+                row("implicit", true, 8),
+                row("win", true, 32),
+                row("dotted.txt", false, 16),
+                row("top.txt", false, 4),
+            ]
+        );
+        assert_eq!(
+            listed("docs").unwrap(),
+            [row("sub", true, 2), row("a.txt", false, 1)]
+        );
+        assert_eq!(listed("docs/sub").unwrap(), [row("b.txt", false, 2)]);
+        assert_eq!(listed("implicit").unwrap(), [row("deep", true, 8)]);
+        assert_eq!(
+            listed("missing").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        // "doc" is only the start of "docs".
+        assert_eq!(listed("doc").unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(
+            listed("top.txt").unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn names_are_normalized() {
+        assert_eq!(normalize_name("docs/"), "docs");
+        assert_eq!(normalize_name("/abs/x.txt"), "abs/x.txt");
+        assert_eq!(normalize_name("./x.txt"), "x.txt");
+        assert_eq!(normalize_name(r"a\b\c.txt"), "a/b/c.txt");
+
+        assert_eq!(relative_to("docs/a.txt", "docs"), Some("a.txt"));
+        assert_eq!(relative_to("docs", "docs"), Some(""));
+        assert_eq!(relative_to("docs2/a.txt", "docs"), None);
+        assert_eq!(relative_to("a.txt", ""), Some("a.txt"));
+    }
+
+    /// Writes a zip of `entries` to `path`, by name and contents; names ending in `/` are
+    /// folders.
+    fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(fs::File::create(path).unwrap());
+        for (name, contents) in entries {
+            if name.ends_with('/') {
+                zip.add_directory(*name, FileOptions::default())
+                    .unwrap();
+            } else {
+                zip.start_file(*name, FileOptions::default()).unwrap();
+                zip.write_all(contents).unwrap();
+            }
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn previews_find_entries_by_their_listed_name() {
+        let dir = TempDir::new("archive_preview");
+        let archive = dir.join("a.zip");
+        write_zip(
+            &archive,
+            &[
+                ("docs/", b""),
+                ("docs/a.txt", b"plain"),
+                ("./dotted.txt", b"dotted"),
+                ("win\\d.txt", b"from windows"),
+                ("app.bin", b"\x7fELF\0\0\0"),
+            ],
+        );
+        let preview = |inner: &str| {
+            preview(&ArchivePath {
+                archive: archive.clone(),
+                inner: inner.to_owned(),
+            })
+        };
+
+        assert_eq!(preview("docs/a.txt").unwrap(), "plain");
+        assert_eq!(preview("dotted.txt").unwrap(), "dotted");
+        assert_eq!(preview("win/d.txt").unwrap(), "from windows");
+        assert_eq!(
+            preview("app.bin").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(preview("docs").unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(
+            preview("missing.txt").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn extracts_folders_and_files_but_nothing_outside() {
+        let dir = TempDir::new("archive_extract");
+        let archive = dir.join("a.zip");
+        write_zip(
+            &archive,
+            &[
+                ("docs/", b""),
+                ("docs/a.txt", b"a"),
+                ("docs/sub/b.txt", b"b"),
+                ("docs/../../evil.txt", b"evil"),
+                ("win\\d.txt", b"d"),
+            ],
+        );
+        let dest = dir.join("dest");
+        fs::create_dir(&dest).unwrap();
+        let extract = |inner: &str| {
+            let path = ArchivePath {
+                archive: archive.clone(),
+                inner: inner.to_owned(),
+            };
+            extract(&path, &dest)
+        };
+
+        assert_eq!(extract("docs").unwrap(), dest.join("docs"));
+        let read = |path: &str| fs::read_to_string(dest.join(path)).unwrap();
+        assert_eq!(read("docs/a.txt"), "a");
+        assert_eq!(read("docs/sub/b.txt"), "b");
+        assert!(!dir.join("evil.txt").exists());
+        assert!(!dest.join("evil.txt").exists());
+
+        assert_eq!(extract("win/d.txt").unwrap(), dest.join("d.txt"));
+        assert_eq!(read("d.txt"), "d");
+
+        // Nothing is overwritten.
+        assert_eq!(
+            extract("docs").unwrap_err().kind(),
+            io::ErrorKind::AlreadyExists
+        );
+        assert_eq!(
+            extract("missing").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn zip_times_are_read_as_utc() {
+        let secs = |time: Option<SystemTime>| {
+            time.map(|time| time.duration_since(UNIX_EPOCH).unwrap().as_secs())
+        };
+        // The earliest time a zip can store.
+        assert_eq!(secs(zip_time(1980, 1, 1, 0, 0, 0)), Some(315_532_800));
+        assert_eq!(secs(zip_time(2024, 8, 31, 12, 30, 15)), Some(1_725_107_415));
+        assert_eq!(zip_time(2024, 0, 1, 0, 0, 0), None);
+        assert_eq!(zip_time(2024, 13, 1, 0, 0, 0), None);
+        assert_eq!(zip_time(2024, 1, 0, 0, 0, 0), None);
+        assert_eq!(zip_time(2024, 1, 1, 24, 0, 0), None);
+    }
+}
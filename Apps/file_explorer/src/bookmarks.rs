@@ -0,0 +1,176 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// A folder in the sidebar.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl Bookmark {
+    /// A bookmark named after the last component of `path` (or `path` itself, for a root).
+    pub fn new(path: PathBuf) -> Self {
+        let name = path.file_name().map_or_else(
+            || path.to_string_lossy().into_owned(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        Self { name, path }
+    }
+
+    /// Is the folder gone (deleted, moved, or on a drive that isn't mounted)?
+    pub fn is_stale(&self) -> bool {
+        !self.path.is_dir()
+    }
+}
+
+/// The user's bookmarks, in the order they're shown.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmarks(pub Vec<Bookmark>);
+
+impl Bookmarks {
+    pub fn contains(&self, path: &Path) -> bool {
+        self.0.iter().any(|bookmark| bookmark.path == path)
+    }
+
+    /// Bookmarks `path`, or removes its bookmark if it has one.
+    pub fn toggle(&mut self, path: &Path) {
+        if self.contains(path) {
+            self.0.retain(|bookmark| bookmark.path != path);
+        } else {
+            self.0.push(Bookmark::new(path.to_owned()));
+        }
+    }
+
+    /// Moves the bookmark at `from` to where the one at `to` is, shifting those in between.
+    pub fn move_to(&mut self, from: usize, to: usize) {
+        if from < self.0.len() && to < self.0.len() {
+            let bookmark = self.0.remove(from);
+            self.0.insert(to, bookmark);
+        }
+    }
+}
+
+/// The home folder and the usual folders in it, where they exist.
+pub fn default_locations() -> Vec<Bookmark> {
+    let mut locations = Vec::new();
+    let home =
+        std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).map(PathBuf::from);
+    if let Some(home) = home {
+        locations.push(Bookmark {
+            name: "Home".to_owned(),
+            path: home.clone(),
+        });
+        for name in ["Desktop", "Documents", "Downloads"] {
+            locations.push(Bookmark {
+                name: name.to_owned(),
+                path: home.join(name),
+            });
+        }
+    }
+    locations.retain(|location| !location.is_stale());
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+    use std::collections::HashMap;
+    use std::fs;
+
+    /// Stands in for eframe's file storage.
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_owned(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    fn bookmarks(paths: &[&str]) -> Bookmarks {
+        Bookmarks(
+            paths
+                .iter()
+                .map(|path| Bookmark::new(path.into()))
+                .collect(),
+        )
+    }
+
+    fn paths(bookmarks: &Bookmarks) -> Vec<&Path> {
+        bookmarks
+            .0
+            .iter()
+            .map(|bookmark| bookmark.path.as_path())
+            .collect()
+    }
+
+    #[test]
+    fn bookmarks_survive_a_restart() {
+        let mut saved = bookmarks(&["/home/me/projects/ui", "/"]);
+        saved.0[0].name = "UI".to_owned();
+
+        let mut storage = MemoryStorage::default();
+        eframe::set_value(&mut storage, "bookmarks", &saved);
+        let loaded: Option<Bookmarks> = eframe::get_value(&storage, "bookmarks");
+        assert_eq!(loaded, Some(saved));
+    }
+
+    #[test]
+    fn names_come_from_the_path() {
+        assert_eq!(Bookmark::new("/home/me/projects".into()).name, "projects");
+        assert_eq!(Bookmark::new("/".into()).name, "/");
+    }
+
+    #[test]
+    fn toggle_adds_and_removes() {
+        let mut bookmarks = bookmarks(&["/a"]);
+        bookmarks.toggle(Path::new("/b"));
+        assert_eq!(paths(&bookmarks), [Path::new("/a"), Path::new("/b")]);
+        bookmarks.toggle(Path::new("/a"));
+        assert_eq!(paths(&bookmarks), [Path::new("/b")]);
+    }
+
+    #[test]
+    fn move_to_reorders() {
+        let mut bookmarks = bookmarks(&["/a", "/b", "/c"]);
+        bookmarks.move_to(0, 2);
+        assert_eq!(
+            paths(&bookmarks),
+            [Path::new("/b"), Path::new("/c"), Path::new("/a")]
+        );
+        bookmarks.move_to(2, 0);
+        assert_eq!(
+            paths(&bookmarks),
+            [Path::new("/a"), Path::new("/b"), Path::new("/c")]
+        );
+        bookmarks.move_to(1, 5);
+        assert_eq!(
+            paths(&bookmarks),
+            [Path::new("/a"), Path::new("/b"), Path::new("/c")]
+        );
+    }
+
+    #[test]
+    fn missing_folders_are_stale() {
+        let dir = TempDir::new("bookmarks");
+        let file = dir.join("file.txt");
+        fs::write(&file, "").unwrap();
+
+        assert!(!Bookmark::new(dir.to_path_buf()).is_stale());
+        assert!(Bookmark::new(dir.join("gone")).is_stale());
+        assert!(Bookmark::new(file).is_stale());
+
+        let path = dir.to_path_buf();
+        drop(dir);
+        assert!(Bookmark::new(path).is_stale());
+    }
+}
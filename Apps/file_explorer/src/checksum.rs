@@ -0,0 +1,686 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// How much of a file is read at a time while hashing it.
+const CHUNK_LEN: usize = 64 * 1024;
+
+/// The digests the properties dialog can compute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl Algorithm {
+    pub const ALL: [Self; 3] = [Self::Md5, Self::Sha1, Self::Sha256];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+        }
+    }
+}
+
+/// The digests of some data, as lowercase hex.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checksums {
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+}
+
+impl Checksums {
+    pub fn get(&self, algorithm: Algorithm) -> &str {
+        match algorithm {
+            Algorithm::Md5 => &self.md5,
+            Algorithm::Sha1 => &self.sha1,
+            Algorithm::Sha256 => &self.sha256,
+        }
+    }
+
+    /// Which digest `expected` is, if any; see [`normalize_digest`].
+    pub fn matching(&self, expected: &str) -> Option<Algorithm> {
+        let expected = normalize_digest(expected);
+        Algorithm::ALL
+            .into_iter()
+            .find(|&algorithm| self.get(algorithm) == expected)
+    }
+}
+
+/// A pasted digest, ready to compare with [`Checksums`]: trimmed and in lowercase.
+///
+/// Only the first word is kept, so a whole line of `sha256sum` output works too.
+pub fn normalize_digest(text: &str) -> String {
+    text.split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Computes all the [`Algorithm`]s in one pass over the data.
+#[derive(Clone, Default)]
+pub struct Hasher {
+    md5: Md5,
+    sha1: Sha1,
+    sha256: Sha256,
+}
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        self.md5.update(data);
+        self.sha1.update(data);
+        self.sha256.update(data);
+    }
+
+    pub fn finish(self) -> Checksums {
+        Checksums {
+            md5: to_hex(&self.md5.finish()),
+            sha1: to_hex(&self.sha1.finish()),
+            sha256: to_hex(&self.sha256.finish()),
+        }
+    }
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write as _;
+
+    let mut hex = String::with_capacity(2 * bytes.len());
+    for byte in bytes {
+        write!(hex, "{byte:02x}").ok();
+    }
+    hex
+}
+
+/// How far hashing a file has got, shared between it and the UI.
+#[derive(Debug, Default)]
+pub struct ChecksumProgress {
+    bytes: AtomicU64,
+    cancel: AtomicBool,
+}
+
+impl ChecksumProgress {
+    /// How much of the file has been hashed so far.
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Stops hashing at the next chunk.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+/// The checksums of the file at `path`, read a chunk at a time so files of any size can be
+/// hashed on a background thread. `None` if it was canceled through `progress`.
+pub fn checksum_file(path: &Path, progress: &ChecksumProgress) -> io::Result<Option<Checksums>> {
+    checksum_reader(fs::File::open(path)?, progress)
+}
+
+fn checksum_reader(
+    mut reader: impl Read,
+    progress: &ChecksumProgress,
+) -> io::Result<Option<Checksums>> {
+    let mut hasher = Hasher::default();
+    let mut chunk = vec![0; CHUNK_LEN];
+    loop {
+        if progress.is_canceled() {
+            return Ok(None);
+        }
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => return Ok(Some(hasher.finish())),
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        hasher.update(&chunk[..read]);
+        progress.bytes.fetch_add(read as u64, Ordering::Relaxed);
+    }
+}
+
+/// The 64-byte blocks all three algorithms work on, and their padding.
+#[derive(Clone)]
+struct Blocks {
+    buffer: [u8; 64],
+    /// How much of [`Self::buffer`] is filled.
+    len: usize,
+    /// How many bytes went in, in all.
+    total: u64,
+}
+
+impl Default for Blocks {
+    fn default() -> Self {
+        Self {
+            buffer: [0; 64],
+            len: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Blocks {
+    /// Adds `data`, passing every block it completes to `compress`.
+    fn update(&mut self, mut data: &[u8], mut compress: impl FnMut(&[u8; 64])) {
+        self.total = self.total.wrapping_add(data.len() as u64);
+        if self.len > 0 {
+            let take = (64 - self.len).min(data.len());
+            self.buffer[self.len..self.len + take].copy_from_slice(&data[..take]);
+            self.len += take;
+            data = &data[take..];
+            if self.len < 64 {
+                return;
+            }
+            compress(&self.buffer);
+            self.len = 0;
+        }
+        let mut blocks = data.chunks_exact(64);
+        for block in &mut blocks {
+            compress(block.try_into().unwrap());
+        }
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.len = rest.len();
+    }
+
+    /// Pads the data with a 1 bit, zeros and its length in bits, which MD5 writes
+    /// little-endian and the SHAs big-endian.
+    fn finish(mut self, little_endian: bool, compress: impl FnMut(&[u8; 64])) {
+        let bits = self.total.wrapping_mul(8);
+        let zeros = (64 + 56 - (self.len + 1) % 64) % 64;
+        let mut padding = vec![0x80];
+        padding.resize(1 + zeros, 0);
+        if little_endian {
+            padding.extend_from_slice(&bits.to_le_bytes());
+        } else {
+            padding.extend_from_slice(&bits.to_be_bytes());
+        }
+        self.update(&padding, compress);
+        debug_assert_eq!(self.len, 0);
+    }
+}
+
+/// MD5, RFC 1321. Broken for security, but still what many downloads list.
+#[derive(Clone)]
+struct Md5 {
+    state: [u32; 4],
+    blocks: Blocks,
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Self {
+            state: [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476],
+            blocks: Blocks::default(),
+        }
+    }
+}
+
+impl Md5 {
+    const SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+
+    /// `floor(abs(sin(i + 1)) * 2^32)`.
+    const K: [u32; 64] = [
+        0xd76a_a478,
+        0xe8c7_b756,
+        0x2420_70db,
+        0xc1bd_ceee,
+        0xf57c_0faf,
+        0x4787_c62a,
+        0xa830_4613,
+        0xfd46_9501,
+        0x6980_98d8,
+        0x8b44_f7af,
+        0xffff_5bb1,
+        0x895c_d7be,
+        0x6b90_1122,
+        0xfd98_7193,
+        0xa679_438e,
+        0x49b4_0821,
+        0xf61e_2562,
+        0xc040_b340,
+        0x265e_5a51,
+        0xe9b6_c7aa,
+        0xd62f_105d,
+        0x0244_1453,
+        0xd8a1_e681,
+        0xe7d3_fbc8,
+        0x21e1_cde6,
+        0xc337_07d6,
+        0xf4d5_0d87,
+        0x455a_14ed,
+        0xa9e3_e905,
+        0xfcef_a3f8,
+        0x676f_02d9,
+        0x8d2a_4c8a,
+        0xfffa_3942,
+        0x8771_f681,
+        0x6d9d_6122,
+        0xfde5_380c,
+        0xa4be_ea44,
+        0x4bde_cfa9,
+        0xf6bb_4b60,
+        0xbebf_bc70,
+        0x289b_7ec6,
+        0xeaa1_27fa,
+        0xd4ef_3085,
+        0x0488_1d05,
+        0xd9d4_d039,
+        0xe6db_99e5,
+        0x1fa2_7cf8,
+        0xc4ac_5665,
+        0xf429_2244,
+        0x432a_ff97,
+        0xab94_23a7,
+        0xfc93_a039,
+        0x655b_59c3,
+        0x8f0c_cc92,
+        0xffef_f47d,
+        0x8584_5dd1,
+        0x6fa8_7e4f,
+        0xfe2c_e6e0,
+        0xa301_4314,
+        0x4e08_11a1,
+        0xf753_7e82,
+        0xbd3a_f235,
+        0x2ad7_d2bb,
+        0xeb86_d391,
+    ];
+
+    fn update(&mut self, data: &[u8]) {
+        let state = &mut self.state;
+        self.blocks
+            .update(data, |block| Self::compress(state, block));
+    }
+
+    fn finish(mut self) -> [u8; 16] {
+        let state = &mut self.state;
+        self.blocks
+            .finish(true, |block| Self::compress(state, block));
+        let mut digest = [0; 16];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+
+    fn compress(state: &mut [u32; 4], block: &[u8; 64]) {
+        let m: Vec<u32> = block
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect();
+        let [mut a, mut b, mut c, mut d] = *state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let shift = Self::SHIFTS[(i / 16) * 4 + i % 4];
+            let rotated = a
+                .wrapping_add(f)
+                .wrapping_add(Self::K[i])
+                .wrapping_add(m[g])
+                .rotate_left(shift);
+            (a, b, c, d) = (d, b.wrapping_add(rotated), b, c);
+        }
+        for (word, added) in state.iter_mut().zip([a, b, c, d]) {
+            *word = word.wrapping_add(added);
+        }
+    }
+}
+
+/// SHA-1, FIPS 180-4.
+#[derive(Clone)]
+struct Sha1 {
+    state: [u32; 5],
+    blocks: Blocks,
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self {
+            state: [
+                0x6745_2301,
+                0xefcd_ab89,
+                0x98ba_dcfe,
+                0x1032_5476,
+                0xc3d2_e1f0,
+            ],
+            blocks: Blocks::default(),
+        }
+    }
+}
+
+impl Sha1 {
+    fn update(&mut self, data: &[u8]) {
+        let state = &mut self.state;
+        self.blocks
+            .update(data, |block| Self::compress(state, block));
+    }
+
+    fn finish(mut self) -> [u8; 20] {
+        let state = &mut self.state;
+        self.blocks
+            .finish(false, |block| Self::compress(state, block));
+        let mut digest = [0; 20];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    fn compress(state: &mut [u32; 5], block: &[u8; 64]) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = *state;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i / 20 {
+                0 => ((b & c) | (!b & d), 0x5a82_7999),
+                1 => (b ^ c ^ d, 0x6ed9_eba1),
+                2 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            (a, b, c, d, e) = (temp, a, b.rotate_left(30), c, d);
+        }
+        for (word, added) in state.iter_mut().zip([a, b, c, d, e]) {
+            *word = word.wrapping_add(added);
+        }
+    }
+}
+
+/// SHA-256, FIPS 180-4.
+#[derive(Clone)]
+struct Sha256 {
+    state: [u32; 8],
+    blocks: Blocks,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self {
+            state: [
+                0x6a09_e667,
+                0xbb67_ae85,
+                0x3c6e_f372,
+                0xa54f_f53a,
+                0x510e_527f,
+                0x9b05_688c,
+                0x1f83_d9ab,
+                0x5be0_cd19,
+            ],
+            blocks: Blocks::default(),
+        }
+    }
+}
+
+impl Sha256 {
+    /// The first 32 bits of the fractional parts of the cube roots of the first 64 primes.
+    const K: [u32; 64] = [
+        0x428a_2f98,
+        0x7137_4491,
+        0xb5c0_fbcf,
+        0xe9b5_dba5,
+        0x3956_c25b,
+        0x59f1_11f1,
+        0x923f_82a4,
+        0xab1c_5ed5,
+        0xd807_aa98,
+        0x1283_5b01,
+        0x2431_85be,
+        0x550c_7dc3,
+        0x72be_5d74,
+        0x80de_b1fe,
+        0x9bdc_06a7,
+        0xc19b_f174,
+        0xe49b_69c1,
+        0xefbe_4786,
+        0x0fc1_9dc6,
+        0x240c_a1cc,
+        0x2de9_2c6f,
+        0x4a74_84aa,
+        0x5cb0_a9dc,
+        0x76f9_88da,
+        0x983e_5152,
+        0xa831_c66d,
+        0xb003_27c8,
+        0xbf59_7fc7,
+        0xc6e0_0bf3,
+        0xd5a7_9147,
+        0x06ca_6351,
+        0x1429_2967,
+        0x27b7_0a85,
+        0x2e1b_2138,
+        0x4d2c_6dfc,
+        0x5338_0d13,
+        0x650a_7354,
+        0x766a_0abb,
+        0x81c2_c92e,
+        0x9272_2c85,
+        0xa2bf_e8a1,
+        0xa81a_664b,
+        0xc24b_8b70,
+        0xc76c_51a3,
+        0xd192_e819,
+        0xd699_0624,
+        0xf40e_3585,
+        0x106a_a070,
+        0x19a4_c116,
+        0x1e37_6c08,
+        0x2748_774c,
+        0x34b0_bcb5,
+        0x391c_0cb3,
+        0x4ed8_aa4a,
+        0x5b9c_ca4f,
+        0x682e_6ff3,
+        0x748f_82ee,
+        0x78a5_636f,
+        0x84c8_7814,
+        0x8cc7_0208,
+        0x90be_fffa,
+        0xa450_6ceb,
+        0xbef9_a3f7,
+        0xc671_78f2,
+    ];
+
+    fn update(&mut self, data: &[u8]) {
+        let state = &mut self.state;
+        self.blocks
+            .update(data, |block| Self::compress(state, block));
+    }
+
+    fn finish(mut self) -> [u8; 32] {
+        let state = &mut self.state;
+        self.blocks
+            .finish(false, |block| Self::compress(state, block));
+        let mut digest = [0; 32];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for (&k, &word) in Self::K.iter().zip(&w) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+            (a, b, c, d, e, f, g, h) = (
+                temp1.wrapping_add(temp2),
+                a,
+                b,
+                c,
+                d.wrapping_add(temp1),
+                e,
+                f,
+                g,
+            );
+        }
+        for (word, added) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(added);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    fn checksums(data: &[u8]) -> Checksums {
+        let mut hasher = Hasher::default();
+        hasher.update(data);
+        hasher.finish()
+    }
+
+    #[test]
+    fn known_vectors() {
+        let cases: [(&[u8], &str, &str, &str); 4] = [
+            (
+                b"",
+                "d41d8cd98f00b204e9800998ecf8427e",
+                "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                b"abc",
+                "900150983cd24fb0d6963f7d28e17f72",
+                "a9993e364706816aba3e25717850c26c9cd0d89d",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                b"The quick brown fox jumps over the lazy dog",
+                "9e107d9d372bb6826bd81d3542a419d6",
+                "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12",
+                "d7a8fbb307d7809469ca9abcb0082e4f8d5651e46d3cdb762d02d0bf37c9e592",
+            ),
+            (
+                // 56 bytes: the length no longer fits in the first block.
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "8215ef0796a20bcaaae116d3876c664a",
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+        ];
+        for (data, md5, sha1, sha256) in cases {
+            let expected = Checksums {
+                md5: md5.to_owned(),
+                sha1: sha1.to_owned(),
+                sha256: sha256.to_owned(),
+            };
+            assert_eq!(
+                checksums(data),
+                expected,
+                "{}",
+                String::from_utf8_lossy(data)
+            );
+        }
+    }
+
+    #[test]
+    fn streaming_in_any_chunks_gives_the_same_digests() {
+        let data: Vec<u8> = (0..1_000_000u32).map(|i| b'a' + (i % 26) as u8).collect();
+        let whole = checksums(&data);
+        for chunk_len in [1, 3, 63, 64, 65, 1000, CHUNK_LEN] {
+            let mut hasher = Hasher::default();
+            for chunk in data.chunks(chunk_len) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finish(), whole, "{chunk_len}");
+        }
+
+        let million_a = checksums(&vec![b'a'; 1_000_000]);
+        assert_eq!(million_a.md5, "7707d6ae4e027c70eea2a935c2296f21");
+        assert_eq!(million_a.sha1, "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+        assert_eq!(
+            million_a.sha256,
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn files_are_hashed_until_canceled() {
+        let dir = TempDir::new("checksum");
+        let path = dir.join("download.iso");
+        // More than one chunk.
+        let data = vec![7; CHUNK_LEN * 2 + 5];
+        fs::write(&path, &data).unwrap();
+
+        let progress = ChecksumProgress::default();
+        assert_eq!(
+            checksum_file(&path, &progress).unwrap(),
+            Some(checksums(&data))
+        );
+        assert_eq!(progress.bytes(), data.len() as u64);
+
+        let canceled = ChecksumProgress::default();
+        canceled.cancel();
+        assert_eq!(checksum_file(&path, &canceled).unwrap(), None);
+        assert!(checksum_file(&dir.join("missing"), &progress).is_err());
+    }
+
+    #[test]
+    fn pasted_digests_are_normalized() {
+        assert_eq!(normalize_digest("  ABCdef01\n"), "abcdef01");
+        assert_eq!(
+            normalize_digest("e3b0c442  ubuntu-24.04-desktop-amd64.iso"),
+            "e3b0c442"
+        );
+        assert_eq!(normalize_digest("   "), "");
+
+        let abc = checksums(b"abc");
+        assert_eq!(
+            abc.matching(" A9993E364706816ABA3E25717850C26C9CD0D89D "),
+            Some(Algorithm::Sha1)
+        );
+        assert_eq!(
+            abc.matching("900150983cd24fb0d6963f7d28e17f72 *abc.txt"),
+            Some(Algorithm::Md5)
+        );
+        assert_eq!(
+            abc.matching(&abc.sha256.to_uppercase()),
+            Some(Algorithm::Sha256)
+        );
+        assert_eq!(abc.matching("900150983cd24fb0d6963f7d28e17f73"), None);
+        assert_eq!(abc.matching(""), None);
+    }
+}
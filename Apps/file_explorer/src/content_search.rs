@@ -0,0 +1,433 @@
+use std::fs;
+use std::io::{self, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use rayon::prelude::*;
+
+use crate::file_type::{sniff, FileType, SNIFF_LEN};
+use crate::hidden::is_hidden_entry;
+use crate::read_errors::{ReadError, ReadErrors};
+use crate::search::Matcher;
+
+/// Only this much of each file is searched, so one huge log doesn't hold up the rest.
+pub const MAX_FILE_LEN: u64 = 4 * 1024 * 1024;
+
+/// The search stops once this many lines have matched.
+pub const MAX_MATCHES: usize = 1000;
+
+/// How many characters of a long line are shown around the match.
+pub const EXCERPT_CHARS: usize = 120;
+
+/// A line of a file that the query was found in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentMatch {
+    pub path: PathBuf,
+    /// Counting from 1.
+    pub line_number: usize,
+    /// The line, or the part of it around the match if it's long.
+    pub excerpt: String,
+    /// Where the query matches in [`Self::excerpt`], as byte ranges.
+    pub ranges: Vec<Range<usize>>,
+}
+
+/// The lines of `text` that `matcher` finds something in, as line numbers (from 1), excerpts
+/// and where it matches in them; see [`excerpt`].
+pub fn matching_lines(text: &str, matcher: &Matcher) -> Vec<(usize, String, Vec<Range<usize>>)> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let ranges = matcher.find_ranges(line);
+            if ranges.is_empty() {
+                return None;
+            }
+            let (excerpt, ranges) = excerpt(line, &ranges, EXCERPT_CHARS);
+            Some((index + 1, excerpt, ranges))
+        })
+        .collect()
+}
+
+/// The part of `line` worth showing for the matches at `ranges`: at most `max_chars`
+/// characters around the first one, with "…" where the line goes on, and indentation
+/// left out. Also returns where the matches are in it; ones cut off are dropped or cut short.
+///
+/// Cuts only fall between characters, however many bytes they take.
+pub fn excerpt(
+    line: &str,
+    ranges: &[Range<usize>],
+    max_chars: usize,
+) -> (String, Vec<Range<usize>>) {
+    let first = ranges.first().cloned().unwrap_or(0..0);
+    let line = line.trim_end();
+    let indent = (line.len() - line.trim_start().len()).min(first.start);
+
+    // Where each character starts, and where the line ends.
+    let bounds: Vec<usize> = line
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([line.len()])
+        .filter(|&i| i >= indent)
+        .collect();
+    let chars = bounds.len() - 1;
+    let char_at = |byte: usize| bounds.partition_point(|&bound| bound < byte);
+    let (match_start, match_end) = (char_at(first.start), char_at(first.end));
+
+    let before = max_chars.saturating_sub(match_end - match_start) / 2;
+    let end = (match_start.saturating_sub(before) + max_chars).min(chars);
+    let start = end.saturating_sub(max_chars).min(match_start);
+    let (start_byte, end_byte) = (bounds[start], bounds[end]);
+
+    let mut excerpt = String::new();
+    if start > 0 {
+        excerpt.push('…');
+    }
+    let offset = excerpt.len();
+    excerpt.push_str(&line[start_byte..end_byte]);
+    if end < chars {
+        excerpt.push('…');
+    }
+
+    let ranges = ranges
+        .iter()
+        .map(|range| range.start.max(start_byte)..range.end.min(end_byte))
+        .filter(|range| range.start < range.end)
+        .map(|range| range.start - start_byte + offset..range.end - start_byte + offset)
+        .collect();
+    (excerpt, ranges)
+}
+
+/// What [`search_contents`] went through, besides what it found.
+#[derive(Debug, Default)]
+pub struct ContentOutcome {
+    /// How many entries were looked at.
+    pub visited: usize,
+    /// The folders and files that couldn't be read.
+    pub errors: ReadErrors,
+    /// It stopped after [`MAX_MATCHES`], so there may be more.
+    pub truncated: bool,
+}
+
+struct ContentSearch<'a, F> {
+    matcher: &'a Matcher,
+    show_hidden: bool,
+    cancel: &'a AtomicBool,
+    max_matches: usize,
+    found: AtomicUsize,
+    truncated: AtomicBool,
+    visited: AtomicUsize,
+    errors: Mutex<ReadErrors>,
+    emit: F,
+}
+
+/// Recursively searches the files under `dir` for lines `matcher` finds something in,
+/// calling `emit` with the matches of each file as it's done.
+///
+/// Files that don't look like text (see [`sniff`]) are skipped, and only the first
+/// [`MAX_FILE_LEN`] bytes of the others read. Symbolic links to folders aren't followed.
+/// Hidden entries are skipped unless `show_hidden` is set.
+///
+/// Stops soon after `cancel` is set, or once `max_matches` lines have matched.
+pub fn search_contents(
+    dir: &Path,
+    matcher: &Matcher,
+    show_hidden: bool,
+    cancel: &AtomicBool,
+    max_matches: usize,
+    emit: impl Fn(Vec<ContentMatch>) + Sync,
+) -> ContentOutcome {
+    let search = ContentSearch {
+        matcher,
+        show_hidden,
+        cancel,
+        max_matches,
+        found: AtomicUsize::new(0),
+        truncated: AtomicBool::new(false),
+        visited: AtomicUsize::new(0),
+        errors: Mutex::new(ReadErrors::default()),
+        emit,
+    };
+
+    search.visit(dir);
+
+    ContentOutcome {
+        visited: search.visited.into_inner(),
+        errors: search.errors.into_inner().unwrap(),
+        truncated: search.truncated.into_inner(),
+    }
+}
+
+impl<F: Fn(Vec<ContentMatch>) + Sync> ContentSearch<'_, F> {
+    fn is_stopped(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed) || self.truncated.load(Ordering::Relaxed)
+    }
+
+    fn visit(&self, dir: &Path) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => return self.failed(dir, &err),
+        };
+        let entries: Vec<_> = entries
+            .filter_map(|entry| entry.map_err(|err| self.failed(dir, &err)).ok())
+            .filter(|entry| self.show_hidden || !is_hidden_entry(entry))
+            .collect();
+
+        entries.par_iter().for_each(|entry| {
+            if self.is_stopped() {
+                return;
+            }
+            self.visited.fetch_add(1, Ordering::Relaxed);
+
+            let path = entry.path();
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                self.visit(&path);
+            } else if path.is_file() {
+                match read_text(&path) {
+                    Ok(Some(text)) => self.scan(&path, &text),
+                    Ok(None) => {}
+                    Err(err) => self.failed(&path, &err),
+                }
+            }
+        });
+    }
+
+    fn scan(&self, path: &Path, text: &str) {
+        let lines = matching_lines(text, self.matcher);
+        if lines.is_empty() {
+            return;
+        }
+        let count = lines.len();
+        let before = self.found.fetch_add(count, Ordering::Relaxed);
+        let room = self.max_matches.saturating_sub(before);
+        if count > room {
+            self.truncated.store(true, Ordering::Relaxed);
+        }
+        let matches: Vec<_> = lines
+            .into_iter()
+            .take(room)
+            .map(|(line_number, excerpt, ranges)| ContentMatch {
+                path: path.to_owned(),
+                line_number,
+                excerpt,
+                ranges,
+            })
+            .collect();
+        if !matches.is_empty() && !self.cancel.load(Ordering::Relaxed) {
+            (self.emit)(matches);
+        }
+    }
+
+    fn failed(&self, path: &Path, err: &io::Error) {
+        if !self.cancel.load(Ordering::Relaxed) {
+            self.errors.lock().unwrap().push(ReadError::new(path, err));
+        }
+    }
+}
+
+/// The start of the file at `path`, up to [`MAX_FILE_LEN`] bytes, if it's text.
+fn read_text(path: &Path) -> io::Result<Option<String>> {
+    let mut bytes = Vec::new();
+    fs::File::open(path)?
+        .take(MAX_FILE_LEN)
+        .read_to_end(&mut bytes)?;
+    let start = &bytes[..bytes.len().min(SNIFF_LEN as usize)];
+    if sniff(start) != Some(FileType::Text) {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::SearchMode;
+    use crate::temp_dir::TempDir;
+
+    fn plain(query: &str) -> Matcher {
+        Matcher::new(SearchMode::Plain, query).unwrap()
+    }
+
+    /// The excerpt with the matches in brackets.
+    fn marked(line: &str, query: &str, max_chars: usize) -> String {
+        let ranges = plain(query).find_ranges(line);
+        let (excerpt, ranges) = excerpt(line, &ranges, max_chars);
+        let mut marked = String::new();
+        let mut end = 0;
+        for range in ranges {
+            marked.push_str(&excerpt[end..range.start]);
+            marked.push('[');
+            marked.push_str(&excerpt[range.clone()]);
+            marked.push(']');
+            end = range.end;
+        }
+        marked.push_str(&excerpt[end..]);
+        marked
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)] // One match in the line.
+    fn finds_lines_with_their_numbers() {
+        let text = "[server]\nport = 8080\n\n# The port to listen on\r\nhost = \"::\"\n";
+        let lines = matching_lines(text, &plain("PORT"));
+        assert_eq!(
+            lines,
+            [
+                (2, "port = 8080".to_owned(), vec![0..4]),
+                (4, "# The port to listen on".to_owned(), vec![6..10]),
+            ]
+        );
+        assert!(matching_lines(text, &plain("missing")).is_empty());
+        assert!(matching_lines("", &plain("port")).is_empty());
+
+        let regex = Matcher::new(SearchMode::Regex, r"\d+").unwrap();
+        assert_eq!(
+            matching_lines(text, &regex),
+            [(2, "port = 8080".to_owned(), vec![7..11])]
+        );
+    }
+
+    #[test]
+    fn short_lines_are_shown_whole() {
+        assert_eq!(
+            marked("let port = 8080;", "port", 120),
+            "let [port] = 8080;"
+        );
+        assert_eq!(marked("a b a", "a", 120), "[a] b [a]");
+        // Indentation and trailing whitespace say nothing.
+        assert_eq!(marked("        port: 80   ", "port", 120), "[port]: 80");
+        assert_eq!(marked("    x", " ", 120), "[ ][ ][ ][ ]x");
+    }
+
+    #[test]
+    fn long_lines_are_cut_around_the_first_match() {
+        let line = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+        assert_eq!(
+            marked(&line, "needle", 16),
+            format!("…{}[needle]{}…", "a".repeat(5), "b".repeat(5))
+        );
+        // Near either end, the whole width is still used.
+        let line = format!("needle{}", "b".repeat(100));
+        assert_eq!(marked(&line, "needle", 10), "[needle]bbbb…");
+        let line = format!("{}needle", "a".repeat(100));
+        assert_eq!(marked(&line, "needle", 10), "…aaaa[needle]");
+        // A match longer than the excerpt is cut short.
+        assert_eq!(marked("xx needle yy", "needle", 4), "…[need]…");
+        // Later matches are kept if they fit, and cut short if they don't quite.
+        let line = format!("{}ab ab ab{}", "x".repeat(50), "x".repeat(50));
+        assert_eq!(marked(&line, "ab", 6), "…xx[ab] [a]…");
+    }
+
+    #[test]
+    fn cuts_fall_between_multibyte_characters() {
+        let line = format!("{}Grüße{}", "ä".repeat(50), "日本".repeat(50));
+        assert_eq!(marked(&line, "GRÜßE", 11), "…äää[Grüße]日本日…");
+        assert_eq!(marked("🦀🦀needle🦀🦀", "needle", 8), "…🦀[needle]🦀…");
+        // 'İ' takes two bytes, and lowercases to two characters.
+        assert_eq!(marked("İİneedle", "needle", 7), "…İ[needle]");
+        assert_eq!(marked("İİneedle", "i̇", 3), "[İ][İ]n…");
+    }
+
+    fn tree(name: &str) -> TempDir {
+        let root = TempDir::new(name);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join("config.toml"), "[server]\nport = 8080\n").unwrap();
+        fs::write(
+            root.join("src").join("main.rs"),
+            "fn main() {\n    let port = 1;\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join(".git").join("config"), "port\n").unwrap();
+        fs::write(root.join("empty.txt"), "").unwrap();
+        // Has the query in it, but isn't text.
+        fs::write(root.join("app.bin"), b"\x7fELF\0\0port\0").unwrap();
+        fs::write(root.join("data.bin"), b"port\0\0\x01\x02").unwrap();
+        root
+    }
+
+    fn search(
+        root: &Path,
+        show_hidden: bool,
+        max_matches: usize,
+    ) -> (Vec<(String, usize)>, ContentOutcome) {
+        let found = Mutex::new(Vec::new());
+        let outcome = search_contents(
+            root,
+            &plain("port"),
+            show_hidden,
+            &AtomicBool::new(false),
+            max_matches,
+            |matches| {
+                found.lock().unwrap().extend(matches);
+            },
+        );
+        let mut found: Vec<_> = found
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|m| {
+                (
+                    m.path
+                        .strip_prefix(root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/"),
+                    m.line_number,
+                )
+            })
+            .collect();
+        found.sort();
+        (found, outcome)
+    }
+
+    #[test]
+    fn searches_text_files_under_a_folder() {
+        let root = tree("content_search");
+        let (found, outcome) = search(&root, false, MAX_MATCHES);
+        assert_eq!(
+            found,
+            [("config.toml".to_owned(), 2), ("src/main.rs".to_owned(), 2)]
+        );
+        assert!(!outcome.truncated);
+        assert!(outcome.errors.is_empty());
+
+        let (found, _) = search(&root, true, MAX_MATCHES);
+        assert_eq!(found.len(), 3);
+    }
+
+    #[test]
+    fn stops_after_enough_matches() {
+        let root = tree("content_search_max");
+        fs::write(root.join("many.txt"), "port\n".repeat(50)).unwrap();
+
+        let (found, outcome) = search(&root, false, 10);
+        assert_eq!(found.len(), 10);
+        assert!(outcome.truncated);
+
+        let (found, outcome) = search(&root, false, 51);
+        assert_eq!(found.len(), 51);
+        assert!(outcome.truncated);
+
+        // All there is, exactly.
+        let (found, outcome) = search(&root, false, 52);
+        assert_eq!(found.len(), 52);
+        assert!(!outcome.truncated);
+    }
+
+    #[test]
+    fn canceled_search_finds_nothing() {
+        let root = tree("content_search_canceled");
+        let outcome = search_contents(
+            &root,
+            &plain("port"),
+            true,
+            &AtomicBool::new(true),
+            MAX_MATCHES,
+            |_| {
+                panic!("nothing should be found");
+            },
+        );
+        assert_eq!(outcome.visited, 0);
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The delete confirmation.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub enum DeleteDialog {
+    #[default]
+    Closed,
+
+    /// Waiting for the user to confirm deleting `paths`.
+    Confirming {
+        paths: Vec<PathBuf>,
+
+        /// Remove for good instead of moving to the trash.
+        permanently: bool,
+    },
+}
+
+impl DeleteDialog {
+    /// Asks to delete `paths`. Does nothing if there's nothing to delete.
+    pub fn open(&mut self, paths: Vec<PathBuf>) {
+        if !paths.is_empty() {
+            *self = Self::Confirming {
+                paths,
+                permanently: false,
+            };
+        }
+    }
+
+    pub fn close(&mut self) {
+        *self = Self::Closed;
+    }
+
+    /// The user confirmed: returns what to delete and whether to do it permanently, and closes.
+    pub fn confirm(&mut self) -> Option<(Vec<PathBuf>, bool)> {
+        match std::mem::take(self) {
+            Self::Confirming { paths, permanently } => Some((paths, permanently)),
+            Self::Closed => None,
+        }
+    }
+}
+
+/// Moves each of `paths` to the trash (or removes it, if `permanently`).
+///
+/// Keeps going after a failure; returns a message for each item that couldn't be deleted.
+pub fn delete_paths(paths: &[PathBuf], permanently: bool) -> Vec<String> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let result = if permanently {
+                remove(path)
+            } else {
+                trash::delete(path).map_err(|err| err.to_string())
+            };
+            result.err().map(|err| format!("{}: {err}", path.display()))
+        })
+        .collect()
+}
+
+/// Removes `path` for good, along with everything in it if it's a folder.
+pub fn remove(path: &Path) -> Result<(), String> {
+    // Don't follow symlinks to folders: delete the link, not what it points to.
+    let is_dir = fs::symlink_metadata(path)
+        .map_err(|err| err.to_string())?
+        .is_dir();
+    if is_dir {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+    .map_err(|err| err.to_string())
+}
+
+/// The entry to select once `deleted` entries are gone from `order`:
+/// the first one after the last deleted entry, or failing that the closest one before it.
+pub fn next_selection(order: &[PathBuf], deleted: &HashSet<PathBuf>) -> Option<PathBuf> {
+    let last_deleted = order.iter().rposition(|path| deleted.contains(path))?;
+    order[last_deleted + 1..]
+        .iter()
+        .chain(order[..last_deleted].iter().rev())
+        .find(|path| !deleted.contains(*path))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn dialog_goes_from_confirming_to_closed() {
+        let mut dialog = DeleteDialog::default();
+        dialog.open(Vec::new());
+        assert_eq!(dialog, DeleteDialog::Closed);
+        assert_eq!(dialog.confirm(), None);
+
+        dialog.open(vec![PathBuf::from("/a")]);
+        if let DeleteDialog::Confirming { permanently, .. } = &mut dialog {
+            *permanently = true;
+        }
+        assert_eq!(dialog.confirm(), Some((vec![PathBuf::from("/a")], true)));
+        assert_eq!(dialog, DeleteDialog::Closed);
+        assert_eq!(dialog.confirm(), None);
+    }
+
+    #[test]
+    fn cancel_keeps_nothing() {
+        let mut dialog = DeleteDialog::default();
+        dialog.open(vec![PathBuf::from("/a")]);
+        dialog.close();
+        assert_eq!(dialog.confirm(), None);
+    }
+
+    #[test]
+    fn selection_moves_to_the_next_row() {
+        let order: Vec<PathBuf> = ["/a", "/b", "/c", "/d"].map(PathBuf::from).to_vec();
+        let deleted = |paths: &[&str]| paths.iter().map(PathBuf::from).collect();
+
+        assert_eq!(
+            next_selection(&order, &deleted(&["/b"])),
+            Some(PathBuf::from("/c"))
+        );
+        assert_eq!(
+            next_selection(&order, &deleted(&["/a", "/c"])),
+            Some(PathBuf::from("/d"))
+        );
+        assert_eq!(
+            next_selection(&order, &deleted(&["/c", "/d"])),
+            Some(PathBuf::from("/b"))
+        );
+        assert_eq!(
+            next_selection(&order, &deleted(&["/a", "/b", "/c", "/d"])),
+            None
+        );
+        assert_eq!(next_selection(&order, &deleted(&["/elsewhere"])), None);
+    }
+
+    #[test]
+    fn permanently_deletes_files_and_nested_folders() {
+        let dir = TempDir::new("delete");
+        fs::create_dir_all(dir.join("folder/nested")).unwrap();
+        fs::write(dir.join("folder/nested/file.txt"), "").unwrap();
+        fs::write(dir.join("file.txt"), "").unwrap();
+        fs::write(dir.join("kept.txt"), "").unwrap();
+
+        let paths = [
+            dir.join("folder"),
+            dir.join("missing.txt"),
+            dir.join("file.txt"),
+        ];
+        let errors = delete_paths(&paths, true);
+
+        // The missing file is reported, but doesn't stop the rest.
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("missing.txt"), "{errors:?}");
+        assert!(!dir.join("folder").exists());
+        assert!(!dir.join("file.txt").exists());
+        assert!(dir.join("kept.txt").exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn permanently_deleting_a_symlink_keeps_its_target() {
+        let dir = TempDir::new("delete_link");
+        fs::create_dir_all(dir.join("target")).unwrap();
+        fs::write(dir.join("target/file.txt"), "").unwrap();
+        std::os::unix::fs::symlink(dir.join("target"), dir.join("link")).unwrap();
+
+        assert!(delete_paths(&[dir.join("link")], true).is_empty());
+        assert!(dir.join("target/file.txt").exists());
+    }
+}
@@ -0,0 +1,357 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use egui::{Color32, Rgba};
+
+use crate::size_format::{format_size_with_base, SizeBase};
+
+/// How often the devices are listed again, so media plugged in or out shows up.
+pub const DEVICES_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the space on the volume of the current folder is checked again, besides
+/// whenever the folder changes.
+pub const SPACE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// File systems on the network, which don't have a device in `/dev`.
+const NETWORK_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "fuse.sshfs",
+    "fuse.rclone",
+];
+
+/// Where the system keeps what only it needs; mounts in there aren't listed.
+const SYSTEM_DIRS: &[&str] = &[
+    "/boot", "/dev", "/efi", "/etc", "/proc", "/snap", "/sys", "/var",
+];
+
+/// A line of `/proc/mounts`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mount {
+    pub device: String,
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+}
+
+/// The mounts listed in `text`, in the format of `/proc/mounts` (or `/etc/mtab`): the
+/// device, the mount point and the file system type, then options, separated by spaces.
+///
+/// Spaces, tabs, newlines and backslashes in the first two are escaped in octal, like
+/// `\040`. Lines that are cut short are skipped.
+pub fn parse_mounts(text: &str) -> Vec<Mount> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = unescape(fields.next()?);
+            let mount_point = PathBuf::from(unescape(fields.next()?));
+            let fs_type = fields.next()?.to_owned();
+            Some(Mount {
+                device,
+                mount_point,
+                fs_type,
+            })
+        })
+        .collect()
+}
+
+fn unescape(field: &str) -> String {
+    let mut bytes = Vec::with_capacity(field.len());
+    let mut rest = field.as_bytes();
+    while let Some((&byte, after)) = rest.split_first() {
+        if let (b'\\', [high @ b'0'..=b'3', mid @ b'0'..=b'7', low @ b'0'..=b'7', after @ ..]) =
+            (byte, after)
+        {
+            bytes.push((high - b'0') * 64 + (mid - b'0') * 8 + (low - b'0'));
+            rest = after;
+        } else {
+            bytes.push(byte);
+            rest = after;
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// The mounts of `mounts` worth going to: disks, partitions, USB sticks and network shares,
+/// but not what the system mounts for itself (`proc`, `tmpfs`, snaps, `/boot` and such).
+///
+/// A mount point mounted over again is listed once, where it was first mounted.
+pub fn device_mounts(mounts: Vec<Mount>) -> Vec<Mount> {
+    let mut devices: Vec<Mount> = Vec::new();
+    for mount in mounts {
+        let is_disk = mount.device.starts_with("/dev/") && !mount.device.starts_with("/dev/loop");
+        let is_network = NETWORK_TYPES.contains(&mount.fs_type.as_str());
+        let is_system = SYSTEM_DIRS
+            .iter()
+            .any(|dir| mount.mount_point.starts_with(dir));
+        let is_listed = devices
+            .iter()
+            .any(|device| device.mount_point == mount.mount_point);
+        if (is_disk || is_network) && !is_system && !is_listed {
+            devices.push(mount);
+        }
+    }
+    devices
+}
+
+/// How big a volume is, and how much of it is free.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiskSpace {
+    pub total: u64,
+    /// What the user can still use, which on Unix leaves out what's reserved for root.
+    pub free: u64,
+}
+
+impl DiskSpace {
+    /// How much of the volume is in use, from 0 to 1.
+    pub fn used_fraction(&self) -> f32 {
+        self.total.saturating_sub(self.free) as f32 / self.total.max(1) as f32
+    }
+}
+
+/// Like "123.4 GB free of 512.0 GB", or that it's unknown.
+pub fn free_space_text(space: Option<DiskSpace>, base: SizeBase) -> String {
+    match space {
+        Some(space) => format!(
+            "{} free of {}",
+            format_size_with_base(Some(space.free), base),
+            format_size_with_base(Some(space.total), base)
+        ),
+        None => "Free space unknown".to_owned(),
+    }
+}
+
+/// The color of a bar showing `used` of a volume, from green when it's empty to red when
+/// it's full, blended in linear space so it passes through yellow rather than brown.
+pub fn usage_color(used: f32) -> Color32 {
+    let used = used.clamp(0.0, 1.0);
+    (Rgba::from(Color32::GREEN) * (1.0 - used) + Rgba::from(Color32::RED) * used).into()
+}
+
+/// The space on the volume that `path` is on: `statvfs` on Unix, `GetDiskFreeSpaceExW` on
+/// Windows.
+pub fn disk_space(path: &Path) -> io::Result<DiskSpace> {
+    Ok(DiskSpace {
+        total: fs2::total_space(path)?,
+        free: fs2::available_space(path)?,
+    })
+}
+
+/// A drive or mount point in the "Devices" section of the sidebar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Device {
+    pub name: String,
+    pub path: PathBuf,
+    /// What's mounted there, like `/dev/sdb1` or `server:/export`, where that's known.
+    pub source: Option<String>,
+    /// Unknown if the file system doesn't say.
+    pub space: Option<DiskSpace>,
+}
+
+impl Device {
+    fn new(path: PathBuf, source: Option<String>) -> Self {
+        let name = path.file_name().map_or_else(
+            || path.to_string_lossy().into_owned(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        let space = disk_space(&path).ok();
+        Self {
+            name,
+            path,
+            source,
+            space,
+        }
+    }
+}
+
+/// The drives and mount points there are now. This asks each of them how much space is
+/// free, which can take a while for network shares, so it's best done in the background.
+pub fn devices() -> Vec<Device> {
+    roots()
+        .into_iter()
+        .filter(|(path, _)| path.is_dir())
+        .map(|(path, source)| Device::new(path, source))
+        .collect()
+}
+
+#[cfg(windows)]
+fn roots() -> Vec<(PathBuf, Option<String>)> {
+    (b'A'..=b'Z')
+        .map(|letter| (PathBuf::from(format!("{}:\\", letter as char)), None))
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn roots() -> Vec<(PathBuf, Option<String>)> {
+    if let Ok(text) = std::fs::read_to_string("/proc/mounts") {
+        return device_mounts(parse_mounts(&text))
+            .into_iter()
+            .map(|mount| (mount.mount_point, Some(mount.device)))
+            .collect();
+    }
+
+    // No /proc (e.g. macOS): the usual places drives are mounted in.
+    let mut mount_points = vec![PathBuf::from("/mnt"), PathBuf::from("/Volumes")];
+    if let Some(user) = std::env::var_os("USER") {
+        mount_points.push(Path::new("/media").join(&user));
+        mount_points.push(Path::new("/run/media").join(&user));
+    }
+    let mut roots: Vec<PathBuf> = mount_points
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .collect();
+    roots.sort();
+    roots.insert(0, PathBuf::from("/"));
+    roots.into_iter().map(|root| (root, None)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// From a desktop with a USB stick plugged in, abridged.
+    const DESKTOP: &str = "\
+sysfs /sys sysfs rw,nosuid,nodev,noexec,relatime 0 0
+proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0
+udev /dev devtmpfs rw,nosuid,relatime,size=8029916k,nr_inodes=2007479,mode=755 0 0
+devpts /dev/pts devpts rw,nosuid,noexec,relatime,gid=5,mode=620,ptmxmode=000 0 0
+tmpfs /run tmpfs rw,nosuid,nodev,noexec,relatime,size=1612384k,mode=755 0 0
+/dev/nvme0n1p2 / ext4 rw,relatime,errors=remount-ro 0 0
+securityfs /sys/kernel/security securityfs rw,nosuid,nodev,noexec,relatime 0 0
+tmpfs /dev/shm tmpfs rw,nosuid,nodev 0 0
+cgroup2 /sys/fs/cgroup cgroup2 rw,nosuid,nodev,noexec,relatime,nsdelegate 0 0
+/dev/loop0 /snap/core22/1380 squashfs ro,nodev,relatime,errors=continue 0 0
+/dev/loop1 /snap/firefox/4336 squashfs ro,nodev,relatime,errors=continue 0 0
+/dev/nvme0n1p1 /boot/efi vfat rw,relatime,fmask=0077,dmask=0077,codepage=437 0 0
+/dev/nvme0n1p3 /home ext4 rw,relatime 0 0
+tmpfs /run/user/1000 tmpfs rw,nosuid,nodev,relatime,size=1612380k,mode=700,uid=1000,gid=1000 0 0
+gvfsd-fuse /run/user/1000/gvfs fuse.gvfsd-fuse rw,nosuid,nodev,relatime,user_id=1000,group_id=1000 0 0
+/dev/sdb1 /media/me/USB\\040STICK vfat rw,nosuid,nodev,relatime,uid=1000,gid=1000 0 0
+nas:/export/photos /mnt/photos nfs4 rw,relatime,vers=4.2,rsize=1048576 0 0
+";
+
+    /// From inside a container, abridged.
+    const CONTAINER: &str = "\
+overlay / overlay rw,relatime,lowerdir=/var/lib/docker/overlay2/l/ABC:/var/lib/docker/overlay2/l/DEF 0 0
+proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0
+tmpfs /dev tmpfs rw,nosuid,size=65536k,mode=755 0 0
+/dev/vda1 /etc/hosts ext4 rw,relatime 0 0
+/dev/vda1 /data ext4 rw,relatime 0 0
+/dev/vda1 /data ext4 ro,relatime 0 0
+";
+
+    fn mount_points(text: &str) -> Vec<PathBuf> {
+        device_mounts(parse_mounts(text))
+            .into_iter()
+            .map(|mount| mount.mount_point)
+            .collect()
+    }
+
+    #[test]
+    fn mounts_are_parsed() {
+        let mounts = parse_mounts(DESKTOP);
+        assert_eq!(mounts.len(), 17);
+        assert_eq!(
+            mounts[5],
+            Mount {
+                device: "/dev/nvme0n1p2".to_owned(),
+                mount_point: PathBuf::from("/"),
+                fs_type: "ext4".to_owned(),
+            }
+        );
+        assert_eq!(mounts[15].mount_point, PathBuf::from("/media/me/USB STICK"));
+    }
+
+    #[test]
+    fn escapes_are_decoded() {
+        assert_eq!(unescape(r"USB\040STICK"), "USB STICK");
+        assert_eq!(unescape(r"tab\011new\012line"), "tab\tnew\nline");
+        assert_eq!(unescape(r"back\134slash"), r"back\slash");
+        // Not escapes: too short, or not octal.
+        assert_eq!(unescape(r"end\04"), r"end\04");
+        assert_eq!(unescape(r"a\089b"), r"a\089b");
+        assert_eq!(unescape(r"a\777b"), r"a\777b");
+        assert_eq!(unescape("Grüße\\040dir"), "Grüße dir");
+    }
+
+    #[test]
+    fn short_lines_are_skipped() {
+        let mounts = parse_mounts("/dev/sda1 /mnt\n\n/dev/sdb1 /media/x vfat rw 0 0\n");
+        assert_eq!(mounts.len(), 1);
+        assert_eq!(mounts[0].device, "/dev/sdb1");
+    }
+
+    #[test]
+    fn only_devices_are_listed() {
+        assert_eq!(
+            mount_points(DESKTOP),
+            [
+                PathBuf::from("/"),
+                PathBuf::from("/home"),
+                PathBuf::from("/media/me/USB STICK"),
+                PathBuf::from("/mnt/photos"),
+            ]
+        );
+    }
+
+    #[test]
+    fn mounts_in_a_container_are_listed_once() {
+        // The root is an overlay, which isn't a device, and /etc/hosts is only the host's.
+        assert_eq!(mount_points(CONTAINER), [PathBuf::from("/data")]);
+    }
+
+    #[test]
+    fn devices_are_named_after_their_folder() {
+        let device = Device::new(PathBuf::from("/media/me/USB STICK"), None);
+        assert_eq!(device.name, "USB STICK");
+        let root = Device::new(PathBuf::from("/"), None);
+        assert_eq!(root.name, "/");
+    }
+
+    #[test]
+    fn free_space_is_described() {
+        let space = DiskSpace {
+            total: 512_000_000_000,
+            free: 123_400_000_000,
+        };
+        assert_eq!(
+            free_space_text(Some(space), SizeBase::Decimal),
+            "123.4 GB free of 512.0 GB"
+        );
+        assert_eq!(
+            free_space_text(None, SizeBase::Binary),
+            "Free space unknown"
+        );
+        assert!((space.used_fraction() - 0.758_984_4).abs() < 1e-6);
+        // A volume that says it's empty isn't divided by.
+        let empty = DiskSpace { total: 0, free: 0 };
+        assert_eq!(empty.used_fraction(), 0.0);
+    }
+
+    #[test]
+    fn usage_goes_from_green_to_red() {
+        assert_eq!(usage_color(0.0), Color32::GREEN);
+        assert_eq!(usage_color(1.0), Color32::RED);
+        // Out of range is clamped.
+        assert_eq!(usage_color(-1.0), Color32::GREEN);
+        assert_eq!(usage_color(2.0), Color32::RED);
+
+        // Halfway is yellow, as bright as it gets in both.
+        let half = usage_color(0.5);
+        assert_eq!(half.r(), half.g());
+        assert!(half.r() > 128 && half.b() == 0, "{half:?}");
+        assert!(usage_color(0.9).r() > usage_color(0.9).g());
+    }
+
+    #[test]
+    fn space_is_known_for_the_temp_dir() {
+        let space = disk_space(&std::env::temp_dir()).unwrap();
+        assert!(space.total > 0);
+        assert!(space.free <= space.total);
+    }
+}
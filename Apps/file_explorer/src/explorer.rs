@@ -1,21 +1,72 @@
 use crate::egui::Button;
 use eframe::egui;
-use rayon::prelude::*;
+use std::collections::{BTreeSet, HashSet};
 use std::fs;
 use std::fs::metadata;
 use std::path::{Path, PathBuf};
-use std::sync::{mpsc, Arc, Mutex};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use egui::{Color32, Context, Style, TextEdit, Ui};
-use crate::list::list_explorer;
+use std::time::SystemTime;
+use egui::{Color32, Context, TextEdit, Ui};
+use crate::address_bar::AddressBar;
+use crate::archive::{
+    crumb_label, extract, is_zip, list_archive, location_exists, parse_archive_path, preview, ArchiveEntry,
+    ArchivePath, READ_ONLY,
+};
+use crate::bookmarks::{default_locations, Bookmark, Bookmarks};
+use crate::content_search::{search_contents, ContentMatch, MAX_MATCHES};
+use crate::checksum::{normalize_digest, Algorithm};
+use crate::delete::{delete_paths, next_selection, DeleteDialog};
+use crate::devices::{devices, disk_space, free_space_text, usage_color, Device, DiskSpace, DEVICES_INTERVAL, SPACE_INTERVAL};
+use crate::file_type::FileTypes;
+use crate::grid::{grid_explorer, step_in_grid, GridStep, ViewMode};
+use crate::hidden::is_hidden;
+use crate::history::{NavHistory, Step};
+use crate::icons::{IconTheme, IconThemeEditor};
+use crate::launch::{open_in_editor, open_terminal, open_with_default_app};
+use crate::list::{drop_target, file_type_line, list_explorer};
+use crate::loader::Loader;
+use crate::new_folder::{self, NewFolder};
+use crate::paste::{
+    collisions, drop_moves, paste, Clipboard, ClipboardMode, Collision, PasteDialog, PasteUpdate,
+};
+use crate::permissions::{mode_string, parse_mode, set_mode, Owner, OwnerNames, Permissions, PermissionsDialog};
+use crate::properties::{calculate_checksums, ChecksumState, PropertiesDialog};
+use crate::read_errors::{ReadError, ReadErrors};
+use crate::recent::{Recent, MAX_RECENT_FOLDERS};
+use crate::rename::{rename, Rename, RenameError};
+use crate::search::{result_location, search_streaming, split_matches, Matcher, SearchDebounce, SearchMode};
+use crate::selection::{clamp_cursor, range_select, step_cursor};
+use crate::settings::ExplorerSettings;
+use crate::size_cache::{SizeCache, MAX_SAVED_ENTRIES};
+use crate::size_format::{format_size_with_base, SizeBase};
+use crate::size_mode::{FileMeta, HardLinks, SizeMode};
+use crate::size_state::{FolderSize, SizeProgress, SizeState};
+use crate::statistics::{chart_ui, folder_statistics, Statistics};
+use crate::sort::{sort_entries, Entry};
+use crate::status::{group_thousands, Summary};
+use crate::style_file::{default_style, style_path, StyleFile, SAVE_DELAY};
+use crate::thumbnails::Thumbnails;
+use crate::time_format::format_time;
+use crate::toasts::Toasts;
+use crate::type_ahead::{find_prefix, TypeAhead};
+use crate::type_filter::{extension_counts, FileGroup, TypeFilter};
+use crate::watcher::{DirWatcher, DEBOUNCE};
 
 #[derive(Debug, Clone)]
 pub struct Folder {
     pub dir: String,
     pub name: String,
-    pub size: Arc<Mutex<Option<u64>>>,
-    pub calculating: Arc<Mutex<bool>>,
-    pub error: Arc<Mutex<Option<String>>>,
+    /// Shared with the calculation running in the background, if any.
+    pub size: Arc<Mutex<SizeState>>,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    /// Where the folder really is, if this is a symbolic link to it.
+    pub link_target: Option<PathBuf>,
+    /// Unknown if the folder's metadata couldn't be read.
+    pub permissions: Option<Permissions>,
+    /// Only known on Unix.
+    pub owner: Option<Owner>,
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +74,14 @@ pub struct File {
     pub dir: String,
     pub name: String,
     pub size: Option<u64>,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    /// What the link points to, if this is a symbolic link, which may not exist.
+    pub link_target: Option<PathBuf>,
+    /// Unknown if the file's metadata couldn't be read.
+    pub permissions: Option<Permissions>,
+    /// Only known on Unix.
+    pub owner: Option<Owner>,
 }
 
 impl Default for Folder {
@@ -30,9 +89,12 @@ impl Default for Folder {
         Self {
             dir: String::new(),
             name: String::new(),
-            size: Arc::new(Mutex::new(None)),
-            calculating: Arc::new(Mutex::new(false)),
-            error: Arc::new(Mutex::new(None)),
+            size: Arc::new(Mutex::new(SizeState::NotCalculated)),
+            modified: None,
+            created: None,
+            link_target: None,
+            permissions: None,
+            owner: None,
         }
     }
 }
@@ -43,186 +105,2087 @@ impl Default for File {
             dir: String::new(),
             name: String::new(),
             size: None,
+            modified: None,
+            created: None,
+            link_target: None,
+            permissions: None,
+            owner: None,
         }
     }
 }
 
+/// The contents of a folder (or search results), as loaded in the background.
+#[derive(Default)]
+pub struct Listing {
+    pub directories: Vec<Folder>,
+    pub files: Vec<File>,
+    /// The folders that couldn't be listed.
+    pub errors: ReadErrors,
+    /// The lines found by a search in contents.
+    pub matches: Vec<ContentMatch>,
+    /// The search in contents stopped after [`MAX_MATCHES`].
+    pub truncated: bool,
+}
+
+impl Listing {
+    fn from_paths(paths: Vec<PathBuf>) -> Self {
+        let mut listing = Self::default();
+
+        for path in paths {
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            // Any of these may fail (e.g. `created` on some Linux file systems); show them as unknown.
+            let meta = metadata(&path).ok();
+            let modified = meta.as_ref().and_then(|m| m.modified().ok());
+            let created = meta.as_ref().and_then(|m| m.created().ok());
+            let permissions = meta.as_ref().map(Permissions::of);
+            let owner = meta.as_ref().and_then(Owner::of);
+            // A broken link is listed as a file whose size is unknown.
+            let is_link = fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_symlink());
+            let link_target = is_link.then(|| fs::read_link(&path).ok()).flatten();
+
+            if path.is_dir() {
+                let dir_path = path.to_string_lossy().to_string();
+                let folder = Folder {
+                    dir: dir_path,
+                    name,
+                    size: Arc::new(Mutex::new(SizeState::NotCalculated)),
+                    modified,
+                    created,
+                    link_target,
+                    permissions,
+                    owner,
+                };
+
+                listing.directories.push(folder);
+            } else {
+                let file = File {
+                    dir: path.to_string_lossy().to_string(),
+                    name,
+                    size: meta.map(|m| m.len()),
+                    modified,
+                    created,
+                    link_target,
+                    permissions,
+                    owner,
+                };
+                listing.files.push(file);
+            }
+        }
+
+        listing
+    }
+
+    /// The `entries` of the folder `dir` of an archive, whose folders know their sizes already.
+    fn from_archive(dir: &ArchivePath, entries: Vec<ArchiveEntry>) -> Self {
+        let mut listing = Self::default();
+        for entry in entries {
+            let path = dir.join(&entry.name).to_string();
+            if entry.is_dir {
+                let size = FolderSize {
+                    bytes: entry.size,
+                    ..FolderSize::default()
+                };
+                listing.directories.push(Folder {
+                    dir: path,
+                    name: entry.name,
+                    size: Arc::new(Mutex::new(SizeState::Done(size))),
+                    modified: entry.modified,
+                    ..Folder::default()
+                });
+            } else {
+                listing.files.push(File {
+                    dir: path,
+                    name: entry.name,
+                    size: Some(entry.size),
+                    modified: entry.modified,
+                    ..File::default()
+                });
+            }
+        }
+        listing
+    }
+}
+
 pub struct FileBrowserApp {
     pub current_path: String,
+    /// The files of [`Self::listed_files`] that [`Self::type_filter`] lets through.
     pub files: Vec<File>,
+    /// Every file loaded, before the type filter.
+    pub listed_files: Vec<File>,
+    pub type_filter: TypeFilter,
+    /// Keep [`Self::type_filter`] when going to another folder.
+    pub pin_type_filter: bool,
     pub directories: Vec<Folder>,
-    pub selected: File,
+    /// The folders and files in the order they're shown, see [`Self::visible_order`].
+    pub entries: Vec<Entry>,
+    /// Show all folders before the files, rather than mixed in by name.
+    pub group_folders_first: bool,
+    pub selection: BTreeSet<PathBuf>,
+    /// The last entry clicked without Shift, where Shift-click ranges start.
+    pub selection_anchor: Option<PathBuf>,
+    /// The row of [`Self::visible_order`] the arrow keys move from.
+    pub cursor: Option<usize>,
+    /// Scroll the table to [`Self::cursor`] the next time it's shown.
+    pub scroll_to_cursor: bool,
+    /// How many rows fit in the table (or the grid), which is how far PageUp and PageDown move.
+    pub page_rows: usize,
+    /// Whether the entries are shown as a table or as tiles.
+    pub view_mode: ViewMode,
+    /// How many tiles fit side by side in the grid, which is how far Up and Down move in it.
+    pub grid_columns: usize,
+    pub type_ahead: TypeAhead,
+    pub delete_dialog: DeleteDialog,
+    /// Deletes in the background; each result is what couldn't be deleted.
+    pub delete_loader: Loader<Vec<String>>,
     pub search: String,
     pub previous_search: String,
+    /// Starts the search once typing in the search box pauses.
+    pub search_debounce: SearchDebounce,
+    pub search_mode: SearchMode,
+    /// Why the current query can't be searched for, e.g. an invalid regex.
+    pub search_error: Option<String>,
+    /// What the listing was searched for, so the matching part of each name can be highlighted.
+    pub highlight: Option<Matcher>,
+    /// Search inside text files rather than their names.
+    pub search_contents: bool,
+    /// Whether the search being loaded is one in contents.
+    content_search: bool,
+    /// The lines found by a search in contents, shown instead of the listing while there is one.
+    pub content_matches: Option<Vec<ContentMatch>>,
+    /// The search in contents stopped after [`MAX_MATCHES`], so there may be more.
+    pub content_truncated: bool,
+    /// Show dotfiles (Unix) and entries with the hidden attribute (Windows).
+    pub show_hidden: bool,
+    /// The folder being named in the inline row at the top of the table, if any.
+    pub new_folder: Option<NewFolder>,
+    /// The entry whose name is being edited in place, if any.
+    pub renaming: Option<Rename>,
+    /// Selected once it shows up in the listing, e.g. a folder that was just created.
+    pub pending_selection: Option<PathBuf>,
     pub selected_option: Option<usize>,
     pub settings: bool,
+    pub address_bar: AddressBar,
+    pub loader: Loader<Listing>,
+    /// What was copied or cut, to be pasted into [`Self::current_path`].
+    pub clipboard: Option<Clipboard>,
+    pub paste_dialog: PasteDialog,
+    pub paste_loader: Loader<PasteUpdate>,
+    pub thumbnails: Thumbnails,
+    /// What files are going by their contents, sniffed when their popup opens.
+    pub file_types: FileTypes,
+    /// The icons of files, with the user's own for some extensions.
+    pub icon_theme: IconTheme,
+    pub icon_theme_editor: IconThemeEditor,
+    /// Home and the usual folders in it, listed above [`Self::devices`].
+    pub locations: Vec<Bookmark>,
+    /// The drives and mount points, listed above [`Self::bookmarks`].
+    pub devices: Vec<Device>,
+    pub devices_loader: Loader<Vec<Device>>,
+    /// When [`Self::devices`] were last listed, to list them again after [`DEVICES_INTERVAL`].
+    pub devices_listed: Option<std::time::Instant>,
+    /// The space on the volume of the current folder, unknown if it couldn't be had.
+    pub disk_space: Option<DiskSpace>,
+    pub disk_space_loader: Loader<Option<DiskSpace>>,
+    /// The folder [`Self::disk_space`] was last checked for, and when, to check it again
+    /// when either changes too much.
+    pub disk_space_checked: Option<(String, std::time::Instant)>,
+    /// Show the "Statistics" window.
+    pub show_statistics: bool,
+    /// Count the files in every folder under [`Self::current_path`] too, not just the listing.
+    pub statistics_recursive: bool,
+    /// What's in the current listing by category, or `None` until it's been counted.
+    pub statistics: Option<Statistics>,
+    pub statistics_loader: Loader<Statistics>,
+    pub bookmarks: Bookmarks,
+    pub watcher: DirWatcher,
+    /// The current load is a [`Self::refresh`], so the selection is kept where possible.
+    pub refreshing: bool,
+    pub history: NavHistory,
+    /// The folders visited lately, also saved between sessions, see [`Self::open_recent`].
+    pub recent_folders: Recent<PathBuf>,
+    /// Folder sizes calculated so far, also saved between sessions.
+    pub size_cache: Arc<Mutex<SizeCache>>,
+    /// Whether sizes are shown in KiB (1024) or KB (1000).
+    pub size_base: SizeBase,
+    /// Whether folder sizes add up the files' lengths or the disk space they take.
+    pub size_mode: SizeMode,
+    /// Count what symbolic links to folders point to when calculating sizes,
+    /// rather than just the links.
+    pub follow_links: bool,
+    /// Show the "Permissions" column of the table.
+    pub show_permissions_column: bool,
+    /// Show the "Owner" column of the table.
+    pub show_owner_column: bool,
+    pub owner_names: OwnerNames,
+    pub permissions_dialog: PermissionsDialog,
+    pub properties_dialog: PropertiesDialog,
+    /// Saves the style changed in the settings, and loads it on start.
+    pub style_file: StyleFile,
+    /// Why the style couldn't be saved.
+    pub style_error: Option<String>,
+    /// What went wrong lately, and other notices, shown in the corner for a while.
+    pub toasts: Toasts,
+    /// See [`ExplorerSettings::terminal_command`].
+    pub terminal_command: String,
+    /// See [`ExplorerSettings::editor_command`].
+    pub editor_command: String,
+    /// Quote what "Copy path" and "Copy name" copy, so it can be pasted into a shell.
+    pub quote_copied_paths: bool,
+    /// Follows the style, which can also be changed in the style settings.
+    pub dark_mode: bool,
+    /// The folders of the listing (or search) that couldn't be read.
+    pub read_errors: ReadErrors,
+    /// The archive being browsed, if [`Self::current_path`] is inside one.
+    pub archive: Option<ArchivePath>,
+    /// The entry of an archive shown in the "Preview" window, or why it can't be.
+    pub archive_preview: Option<(ArchivePath, Result<String, String>)>,
+    pub extract_loader: Loader<Result<PathBuf, String>>,
+    /// Where the last entry extracted from an archive went.
+    pub extracted: Option<PathBuf>,
 }
 
 impl Default for FileBrowserApp {
     fn default() -> Self {
-        let start_path = if cfg!(target_os = "windows") {
-            "C:\\".to_string()
-        } else {
-            "/".to_string()
-        };
+        let start_path = root_path();
 
         let mut app = Self {
             current_path: start_path.clone(),
             files: Vec::new(),
+            listed_files: Vec::new(),
+            type_filter: TypeFilter::All,
+            pin_type_filter: false,
             directories: Vec::new(),
-            selected: File::default(),
+            entries: Vec::new(),
+            group_folders_first: true,
+            selection: BTreeSet::new(),
+            selection_anchor: None,
+            cursor: None,
+            scroll_to_cursor: false,
+            page_rows: 1,
+            view_mode: ViewMode::default(),
+            grid_columns: 1,
+            type_ahead: TypeAhead::default(),
+            delete_dialog: DeleteDialog::default(),
+            delete_loader: Loader::default(),
             search: String::new(),
             previous_search: String::new(),
+            search_debounce: SearchDebounce::default(),
+            search_mode: SearchMode::default(),
+            search_error: None,
+            highlight: None,
+            search_contents: false,
+            content_search: false,
+            content_matches: None,
+            content_truncated: false,
+            show_hidden: false,
+            new_folder: None,
+            renaming: None,
+            pending_selection: None,
             selected_option: None,
             settings: false,
+            address_bar: AddressBar::default(),
+            loader: Loader::default(),
+            clipboard: None,
+            paste_dialog: PasteDialog::default(),
+            paste_loader: Loader::default(),
+            thumbnails: Thumbnails::new(
+                eframe::storage_dir(APP_ID).map(|dir| dir.join("thumbnails")),
+            ),
+            file_types: FileTypes::default(),
+            icon_theme: IconTheme::default(),
+            icon_theme_editor: IconThemeEditor::default(),
+            locations: default_locations(),
+            devices: Vec::new(),
+            devices_loader: Loader::default(),
+            devices_listed: None,
+            disk_space: None,
+            disk_space_loader: Loader::default(),
+            disk_space_checked: None,
+            show_statistics: false,
+            statistics_recursive: false,
+            statistics: None,
+            statistics_loader: Loader::default(),
+            bookmarks: Bookmarks::default(),
+            watcher: DirWatcher::default(),
+            refreshing: false,
+            history: NavHistory::default(),
+            recent_folders: Recent::new(MAX_RECENT_FOLDERS),
+            size_cache: Arc::default(),
+            size_base: SizeBase::default(),
+            size_mode: SizeMode::default(),
+            follow_links: false,
+            show_permissions_column: false,
+            show_owner_column: false,
+            owner_names: OwnerNames::load(),
+            permissions_dialog: PermissionsDialog::default(),
+            properties_dialog: PropertiesDialog::default(),
+            style_file: StyleFile::new(style_path()),
+            style_error: None,
+            toasts: Toasts::default(),
+            terminal_command: ExplorerSettings::default().terminal_command,
+            editor_command: ExplorerSettings::default().editor_command,
+            quote_copied_paths: false,
+            dark_mode: false,
+            read_errors: ReadErrors::default(),
+            archive: None,
+            archive_preview: None,
+            extract_loader: Loader::default(),
+            extracted: None,
         };
         app.update_directory_list(&start_path);
         app
     }
-}
+}
+
+/// Name of the app, which is also where eframe keeps its files.
+pub const APP_ID: &str = "File Browser";
+
+/// [`eframe::Storage`] key of [`FileBrowserApp::bookmarks`].
+const BOOKMARKS_KEY: &str = "bookmarks";
+
+/// With Ctrl, these open the first nine of [`FileBrowserApp::recent_elsewhere`].
+const RECENT_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
+/// [`eframe::Storage`] key of [`FileBrowserApp::recent_folders`].
+const RECENT_FOLDERS_KEY: &str = "recent_folders";
+
+/// [`eframe::Storage`] key of [`FileBrowserApp::size_cache`].
+const SIZE_CACHE_KEY: &str = "size_cache";
+
+impl FileBrowserApp {
+    /// Restores the settings saved by [`eframe::App::save`], if any.
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self::default();
+        if let Some(storage) = cc.storage {
+            app.apply_settings(ExplorerSettings::load(storage));
+        }
+        cc.egui_ctx.set_visuals(egui::theme_visuals(app.dark_mode));
+        let bookmarks = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, BOOKMARKS_KEY));
+        if let Some(bookmarks) = bookmarks {
+            app.bookmarks = bookmarks;
+        }
+        let recent_folders = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, RECENT_FOLDERS_KEY));
+        if let Some(recent_folders) = recent_folders {
+            app.recent_folders = Recent::from_items(recent_folders, MAX_RECENT_FOLDERS);
+        }
+        app.recent_folders.add(PathBuf::from(&app.current_path));
+        let size_cache = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, SIZE_CACHE_KEY));
+        if let Some(size_cache) = size_cache {
+            app.size_cache = Arc::new(Mutex::new(size_cache));
+        }
+        app.update_directory_list(&app.current_path.clone());
+        app
+    }
+
+    /// The options to save, see [`Self::apply_settings`].
+    pub fn saved_settings(&self) -> ExplorerSettings {
+        ExplorerSettings {
+            current_path: Some(self.current_path.clone()),
+            show_hidden: self.show_hidden,
+            size_base: self.size_base,
+            size_mode: self.size_mode,
+            follow_links: self.follow_links,
+            show_permissions_column: self.show_permissions_column,
+            show_owner_column: self.show_owner_column,
+            view_mode: self.view_mode,
+            group_folders_first: self.group_folders_first,
+            terminal_command: self.terminal_command.clone(),
+            editor_command: self.editor_command.clone(),
+            quote_copied_paths: self.quote_copied_paths,
+            icon_overrides: self.icon_theme.overrides().clone(),
+            dark_mode: self.dark_mode,
+            ..Default::default()
+        }
+    }
+
+    /// Takes on the saved `settings`, going home instead if the saved folder is gone.
+    pub fn apply_settings(&mut self, settings: ExplorerSettings) {
+        self.current_path = restored_path(settings.current_path, home_path());
+        self.show_hidden = settings.show_hidden;
+        self.size_base = settings.size_base;
+        self.size_mode = settings.size_mode;
+        self.follow_links = settings.follow_links;
+        self.show_permissions_column = settings.show_permissions_column;
+        self.show_owner_column = settings.show_owner_column;
+        self.view_mode = settings.view_mode;
+        self.group_folders_first = settings.group_folders_first;
+        self.terminal_command = settings.terminal_command;
+        self.editor_command = settings.editor_command;
+        self.quote_copied_paths = settings.quote_copied_paths;
+        self.icon_theme = IconTheme::new(settings.icon_overrides);
+        self.dark_mode = settings.dark_mode;
+    }
+
+    /// Shows the contents of the folder at `path`, clearing the search.
+    pub fn open_folder(&mut self, path: &Path) {
+        self.history.visit(Path::new(&self.current_path), path);
+        self.show_folder(path);
+    }
+
+    /// [`Self::open_folder`] without recording it in [`Self::history`].
+    fn show_folder(&mut self, path: &Path) {
+        if !self.pin_type_filter {
+            self.type_filter = TypeFilter::All;
+        }
+        self.current_path = path.to_string_lossy().into_owned();
+        self.recent_folders.add(path.to_owned());
+        self.search = String::new();
+        self.update_directory_list(&self.current_path.clone());
+    }
+
+    pub fn go_back(&mut self) {
+        let step = self.history.back(Path::new(&self.current_path), location_exists);
+        self.take_step(step);
+    }
+
+    pub fn go_forward(&mut self) {
+        let step = self.history.forward(Path::new(&self.current_path), location_exists);
+        self.take_step(step);
+    }
+
+    /// The [`Self::recent_folders`] other than the current one, which Ctrl+1 to Ctrl+9 open.
+    pub fn recent_elsewhere(&self) -> Vec<PathBuf> {
+        let current = Path::new(&self.current_path);
+        self.recent_folders.items().iter().filter(|path| *path != current).cloned().collect()
+    }
+
+    /// Opens the folder at `index` of [`Self::recent_elsewhere`], or forgets it if it's gone.
+    pub fn open_recent(&mut self, index: usize) {
+        let Some(path) = self.recent_elsewhere().into_iter().nth(index) else {
+            return;
+        };
+        if location_exists(&path) {
+            self.open_folder(&path);
+        } else {
+            self.recent_folders.remove(&path);
+            self.toasts.error(format!("{} no longer exists", path.display()));
+        }
+    }
+
+    /// The menu of [`Self::recent_elsewhere`], next to Back and Forward.
+    fn recent_menu_ui(&mut self, ui: &mut Ui) {
+        let recent = self.recent_elsewhere();
+        let mut open = None;
+        ui.add_enabled_ui(!recent.is_empty(), |ui| {
+            ui.menu_button("🕓", |ui| {
+                for (index, path) in recent.iter().enumerate() {
+                    let name = Bookmark::new(path.clone()).name;
+                    let mut button = Button::new(name);
+                    if index < RECENT_KEYS.len() {
+                        button = button.shortcut_text(format!("Ctrl+{}", index + 1));
+                    }
+                    if ui.add(button).on_hover_text(path.display().to_string()).clicked() {
+                        open = Some(index);
+                        ui.close_menu();
+                    }
+                }
+            })
+            .response
+            .on_hover_text("Recent folders");
+        });
+        if let Some(index) = open {
+            self.open_recent(index);
+        }
+    }
+
+    fn take_step(&mut self, step: Option<Step>) {
+        match step {
+            Some(Step::Go(path)) => self.show_folder(&path),
+            Some(Step::Missing(path)) => {
+                self.toasts.error(format!("{} no longer exists", path.display()));
+            }
+            None => {}
+        }
+    }
+
+    /// Starts loading `path` (filtered by `search`) in the background, canceling any earlier load.
+    ///
+    /// The current listing stays on screen until [`Self::poll_directory_list`] receives
+    /// the first part of the new one.
+    ///
+    /// A query that doesn't compile (see [`Self::search_error`]) leaves the listing as it is.
+    pub(crate) fn update_directory_list(&mut self, path: &str) {
+        self.archive = parse_archive_path(path);
+        let matcher = match Matcher::new(self.search_mode, &self.search) {
+            Ok(matcher) => matcher,
+            Err(err) => {
+                self.loader.cancel();
+                self.search_error = Some(err);
+                return;
+            }
+        };
+        self.search_error = None;
+        self.refreshing = false;
+        self.highlight = (!self.search.is_empty()).then(|| matcher.clone());
+
+        let sender = self.loader.start();
+        let dirpath = Path::new(path).to_owned();
+        let show_hidden = self.show_hidden;
+
+        // Only names are searched in archives, and only in the folder shown.
+        if let Some(archive) = self.archive.clone() {
+            self.content_search = false;
+            thread::spawn(move || {
+                match list_archive(&archive) {
+                    Ok(mut entries) => {
+                        entries.retain(|entry| {
+                            matcher.is_match(&entry.name) && (show_hidden || !is_hidden(&entry.name, false, cfg!(windows)))
+                        });
+                        sender.send(Listing::from_archive(&archive, entries));
+                    }
+                    Err(err) => {
+                        let mut errors = ReadErrors::default();
+                        errors.push(ReadError::new(&dirpath, &err));
+                        sender.send(Listing {
+                            errors,
+                            ..Listing::default()
+                        });
+                    }
+                }
+                sender.finish();
+            });
+            return;
+        }
+
+        self.content_search = self.search_contents && !self.search.is_empty();
+        if self.content_search {
+            thread::spawn(move || {
+                let outcome = search_contents(&dirpath, &matcher, show_hidden, sender.canceled(), MAX_MATCHES, |matches| {
+                    sender.send(Listing {
+                        matches,
+                        ..Listing::default()
+                    });
+                });
+                sender.send(Listing {
+                    errors: outcome.errors,
+                    truncated: outcome.truncated,
+                    ..Listing::default()
+                });
+                sender.finish();
+            });
+            return;
+        }
+        thread::spawn(move || {
+            let outcome = search_streaming(&dirpath, &matcher, show_hidden, sender.canceled(), |batch| {
+                sender.send(Listing::from_paths(batch));
+            });
+            if !outcome.errors.is_empty() {
+                sender.send(Listing {
+                    errors: outcome.errors,
+                    ..Listing::default()
+                });
+            }
+            sender.finish();
+        });
+    }
+
+    /// Shows whatever [`Self::update_directory_list`] has found so far.
+    pub(crate) fn poll_directory_list(&mut self) {
+        let Some(update) = self.loader.poll() else {
+            return;
+        };
+        if update.is_first {
+            self.directories.clear();
+            self.files.clear();
+            self.listed_files.clear();
+            self.entries.clear();
+            self.read_errors.clear();
+            self.content_matches = self.content_search.then(Vec::new);
+            self.content_truncated = false;
+            if !self.refreshing {
+                self.selection.clear();
+                self.selection_anchor = None;
+                self.cursor = None;
+                self.renaming = None;
+                self.thumbnails.clear();
+            }
+        }
+        let (folder_count, file_count) = (self.directories.len(), self.files.len());
+        for listing in update.results {
+            self.directories.extend(listing.directories);
+            let shown = listing.files.iter().filter(|file| self.type_filter.matches(&file.name));
+            self.files.extend(shown.cloned());
+            self.listed_files.extend(listing.files);
+            self.read_errors.extend(listing.errors);
+            if let Some(matches) = &mut self.content_matches {
+                matches.extend(listing.matches);
+            }
+            self.content_truncated |= listing.truncated;
+        }
+        self.add_entries(folder_count, file_count);
+        self.statistics = None;
+        self.statistics_loader.cancel();
+
+        if self.refreshing && !self.loader.is_loading() {
+            // Let go of whatever is gone now.
+            let order = self.visible_order();
+            self.selection.retain(|path| order.contains(path));
+            if self.selection_anchor.as_ref().is_some_and(|path| !order.contains(path)) {
+                self.selection_anchor = None;
+            }
+            if self.renaming.as_ref().is_some_and(|rename| !order.contains(&rename.path)) {
+                self.renaming = None;
+            }
+            self.cursor = clamp_cursor(self.cursor, order.len());
+            self.refreshing = false;
+        }
+
+        if let Some(path) = &self.pending_selection {
+            if let Some(index) = self.visible_order().iter().position(|p| p == path) {
+                self.selection = BTreeSet::from([path.clone()]);
+                self.selection_anchor = Some(path.clone());
+                self.cursor = Some(index);
+                self.scroll_to_cursor = true;
+                self.pending_selection = None;
+            } else if !self.loader.is_loading() {
+                self.pending_selection = None;
+            }
+        }
+    }
+
+    /// Shows only the files `filter` lets through, without loading the folder again.
+    pub fn set_type_filter(&mut self, filter: TypeFilter) {
+        self.type_filter = filter;
+        self.files = self
+            .listed_files
+            .iter()
+            .filter(|file| self.type_filter.matches(&file.name))
+            .cloned()
+            .collect();
+        self.entries.clear();
+        self.add_entries(0, 0);
+
+        // Hidden entries can't stay selected.
+        let order = self.visible_order();
+        self.selection.retain(|path| order.contains(path));
+        self.cursor = clamp_cursor(self.cursor, order.len());
+    }
+
+    /// Adds the folders from `folder_count` and the files from `file_count` on to
+    /// [`Self::entries`], sorted in among the ones already there.
+    fn add_entries(&mut self, folder_count: usize, file_count: usize) {
+        let added = (folder_count..self.directories.len())
+            .map(Entry::Folder)
+            .chain((file_count..self.files.len()).map(Entry::File));
+        let count = self.entries.len();
+        self.entries.extend(added);
+        if self.entries.len() > count {
+            self.sort_entries();
+        }
+    }
+
+    /// Sorts [`Self::entries`] again, e.g. after [`Self::group_folders_first`] changed.
+    pub fn sort_entries(&mut self) {
+        let (directories, files) = (&self.directories, &self.files);
+        let name = |entry| match entry {
+            Entry::Folder(index) => directories[index].name.as_str(),
+            Entry::File(index) => files[index].name.as_str(),
+        };
+        sort_entries(&mut self.entries, name, self.group_folders_first);
+    }
+
+    /// The path of `entry` of [`Self::entries`].
+    fn entry_path(&self, entry: Entry) -> &str {
+        match entry {
+            Entry::Folder(index) => &self.directories[index].dir,
+            Entry::File(index) => &self.files[index].dir,
+        }
+    }
+
+    /// The type filter dropdown, with the groups and the extensions in the listing.
+    fn type_filter_ui(&mut self, ui: &mut Ui) {
+        let mut chosen = None;
+        egui::ComboBox::from_id_source("type_filter")
+            .selected_text(self.type_filter.label())
+            .show_ui(ui, |ui| {
+                let mut option = |ui: &mut Ui, filter: TypeFilter, label: String| {
+                    if ui.selectable_label(self.type_filter == filter, label).clicked() {
+                        chosen = Some(filter);
+                    }
+                };
+                option(ui, TypeFilter::All, TypeFilter::All.label());
+                for group in FileGroup::ALL {
+                    option(ui, TypeFilter::Group(group), format!("{} {}", group.icon(), group.label()));
+                }
+                ui.separator();
+                let names = self.listed_files.iter().map(|file| file.name.as_str());
+                for (extension, count) in extension_counts(names) {
+                    let label = format!(".{extension} ({count})");
+                    option(ui, TypeFilter::Extension(extension), label);
+                }
+            });
+        if let Some(filter) = chosen {
+            self.set_type_filter(filter);
+        }
+
+        ui.toggle_value(&mut self.pin_type_filter, "📌")
+            .on_hover_text("Keep the filter in other folders");
+        if self.type_filter != TypeFilter::All {
+            ui.label(format!(
+                "{} of {} shown",
+                group_thousands(self.files.len() as u64),
+                group_thousands(self.listed_files.len() as u64)
+            ));
+        }
+    }
+
+    /// A banner above the listing saying how many folders couldn't be read, opening to which and why.
+    fn read_errors_ui(&self, ui: &mut Ui) {
+        let Some(summary) = self.read_errors.summary() else {
+            return;
+        };
+        egui::CollapsingHeader::new(egui::RichText::new(format!("⚠ {summary}")).color(Color32::RED))
+            .id_source("read_errors")
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical().max_height(100.0).show(ui, |ui| {
+                    for error in &self.read_errors.0 {
+                        ui.label(format!("{}: {}", error.path.display(), error.kind));
+                    }
+                });
+            });
+    }
+
+    /// Says that the archive being browsed can't be changed, and where the last entry
+    /// extracted from it went.
+    fn archive_banner_ui(&mut self, ui: &mut Ui) {
+        let Some(archive) = &self.archive else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("📦 {}", archive.archive.display())).strong());
+            ui.label(egui::RichText::new(format!("🔒 {READ_ONLY}")).color(Color32::YELLOW));
+            if self.extract_loader.is_loading() {
+                ui.add(egui::Spinner::new());
+                ui.label("Extracting…");
+            }
+        });
+        if let Some(path) = self.extracted.clone() {
+            ui.horizontal(|ui| {
+                ui.label(format!("Extracted to {}", path.display()));
+                if ui.small_button("📂").on_hover_text("Reveal in folder").clicked() {
+                    self.extracted = None;
+                    self.reveal(&path);
+                }
+            });
+        }
+    }
+
+    /// The lines found by a search in contents, with what matched highlighted. Clicking one
+    /// opens its file, and 📂 goes to the folder it's in.
+    fn content_results_ui(&mut self, ui: &mut Ui) {
+        let Some(matches) = &self.content_matches else {
+            return;
+        };
+        if self.content_truncated {
+            let notice = format!("Stopped after {MAX_MATCHES} matches; search a smaller folder or for more to see the rest");
+            ui.label(egui::RichText::new(notice).color(Color32::YELLOW));
+        }
+        if matches.is_empty() && !self.loader.is_loading() {
+            ui.label("Nothing found");
+            return;
+        }
+
+        let root = Path::new(&self.current_path);
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        let row_height = ui.spacing().interact_size.y;
+        let mut open = None;
+        let mut reveal = None;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .show_rows(ui, row_height, matches.len(), |ui, rows| {
+                for found in &matches[rows] {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("📂").on_hover_text("Reveal in folder").clicked() {
+                            reveal = Some(found.path.clone());
+                        }
+                        let name = found.path.file_name().unwrap_or_default().to_string_lossy();
+                        let location = match result_location(root, &found.path).as_str() {
+                            "." => format!("{name}:{}", found.line_number),
+                            location => format!("{location}/{name}:{}", found.line_number),
+                        };
+                        if ui.link(location).on_hover_text(found.path.display().to_string()).clicked() {
+                            open = Some(found.path.clone());
+                        }
+
+                        let mut job = egui::text::LayoutJob::default();
+                        for (segment, matched) in split_matches(&found.excerpt, &found.ranges) {
+                            let mut format = egui::text::TextFormat::simple(font_id.clone(), ui.visuals().text_color());
+                            if matched {
+                                format.background = ui.visuals().selection.bg_fill;
+                            }
+                            job.append(segment, 0.0, format);
+                        }
+                        ui.add(egui::Label::new(job).truncate());
+                    });
+                }
+            });
+
+        if let Some(Err(err)) = open.map(|path| open_with_default_app(&path)) {
+            self.toasts.error(err);
+        }
+        if let Some(path) = reveal {
+            self.reveal(&path);
+        }
+    }
+
+    /// Lists the drives and mount points again in the background, so media plugged in or out
+    /// shows up (or goes away) in the sidebar.
+    pub fn list_devices(&mut self) {
+        self.devices_listed = Some(std::time::Instant::now());
+        let sender = self.devices_loader.start();
+        thread::spawn(move || {
+            sender.send(devices());
+            sender.finish();
+        });
+    }
+
+    pub(crate) fn poll_devices(&mut self, ctx: &Context) {
+        let now = std::time::Instant::now();
+        let due = !self.devices_listed.is_some_and(|listed| now < listed + DEVICES_INTERVAL);
+        if due && !self.devices_loader.is_loading() {
+            self.list_devices();
+        }
+        if let Some(mut update) = self.devices_loader.poll() {
+            if let Some(devices) = update.results.pop() {
+                self.devices = devices;
+            }
+        }
+        if let Some(listed) = self.devices_listed {
+            ctx.request_repaint_after((listed + DEVICES_INTERVAL).saturating_duration_since(now));
+        }
+    }
+
+    /// Checks the space on the volume of the current folder in the background when the folder
+    /// changes, and every [`SPACE_INTERVAL`] while it doesn't.
+    pub(crate) fn poll_disk_space(&mut self, ctx: &Context) {
+        let now = std::time::Instant::now();
+        let due = !self
+            .disk_space_checked
+            .as_ref()
+            .is_some_and(|(path, checked)| *path == self.current_path && now < *checked + SPACE_INTERVAL);
+        if due && !self.disk_space_loader.is_loading() {
+            self.disk_space_checked = Some((self.current_path.clone(), now));
+            let path = PathBuf::from(&self.current_path);
+            let sender = self.disk_space_loader.start();
+            thread::spawn(move || {
+                // Unsupported file systems, and folders inside archives, just show it's unknown.
+                sender.send(disk_space(&path).ok());
+                sender.finish();
+            });
+        }
+        if let Some(mut update) = self.disk_space_loader.poll() {
+            if let Some(space) = update.results.pop() {
+                self.disk_space = space;
+            }
+        }
+        if let Some((_, checked)) = self.disk_space_checked {
+            ctx.request_repaint_after((checked + SPACE_INTERVAL).saturating_duration_since(now));
+        }
+    }
+
+    /// Counts what's in the listing by category for [`Self::statistics`], once it's loaded
+    /// and only while the window is open; in the background when that includes subfolders.
+    pub(crate) fn poll_statistics(&mut self) {
+        if let Some(mut update) = self.statistics_loader.poll() {
+            if let Some(statistics) = update.results.pop() {
+                self.statistics = Some(statistics);
+            }
+        }
+        if !self.show_statistics || self.statistics.is_some() || self.statistics_loader.is_loading() || self.loader.is_loading() {
+            return;
+        }
+        if self.statistics_recursive && self.archive.is_none() {
+            let sender = self.statistics_loader.start();
+            let dir = PathBuf::from(&self.current_path);
+            let mode = self.size_mode;
+            thread::spawn(move || {
+                if let Some(statistics) = folder_statistics(&dir, mode, sender.canceled()) {
+                    sender.send(statistics);
+                }
+                sender.finish();
+            });
+        } else {
+            self.statistics = Some(Statistics::of(&self.listed_files));
+        }
+    }
+
+    fn statistics_ui(&mut self, ctx: &Context) {
+        if !self.show_statistics {
+            return;
+        }
+        let mut open = true;
+        let mut recount = false;
+        egui::Window::new("📊 Statistics").open(&mut open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let recursive = ui
+                    .add_enabled(self.archive.is_none(), egui::Checkbox::new(&mut self.statistics_recursive, "Include subfolders"))
+                    .on_hover_text("Count the files in every folder under this one, like folder sizes are");
+                recount |= recursive.changed();
+                recount |= ui.small_button("⟳").on_hover_text("Count again").clicked();
+            });
+            match &self.statistics {
+                Some(statistics) => chart_ui(ui, statistics, self.size_base),
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::Spinner::new());
+                        ui.label("Counting…");
+                    });
+                }
+            }
+        });
+        if recount {
+            self.statistics = None;
+            self.statistics_loader.cancel();
+        }
+        self.show_statistics = open;
+    }
+
+    /// Reloads the listing after it changed on disk, keeping the selection of entries that are still there.
+    pub fn refresh(&mut self) {
+        self.update_directory_list(&self.current_path.clone());
+        self.refreshing = true;
+    }
+
+    /// Shows the inline row for naming a new folder in [`Self::current_path`].
+    pub fn start_new_folder(&mut self) {
+        if self.read_only() {
+            return;
+        }
+        self.new_folder = Some(NewFolder::new(Path::new(&self.current_path)));
+    }
+
+    /// Creates the folder `name` in [`Self::current_path`] and reloads the listing with it selected.
+    ///
+    /// On failure the inline row stays open, showing the error.
+    pub fn create_folder(&mut self, name: &str) {
+        match new_folder::create(Path::new(&self.current_path), name) {
+            Ok(path) => {
+                self.new_folder = None;
+                self.pending_selection = Some(path);
+                self.search.clear();
+                self.previous_search.clear();
+                self.update_directory_list(&self.current_path.clone());
+            }
+            Err(err) => {
+                if let Some(new_folder) = &mut self.new_folder {
+                    new_folder.error = Some(err);
+                }
+            }
+        }
+    }
+
+    /// Renames the entry of [`Self::renaming`] and reloads the listing with it selected.
+    ///
+    /// If the new name is taken, asks for confirmation first; renaming again with `overwrite`
+    /// replaces the existing entry. Errors are toasts, leaving the name to be fixed.
+    pub fn commit_rename(&mut self, overwrite: bool) {
+        let Some(renaming) = &mut self.renaming else {
+            return;
+        };
+        match rename(&renaming.path, &renaming.name, overwrite) {
+            Ok(path) => {
+                self.renaming = None;
+                self.pending_selection = Some(path);
+                self.update_directory_list(&self.current_path.clone());
+            }
+            Err(RenameError::Exists(_)) => {
+                renaming.confirm_overwrite = true;
+            }
+            Err(RenameError::Failed(err)) => {
+                renaming.confirm_overwrite = false;
+                self.toasts.error(err);
+            }
+        }
+    }
+
+    /// The text of the entry of an archive opened with Enter or "Preview".
+    fn archive_preview_ui(&mut self, ctx: &Context) {
+        let Some((entry, text)) = &self.archive_preview else {
+            return;
+        };
+        let mut open = true;
+        let mut extract = None;
+        egui::Window::new(format!("👁 {}", entry.name().unwrap_or_default()))
+            .id(egui::Id::new("archive_preview"))
+            .open(&mut open)
+            .default_size([500.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(entry.to_string()).weak());
+                    if ui.button("📤 Extract").on_hover_text("Next to the archive").clicked() {
+                        extract = Some(entry.clone());
+                    }
+                });
+                match text {
+                    Ok(text) => {
+                        egui::ScrollArea::both().show(ui, |ui| {
+                            ui.add(TextEdit::multiline(&mut text.as_str()).code_editor().desired_width(f32::INFINITY));
+                        });
+                    }
+                    Err(err) => {
+                        ui.label(egui::RichText::new(err).color(Color32::RED));
+                    }
+                }
+            });
+        if let Some(entry) = extract {
+            self.extract_entry(entry);
+        }
+        if !open {
+            self.archive_preview = None;
+        }
+    }
+
+    /// The nine permission bits of an entry as checkboxes, applied like `chmod`.
+    fn permissions_dialog_ui(&mut self, ctx: &Context) {
+        let PermissionsDialog::Editing { path, mode, text, error } = &mut self.permissions_dialog else {
+            return;
+        };
+        let mut apply = false;
+        let mut close = false;
+        egui::Window::new("Permissions")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(path.to_string_lossy());
+                egui::Grid::new("permission_bits").show(ui, |ui| {
+                    ui.label("");
+                    for header in ["Read", "Write", "Execute"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    for (class, shift) in [("Owner", 6), ("Group", 3), ("Others", 0)] {
+                        ui.label(class);
+                        for bit in [0o4, 0o2, 0o1] {
+                            let bit = bit << shift;
+                            let mut set = *mode & bit != 0;
+                            if ui.checkbox(&mut set, "").changed() {
+                                *mode ^= bit;
+                                *text = mode_string(*mode);
+                                *error = None;
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let edit = egui::TextEdit::singleline(text)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(80.0);
+                    if ui.add(edit).changed() {
+                        match parse_mode(text) {
+                            Ok(parsed) => {
+                                *mode = (*mode & !0o7777) | parsed;
+                                *error = None;
+                            }
+                            Err(err) => *error = Some(err),
+                        }
+                    }
+                    ui.monospace(format!("({:o})", *mode & 0o7777));
+                });
+                if let Some(error) = error.as_ref() {
+                    ui.label(egui::RichText::new(error).color(Color32::RED));
+                }
+                ui.horizontal(|ui| {
+                    apply = ui.button("Apply").clicked();
+                    close = ui.button("Cancel").clicked();
+                });
+            });
+        if apply {
+            match parse_mode(text).and_then(|parsed| set_mode(path, parsed)) {
+                Ok(()) => {
+                    self.permissions_dialog.close();
+                    self.refresh();
+                }
+                Err(err) => *error = Some(err),
+            }
+        } else if close {
+            self.permissions_dialog.close();
+        }
+    }
+
+    /// Everything about a file at once, and its checksums on demand to verify a download.
+    fn properties_dialog_ui(&mut self, ctx: &Context) {
+        let PropertiesDialog::Open { file, checksums, expected } = &mut self.properties_dialog else {
+            return;
+        };
+        // The window also needs the rest of the app, to sniff the type and name the owner.
+        let (file, checksums, mut expected) = (file.clone(), checksums.clone(), std::mem::take(expected));
+        let mut close = false;
+        let now = SystemTime::now();
+        egui::Window::new("Properties")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.strong(&file.name);
+                egui::Grid::new("file_properties").num_columns(2).show(ui, |ui| {
+                    ui.label("Path");
+                    ui.add(egui::Label::new(&file.dir).wrap());
+                    ui.end_row();
+                    ui.label("Size");
+                    ui.label(format_size_with_base(file.size, self.size_base));
+                    ui.end_row();
+                    ui.label("Modified");
+                    ui.label(format_time(file.modified, now));
+                    ui.end_row();
+                    ui.label("Created");
+                    ui.label(format_time(file.created, now));
+                    ui.end_row();
+                    ui.label("Permissions");
+                    ui.label(file.permissions.map_or_else(|| "Unknown".to_owned(), Permissions::label));
+                    ui.end_row();
+                    if let Some(owner) = file.owner {
+                        ui.label("Owner");
+                        ui.label(format!("{}:{}", self.owner_names.user(owner.uid), self.owner_names.group(owner.gid)));
+                        ui.end_row();
+                    }
+                });
+                file_type_line(self, ui, &file);
+
+                ui.separator();
+                let state = checksums.lock().unwrap().clone();
+                match state {
+                    ChecksumState::NotCalculated | ChecksumState::Canceled | ChecksumState::Failed(_) => {
+                        if let ChecksumState::Failed(error) = &state {
+                            ui.label(egui::RichText::new(format!("Couldn't read the file: {error}")).color(Color32::RED));
+                        }
+                        if ui.button("Calculate checksums").clicked() {
+                            calculate_checksums(ctx, PathBuf::from(&file.dir), &checksums);
+                        }
+                    }
+                    ChecksumState::Calculating(progress) => {
+                        let total = file.size.unwrap_or(0);
+                        let fraction = if total == 0 { 0.0 } else { progress.bytes() as f32 / total as f32 };
+                        ui.add(
+                            egui::ProgressBar::new(fraction.min(1.0))
+                                .text(format!(
+                                    "{} / {}",
+                                    format_size_with_base(Some(progress.bytes()), self.size_base),
+                                    format_size_with_base(file.size, self.size_base)
+                                ))
+                                .desired_width(300.0),
+                        );
+                        if ui.button("Cancel").clicked() {
+                            checksums.lock().unwrap().cancel(ChecksumState::Canceled);
+                        }
+                        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                    }
+                    ChecksumState::Done(digests) => {
+                        egui::Grid::new("file_checksums").num_columns(2).show(ui, |ui| {
+                            for algorithm in Algorithm::ALL {
+                                ui.label(algorithm.label());
+                                let mut digest = digests.get(algorithm);
+                                ui.add(
+                                    TextEdit::singleline(&mut digest)
+                                        .font(egui::TextStyle::Monospace)
+                                        .desired_width(470.0),
+                                );
+                                ui.end_row();
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Verify");
+                            ui.add(TextEdit::singleline(&mut expected).hint_text("Paste a checksum").desired_width(300.0));
+                            if !normalize_digest(&expected).is_empty() {
+                                match digests.matching(&expected) {
+                                    Some(algorithm) => ui.label(
+                                        egui::RichText::new(format!("✔ Matches the {}", algorithm.label())).color(Color32::GREEN),
+                                    ),
+                                    None => ui.label(egui::RichText::new("✖ Doesn't match").color(Color32::RED)),
+                                };
+                            }
+                        });
+                    }
+                }
+
+                close = ui.button("Close").clicked();
+            });
+        if close {
+            self.properties_dialog.close();
+        } else if let PropertiesDialog::Open { expected: kept, .. } = &mut self.properties_dialog {
+            *kept = expected;
+        }
+    }
+
+    fn delete_dialog_ui(&mut self, ctx: &Context) {
+        let DeleteDialog::Confirming { paths, permanently } = &mut self.delete_dialog else {
+            return;
+        };
+        let mut confirmed = false;
+        let mut close = false;
+        egui::Window::new("Delete")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let target = if *permanently { "permanently" } else { "to the trash" };
+                ui.label(format!("Delete {} item(s) {target}?", paths.len()));
+                egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                    for path in paths.iter() {
+                        ui.label(path.to_string_lossy());
+                    }
+                });
+                ui.checkbox(permanently, "Delete permanently (can't be undone)");
+                ui.horizontal(|ui| {
+                    confirmed = ui.button("Delete").clicked();
+                    close = ui.button("Cancel").clicked();
+                });
+            });
+        if close {
+            self.delete_dialog.close();
+        }
+        if confirmed {
+            self.delete_confirmed();
+        }
+    }
+
+    /// Paths of all listed entries, in the order they are displayed (see [`Self::entries`]).
+    pub fn visible_order(&self) -> Vec<PathBuf> {
+        self.entries
+            .iter()
+            .map(|&entry| PathBuf::from(self.entry_path(entry)))
+            .collect()
+    }
+
+    /// Updates the selection after a click on the row at `index` of [`Self::visible_order`].
+    ///
+    /// `toggle` (Ctrl-click) adds or removes a single entry, `range` (Shift-click) selects
+    /// everything between the last clicked row and this one, as they are displayed now.
+    pub fn click_select(&mut self, index: usize, toggle: bool, range: bool) {
+        let order = self.visible_order();
+        let Some(path) = order.get(index) else {
+            return;
+        };
+
+        self.cursor = Some(index);
+        if range {
+            let range = range_select(&order, self.selection_anchor.as_ref(), index);
+            self.selection = range.into_iter().collect();
+        } else if toggle {
+            if !self.selection.remove(path) {
+                self.selection.insert(path.clone());
+            }
+            self.selection_anchor = Some(path.clone());
+        } else {
+            self.selection = BTreeSet::from([path.clone()]);
+            self.selection_anchor = Some(path.clone());
+        }
+    }
+
+    /// Moves the keyboard cursor `delta` rows up or down, selecting the row it lands on.
+    pub fn move_cursor(&mut self, delta: isize) {
+        let len = self.entries.len();
+        if let Some(index) = step_cursor(self.cursor, delta, len) {
+            self.select_row(index);
+        }
+    }
+
+    /// Puts the keyboard cursor on the row at `index`, selects it, and scrolls to it.
+    pub fn select_row(&mut self, index: usize) {
+        let order = self.visible_order();
+        let Some(path) = order.get(index) else {
+            return;
+        };
+        self.cursor = Some(index);
+        self.selection = BTreeSet::from([path.clone()]);
+        self.selection_anchor = Some(path.clone());
+        self.scroll_to_cursor = true;
+    }
+
+    /// Moves through the list with the keyboard, while nothing else wants it.
+    fn list_keys_ui(&mut self, ctx: &Context) {
+        use egui::{Key, Modifiers};
+
+        let len = self.entries.len();
+        match self.view_mode {
+            ViewMode::List => {
+                for (key, delta) in [(Key::ArrowUp, -1), (Key::ArrowDown, 1)] {
+                    if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, key)) {
+                        self.move_cursor(delta);
+                    }
+                }
+            }
+            ViewMode::Grid => {
+                let steps = [
+                    (Key::ArrowLeft, GridStep::Left),
+                    (Key::ArrowRight, GridStep::Right),
+                    (Key::ArrowUp, GridStep::Up),
+                    (Key::ArrowDown, GridStep::Down),
+                ];
+                for (key, step) in steps {
+                    if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, key)) {
+                        if let Some(index) = step_in_grid(self.cursor, step, self.grid_columns, len) {
+                            self.select_row(index);
+                        }
+                    }
+                }
+            }
+        }
+        // A page of the grid is as many rows of tiles as fit.
+        let page = match self.view_mode {
+            ViewMode::List => self.page_rows as isize,
+            ViewMode::Grid => (self.page_rows * self.grid_columns) as isize,
+        };
+        for (key, delta) in [(Key::PageUp, -page), (Key::PageDown, page)] {
+            if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, key)) {
+                self.move_cursor(delta);
+            }
+        }
+        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Home)) {
+            self.select_row(0);
+        }
+        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::End)) && len > 0 {
+            self.select_row(len - 1);
+        }
+
+        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Enter)) {
+            self.open_cursor();
+        } else if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Backspace)) {
+            if let Some(parent) = Path::new(&self.current_path).parent().map(Path::to_path_buf) {
+                self.open_folder(&parent);
+            }
+        }
+
+        let typed: String = ctx.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Text(text) if !i.modifiers.command => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect()
+        });
+        if !typed.is_empty() {
+            let prefix = self.type_ahead.push(&typed, std::time::Instant::now());
+            let names = self.entries.iter().map(|&entry| match entry {
+                Entry::Folder(index) => self.directories[index].name.as_str(),
+                Entry::File(index) => self.files[index].name.as_str(),
+            });
+            if let Some(index) = find_prefix(names, prefix) {
+                self.select_row(index);
+            }
+        }
+    }
+
+    /// Opens the folder or file under the keyboard cursor (Enter).
+    pub fn open_cursor(&mut self) {
+        let Some(index) = self.cursor else {
+            return;
+        };
+        match self.entries.get(index) {
+            Some(&Entry::Folder(index)) => {
+                let path = PathBuf::from(&self.directories[index].dir);
+                self.open_folder(&path);
+            }
+            Some(&Entry::File(index)) => {
+                let path = PathBuf::from(&self.files[index].dir);
+                self.open_file(&path);
+            }
+            None => {}
+        }
+    }
+
+    /// Opens the file at `path` with its default app, except that a zip archive is browsed
+    /// like a folder and the files in one are previewed.
+    pub fn open_file(&mut self, path: &Path) {
+        if let Some(entry) = parse_archive_path(&path.to_string_lossy()) {
+            self.preview_entry(entry);
+        } else if is_zip(path) {
+            self.open_folder(&ArchivePath::root(path).to_path());
+        } else if let Err(err) = open_with_default_app(path) {
+            self.toasts.error(err);
+        }
+    }
+
+    /// Whether [`Self::current_path`] is in an archive, which can't be changed; if so,
+    /// says that instead of doing what was asked.
+    pub fn read_only(&mut self) -> bool {
+        if self.archive.is_some() {
+            self.toasts.warning(READ_ONLY);
+        }
+        self.archive.is_some()
+    }
+
+    /// Shows the file at `entry` of an archive in the "Preview" window.
+    pub fn preview_entry(&mut self, entry: ArchivePath) {
+        let text = preview(&entry).map_err(|err| err.to_string());
+        self.archive_preview = Some((entry, text));
+    }
+
+    /// Unpacks `entry` of an archive next to the archive, in the background.
+    pub fn extract_entry(&mut self, entry: ArchivePath) {
+        let Some(dest_dir) = entry.archive.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        self.extracted = None;
+        let sender = self.extract_loader.start();
+        thread::spawn(move || {
+            sender.send(extract(&entry, &dest_dir).map_err(|err| err.to_string()));
+            sender.finish();
+        });
+    }
+
+    pub(crate) fn poll_extract(&mut self) {
+        let Some(update) = self.extract_loader.poll() else {
+            return;
+        };
+        for result in update.results {
+            match result {
+                Ok(path) => self.extracted = Some(path),
+                Err(err) => self.toasts.error(err),
+            }
+        }
+    }
+
+    /// The folder searched in, while the listing shows search results.
+    pub fn search_root(&self) -> Option<&Path> {
+        self.highlight.is_some().then(|| Path::new(&self.current_path))
+    }
+
+    /// Leaves the search results for the folder the entry at `path` is in, with the entry
+    /// selected and scrolled to.
+    pub fn reveal(&mut self, path: &Path) {
+        let Some(parent) = path.parent() else {
+            return;
+        };
+        self.previous_search.clear();
+        self.search_debounce.clear();
+        self.pending_selection = Some(path.to_owned());
+        self.open_folder(parent);
+    }
+
+    /// Opens a terminal in the folder at `dir`, with [`Self::terminal_command`].
+    pub fn open_terminal(&mut self, dir: &Path) {
+        if self.read_only() {
+            return;
+        }
+        if let Err(err) = open_terminal(&self.terminal_command, dir) {
+            self.toasts.error(err);
+        }
+    }
+
+    /// Opens the file at `path` with [`Self::editor_command`].
+    pub fn open_in_editor(&mut self, path: &Path) {
+        if let Err(err) = open_in_editor(&self.editor_command, path) {
+            self.toasts.error(err);
+        }
+    }
+
+    /// Selects every listed entry (Ctrl+A).
+    pub fn select_all(&mut self) {
+        self.selection = self.visible_order().into_iter().collect();
+    }
+
+    /// The combined size of the selected files, and of the selected folders whose size is known.
+    pub fn selection_size(&self) -> u64 {
+        let folders = self
+            .directories
+            .iter()
+            .filter(|folder| self.selection.contains(Path::new(&folder.dir)));
+        let files = self
+            .files
+            .iter()
+            .filter(|file| self.selection.contains(Path::new(&file.dir)));
+        Summary::of(folders, files).size
+    }
+
+    /// The default locations and the bookmarks, which can be reordered by dragging.
+    fn sidebar_ui(&mut self, ui: &mut Ui) {
+        let mut open = None;
+        for location in &self.locations {
+            let selected = Path::new(&self.current_path) == location.path;
+            if ui.selectable_label(selected, &location.name).clicked() {
+                open = Some(location.path.clone());
+            }
+        }
+
+        ui.separator();
+        ui.strong("Devices");
+        for device in &self.devices {
+            let selected = Path::new(&self.current_path) == device.path;
+            let mut hover = device.path.display().to_string();
+            if let Some(source) = &device.source {
+                hover = format!("{hover}\n{source}");
+            }
+            let response = ui.selectable_label(selected, format!("🖴 {}", device.name)).on_hover_text(hover);
+            if response.clicked() {
+                open = Some(device.path.clone());
+            }
+            if let Some(space) = device.space {
+                let used = space.used_fraction();
+                let free = free_space_text(Some(space), self.size_base);
+                ui.add(egui::ProgressBar::new(used).desired_height(4.0).fill(usage_color(used)))
+                    .on_hover_text(&free);
+                ui.label(egui::RichText::new(free).small().weak());
+            }
+        }
+
+        ui.separator();
+        ui.strong("Bookmarks");
+        if self.bookmarks.0.is_empty() {
+            ui.weak("Bookmark a folder with ☆");
+        }
+
+        let mut remove = None;
+        let mut reorder = None;
+        for (index, bookmark) in self.bookmarks.0.iter().enumerate() {
+            let stale = bookmark.is_stale();
+            let selected = Path::new(&self.current_path) == bookmark.path;
+            let mut text = egui::RichText::new(&bookmark.name);
+            if stale {
+                text = text.weak().strikethrough();
+            }
+            let button = Button::new(text)
+                .selected(selected)
+                .frame(false)
+                .sense(egui::Sense::click_and_drag());
+            let response = ui
+                .horizontal(|ui| {
+                    let response = ui.add(button);
+                    if stale && ui.small_button("✖").on_hover_text("Remove bookmark").clicked() {
+                        remove = Some(index);
+                    }
+                    response
+                })
+                .inner
+                .on_hover_text(if stale {
+                    format!("{} no longer exists", bookmark.path.display())
+                } else {
+                    bookmark.path.display().to_string()
+                });
+
+            if response.drag_started() {
+                egui::DragAndDrop::set_payload(ui.ctx(), DraggedBookmark(index));
+            }
+            if let Some(dragged) = response.dnd_release_payload::<DraggedBookmark>() {
+                reorder = Some((dragged.0, index));
+            } else if response.clicked() && !stale {
+                open = Some(bookmark.path.clone());
+            }
+
+            response.context_menu(|ui| {
+                if ui.button("Remove bookmark").clicked() {
+                    remove = Some(index);
+                    ui.close_menu();
+                }
+            });
+        }
+
+        if let Some((from, to)) = reorder {
+            self.bookmarks.move_to(from, to);
+        }
+        if let Some(index) = remove {
+            self.bookmarks.0.remove(index);
+        }
+        if let Some(path) = open {
+            self.open_folder(&path);
+        }
+    }
+
+    fn status_bar_ui(&self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let summary = Summary::of(&self.directories, &self.files);
+            ui.label(format!(
+                "{} folders, {} files, total {}",
+                summary.folders,
+                summary.files,
+                format_size_with_base(Some(summary.size), self.size_base)
+            ));
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                self.disk_space_ui(ui);
+                if !self.selection.is_empty() {
+                    ui.separator();
+                    ui.label(format!(
+                        "{} items selected, total {}",
+                        self.selection.len(),
+                        format_size_with_base(Some(self.selection_size()), self.size_base)
+                    ));
+                }
+            });
+        });
+    }
+
+    /// How full the volume of the current folder is, for the right of the status bar.
+    fn disk_space_ui(&self, ui: &mut Ui) {
+        if let Some(space) = self.disk_space {
+            let used = space.used_fraction();
+            ui.add(egui::ProgressBar::new(used).desired_width(60.0).desired_height(4.0).fill(usage_color(used)))
+                .on_hover_text(format!("{:.0}% used", used * 100.0));
+        }
+        ui.label(free_space_text(self.disk_space, self.size_base));
+    }
 
-fn search_in_directory_parallel(dir: &Path, search_term: &str) -> Vec<PathBuf> {
-    let mut results = Vec::new();
+    /// The entry at `path`, along with the rest of the selection if it's part of it.
+    pub fn action_paths(&self, path: &str) -> Vec<PathBuf> {
+        if self.selection.contains(Path::new(path)) {
+            self.sorted_selection()
+        } else {
+            vec![PathBuf::from(path)]
+        }
+    }
 
-    if let Ok(entries) = fs::read_dir(dir) {
-        let entries: Vec<_> = entries.filter_map(Result::ok).collect();
+    /// Asks to delete the entry at `path`, along with the rest of the selection if it's part of it.
+    pub fn request_delete(&mut self, path: &str) {
+        let paths = self.action_paths(path);
+        self.confirm_delete(paths);
+    }
 
-        let matched_paths: Vec<_> = entries
-            .par_iter()
-            .flat_map(|entry| {
-                let path = entry.path();
-                if path.is_dir() {
-                    if path.file_name().and_then(|n| n.to_str()).unwrap_or("").contains(search_term) {
-                        vec![path]
-                    } else {
-                        search_in_directory_parallel(&path, search_term)
-                    }
-                } else if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    if name.contains(search_term) {
-                        vec![path]
-                    } else {
-                        Vec::new()
-                    }
-                } else {
-                    Vec::new()
-                }
-            })
-            .collect();
+    /// Asks to delete the selection (Delete).
+    pub fn delete_selection(&mut self) {
+        let paths = self.sorted_selection();
+        self.confirm_delete(paths);
+    }
 
-        results.extend(matched_paths);
+    fn confirm_delete(&mut self, paths: Vec<PathBuf>) {
+        if !self.read_only() {
+            self.delete_dialog.open(paths);
+        }
     }
 
-    results
-}
+    /// Puts `paths` on the internal clipboard, to be copied or moved by [`Self::paste`].
+    ///
+    /// The paths also go to the system clipboard as text. Besides being handy, that's what
+    /// makes Ctrl+V work: egui only reports a paste when there's text to paste.
+    pub fn copy_paths(&mut self, ctx: &Context, paths: Vec<PathBuf>, mode: ClipboardMode) {
+        if paths.is_empty() || self.read_only() {
+            return;
+        }
+        let text: Vec<_> = paths.iter().map(|path| path.to_string_lossy()).collect();
+        ctx.output_mut(|o| o.copied_text = text.join("\n"));
+        self.clipboard = Some(Clipboard { paths, mode });
+    }
 
-impl FileBrowserApp {
-    pub(crate) fn update_directory_list(&mut self, path: &str) {
-        self.files.clear();
-        self.directories.clear();
+    /// Pastes the clipboard into [`Self::current_path`], asking first what to do if names are taken.
+    pub fn paste(&mut self) {
+        if let Some(clipboard) = self.clipboard.clone() {
+            self.paste_into(clipboard, PathBuf::from(&self.current_path));
+        }
+    }
 
-        let (tx, rx) = mpsc::channel();
-        let dirpath = Path::new(path).to_owned();
-        let search_term = self.search.clone();
+    /// Moves the dragged `paths` into the folder they were dropped on, like cutting and pasting them.
+    ///
+    /// Drops that wouldn't change anything are ignored; moving a folder into itself is reported.
+    pub fn drop_into(&mut self, paths: &[PathBuf], dest_dir: PathBuf) {
+        match drop_moves(paths, &dest_dir) {
+            Ok(paths) if paths.is_empty() => {}
+            Ok(paths) => {
+                let clipboard = Clipboard {
+                    paths,
+                    mode: ClipboardMode::Cut,
+                };
+                self.paste_into(clipboard, dest_dir);
+            }
+            Err(err) => self.paste_dialog.finish(vec![err]),
+        }
+    }
+
+    /// Copies files dropped from other applications into [`Self::current_path`].
+    fn drop_files(&mut self, files: &[egui::DroppedFile]) {
+        let paths: Vec<_> = files.iter().filter_map(|file| file.path.clone()).collect();
+        if !paths.is_empty() {
+            let clipboard = Clipboard {
+                paths,
+                mode: ClipboardMode::Copy,
+            };
+            self.paste_into(clipboard, PathBuf::from(&self.current_path));
+        }
+    }
+
+    /// Starts pasting `clipboard` into `dest_dir`, asking first what to do if names are taken.
+    fn paste_into(&mut self, clipboard: Clipboard, dest_dir: PathBuf) {
+        if self.paste_dialog.is_running() {
+            return;
+        }
+        let in_archive = |path: &PathBuf| parse_archive_path(&path.to_string_lossy()).is_some();
+        if in_archive(&dest_dir) || clipboard.paths.iter().any(in_archive) {
+            self.toasts.warning(READ_ONLY);
+            return;
+        }
+
+        let names = collisions(&clipboard.paths, &dest_dir);
+        if names.is_empty() {
+            self.start_paste(clipboard, dest_dir, Collision::Skip);
+        } else {
+            self.paste_dialog = PasteDialog::Collisions {
+                clipboard,
+                dest_dir,
+                names,
+            };
+        }
+    }
+
+    /// Copies or moves the entries of `clipboard` into `dest_dir` in the background.
+    ///
+    /// [`Self::poll_paste`] picks up the progress and reloads the listing once it's done.
+    pub fn start_paste(&mut self, clipboard: Clipboard, dest_dir: PathBuf, collision: Collision) {
+        if clipboard.mode == ClipboardMode::Cut && self.clipboard.as_ref() == Some(&clipboard) {
+            // The entries won't be where they were cut from anymore.
+            self.clipboard = None;
+        }
 
+        let sender = self.paste_loader.start();
+        self.paste_dialog.start();
         thread::spawn(move || {
-            let paths = search_in_directory_parallel(&dirpath, &search_term);
-            tx.send(paths).expect("Failed to send data through channel");
+            let errors = paste(&clipboard, &dest_dir, collision, sender.canceled(), &mut |update| {
+                sender.send(update);
+            });
+            sender.send(PasteUpdate::Finished { errors });
+            sender.finish();
         });
+    }
 
-        // In the main thread, receive the results and update the UI
-        let paths = rx.recv().expect("Failed to receive data through channel");
+    /// Stops the running paste. Whatever was pasted so far stays.
+    pub fn cancel_paste(&mut self) {
+        self.paste_loader.cancel();
+        self.paste_dialog.close();
+        self.update_directory_list(&self.current_path.clone());
+    }
 
-        for path in paths {
-            let name = path.file_name().unwrap().to_string_lossy().to_string();
+    /// Shows the progress of [`Self::start_paste`].
+    pub(crate) fn poll_paste(&mut self) {
+        let Some(update) = self.paste_loader.poll() else {
+            return;
+        };
+        for update in update.results {
+            match update {
+                PasteUpdate::Progress {
+                    done,
+                    total,
+                    current,
+                } => {
+                    if let PasteDialog::Running {
+                        done: shown_done,
+                        total: shown_total,
+                        current: shown_current,
+                        ..
+                    } = &mut self.paste_dialog
+                    {
+                        *shown_done = done;
+                        *shown_total = total;
+                        *shown_current = current;
+                    }
+                }
+                PasteUpdate::Finished { errors } => {
+                    self.paste_dialog.finish(errors);
+                    self.update_directory_list(&self.current_path.clone());
+                }
+            }
+        }
+    }
 
-            if path.is_dir() {
-                let dir_path = path.to_string_lossy().to_string();
-                let folder = Folder {
-                    dir: dir_path,
-                    name,
-                    size: Arc::new(Mutex::new(None)),
-                    calculating: Arc::new(Mutex::new(false)),
-                    error: Arc::new(Mutex::new(None)),
-                };
+    fn paste_dialog_ui(&mut self, ctx: &Context) {
+        let mut collision = None;
+        let mut close = false;
+        let mut cancel = false;
+        match &self.paste_dialog {
+            PasteDialog::Closed => return,
+            PasteDialog::Collisions {
+                dest_dir, names, ..
+            } => {
+                egui::Window::new("Paste")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        ui.label(format!(
+                            "{} item(s) already exist in {}:",
+                            names.len(),
+                            dest_dir.display()
+                        ));
+                        egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                            for name in names {
+                                ui.label(name);
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            if ui.button("Skip").clicked() {
+                                collision = Some(Collision::Skip);
+                            }
+                            if ui.button("Overwrite").clicked() {
+                                collision = Some(Collision::Overwrite);
+                            }
+                            if ui.button("Keep both").clicked() {
+                                collision = Some(Collision::Rename);
+                            }
+                            close = ui.button("Cancel").clicked();
+                        });
+                    });
+            }
+            PasteDialog::Running {
+                started,
+                done,
+                total,
+                current,
+            } => {
+                // Quick pastes are over before a window would be any use.
+                if started.elapsed() < std::time::Duration::from_millis(300) {
+                    return;
+                }
+                egui::Window::new("Pasting")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        let fraction = if *total == 0 { 0.0 } else { *done as f32 / *total as f32 };
+                        ui.add(
+                            egui::ProgressBar::new(fraction)
+                                .text(format!("{done} / {total} files"))
+                                .desired_width(300.0),
+                        );
+                        ui.label(current.to_string_lossy());
+                        cancel = ui.button("Cancel").clicked();
+                    });
+            }
+            PasteDialog::Failed { errors } => {
+                let message = format!("{} item(s) couldn't be copied or moved", errors.len());
+                self.toasts.error_with_details(message, errors.join("\n"));
+                close = true;
+            }
+        }
 
-                self.directories.push(folder);
-            } else {
-                let file = File {
-                    dir: path.to_string_lossy().to_string(),
-                    name,
-                    size: metadata(&path).ok().map(|m| m.len()),
-                };
-                self.files.push(file);
+        if let Some(collision) = collision {
+            if let Some((clipboard, dest_dir)) = self.paste_dialog.resolve() {
+                self.start_paste(clipboard, dest_dir, collision);
             }
+        } else if close {
+            self.paste_dialog.close();
+        } else if cancel {
+            self.cancel_paste();
         }
     }
 
-    pub fn directory_size(folder: &Folder) {
-        let folder_path = folder.dir.clone();
-        let calculating = folder.calculating.clone();
-        let error = folder.error.clone();
-        let size = folder.size.clone();
-
-        thread::spawn(move || {
-            *calculating.lock().unwrap() = true;
-            *error.lock().unwrap() = None;
+    /// The selected paths, in a stable order.
+    pub fn sorted_selection(&self) -> Vec<PathBuf> {
+        self.selection.iter().cloned().collect()
+    }
 
-            let result = Self::calculate_size(&folder_path);
+    /// Deletes what [`Self::delete_dialog`] was confirmed for in the background, with the
+    /// entry after the deleted ones to be selected.
+    ///
+    /// Size calculations of deleted folders are canceled first. [`Self::poll_delete`] reloads
+    /// the listing once it's done. Errors don't stop the batch; they're shown afterwards.
+    pub fn delete_confirmed(&mut self) {
+        if self.delete_loader.is_loading() {
+            self.toasts.warning("Still deleting: wait for it to finish");
+            return;
+        }
+        let Some((paths, permanently)) = self.delete_dialog.confirm() else {
+            return;
+        };
 
-            *calculating.lock().unwrap() = false;
-            match result {
-                Ok(s) => *size.lock().unwrap() = Some(s),
-                Err(e) => *error.lock().unwrap() = Some(e),
+        let deleted: HashSet<PathBuf> = paths.iter().cloned().collect();
+        for folder in &self.directories {
+            if deleted.contains(Path::new(&folder.dir)) {
+                folder.size.lock().unwrap().cancel(SizeState::NotCalculated);
             }
+        }
+
+        self.pending_selection = next_selection(&self.visible_order(), &deleted);
+        let sender = self.delete_loader.start();
+        thread::spawn(move || {
+            sender.send(delete_paths(&paths, permanently));
+            sender.finish();
         });
     }
 
-    pub fn calculate_size(path: &str) -> Result<u64, String> {
-        let mut total_size = 0;
+    /// Reloads the listing once [`Self::delete_confirmed`] is done, saying what couldn't be
+    /// deleted.
+    pub(crate) fn poll_delete(&mut self) {
+        let Some(update) = self.delete_loader.poll() else {
+            return;
+        };
+        for errors in update.results {
+            if !errors.is_empty() {
+                let message = format!("{} item(s) couldn't be deleted", errors.len());
+                self.toasts.error_with_details(message, errors.join("\n"));
+            }
+            self.update_directory_list(&self.current_path.clone());
+        }
+    }
 
-        for entry in fs::read_dir(path).map_err(|e| e.to_string())? {
-            let entry = entry.map_err(|e| e.to_string())?;
-            let path = entry.path();
+    /// Calculates the size of `folder` in the background, unless `cache` has it.
+    ///
+    /// `recalculate` ignores what's cached, for when the user knows better.
+    /// `follow_links` is [`Self::follow_links`] and `mode` is [`Self::size_mode`].
+    ///
+    /// The progress is in [`Folder::size`] until it's done, and canceling it there
+    /// stops the calculation.
+    pub fn directory_size(
+        folder: &Folder,
+        cache: &Arc<Mutex<SizeCache>>,
+        recalculate: bool,
+        follow_links: bool,
+        mode: SizeMode,
+    ) {
+        let folder_path = folder.dir.clone();
+        let state = folder.size.clone();
+        let cache = cache.clone();
+        let progress = state.lock().unwrap().start();
+        thread::spawn(move || {
+            // Cached by canonical path, so the same folder is found through any symlink.
+            let path = fs::canonicalize(&folder_path).unwrap_or_else(|_| PathBuf::from(&folder_path));
+            let path = path.to_str().unwrap_or(&folder_path);
+            let result = Self::calculate_size(path, &progress, &cache, !recalculate, follow_links, mode);
+            state.lock().unwrap().finish(&progress, result);
+        });
+    }
 
-            if path.is_dir() {
-                total_size += Self::calculate_size(path.to_str().ok_or("Invalid path")?)?;
-            } else {
-                total_size += metadata(&path).map_err(|e| e.to_string())?.len();
+    /// The total size of the files under `path`.
+    ///
+    /// Hidden files are always counted, whatever [`Self::show_hidden`] says:
+    /// the size is what the folder takes up on disk, not what's on screen.
+    ///
+    /// Symbolic links count as the links themselves, so a link to `/` doesn't count the whole
+    /// disk. With `follow_links`, links are followed instead, but each folder is only counted
+    /// once, so links back up the tree can't go around forever.
+    ///
+    /// Files count as big as `mode` says. A file with several hard links counts once, where
+    /// its first link is found, whichever mode.
+    ///
+    /// What can't be read is left out, and the size marked incomplete, rather than failing:
+    /// most of a folder is more useful than none of it. Only canceling fails, soon after
+    /// [`SizeProgress::cancel`].
+    ///
+    /// How far it's got is kept in `progress` along the way.
+    ///
+    /// Every folder's size goes into `cache` along the way, so the subfolders' sizes are known
+    /// too once it's done. With `use_cached`, fresh sizes in `cache` are used instead of
+    /// calculating them again. Sizes that followed links depend on what else was counted,
+    /// so they're neither cached nor taken from the cache; nor are those of folders with
+    /// hard-linked files in them, or of disk usage, which the cache doesn't tell apart.
+    pub fn calculate_size(
+        path: &str,
+        progress: &SizeProgress,
+        cache: &Mutex<SizeCache>,
+        use_cached: bool,
+        follow_links: bool,
+        mode: SizeMode,
+    ) -> Result<FolderSize, String> {
+        let mut visited = follow_links.then(HashSet::new);
+        if let Some(visited) = &mut visited {
+            visited.insert(fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path)));
+        }
+        let cache = (mode == SizeMode::Apparent).then_some(cache);
+        Self::folder_size(Path::new(path), progress, cache, use_cached, &mut visited, mode, &mut HardLinks::default())
+    }
+
+    /// [`Self::calculate_size`], with the canonical paths of the folders counted so far
+    /// when following links, and the hard-linked files counted so far. No `cache` for sizes
+    /// that can't be cached.
+    fn folder_size(
+        path: &Path,
+        progress: &SizeProgress,
+        cache: Option<&Mutex<SizeCache>>,
+        use_cached: bool,
+        visited: &mut Option<HashSet<PathBuf>>,
+        mode: SizeMode,
+        links: &mut HardLinks,
+    ) -> Result<FolderSize, String> {
+        let cache = cache.filter(|_| visited.is_none());
+        let modified = metadata(path).and_then(|meta| meta.modified()).ok();
+        if let (Some(cache), true) = (cache, use_cached) {
+            if let Some(bytes) = cache.lock().unwrap().get(path, modified, SystemTime::now()) {
+                progress.count(0, bytes);
+                return Ok(FolderSize { bytes, incomplete: false, mode });
             }
         }
 
-        Ok(total_size)
-    }
+        progress.enter(path);
+        let links_before = links.found();
+        let mut total = FolderSize { mode, ..FolderSize::default() };
+        let Ok(entries) = fs::read_dir(path) else {
+            total.incomplete = true;
+            return Ok(total);
+        };
 
-    pub fn format_size(size: Option<u64>) -> String {
-        match size {
-            Some(bytes) => {
-                if bytes < 1024 {
-                    format!("{} B", bytes)
-                } else if bytes < 1024 * 1024 {
-                    format!("{:.1} KB", bytes as f64 / 1024.0)
-                } else if bytes < 1024 * 1024 * 1024 {
-                    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
-                } else {
-                    format!("{:.1} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+        for entry in entries {
+            if progress.is_canceled() {
+                return Err("Canceled".to_owned());
+            }
+            let Ok(entry) = entry else {
+                total.incomplete = true;
+                continue;
+            };
+            let Ok(file_type) = entry.file_type() else {
+                total.incomplete = true;
+                continue;
+            };
+            let path = entry.path();
+
+            let followed = visited.is_some() && file_type.is_symlink() && path.is_dir();
+            if file_type.is_dir() || followed {
+                let first_visit = visited.as_mut().map_or(true, |visited| {
+                    visited.insert(fs::canonicalize(&path).unwrap_or_else(|_| path.clone()))
+                });
+                progress.count(1, 0);
+                if first_visit {
+                    let size = Self::folder_size(&path, progress, cache, use_cached, visited, mode, links)?;
+                    total.bytes += size.bytes;
+                    total.incomplete |= size.incomplete;
+                }
+            } else {
+                // A link counts as itself, unless it's followed to a file. A broken one can't be.
+                let meta = match visited {
+                    Some(_) => metadata(&path).or_else(|_| entry.metadata()),
+                    None => entry.metadata(),
+                };
+                match meta {
+                    Ok(meta) => {
+                        let meta = FileMeta::new(&meta);
+                        let bytes = if links.count_once(&meta) { mode.bytes(&meta) } else { 0 };
+                        total.bytes += bytes;
+                        progress.count(1, bytes);
+                    }
+                    Err(_) => total.incomplete = true,
                 }
             }
-            None => "Unknown".to_string(),
         }
-    }
 
-    pub fn extension_icon(extension: &str) -> Option<&'static str> {
-        match extension.to_lowercase().as_str() {
-            "txt" => Some("📄"),
-            "jpg" | "jpeg" | "png" => Some("🖼"),
-            "mp3" | "wav" => Some("🎵"),
-            "pdf" => Some("📄"),
-            "zip" => Some("📦"),
-            _ => Some("❓"),
+        // A partial size would pass for the whole one next time.
+        if let (Some(cache), false, true) = (cache, total.incomplete, links.found() == links_before) {
+            cache.lock().unwrap().insert(path.to_owned(), total.bytes, modified, SystemTime::now());
         }
+        Ok(total)
+    }
+}
+
+/// Where the app starts when there's nowhere to go back to.
+fn root_path() -> String {
+    if cfg!(target_os = "windows") {
+        "C:\\".to_owned()
+    } else {
+        "/".to_owned()
     }
 }
 
+/// The home folder, where the app goes when the folder it was closed in is gone.
+fn home_path() -> String {
+    std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" })
+        .map(PathBuf::from)
+        .filter(|home| home.is_dir())
+        .map_or_else(root_path, |home| home.to_string_lossy().into_owned())
+}
+
+/// The folder saved when the app was last closed, if it's still there, or else `fallback`.
+fn restored_path(saved: Option<String>, fallback: String) -> String {
+    saved
+        .filter(|path| Path::new(path).is_dir())
+        .unwrap_or(fallback)
+}
+
 fn get_parent_directories(path: &Path) -> Vec<PathBuf> {
     let mut parents = Vec::new();
     let mut current_path = path.to_path_buf();
@@ -240,68 +2203,312 @@ fn get_parent_directories(path: &Path) -> Vec<PathBuf> {
     parents
 }
 
-fn save_style_to_file(ctx: &Context) -> std::io::Result<()> {
-    let style_json = serde_json::to_string_pretty(&ctx.style()).expect("Failed to serialize style");
-    fs::write("../example.json", style_json)
-}
-
-pub fn load_style_from_file(ctx: &Context) -> std::io::Result<()> {
-    let style_json = fs::read_to_string("/home/ali/Projects/UI/example.json")?;
-    let new_style: Style = serde_json::from_str(&style_json).expect("Failed to deserialize style");
-    ctx.set_style(new_style);
-    Ok(())
-}
-
 impl eframe::App for FileBrowserApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.saved_settings().save(storage);
+        eframe::set_value(storage, BOOKMARKS_KEY, &self.bookmarks);
+        eframe::set_value(storage, RECENT_FOLDERS_KEY, &self.recent_folders.items());
+        let size_cache = self.size_cache.lock().unwrap().bounded(MAX_SAVED_ENTRIES);
+        eframe::set_value(storage, SIZE_CACHE_KEY, &size_cache);
+    }
+
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        self.poll_directory_list();
+        self.poll_paste();
+        self.poll_delete();
+        self.poll_extract();
+        self.poll_devices(ctx);
+        self.poll_disk_space(ctx);
+        self.poll_statistics();
+        self.thumbnails.poll(ctx);
+
+        if self.watcher.watched() != Some(Path::new(&self.current_path)) {
+            let ctx = ctx.clone();
+            let path = PathBuf::from(&self.current_path);
+            if let Err(err) = self.watcher.watch(&path, move || ctx.request_repaint_after(DEBOUNCE)) {
+                self.toasts.warning(err);
+            }
+        }
+        if self.watcher.poll(std::time::Instant::now()) {
+            self.refresh();
+        }
+        if self.loader.is_loading()
+            || self.paste_dialog.is_running()
+            || self.delete_loader.is_loading()
+            || self.thumbnails.is_loading()
+            || self.extract_loader.is_loading()
+        {
+            // Keep picking up results while nothing else is happening.
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+
         if self.settings {
             egui::Window::new("🔧 Settings")
                 .vscroll(true)
                 .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Count sizes in");
+                        for base in SizeBase::ALL {
+                            ui.radio_value(&mut self.size_base, base, base.label());
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Folder sizes are");
+                        for mode in SizeMode::ALL {
+                            ui.radio_value(&mut self.size_mode, mode, mode.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Files with several hard links are counted once either way");
+                    ui.checkbox(&mut self.follow_links, "Follow links to folders when calculating sizes")
+                        .on_hover_text("Links that lead back to a folder already counted are skipped");
+                    ui.horizontal(|ui| {
+                        ui.label("Columns");
+                        ui.checkbox(&mut self.show_permissions_column, "Permissions");
+                        ui.checkbox(&mut self.show_owner_column, "Owner");
+                    });
+                    if ui.checkbox(&mut self.group_folders_first, "Show folders before files").changed() {
+                        self.sort_entries();
+                    }
+                    let command_hint = "{path} is replaced by the path; quote arguments with spaces";
+                    ui.horizontal(|ui| {
+                        ui.label("Terminal");
+                        ui.text_edit_singleline(&mut self.terminal_command).on_hover_text(command_hint);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Editor");
+                        ui.text_edit_singleline(&mut self.editor_command).on_hover_text(command_hint);
+                    });
+                    ui.checkbox(&mut self.quote_copied_paths, "Quote copied paths for the shell")
+                        .on_hover_text("Puts paths with spaces, quotes or $ in single quotes");
+                    ui.collapsing("Icons", |ui| {
+                        self.icon_theme_editor.ui(ui, &mut self.icon_theme);
+                    });
+                    ui.separator();
+                    if ui.button("Reset to default").clicked() {
+                        ctx.set_style(default_style());
+                    }
+                    if let Some(error) = &self.style_error {
+                        ui.label(egui::RichText::new(error).color(Color32::RED));
+                    }
                     ctx.settings_ui(ui);
-                    if ui.button("Save").clicked() { save_style_to_file(ctx).expect("TODO: panic message"); }
                 });
+        } else {
+            // Picks up edits made to the file while the app runs.
+            match self.style_file.reload() {
+                Ok(Some(style)) => ctx.set_style(style),
+                Ok(None) => {}
+                Err(err) => self.toasts.error(err),
+            }
+        }
+        match self.style_file.track(&ctx.style(), std::time::Instant::now()) {
+            Ok(()) => self.style_error = None,
+            Err(err) => {
+                self.toasts.error(err.clone());
+                self.style_error = Some(err);
+            }
+        }
+        if self.style_file.is_pending() {
+            ctx.request_repaint_after(SAVE_DELAY);
+        }
+
+        self.delete_dialog_ui(ctx);
+        self.paste_dialog_ui(ctx);
+        self.permissions_dialog_ui(ctx);
+        self.properties_dialog_ui(ctx);
+        self.archive_preview_ui(ctx);
+        self.statistics_ui(ctx);
+        self.toasts.ui(ctx);
+
+        if !ctx.wants_keyboard_input()
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Delete))
+        {
+            self.delete_selection();
+        }
+
+        if !ctx.wants_keyboard_input() {
+            for event in ctx.input(|i| i.events.clone()) {
+                match event {
+                    egui::Event::Copy => {
+                        self.copy_paths(ctx, self.sorted_selection(), ClipboardMode::Copy);
+                    }
+                    egui::Event::Cut => {
+                        self.copy_paths(ctx, self.sorted_selection(), ClipboardMode::Cut);
+                    }
+                    egui::Event::Paste(_) => self.paste(),
+                    _ => {}
+                }
+            }
+        }
+
+        let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
+        self.drop_files(&dropped_files);
+
+        if !ctx.wants_keyboard_input()
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::A))
+        {
+            self.select_all();
+        }
+
+        let typing = ctx.wants_keyboard_input();
+        if !typing {
+            self.list_keys_ui(ctx);
+        }
+        let (back, forward) = ctx.input_mut(|i| {
+            let back = (!typing && i.consume_key(egui::Modifiers::ALT, egui::Key::ArrowLeft))
+                || i.pointer.button_pressed(egui::PointerButton::Extra1);
+            let forward = (!typing && i.consume_key(egui::Modifiers::ALT, egui::Key::ArrowRight))
+                || i.pointer.button_pressed(egui::PointerButton::Extra2);
+            (back, forward)
+        });
+        if back {
+            self.go_back();
+        } else if forward {
+            self.go_forward();
+        }
+
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::L)) {
+            self.address_bar.start_editing(&self.current_path);
+        }
+        let recent = ctx.input_mut(|i| {
+            RECENT_KEYS
+                .iter()
+                .position(|&key| i.consume_key(egui::Modifiers::COMMAND, key))
+        });
+        if let Some(index) = recent {
+            self.open_recent(index);
         }
 
-        else { load_style_from_file(&*ctx).expect("TODO: panic message"); }
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            self.status_bar_ui(ui);
+        });
+
+        egui::SidePanel::left("sidebar")
+            .resizable(true)
+            .default_width(150.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| self.sidebar_ui(ui));
+            });
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("File Browser");
 
             // Navigation buttons and search
             ui.horizontal(|ui| {
-                if ui.button("⏴").clicked() {
-                    if let Some(parent) = Path::new(&self.current_path).parent() {
-                        if let Some(parent_str) = parent.to_str() {
-                            let parent_path = parent_str.to_string();
-                            self.current_path = parent_path.clone();
-                            self.search = "".to_string();
-                            self.update_directory_list(&parent_path);
-                        }
+                let back = ui
+                    .add_enabled(self.history.can_go_back(), Button::new("⬅"))
+                    .on_hover_text("Back (Alt+Left)");
+                if back.clicked() {
+                    self.go_back();
+                }
+                let forward = ui
+                    .add_enabled(self.history.can_go_forward(), Button::new("➡"))
+                    .on_hover_text("Forward (Alt+Right)");
+                if forward.clicked() {
+                    self.go_forward();
+                }
+                self.recent_menu_ui(ui);
+                if ui.button("⏶").on_hover_text("Up").clicked() {
+                    if let Some(parent) = Path::new(&self.current_path).parent().map(Path::to_path_buf) {
+                        self.open_folder(&parent);
                     }
                 }
 
                 ui.horizontal(|ui| {
-                    for parent in get_parent_directories(Path::new(&self.current_path)) {
-                        if parent.file_name().is_some() {
-                            if ui.button(parent.file_name().unwrap().to_string_lossy()).clicked() {
-                                self.current_path = parent.to_string_lossy().parse().unwrap();
-                                self.search = "".to_string();
-                                self.update_directory_list(&self.current_path.clone());
+                    if self.address_bar.editing {
+                        if let Some(path) = self.address_bar.ui(ui) {
+                            self.open_folder(&path);
+                        }
+                    } else {
+                        for parent in get_parent_directories(Path::new(&self.current_path)) {
+                            if parent.file_name().is_some() {
+                                let crumb = ui.button(crumb_label(&parent.file_name().unwrap().to_string_lossy()));
+                                if let Some(dragged) = drop_target(ui, &crumb) {
+                                    self.drop_into(&dragged.0, parent.clone());
+                                } else if crumb.clicked() {
+                                    self.open_folder(&parent);
+                                }
+
+                                ui.label("/");
                             }
+                        }
 
-                            ui.label("/");
+                        // The empty space after the breadcrumbs switches to typing a path.
+                        let size = egui::vec2(60.0, ui.spacing().interact_size.y);
+                        let empty_space = ui
+                            .allocate_response(size, egui::Sense::click())
+                            .on_hover_cursor(egui::CursorIcon::Text)
+                            .on_hover_text("Type a path (Ctrl+L)");
+                        if empty_space.clicked() {
+                            self.address_bar.start_editing(&self.current_path);
+                        }
+                    }
+
+                    if self.loader.is_loading() {
+                        ui.add(egui::Spinner::new());
+                        if !self.search.is_empty() {
+                            let found = match &self.content_matches {
+                                Some(matches) => matches.len(),
+                                None => self.directories.len() + self.files.len(),
+                            };
+                            ui.label(format!("Searching… {found} found"));
+                        }
+                        if ui.button("Stop").clicked() {
+                            self.loader.cancel();
                         }
                     }
 
                     if self.current_path != "/"
                     {
-                        let text = ui.add(TextEdit::singleline(&mut (self.search)).desired_width(50.0));
+                        let mut mode_changed = false;
+                        egui::ComboBox::from_id_source("search_mode")
+                            .width(60.0)
+                            .selected_text(self.search_mode.label())
+                            .show_ui(ui, |ui| {
+                                for mode in SearchMode::ALL {
+                                    mode_changed |= ui.selectable_value(&mut self.search_mode, mode, mode.label()).changed();
+                                }
+                            });
+                        mode_changed |= ui
+                            .checkbox(&mut self.search_contents, "In contents")
+                            .on_hover_text("Search inside text files rather than their names")
+                            .changed();
+
+                        let text = ui.vertical(|ui| {
+                            let text = ui.add(TextEdit::singleline(&mut (self.search)).desired_width(50.0));
+                            if let Some(error) = &self.search_error {
+                                ui.label(egui::RichText::new(error).color(Color32::RED).small());
+                            }
+                            text
+                        }).inner;
+
+                        // Down from the search field goes on to the results.
+                        if text.has_focus() && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown)) {
+                            text.surrender_focus();
+                            self.move_cursor(1);
+                        }
 
-                        if self.previous_search != self.search {
-                            if ui.button("🔎").clicked() || text.clicked_elsewhere() {
+                        let now = std::time::Instant::now();
+                        if text.changed() {
+                            // Results of the old query would be misleading now.
+                            if self.loader.is_loading() {
+                                self.loader.cancel();
+                            }
+                            self.search_debounce.edited(now);
+                        }
+
+                        if mode_changed && !self.search.is_empty() {
+                            self.search_debounce.clear();
+                            self.update_directory_list(&self.current_path.clone());
+                            self.previous_search = self.search.clone();
+                        } else if self.previous_search != self.search {
+                            let search_now = ui.button("🔎").clicked() || text.clicked_elsewhere();
+                            if search_now || self.search_debounce.poll(now) {
+                                self.search_debounce.clear();
                                 self.update_directory_list(&self.current_path.clone());
                                 self.previous_search = self.search.clone();
+                            } else if let Some(remaining) = self.search_debounce.remaining(now) {
+                                ctx.request_repaint_after(remaining);
                             }
                         }
                     }
@@ -312,25 +2519,312 @@ impl eframe::App for FileBrowserApp {
                         if ui.radio(self.selected_option == Some(2), "Option 3").clicked() { self.selected_option = Some(2); }*/
 
                         toggle_button("Settings", &mut self.settings, ui);
+                        self.dark_mode = ui.visuals().dark_mode;
+                        egui::theme_toggle_button(ui, &mut self.dark_mode);
+                        toggle_button("📊 Statistics", &mut self.show_statistics, ui);
+                        let current = PathBuf::from(&self.current_path);
+                        let (star, hover) = if self.bookmarks.contains(&current) {
+                            ("★", "Remove bookmark")
+                        } else {
+                            ("☆", "Bookmark this folder")
+                        };
+                        if ui.button(star).on_hover_text(hover).clicked() {
+                            self.bookmarks.toggle(&current);
+                        }
+                        if ui.button("🗀 New Folder").clicked() {
+                            self.start_new_folder();
+                        }
+                        if ui.button(">_").on_hover_text("Open terminal here").clicked() {
+                            self.open_terminal(&current);
+                        }
+                        if self.clipboard.is_some() && ui.button("📥 Paste").clicked() {
+                            self.paste();
+                        }
+                        if toggle_button("Show hidden", &mut self.show_hidden, ui) {
+                            self.update_directory_list(&self.current_path.clone());
+                        }
+                        self.type_filter_ui(ui);
+                        for mode in ViewMode::ALL {
+                            ui.selectable_value(&mut self.view_mode, mode, mode.label());
+                        }
+
+                        if !self.selection.is_empty() {
+                            if ui.button("🗑 Delete").clicked() {
+                                self.delete_selection();
+                            }
+                            if ui.button("📋 Copy").clicked() {
+                                self.copy_paths(ctx, self.sorted_selection(), ClipboardMode::Copy);
+                            }
+                            if ui.button("✂ Cut").clicked() {
+                                self.copy_paths(ctx, self.sorted_selection(), ClipboardMode::Cut);
+                            }
+                        }
                     });
                 });
             });
 
             ui.separator();
 
-            list_explorer(self, ui);
+            if let Some(root) = self.search_root() {
+                let heading = format!("Search results for '{}' under {}", self.previous_search, root.display());
+                ui.label(egui::RichText::new(heading).italics().weak());
+            }
+            self.archive_banner_ui(ui);
+            self.read_errors_ui(ui);
+            if self.content_matches.is_some() {
+                self.content_results_ui(ui);
+            } else {
+                match self.view_mode {
+                    ViewMode::List => list_explorer(self, ui),
+                    ViewMode::Grid => grid_explorer(self, ui),
+                }
+            }
+
+            // Right-clicking the empty space below the table.
+            let background = ui.allocate_rect(ui.available_rect_before_wrap(), egui::Sense::click());
+            background.context_menu(|ui| {
+                if ui.button("New Folder").clicked() {
+                    self.start_new_folder();
+                    ui.close_menu();
+                }
+                if ui.add_enabled(self.clipboard.is_some(), Button::new("Paste")).clicked() {
+                    self.paste();
+                    ui.close_menu();
+                }
+            });
         });
     }
 }
 
-fn toggle_button(text: &str, toggle: &mut bool, ui: &mut Ui) {
+/// Drag and drop payload of a bookmark being moved in the sidebar, by index.
+struct DraggedBookmark(usize);
+
+/// Returns whether `toggle` was flipped.
+fn toggle_button(text: &str, toggle: &mut bool, ui: &mut Ui) -> bool {
     let color = if *toggle {
         ui.style().visuals.selection.bg_fill
     } else {
         ui.style().visuals.widgets.inactive.weak_bg_fill
     };
 
-    if ui.add(Button::new(text).fill(color)).clicked() {
+    let clicked = ui.add(Button::new(text).fill(color)).clicked();
+    if clicked {
         *toggle = !*toggle;
     }
-}
\ No newline at end of file
+    clicked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn restores_the_last_folder_if_it_still_exists() {
+        let dir = TempDir::new("restore");
+        let saved = dir.to_string_lossy().into_owned();
+        assert_eq!(restored_path(Some(saved.clone()), root_path()), saved);
+
+        drop(dir);
+        assert_eq!(restored_path(Some(saved), root_path()), root_path());
+        assert_eq!(restored_path(None, root_path()), root_path());
+    }
+
+    #[test]
+    fn a_saved_file_is_not_restored() {
+        let dir = TempDir::new("restore_file");
+        let file = dir.join("notes.txt");
+        fs::write(&file, "").unwrap();
+        let saved = file.to_string_lossy().into_owned();
+        assert_eq!(restored_path(Some(saved), root_path()), root_path());
+    }
+
+    /// The size of `dir`, with or without following links.
+    #[cfg(unix)]
+    fn folder_size(dir: &Path, follow_links: bool) -> Result<FolderSize, String> {
+        let cache = Mutex::new(SizeCache::default());
+        let progress = SizeProgress::default();
+        FileBrowserApp::calculate_size(dir.to_str().unwrap(), &progress, &cache, false, follow_links, SizeMode::Apparent)
+    }
+
+    /// [`folder_size`] of a folder that can be read entirely.
+    #[cfg(unix)]
+    fn size_of(dir: &Path, follow_links: bool) -> Result<u64, String> {
+        let size = folder_size(dir, follow_links)?;
+        assert!(!size.incomplete);
+        Ok(size.bytes)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_link_to_its_own_folder_is_counted_once() {
+        use std::os::unix::fs::symlink;
+
+        let dir = TempDir::new("link_loop");
+        fs::write(dir.join("data"), [0; 100]).unwrap();
+        symlink(&dir, dir.join("loop")).unwrap();
+        let link_size = fs::symlink_metadata(dir.join("loop")).unwrap().len();
+
+        assert_eq!(size_of(&dir, false), Ok(100 + link_size));
+        assert_eq!(size_of(&dir, true), Ok(100));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_link_to_a_sibling_folder_is_not_counted_twice() {
+        use std::os::unix::fs::symlink;
+
+        let dir = TempDir::new("link_sibling");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::write(dir.join("b/data"), [0; 100]).unwrap();
+        symlink(dir.join("b"), dir.join("a/to_b")).unwrap();
+        symlink(dir.join("missing"), dir.join("a/broken")).unwrap();
+        let link_sizes = fs::symlink_metadata(dir.join("a/to_b")).unwrap().len()
+            + fs::symlink_metadata(dir.join("a/broken")).unwrap().len();
+
+        assert_eq!(size_of(&dir.join("a"), false), Ok(link_sizes));
+        assert_eq!(size_of(&dir, false), Ok(100 + link_sizes));
+        // Followed, `b` is counted through `a/to_b` or on its own, whichever comes first.
+        let broken_size = fs::symlink_metadata(dir.join("a/broken")).unwrap().len();
+        assert_eq!(size_of(&dir.join("a"), true), Ok(100 + broken_size));
+        assert_eq!(size_of(&dir, true), Ok(100 + broken_size));
+
+        let listing = Listing::from_paths(vec![dir.join("a/to_b"), dir.join("a/broken")]);
+        assert_eq!(listing.directories[0].link_target, Some(dir.join("b")));
+        assert_eq!(listing.files[0].link_target, Some(dir.join("missing")));
+        assert_eq!(listing.files[0].size, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_folders_leave_the_size_incomplete() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let dir = TempDir::new("size_locked");
+        fs::create_dir_all(dir.join("locked")).unwrap();
+        fs::write(dir.join("data"), [0; 100]).unwrap();
+        fs::write(dir.join("locked/secret"), [0; 50]).unwrap();
+        fs::set_permissions(dir.join("locked"), fs::Permissions::from_mode(0o000)).unwrap();
+        if fs::read_dir(dir.join("locked")).is_ok() {
+            // Running as root, which can read anything.
+            fs::set_permissions(dir.join("locked"), fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let cache = Mutex::new(SizeCache::default());
+        let progress = SizeProgress::default();
+        let size = FileBrowserApp::calculate_size(dir.to_str().unwrap(), &progress, &cache, true, false, SizeMode::Apparent);
+        assert_eq!(size, Ok(FolderSize { bytes: 100, incomplete: true, ..FolderSize::default() }));
+        // Cached, a partial size would later pass for the whole one.
+        let now = SystemTime::now();
+        let modified = fs::metadata(&dir).unwrap().modified().ok();
+        assert_eq!(cache.lock().unwrap().get(&dir, modified, now), None);
+
+        let locked = FileBrowserApp::calculate_size(dir.join("locked").to_str().unwrap(), &progress, &cache, true, false, SizeMode::Apparent);
+        assert_eq!(locked, Ok(FolderSize { bytes: 0, incomplete: true, ..FolderSize::default() }));
+
+        fs::set_permissions(dir.join("locked"), fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(size_of(&dir, false), Ok(150));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_links_are_counted_once() {
+        use std::os::unix::fs::MetadataExt as _;
+
+        let dir = TempDir::new("hard_links");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::write(dir.join("a/data"), [1; 5000]).unwrap();
+        fs::hard_link(dir.join("a/data"), dir.join("a/again")).unwrap();
+        fs::hard_link(dir.join("a/data"), dir.join("b/data")).unwrap();
+        let blocks = fs::metadata(dir.join("a/data")).unwrap().blocks();
+
+        let cache = Mutex::new(SizeCache::default());
+        let progress = SizeProgress::default();
+        let size = |path: &Path, mode| FileBrowserApp::calculate_size(path.to_str().unwrap(), &progress, &cache, true, false, mode);
+        assert_eq!(size(&dir, SizeMode::Apparent), Ok(FolderSize { bytes: 5000, ..FolderSize::default() }));
+        assert_eq!(
+            size(&dir, SizeMode::DiskUsage),
+            Ok(FolderSize { bytes: blocks * 512, mode: SizeMode::DiskUsage, ..FolderSize::default() })
+        );
+        // Counted on its own, each folder has the file.
+        assert_eq!(size(&dir.join("b"), SizeMode::Apparent).map(|size| size.bytes), Ok(5000));
+
+        // Which of them the file counts towards depends on what else is counted, so neither is cached.
+        let now = SystemTime::now();
+        for folder in [dir.to_path_buf(), dir.join("a"), dir.join("b")] {
+            let modified = fs::metadata(&folder).unwrap().modified().ok();
+            assert_eq!(cache.lock().unwrap().get(&folder, modified, now), None);
+        }
+    }
+
+    #[test]
+    fn disk_usage_is_not_cached() {
+        let root = deep_tree("size_disk_usage", 3);
+        let cache = Mutex::new(SizeCache::default());
+        let progress = SizeProgress::default();
+        let size = FileBrowserApp::calculate_size(root.to_str().unwrap(), &progress, &cache, false, false, SizeMode::DiskUsage).unwrap();
+        assert_eq!(size.mode, SizeMode::DiskUsage);
+        let modified = fs::metadata(&root).unwrap().modified().ok();
+        assert_eq!(cache.lock().unwrap().get(&root, modified, SystemTime::now()), None);
+    }
+
+    /// `depth` folders, each in the one before, with a 10-byte file in each.
+    fn deep_tree(name: &str, depth: usize) -> TempDir {
+        let root = TempDir::new(name);
+        let mut dir = root.to_path_buf();
+        for level in 0..depth {
+            if level > 0 {
+                dir.push(format!("{level}"));
+            }
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("file"), [0; 10]).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn progress_follows_a_deep_tree() {
+        let root = deep_tree("size_progress", 40);
+        let cache = Mutex::new(SizeCache::default());
+        let progress = SizeProgress::default();
+        let size = FileBrowserApp::calculate_size(root.to_str().unwrap(), &progress, &cache, false, false, SizeMode::Apparent);
+
+        assert_eq!(size, Ok(FolderSize { bytes: 400, incomplete: false, ..FolderSize::default() }));
+        assert_eq!(progress.bytes(), 400);
+        assert_eq!(progress.entries(), 40 + 39);
+        assert_eq!(progress.current().file_name().unwrap(), "39");
+
+        // Cached subfolders still count towards the progress.
+        let progress = SizeProgress::default();
+        FileBrowserApp::calculate_size(root.to_str().unwrap(), &progress, &cache, true, false, SizeMode::Apparent).unwrap();
+        assert_eq!(progress.bytes(), 400);
+    }
+
+    #[test]
+    fn canceling_stops_only_that_calculation() {
+        let root = deep_tree("size_cancel", 40);
+        let cache = Mutex::new(SizeCache::default());
+        let canceled = Arc::new(SizeProgress::default());
+        let running = SizeProgress::default();
+
+        let mut state = SizeState::Calculating(canceled.clone());
+        state.cancel(SizeState::Canceled);
+        assert!(canceled.is_canceled());
+        assert!(matches!(state, SizeState::Canceled));
+        // The canceled calculation can't overwrite what's there now.
+        assert!(!state.is_calculating(&canceled));
+
+        let path = root.to_str().unwrap();
+        thread::scope(|scope| {
+            let stopped = scope.spawn(|| FileBrowserApp::calculate_size(path, &canceled, &cache, false, false, SizeMode::Apparent));
+            let finished = scope.spawn(|| FileBrowserApp::calculate_size(path, &running, &cache, false, false, SizeMode::Apparent));
+            assert_eq!(stopped.join().unwrap(), Err("Canceled".to_owned()));
+            assert_eq!(finished.join().unwrap(), Ok(FolderSize { bytes: 400, incomplete: false, ..FolderSize::default() }));
+        });
+        assert_eq!(canceled.entries(), 0);
+        assert_eq!(running.entries(), 40 + 39);
+    }
+}
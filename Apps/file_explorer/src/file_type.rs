@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::SystemTime;
+
+use egui::Context;
+
+use crate::type_filter::FileGroup;
+
+/// How much of the start of a file is read to tell its type. A tar header is 512 bytes.
+pub const SNIFF_LEN: u64 = 512;
+
+/// What a file is, going by its contents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileType {
+    Elf,
+    Pe,
+    Png,
+    Jpeg,
+    Pdf,
+    Zip,
+    Gzip,
+    Tar,
+    Text,
+}
+
+impl FileType {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Elf => "ELF executable",
+            Self::Pe => "Windows executable",
+            Self::Png => "PNG image",
+            Self::Jpeg => "JPEG image",
+            Self::Pdf => "PDF document",
+            Self::Zip => "ZIP archive",
+            Self::Gzip => "gzip archive",
+            Self::Tar => "tar archive",
+            Self::Text => "Text",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Elf | Self::Pe => "⚙",
+            Self::Png | Self::Jpeg => FileGroup::Images.icon(),
+            Self::Pdf | Self::Text => FileGroup::Documents.icon(),
+            Self::Zip | Self::Gzip | Self::Tar => FileGroup::Archives.icon(),
+        }
+    }
+
+    /// The type a file with `extension` (without the dot) is expected to have, if it's
+    /// one that can be told from the contents.
+    ///
+    /// Office documents are ZIP archives inside.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "exe" | "dll" | "sys" => Some(Self::Pe),
+            "png" => Some(Self::Png),
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "pdf" => Some(Self::Pdf),
+            "zip" | "jar" | "docx" | "xlsx" | "pptx" | "odt" | "ods" | "odp" => Some(Self::Zip),
+            "gz" | "tgz" => Some(Self::Gzip),
+            "tar" => Some(Self::Tar),
+            "txt" | "md" | "csv" | "json" | "toml" | "rs" | "py" | "sh" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+/// The type of a file starting with `bytes`, if it's one of the common ones.
+///
+/// `bytes` may be cut off anywhere, even in the middle of a UTF-8 character.
+pub fn sniff(bytes: &[u8]) -> Option<FileType> {
+    const MAGIC: &[(&[u8], FileType)] = &[
+        (b"\x7fELF", FileType::Elf),
+        (b"MZ", FileType::Pe),
+        (b"\x89PNG\r\n\x1a\n", FileType::Png),
+        (b"\xff\xd8\xff", FileType::Jpeg),
+        (b"%PDF-", FileType::Pdf),
+        (b"PK\x03\x04", FileType::Zip),
+        // An empty archive.
+        (b"PK\x05\x06", FileType::Zip),
+        (b"\x1f\x8b", FileType::Gzip),
+    ];
+    if let Some((_, file_type)) = MAGIC.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+        return Some(*file_type);
+    }
+    if bytes.get(257..262) == Some(b"ustar") {
+        return Some(FileType::Tar);
+    }
+    is_text(bytes).then_some(FileType::Text)
+}
+
+/// Is `bytes` UTF-8 without control characters other than whitespace (and escapes, for
+/// colored output saved to a file)?
+fn is_text(bytes: &[u8]) -> bool {
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        // Only cut off at the end, not invalid.
+        Err(err) if err.error_len().is_none() => {
+            std::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return false,
+    };
+    !text.is_empty()
+        && !text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c' | '\x1b'))
+}
+
+/// The type of the file at `path`, reading only its first [`SNIFF_LEN`] bytes.
+pub fn sniff_file(path: &Path) -> io::Result<Option<FileType>> {
+    let mut bytes = Vec::new();
+    fs::File::open(path)?
+        .take(SNIFF_LEN)
+        .read_to_end(&mut bytes)?;
+    Ok(sniff(&bytes))
+}
+
+/// Does what `name` ends in say the file is something other than `sniffed`?
+///
+/// Extensions that don't say what to expect never disagree.
+pub fn is_mismatch(name: &str, sniffed: FileType) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|extension| FileType::from_extension(&extension.to_string_lossy()))
+        .is_some_and(|expected| expected != sniffed)
+}
+
+/// Where telling the type of a file is at.
+#[derive(Clone, Debug)]
+pub enum Sniff {
+    Sniffing,
+    Done(Result<Option<FileType>, String>),
+}
+
+/// The types of files sniffed so far, each as of when the file was last modified.
+///
+/// Files are only read when their type is asked for, on a thread of their own.
+#[derive(Clone, Default)]
+pub struct FileTypes {
+    types: Arc<Mutex<HashMap<PathBuf, (Option<SystemTime>, Sniff)>>>,
+}
+
+impl FileTypes {
+    /// The type of the file at `path`, starting to sniff it if it hasn't been since it was
+    /// last modified at `modified`.
+    pub fn sniff(&self, ctx: &Context, path: &Path, modified: Option<SystemTime>) -> Sniff {
+        let mut types = self.types.lock().unwrap();
+        if let Some((m, sniff)) = types.get(path) {
+            if *m == modified {
+                return sniff.clone();
+            }
+        }
+        types.insert(path.to_owned(), (modified, Sniff::Sniffing));
+
+        let shared = Arc::clone(&self.types);
+        let path = path.to_owned();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let sniffed = sniff_file(&path).map_err(|err| err.to_string());
+            let mut types = shared.lock().unwrap();
+            // Unless the file changed and is being sniffed again.
+            if let Some((m, sniff)) = types.get_mut(&path) {
+                if *m == modified {
+                    *sniff = Sniff::Done(sniffed);
+                }
+            }
+            ctx.request_repaint();
+        });
+        Sniff::Sniffing
+    }
+
+    /// The type of the file at `path`, if it's been sniffed since it was last modified at
+    /// `modified`. Doesn't start sniffing.
+    pub fn known(&self, path: &Path, modified: Option<SystemTime>) -> Option<FileType> {
+        match self.types.lock().unwrap().get(path) {
+            Some((m, Sniff::Done(Ok(sniffed)))) if *m == modified => *sniffed,
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn sniffs_magic_headers() {
+        let mut tar = vec![0; 512];
+        tar[..8].copy_from_slice(b"file.txt");
+        tar[257..263].copy_from_slice(b"ustar\0");
+
+        let cases: &[(&[u8], Option<FileType>)] = &[
+            (
+                b"\x7fELF\x02\x01\x01\0\0\0\0\0\0\0\0\0",
+                Some(FileType::Elf),
+            ),
+            (b"MZ\x90\0\x03\0\0\0\x04\0\0\0\xff\xff", Some(FileType::Pe)),
+            (b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", Some(FileType::Png)),
+            (b"\xff\xd8\xff\xe0\0\x10JFIF\0", Some(FileType::Jpeg)),
+            (b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n", Some(FileType::Pdf)),
+            (b"PK\x03\x04\x14\0\0\0\x08\0", Some(FileType::Zip)),
+            (b"PK\x05\x06\0\0\0\0\0\0\0\0", Some(FileType::Zip)),
+            (b"\x1f\x8b\x08\0\0\0\0\0\0\x03", Some(FileType::Gzip)),
+            (&tar, Some(FileType::Tar)),
+            (b"#!/bin/sh\necho hello\n", Some(FileType::Text)),
+            ("Grüße, 世界\r\n\tend".as_bytes(), Some(FileType::Text)),
+            (b"\x1b[31mred\x1b[0m\n", Some(FileType::Text)),
+            (b"", None),
+            (b"\0\x01\x02\x03binary", None),
+            (b"\xc3\x28 invalid UTF-8", None),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(sniff(bytes), *expected, "{bytes:?}");
+        }
+    }
+
+    #[test]
+    fn text_cut_off_mid_character_is_text() {
+        let text = "naïve".as_bytes();
+        // Ends after the first byte of the 'ï'.
+        assert_eq!(sniff(&text[..3]), Some(FileType::Text));
+    }
+
+    #[test]
+    fn mismatches_need_an_extension_that_says_otherwise() {
+        let cases = [
+            ("photo.png", FileType::Png, false),
+            ("photo.PNG", FileType::Png, false),
+            ("photo.png", FileType::Jpeg, true),
+            ("report.docx", FileType::Zip, false),
+            ("notes.txt", FileType::Elf, true),
+            ("install", FileType::Text, false),
+            ("backup.bak", FileType::Tar, false),
+        ];
+        for (name, sniffed, mismatch) in cases {
+            assert_eq!(is_mismatch(name, sniffed), mismatch, "{name}");
+        }
+    }
+
+    #[test]
+    fn sniffs_only_the_start_of_files() {
+        let dir = TempDir::new("sniff");
+        // Text for longer than is read, then binary.
+        let path = dir.join("script");
+        let mut contents = vec![b'a'; SNIFF_LEN as usize];
+        contents.extend_from_slice(&[0, 1, 2]);
+        fs::write(&path, contents).unwrap();
+        assert_eq!(sniff_file(&path).unwrap(), Some(FileType::Text));
+        assert!(sniff_file(&dir.join("missing")).is_err());
+    }
+}
@@ -0,0 +1,351 @@
+use std::ops::Range;
+use std::path::Path;
+
+use egui::{Align, Color32, Image, Label, Layout, Rect, RichText, ScrollArea, Sense, Ui, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::explorer::FileBrowserApp;
+use crate::list::{
+    file_entry, file_icon, folder_entry, name_text, new_folder_field, page_rows, rename_field,
+    EntryActions, FileIcon,
+};
+use crate::search::result_location;
+use crate::sort::Entry;
+
+/// How the entries of a folder are shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViewMode {
+    /// A table with a row per entry and its details in columns.
+    #[default]
+    List,
+
+    /// Tiles with a big icon or thumbnail, wrapping into as many columns as fit.
+    Grid,
+}
+
+impl ViewMode {
+    pub const ALL: [Self; 2] = [Self::List, Self::Grid];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::List => "☰ List",
+            Self::Grid => "▣ Grid",
+        }
+    }
+}
+
+/// Size of every tile, including the gap around its contents.
+const TILE_SIZE: Vec2 = Vec2::new(96.0, 84.0);
+
+/// Size of the icon or thumbnail in a tile.
+const ICON_SIZE: f32 = 48.0;
+
+/// How many tiles `tile_width` wide fit side by side in `width`; at least one.
+pub fn columns(width: f32, tile_width: f32) -> usize {
+    ((width / tile_width).floor() as usize).max(1)
+}
+
+/// How many rows `len` tiles take up in `columns` columns.
+pub fn rows(len: usize, columns: usize) -> usize {
+    len.div_ceil(columns.max(1))
+}
+
+/// The row and column of the tile at `index`.
+pub fn cell(index: usize, columns: usize) -> (usize, usize) {
+    let columns = columns.max(1);
+    (index / columns, index % columns)
+}
+
+/// The index of the tile at `row` and `col`, the inverse of [`cell`].
+pub fn index(row: usize, col: usize, columns: usize) -> usize {
+    row * columns.max(1) + col
+}
+
+/// The rows of `row_height` out of `row_count` that are at least partly in view between
+/// `top` and `bottom`, measured from the top of the first row. Only these are built.
+pub fn visible_rows(top: f32, bottom: f32, row_height: f32, row_count: usize) -> Range<usize> {
+    let first = ((top / row_height).floor().max(0.0) as usize).min(row_count);
+    let end = ((bottom / row_height).ceil().max(0.0) as usize).min(row_count);
+    first..end.max(first)
+}
+
+/// Which way an arrow key moves the cursor through the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GridStep {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Where `step` moves the cursor from `cursor` among `len` tiles in `columns` columns.
+///
+/// Left and Right stay within the row, Up and Down keep the column. Down from above a
+/// shorter last row lands on its last tile. Without a cursor, it starts at the first tile
+/// (or the last, going Left or Up).
+pub fn step_in_grid(
+    cursor: Option<usize>,
+    step: GridStep,
+    columns: usize,
+    len: usize,
+) -> Option<usize> {
+    let last = len.checked_sub(1)?;
+    let Some(cursor) = cursor else {
+        return Some(match step {
+            GridStep::Left | GridStep::Up => last,
+            GridStep::Right | GridStep::Down => 0,
+        });
+    };
+    let cursor = cursor.min(last);
+    let columns = columns.max(1);
+    let (row, col) = cell(cursor, columns);
+    Some(match step {
+        GridStep::Left if col > 0 => cursor - 1,
+        GridStep::Right if col + 1 < columns && cursor < last => cursor + 1,
+        GridStep::Up if row > 0 => cursor - columns,
+        GridStep::Down if cell(last, columns).0 > row => (cursor + columns).min(last),
+        _ => cursor,
+    })
+}
+
+/// The entries as tiles, sharing selection, popups and opening with [`crate::list::list_explorer`].
+pub fn grid_explorer(app: &mut FileBrowserApp, ui: &mut Ui) {
+    let mut actions = EntryActions::default();
+    let modifiers = ui.input(|i| i.modifiers);
+    let len = app.entries.len();
+    // Taken out while the tiles are built, which only read the rest of the app.
+    let mut renaming = app.renaming.take();
+
+    if let Some(new_folder) = &mut app.new_folder {
+        ui.horizontal(|ui| {
+            ui.label("📁");
+            new_folder_field(ui, new_folder, &mut actions);
+            if let Some(error) = &new_folder.error {
+                ui.label(RichText::new(error).color(Color32::RED));
+            }
+        });
+    }
+
+    ScrollArea::vertical()
+        .auto_shrink(false)
+        .show_viewport(ui, |ui, viewport| {
+            let columns = columns(ui.available_width(), TILE_SIZE.x);
+            app.grid_columns = columns;
+            app.page_rows = page_rows(viewport.height(), TILE_SIZE.y);
+            let rows = rows(len, columns);
+            ui.set_height(rows as f32 * TILE_SIZE.y);
+
+            let origin = ui.max_rect().min;
+            let tile_rect = |index: usize| {
+                let (row, col) = cell(index, columns);
+                Rect::from_min_size(
+                    origin + Vec2::new(col as f32, row as f32) * TILE_SIZE,
+                    TILE_SIZE,
+                )
+            };
+            if let Some(cursor) = app.cursor.filter(|_| app.scroll_to_cursor) {
+                ui.scroll_to_rect(tile_rect(cursor), None);
+                app.scroll_to_cursor = false;
+            }
+
+            // Only the rows in view are built, which keeps huge folders responsive.
+            for row in visible_rows(viewport.min.y, viewport.max.y, TILE_SIZE.y, rows) {
+                for col in 0..columns {
+                    let index = index(row, col, columns);
+                    if index >= len {
+                        break;
+                    }
+                    let rect = tile_rect(index);
+                    let response = ui.allocate_rect(rect, Sense::click_and_drag());
+
+                    let entry = app.entries[index];
+                    let (path, name, icon) = match entry {
+                        Entry::Folder(i) => {
+                            let directory = &app.directories[i];
+                            (&directory.dir, &directory.name, FileIcon::Text("📁"))
+                        }
+                        Entry::File(i) => {
+                            let file = &app.files[i];
+                            (
+                                &file.dir,
+                                &file.name,
+                                file_icon(
+                                    &mut app.thumbnails,
+                                    &app.file_types,
+                                    &app.icon_theme,
+                                    file,
+                                ),
+                            )
+                        }
+                    };
+
+                    let visuals = ui.visuals();
+                    if app.selection.contains(Path::new(path)) {
+                        ui.painter()
+                            .rect_filled(rect.shrink(2.0), 4.0, visuals.selection.bg_fill);
+                    } else if response.hovered() {
+                        ui.painter().rect_filled(
+                            rect.shrink(2.0),
+                            4.0,
+                            visuals.widgets.hovered.weak_bg_fill,
+                        );
+                    }
+
+                    let layout = Layout::top_down(Align::Center);
+                    let mut tile =
+                        ui.child_ui_with_id_source(rect.shrink(4.0), layout, ("tile", path), None);
+                    match icon {
+                        FileIcon::Thumbnail(texture) => {
+                            tile.add(
+                                Image::new(&texture).fit_to_exact_size(Vec2::splat(ICON_SIZE)),
+                            );
+                        }
+                        FileIcon::Text(icon) => {
+                            tile.add(
+                                Label::new(RichText::new(icon).size(ICON_SIZE * 0.75))
+                                    .selectable(false),
+                            );
+                        }
+                    }
+                    if let Some(rename) = renaming.as_mut().filter(|r| r.path == Path::new(path)) {
+                        let is_dir = matches!(entry, Entry::Folder(_));
+                        actions.rename = rename_field(&mut tile, rename, is_dir).or(actions.rename);
+                        continue;
+                    }
+                    let text = name_text(&tile, name, app.highlight.as_ref());
+                    // The tile may only have room for part of the name.
+                    let hover = match app.search_root() {
+                        Some(root) => {
+                            format!("{name}\nin {}", result_location(root, Path::new(path)))
+                        }
+                        None => name.clone(),
+                    };
+                    tile.add(Label::new(text).truncate().selectable(false))
+                        .on_hover_text(hover);
+
+                    match entry {
+                        Entry::Folder(i) => {
+                            folder_entry(app, ui, i, index, &response, modifiers, &mut actions);
+                        }
+                        Entry::File(i) => {
+                            file_entry(app, ui, i, index, &response, &mut actions);
+                        }
+                    }
+                }
+            }
+        });
+
+    app.renaming = renaming;
+    actions.apply(app, ui, modifiers);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_fit_the_width() {
+        assert_eq!(columns(480.0, 96.0), 5);
+        assert_eq!(columns(575.0, 96.0), 5);
+        assert_eq!(columns(576.0, 96.0), 6);
+        // Even a window narrower than a tile shows one column.
+        assert_eq!(columns(50.0, 96.0), 1);
+        assert_eq!(columns(0.0, 96.0), 1);
+    }
+
+    #[test]
+    fn rows_round_up() {
+        assert_eq!(rows(0, 4), 0);
+        assert_eq!(rows(4, 4), 1);
+        assert_eq!(rows(5, 4), 2);
+        assert_eq!(rows(10_000, 7), 1429);
+    }
+
+    #[test]
+    fn only_rows_in_view_are_built() {
+        // 100k tiles, scrolled anywhere: a frame only builds the rows that fit the viewport.
+        let rows = rows(100_000, 5);
+        let viewport = 600.0;
+        let bound = page_rows(viewport, TILE_SIZE.y) + 2;
+
+        let total_height = rows as f32 * TILE_SIZE.y;
+        for top in [
+            0.0,
+            10.0,
+            total_height / 2.0,
+            total_height - viewport,
+            total_height,
+        ] {
+            let built = visible_rows(top, top + viewport, TILE_SIZE.y, rows);
+            assert!(built.len() <= bound, "{built:?} built at {top}");
+            assert!(built.end <= rows);
+        }
+
+        assert_eq!(visible_rows(0.0, 168.0, 84.0, rows), 0..2);
+        assert_eq!(visible_rows(42.0, 210.0, 84.0, rows), 0..3);
+        assert_eq!(
+            visible_rows(total_height, total_height + viewport, TILE_SIZE.y, rows),
+            rows..rows
+        );
+        assert_eq!(visible_rows(0.0, viewport, TILE_SIZE.y, 3), 0..3);
+        assert_eq!(visible_rows(-50.0, viewport, TILE_SIZE.y, 0), 0..0);
+    }
+
+    #[test]
+    fn cells_and_indices_are_inverse() {
+        assert_eq!(cell(0, 4), (0, 0));
+        assert_eq!(cell(3, 4), (0, 3));
+        assert_eq!(cell(4, 4), (1, 0));
+        assert_eq!(cell(9, 4), (2, 1));
+        for columns in 1..6 {
+            for i in 0..30 {
+                let (row, col) = cell(i, columns);
+                assert_eq!(index(row, col, columns), i);
+            }
+        }
+    }
+
+    #[test]
+    fn left_and_right_stay_in_the_row() {
+        // 0 1 2 3
+        // 4 5 6 7
+        // 8 9
+        let step = |cursor, step| step_in_grid(Some(cursor), step, 4, 10);
+        assert_eq!(step(5, GridStep::Left), Some(4));
+        assert_eq!(step(4, GridStep::Left), Some(4));
+        assert_eq!(step(5, GridStep::Right), Some(6));
+        assert_eq!(step(7, GridStep::Right), Some(7));
+        // The last row ends early.
+        assert_eq!(step(9, GridStep::Right), Some(9));
+    }
+
+    #[test]
+    fn up_and_down_keep_the_column() {
+        let step = |cursor, step| step_in_grid(Some(cursor), step, 4, 10);
+        assert_eq!(step(6, GridStep::Up), Some(2));
+        assert_eq!(step(2, GridStep::Up), Some(2));
+        assert_eq!(step(1, GridStep::Down), Some(5));
+        assert_eq!(step(5, GridStep::Down), Some(9));
+        // Nothing below the 7 in the last row, so it goes to the row's last tile.
+        assert_eq!(step(7, GridStep::Down), Some(9));
+        assert_eq!(step(9, GridStep::Down), Some(9));
+    }
+
+    #[test]
+    fn stepping_starts_at_either_end() {
+        assert_eq!(step_in_grid(None, GridStep::Right, 4, 10), Some(0));
+        assert_eq!(step_in_grid(None, GridStep::Down, 4, 10), Some(0));
+        assert_eq!(step_in_grid(None, GridStep::Left, 4, 10), Some(9));
+        assert_eq!(step_in_grid(None, GridStep::Up, 4, 10), Some(9));
+        assert_eq!(step_in_grid(None, GridStep::Down, 4, 0), None);
+        assert_eq!(step_in_grid(Some(3), GridStep::Down, 4, 0), None);
+    }
+
+    #[test]
+    fn a_single_column_moves_like_the_list() {
+        assert_eq!(step_in_grid(Some(2), GridStep::Down, 1, 5), Some(3));
+        assert_eq!(step_in_grid(Some(2), GridStep::Up, 1, 5), Some(1));
+        assert_eq!(step_in_grid(Some(2), GridStep::Left, 1, 5), Some(2));
+        assert_eq!(step_in_grid(Some(2), GridStep::Right, 1, 5), Some(2));
+    }
+}
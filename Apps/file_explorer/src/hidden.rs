@@ -0,0 +1,59 @@
+use std::fs::DirEntry;
+
+/// Is an entry called `name` hidden by the platform's convention?
+///
+/// On Unix that's a name starting with `.`; on Windows it's the hidden file attribute,
+/// passed in as `hidden_attribute` so this can be tested on any platform.
+pub fn is_hidden(name: &str, hidden_attribute: bool, windows: bool) -> bool {
+    if windows {
+        hidden_attribute
+    } else {
+        name.starts_with('.')
+    }
+}
+
+/// [`is_hidden`] for a directory entry on the current platform.
+pub fn is_hidden_entry(entry: &DirEntry) -> bool {
+    is_hidden(
+        &entry.file_name().to_string_lossy(),
+        has_hidden_attribute(entry),
+        cfg!(windows),
+    )
+}
+
+#[cfg(windows)]
+fn has_hidden_attribute(entry: &DirEntry) -> bool {
+    use std::os::windows::fs::MetadataExt as _;
+
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    entry
+        .metadata()
+        .map_or(false, |m| m.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+}
+
+#[cfg(not(windows))]
+fn has_hidden_attribute(_entry: &DirEntry) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dotfiles_are_hidden_on_unix() {
+        assert!(is_hidden(".git", false, false));
+        assert!(is_hidden(".cache", false, false));
+        assert!(!is_hidden("Documents", false, false));
+        assert!(!is_hidden("notes.txt", false, false));
+        // The attribute means nothing on Unix.
+        assert!(!is_hidden("Documents", true, false));
+    }
+
+    #[test]
+    fn hidden_attribute_decides_on_windows() {
+        assert!(is_hidden("desktop.ini", true, true));
+        assert!(!is_hidden("Documents", false, true));
+        assert!(!is_hidden(".gitconfig", false, true));
+    }
+}
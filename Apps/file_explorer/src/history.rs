@@ -0,0 +1,172 @@
+use std::path::{Path, PathBuf};
+
+/// At most this many folders are remembered in each direction.
+const MAX_HISTORY: usize = 100;
+
+/// Where [`NavHistory::back`] or [`NavHistory::forward`] leads.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Step {
+    Go(PathBuf),
+
+    /// The folder is gone; it has been dropped from the history and nothing else changed.
+    Missing(PathBuf),
+}
+
+/// The folders visited before and after the current one, like a web browser's history.
+#[derive(Debug, Default)]
+pub struct NavHistory {
+    back: Vec<PathBuf>,
+    forward: Vec<PathBuf>,
+}
+
+impl NavHistory {
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Records going from `from` to somewhere new, which forgets the forward history.
+    ///
+    /// Going to where you already are (e.g. clicking the last breadcrumb) isn't a step.
+    pub fn visit(&mut self, from: &Path, to: &Path) {
+        if from == to {
+            return;
+        }
+        push(&mut self.back, from.to_owned());
+        self.forward.clear();
+    }
+
+    /// Goes one folder back from `current`. Folders for which `exists` is false are dropped.
+    pub fn back(&mut self, current: &Path, exists: impl Fn(&Path) -> bool) -> Option<Step> {
+        step(&mut self.back, &mut self.forward, current, exists)
+    }
+
+    /// Goes one folder forward from `current`. Folders for which `exists` is false are dropped.
+    pub fn forward(&mut self, current: &Path, exists: impl Fn(&Path) -> bool) -> Option<Step> {
+        step(&mut self.forward, &mut self.back, current, exists)
+    }
+}
+
+/// Moves from the top of `from` to `current`, which goes on `to`.
+fn step(
+    from: &mut Vec<PathBuf>,
+    to: &mut Vec<PathBuf>,
+    current: &Path,
+    exists: impl Fn(&Path) -> bool,
+) -> Option<Step> {
+    let path = from.pop()?;
+    if !exists(&path) {
+        return Some(Step::Missing(path));
+    }
+    push(to, current.to_owned());
+    Some(Step::Go(path))
+}
+
+fn push(stack: &mut Vec<PathBuf>, path: PathBuf) {
+    if stack.len() == MAX_HISTORY {
+        stack.remove(0);
+    }
+    stack.push(path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exists(_: &Path) -> bool {
+        true
+    }
+
+    fn go(path: &str) -> Step {
+        Step::Go(PathBuf::from(path))
+    }
+
+    /// Visits `paths` in order, starting from the first.
+    fn visited(paths: &[&str]) -> NavHistory {
+        let mut history = NavHistory::default();
+        for pair in paths.windows(2) {
+            history.visit(Path::new(pair[0]), Path::new(pair[1]));
+        }
+        history
+    }
+
+    #[test]
+    fn empty_history_goes_nowhere() {
+        let mut history = NavHistory::default();
+        assert!(!history.can_go_back());
+        assert!(!history.can_go_forward());
+        assert_eq!(history.back(Path::new("/"), exists), None);
+        assert_eq!(history.forward(Path::new("/"), exists), None);
+    }
+
+    #[test]
+    fn back_and_forward_retrace_the_visits() {
+        let mut history = visited(&["/", "/a", "/a/b"]);
+        assert_eq!(history.back(Path::new("/a/b"), exists), Some(go("/a")));
+        assert_eq!(history.back(Path::new("/a"), exists), Some(go("/")));
+        assert!(!history.can_go_back());
+
+        assert_eq!(history.forward(Path::new("/"), exists), Some(go("/a")));
+        assert_eq!(history.forward(Path::new("/a"), exists), Some(go("/a/b")));
+        assert!(!history.can_go_forward());
+        assert_eq!(history.back(Path::new("/a/b"), exists), Some(go("/a")));
+    }
+
+    #[test]
+    fn visiting_clears_the_forward_history() {
+        let mut history = visited(&["/", "/a", "/a/b"]);
+        history.back(Path::new("/a/b"), exists);
+        assert!(history.can_go_forward());
+
+        history.visit(Path::new("/a"), Path::new("/c"));
+        assert!(!history.can_go_forward());
+        assert_eq!(history.back(Path::new("/c"), exists), Some(go("/a")));
+        assert_eq!(history.back(Path::new("/a"), exists), Some(go("/")));
+    }
+
+    #[test]
+    fn visiting_the_current_folder_is_not_a_step() {
+        let mut history = visited(&["/", "/a"]);
+        history.back(Path::new("/a"), exists);
+        history.visit(Path::new("/"), Path::new("/"));
+
+        assert!(!history.can_go_back());
+        assert!(history.can_go_forward());
+    }
+
+    #[test]
+    fn missing_folders_are_dropped() {
+        let mut history = visited(&["/", "/gone", "/a"]);
+        let not_gone = |path: &Path| path != Path::new("/gone");
+
+        assert_eq!(
+            history.back(Path::new("/a"), not_gone),
+            Some(Step::Missing(PathBuf::from("/gone")))
+        );
+        // Still in "/a": nothing to go forward to, and the next step back skips "/gone".
+        assert!(!history.can_go_forward());
+        assert_eq!(history.back(Path::new("/a"), not_gone), Some(go("/")));
+        assert_eq!(history.forward(Path::new("/"), not_gone), Some(go("/a")));
+        assert!(!history.can_go_forward());
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut history = NavHistory::default();
+        for i in 0..MAX_HISTORY + 10 {
+            history.visit(Path::new(&i.to_string()), Path::new(&(i + 1).to_string()));
+        }
+
+        let mut current = PathBuf::from((MAX_HISTORY + 10).to_string());
+        let mut steps = 0;
+        while let Some(Step::Go(path)) = history.back(&current, exists) {
+            current = path;
+            steps += 1;
+        }
+        assert_eq!(steps, MAX_HISTORY);
+        assert_eq!(current, PathBuf::from("10"));
+    }
+}
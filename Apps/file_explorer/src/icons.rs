@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use egui::{Button, TextEdit, Ui};
+
+use crate::file_type::FileType;
+use crate::type_filter::FileGroup;
+
+/// The icon of files nothing is known about.
+pub const FALLBACK_ICON: &str = "❓";
+
+const CODE_ICON: &str = "📝";
+const SPREADSHEET_ICON: &str = "📊";
+const PRESENTATION_ICON: &str = "📽";
+const VIDEO_ICON: &str = "🎞";
+const EXECUTABLE_ICON: &str = "⚙";
+
+/// What files with a known extension are, as far as their icon goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    Code,
+    Document,
+    Spreadsheet,
+    Presentation,
+    Archive,
+    Image,
+    Audio,
+    Video,
+    Executable,
+}
+
+impl FileKind {
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Code => CODE_ICON,
+            Self::Document => FileGroup::Documents.icon(),
+            Self::Spreadsheet => SPREADSHEET_ICON,
+            Self::Presentation => PRESENTATION_ICON,
+            Self::Archive => FileGroup::Archives.icon(),
+            Self::Image => FileGroup::Images.icon(),
+            Self::Audio => FileGroup::Audio.icon(),
+            Self::Video => VIDEO_ICON,
+            Self::Executable => EXECUTABLE_ICON,
+        }
+    }
+}
+
+/// The kind of files with `extension` (without the dot), if it's a known one.
+pub fn file_kind(extension: &str) -> Option<FileKind> {
+    let kind = match extension.to_lowercase().as_str() {
+        "rs" | "py" | "js" | "ts" | "jsx" | "tsx" | "c" | "h" | "cpp" | "cc" | "hpp" | "go"
+        | "java" | "kt" | "cs" | "rb" | "php" | "swift" | "lua" | "sh" | "bash" | "ps1"
+        | "html" | "css" | "json" | "toml" | "yaml" | "yml" | "xml" | "sql" => FileKind::Code,
+        "txt" | "md" | "pdf" | "doc" | "docx" | "odt" | "rtf" | "epub" => FileKind::Document,
+        "xls" | "xlsx" | "ods" | "csv" => FileKind::Spreadsheet,
+        "ppt" | "pptx" | "odp" => FileKind::Presentation,
+        "zip" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "zst" | "7z" | "rar" | "jar" | "deb"
+        | "rpm" => FileKind::Archive,
+        "jpg" | "jpeg" | "png" | "gif" | "bmp" | "svg" | "webp" | "ico" | "tif" | "tiff" => {
+            FileKind::Image
+        }
+        "mp3" | "wav" | "flac" | "ogg" | "oga" | "opus" | "m4a" | "aac" => FileKind::Audio,
+        "mp4" | "mkv" | "webm" | "avi" | "mov" | "wmv" | "m4v" => FileKind::Video,
+        "exe" | "msi" | "dll" | "so" | "dylib" | "appimage" | "bin" | "bat" | "cmd" | "apk" => {
+            FileKind::Executable
+        }
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// The icon of files with `extension` (without the dot) unless the user says otherwise, if
+/// it's a known one.
+pub fn default_icon(extension: &str) -> Option<&'static str> {
+    file_kind(extension).map(FileKind::icon)
+}
+
+/// Which icon stands for which file: the [`default_icon`]s, with the user's own on top.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IconTheme {
+    /// Lowercase extension, without the dot, to icon.
+    overrides: BTreeMap<String, String>,
+}
+
+impl IconTheme {
+    /// A theme with the user's icons for extensions, as saved in the settings.
+    pub fn new(overrides: BTreeMap<String, String>) -> Self {
+        let mut theme = Self::default();
+        for (extension, icon) in overrides {
+            theme.set(&extension, &icon);
+        }
+        theme
+    }
+
+    /// The user's icons, to save in the settings.
+    pub fn overrides(&self) -> &BTreeMap<String, String> {
+        &self.overrides
+    }
+
+    /// Shows `icon` for files with `extension`, whatever they are.
+    pub fn set(&mut self, extension: &str, icon: &str) {
+        let extension = extension.trim().trim_start_matches('.').to_lowercase();
+        let icon = icon.trim().to_owned();
+        if !extension.is_empty() && !icon.is_empty() {
+            self.overrides.insert(extension, icon);
+        }
+    }
+
+    /// Goes back to the default icon for files with `extension`.
+    pub fn remove(&mut self, extension: &str) {
+        self.overrides.remove(extension);
+    }
+
+    /// The icon of the file called `name`, whose contents are `sniffed` if they've been read.
+    ///
+    /// The user's icon for the extension comes first, then the sniffed type, then the
+    /// default for the extension. Plain text says less than the extension does, though: a
+    /// `.rs` file is still code.
+    pub fn icon(&self, name: &str, sniffed: Option<FileType>) -> &str {
+        let extension = Path::new(name)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        let extension = extension.as_deref();
+        if let Some(icon) = extension.and_then(|extension| self.overrides.get(extension)) {
+            return icon;
+        }
+        match (sniffed, extension.and_then(default_icon)) {
+            (Some(FileType::Text), Some(icon)) => icon,
+            (Some(file_type), _) => file_type.icon(),
+            (None, icon) => icon.unwrap_or(FALLBACK_ICON),
+        }
+    }
+}
+
+/// The "Icons" part of the settings: the user's icons, and fields to add another.
+#[derive(Default)]
+pub struct IconThemeEditor {
+    extension: String,
+    icon: String,
+}
+
+impl IconThemeEditor {
+    pub fn ui(&mut self, ui: &mut Ui, theme: &mut IconTheme) {
+        let mut removed = None;
+        for (extension, icon) in theme.overrides() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{icon}  .{extension}"));
+                if ui
+                    .small_button("🗑")
+                    .on_hover_text("Use the default icon")
+                    .clicked()
+                {
+                    removed = Some(extension.clone());
+                }
+            });
+        }
+        if let Some(extension) = removed {
+            theme.remove(&extension);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.extension)
+                    .hint_text("Extension")
+                    .desired_width(80.0),
+            );
+            ui.add(
+                TextEdit::singleline(&mut self.icon)
+                    .hint_text("Icon")
+                    .desired_width(40.0),
+            );
+            let valid = !self.extension.trim().is_empty() && !self.icon.trim().is_empty();
+            if ui.add_enabled(valid, Button::new("Set")).clicked() {
+                theme.set(&self.extension, &self.icon);
+                self.extension.clear();
+                self.icon.clear();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn theme(overrides: &[(&str, &str)]) -> IconTheme {
+        let overrides = overrides.iter().map(|&(e, i)| (e.to_owned(), i.to_owned()));
+        IconTheme::new(overrides.collect())
+    }
+
+    #[test]
+    fn defaults_cover_the_usual_kinds() {
+        let cases = [
+            ("rs", CODE_ICON),
+            ("py", CODE_ICON),
+            ("JS", CODE_ICON),
+            ("cpp", CODE_ICON),
+            ("go", CODE_ICON),
+            ("java", CODE_ICON),
+            ("doc", FileGroup::Documents.icon()),
+            ("md", FileGroup::Documents.icon()),
+            ("xls", SPREADSHEET_ICON),
+            ("ppt", PRESENTATION_ICON),
+            ("tar", FileGroup::Archives.icon()),
+            ("gz", FileGroup::Archives.icon()),
+            ("7z", FileGroup::Archives.icon()),
+            ("rar", FileGroup::Archives.icon()),
+            ("mp4", VIDEO_ICON),
+            ("mkv", VIDEO_ICON),
+            ("flac", FileGroup::Audio.icon()),
+            ("ogg", FileGroup::Audio.icon()),
+            ("exe", EXECUTABLE_ICON),
+            ("AppImage", EXECUTABLE_ICON),
+        ];
+        for (extension, icon) in cases {
+            assert_eq!(default_icon(extension), Some(icon), "{extension}");
+        }
+        assert_eq!(default_icon("qqq"), None);
+        assert_eq!(default_icon(""), None);
+    }
+
+    #[test]
+    fn overrides_win_over_everything() {
+        let theme = theme(&[("rs", "🦀"), ("bin", "💾")]);
+        assert_eq!(theme.icon("main.rs", None), "🦀");
+        assert_eq!(theme.icon("MAIN.RS", Some(FileType::Text)), "🦀");
+        assert_eq!(theme.icon("firmware.bin", Some(FileType::Elf)), "💾");
+    }
+
+    #[test]
+    fn sniffed_types_win_over_extensions() {
+        let theme = IconTheme::default();
+        // Named like a document, but a program.
+        assert_eq!(
+            theme.icon("notes.txt", Some(FileType::Elf)),
+            EXECUTABLE_ICON
+        );
+        assert_eq!(
+            theme.icon("photo.png", Some(FileType::Zip)),
+            FileGroup::Archives.icon()
+        );
+        // Unknown extensions and no extension at all go by the contents.
+        assert_eq!(
+            theme.icon("data.qqq", Some(FileType::Png)),
+            FileGroup::Images.icon()
+        );
+        assert_eq!(theme.icon("install", Some(FileType::Elf)), EXECUTABLE_ICON);
+        assert_eq!(
+            theme.icon("README", Some(FileType::Text)),
+            FileGroup::Documents.icon()
+        );
+        // Text only says as much as a known extension does.
+        assert_eq!(theme.icon("main.rs", Some(FileType::Text)), CODE_ICON);
+    }
+
+    #[test]
+    fn extensions_then_the_fallback() {
+        let theme = IconTheme::default();
+        assert_eq!(theme.icon("main.rs", None), CODE_ICON);
+        assert_eq!(theme.icon("movie.MKV", None), VIDEO_ICON);
+        assert_eq!(
+            theme.icon("archive.tar.gz", None),
+            FileGroup::Archives.icon()
+        );
+        assert_eq!(theme.icon("data.qqq", None), FALLBACK_ICON);
+        assert_eq!(theme.icon("Makefile", None), FALLBACK_ICON);
+        assert_eq!(theme.icon(".bashrc", None), FALLBACK_ICON);
+    }
+
+    #[test]
+    fn overrides_are_normalized() {
+        let mut theme = theme(&[(".PY", " 🐍 ")]);
+        assert_eq!(theme.overrides().get("py").map(String::as_str), Some("🐍"));
+        assert_eq!(theme.icon("script.py", None), "🐍");
+
+        // Nothing to show or nothing to show it for.
+        theme.set("", "x");
+        theme.set("md", "  ");
+        assert_eq!(theme.overrides().len(), 1);
+
+        theme.remove("py");
+        assert_eq!(theme.icon("script.py", None), CODE_ICON);
+    }
+}
@@ -0,0 +1,302 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Opens `path` with the application the system associates with it.
+pub fn open_with_default_app(path: &Path) -> Result<(), String> {
+    default_app_command(path)
+        .spawn()
+        .map(drop)
+        .map_err(|err| format!("Couldn't open {}: {err}", path.display()))
+}
+
+/// The command [`open_with_default_app`] runs. The path is its only argument, and no shell
+/// sees it: on Windows `cmd /C start` would run whatever follows a `&` or `|` in a file name.
+fn default_app_command(path: &Path) -> Command {
+    let mut command = if cfg!(target_os = "windows") {
+        Command::new("explorer")
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(path);
+    command
+}
+
+/// The command "Open terminal here" runs by default, in the folder to open it in.
+pub fn default_terminal_command() -> String {
+    if cfg!(target_os = "windows") {
+        "wt -d {path}".to_owned()
+    } else if cfg!(target_os = "macos") {
+        "open -a Terminal {path}".to_owned()
+    } else if Path::new("/usr/bin/x-terminal-emulator").exists() {
+        // Debian and its derivatives point this at whichever terminal is installed.
+        "x-terminal-emulator".to_owned()
+    } else {
+        "gnome-terminal".to_owned()
+    }
+}
+
+/// The command "Open in editor" runs by default.
+pub fn default_editor_command() -> String {
+    if cfg!(target_os = "windows") {
+        "notepad {path}".to_owned()
+    } else if cfg!(target_os = "macos") {
+        "open -t {path}".to_owned()
+    } else {
+        "gedit {path}".to_owned()
+    }
+}
+
+/// Splits `line` into a program and its arguments, like a shell would.
+///
+/// Whitespace separates arguments, except in single or double quotes. A backslash escapes
+/// the next character outside single quotes, but only a quote or another backslash, so
+/// Windows paths can be written as they are.
+pub fn split_command_line(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    // Whether there's an argument being built, which may be empty: `""`.
+    let mut in_arg = false;
+    let mut quote = None;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None | Some('"'), '\\') if matches!(chars.peek(), Some('"' | '\'' | '\\')) => {
+                arg.extend(chars.next());
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut arg));
+                    in_arg = false;
+                }
+            }
+            (_, c) => {
+                arg.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if let Some(q) = quote {
+        return Err(format!("Unclosed {q} in `{line}`"));
+    }
+    if in_arg {
+        args.push(arg);
+    }
+    Ok(args)
+}
+
+/// How [`shell_quote`] quotes, for the shell a pasted path most likely ends up in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// sh, bash, zsh and the like.
+    Posix,
+    /// What Windows Terminal opens by default.
+    PowerShell,
+}
+
+impl QuoteStyle {
+    pub fn native() -> Self {
+        if cfg!(target_os = "windows") {
+            Self::PowerShell
+        } else {
+            Self::Posix
+        }
+    }
+}
+
+/// `text` as a single argument that a shell takes literally: as it is if nothing in it
+/// is special, or else in single quotes, in which not even `$` is.
+///
+/// A single quote can't be in single quotes in a POSIX shell, so it ends them and goes
+/// in escaped: `'it'\''s'`. PowerShell doubles it instead: `'it''s'`.
+pub fn shell_quote(text: &str, style: QuoteStyle) -> Cow<'_, str> {
+    let is_plain = |c: char| {
+        c.is_ascii_alphanumeric()
+            || matches!(c, '_' | '-' | '.' | '/' | ',' | ':' | '+' | '=' | '@')
+            || (style == QuoteStyle::PowerShell && c == '\\')
+    };
+    if !text.is_empty() && text.chars().all(is_plain) {
+        return Cow::Borrowed(text);
+    }
+    let escaped_quote = match style {
+        QuoteStyle::Posix => r"'\''",
+        QuoteStyle::PowerShell => "''",
+    };
+    Cow::Owned(format!("'{}'", text.replace('\'', escaped_quote)))
+}
+
+/// `path` with the separators of this platform, e.g. backslashes on Windows even if it was
+/// typed with slashes.
+pub fn native_path(path: &str) -> String {
+    Path::new(path)
+        .components()
+        .collect::<PathBuf>()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// The program and arguments of `template` with every `{path}` replaced by `path`.
+///
+/// The path is put in after splitting, so spaces in it don't need quoting.
+pub fn command_args(template: &str, path: &Path) -> Result<Vec<String>, String> {
+    let path = path.to_string_lossy();
+    Ok(split_command_line(template)?
+        .into_iter()
+        .map(|arg| arg.replace("{path}", &path))
+        .collect())
+}
+
+/// Runs `args` in `dir` without waiting for it.
+fn spawn(args: &[String], dir: &Path) -> Result<(), String> {
+    let Some((program, args)) = args.split_first() else {
+        return Err("The command is empty; set one in the settings".to_owned());
+    };
+    Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .spawn()
+        .map(drop)
+        .map_err(|err| format!("Couldn't run {program}: {err}"))
+}
+
+/// Opens a terminal in `dir` with `template`, see [`command_args`].
+pub fn open_terminal(template: &str, dir: &Path) -> Result<(), String> {
+    spawn(&command_args(template, dir)?, dir)
+}
+
+/// Opens the file at `path` with the editor `template`, see [`command_args`].
+///
+/// Without a `{path}` in it, the path is the last argument.
+pub fn open_in_editor(template: &str, path: &Path) -> Result<(), String> {
+    let mut args = command_args(template, path)?;
+    if !template.contains("{path}") && !args.is_empty() {
+        args.push(path.to_string_lossy().into_owned());
+    }
+    spawn(&args, path.parent().unwrap_or(Path::new(".")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(line: &str) -> Vec<String> {
+        split_command_line(line).unwrap()
+    }
+
+    #[test]
+    fn default_app_gets_the_path_as_it_is() {
+        for name in ["a&calc.exe", "x|y", "a b; rm -rf ~", "$(reboot)", "%PATH%^.txt"] {
+            let path = Path::new("folder").join(name);
+            let command = default_app_command(&path);
+            assert_ne!(command.get_program(), "cmd");
+            assert_eq!(command.get_args().collect::<Vec<_>>(), [path.as_os_str()]);
+        }
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(split("gnome-terminal"), ["gnome-terminal"]);
+        assert_eq!(split("  code  --wait\t-n "), ["code", "--wait", "-n"]);
+        assert!(split("").is_empty());
+        assert!(split("   ").is_empty());
+    }
+
+    #[test]
+    fn quotes_keep_spaces() {
+        assert_eq!(
+            split(r#""C:\Program Files\Vim\gvim.exe" --remote {path}"#),
+            [r"C:\Program Files\Vim\gvim.exe", "--remote", "{path}"]
+        );
+        assert_eq!(
+            split("open -a 'Sublime Text'"),
+            ["open", "-a", "Sublime Text"]
+        );
+        assert_eq!(split(r#"say "it's" ''"#), ["say", "it's", ""]);
+        assert_eq!(split(r#"a"b c"d"#), ["ab cd"]);
+    }
+
+    #[test]
+    fn backslashes_only_escape_quotes_and_backslashes() {
+        assert_eq!(split(r#"echo \"hi\" a\\b"#), ["echo", "\"hi\"", r"a\b"]);
+        assert_eq!(split(r"notepad C:\Users\me"), ["notepad", r"C:\Users\me"]);
+        assert_eq!(split(r"echo 'a\'"), ["echo", r"a\"]);
+    }
+
+    #[test]
+    fn unclosed_quotes_are_errors() {
+        assert!(split_command_line(r#"code "my file"#).is_err());
+        assert!(split_command_line("open -a 'Terminal").is_err());
+    }
+
+    #[test]
+    fn paths_with_spaces_stay_one_argument() {
+        let path = Path::new("/home/me/My Documents/notes.txt");
+        assert_eq!(
+            command_args("code --goto {path}", path).unwrap(),
+            ["code", "--goto", "/home/me/My Documents/notes.txt"]
+        );
+        assert_eq!(
+            command_args("kitty --directory={path}", Path::new("/tmp/a b")).unwrap(),
+            ["kitty", "--directory=/tmp/a b"]
+        );
+        assert_eq!(
+            command_args("x-terminal-emulator", path).unwrap(),
+            ["x-terminal-emulator"]
+        );
+    }
+
+    #[test]
+    fn plain_text_is_not_quoted() {
+        for style in [QuoteStyle::Posix, QuoteStyle::PowerShell] {
+            assert_eq!(shell_quote("/home/me/notes-1.txt", style), "/home/me/notes-1.txt");
+            assert_eq!(shell_quote("a=b,c:d+e@f_g", style), "a=b,c:d+e@f_g");
+            assert_eq!(shell_quote("", style), "''");
+        }
+        assert_eq!(shell_quote(r"C:\Users\me", QuoteStyle::PowerShell), r"C:\Users\me");
+        assert_eq!(shell_quote(r"C:\Users\me", QuoteStyle::Posix), r"'C:\Users\me'");
+    }
+
+    #[test]
+    fn shell_unsafe_text_is_quoted() {
+        let cases = [
+            ("My Documents", "'My Documents'", "'My Documents'"),
+            ("$HOME", "'$HOME'", "'$HOME'"),
+            ("cost $5 (draft)", "'cost $5 (draft)'", "'cost $5 (draft)'"),
+            (r#"say "hi""#, r#"'say "hi"'"#, r#"'say "hi"'"#),
+            ("it's", r"'it'\''s'", "'it''s'"),
+            ("''", r"''\'''\'''", "''''''"),
+            ("a*b?[c]", "'a*b?[c]'", "'a*b?[c]'"),
+            ("~/x;rm", "'~/x;rm'", "'~/x;rm'"),
+            ("naïve", "'naïve'", "'naïve'"),
+        ];
+        for (text, posix, powershell) in cases {
+            assert_eq!(shell_quote(text, QuoteStyle::Posix), posix, "{text}");
+            assert_eq!(shell_quote(text, QuoteStyle::PowerShell), powershell, "{text}");
+        }
+    }
+
+    #[test]
+    fn quoted_text_splits_back_into_itself() {
+        for text in ["My Documents", "$HOME", r#"say "hi""#, "it's", "''", "a\tb", ""] {
+            let quoted = shell_quote(text, QuoteStyle::Posix);
+            assert_eq!(split(&format!("echo {quoted}")), ["echo", text], "{quoted}");
+        }
+    }
+
+    #[test]
+    fn paths_are_made_native() {
+        assert_eq!(native_path("/home/me/notes.txt"), "/home/me/notes.txt");
+        #[cfg(windows)]
+        assert_eq!(native_path("C:/Users/me/notes.txt"), r"C:\Users\me\notes.txt");
+        #[cfg(not(windows))]
+        assert_eq!(native_path("/home//me/./notes.txt"), "/home/me/notes.txt");
+    }
+}
@@ -1,130 +1,1013 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use eframe::epaint::Color32;
-use egui::{popup_above_or_below_widget, AboveOrBelow, Id, PopupCloseBehavior, RichText, Ui};
-use crate::explorer::FileBrowserApp;
+use egui::text::{CCursor, CCursorRange, LayoutJob, TextFormat};
+use egui_extras::TableRow;
+use egui::{popup_above_or_below_widget, AboveOrBelow, Button, DragAndDrop, Id, Image, Modifiers, PopupCloseBehavior, Response, RichText, Sense, TextEdit, TextStyle, TextureHandle, Ui, Vec2, WidgetText};
+use crate::archive::{parse_archive_path, ArchivePath};
+use crate::explorer::{File, FileBrowserApp, Folder};
+use crate::file_type::{is_mismatch, FileTypes, Sniff};
+use crate::icons::IconTheme;
+use crate::launch::{native_path, shell_quote, QuoteStyle};
+use crate::new_folder::NewFolder;
+use crate::paste::ClipboardMode;
+use crate::permissions::{Owner, Permissions};
+use crate::rename::{initial_selection, Rename};
+use crate::search::{result_location, split_matches, Matcher};
+use crate::size_format::format_size_with_base;
+use crate::size_state::SizeState;
+use crate::sort::Entry;
+use crate::status::group_thousands;
+use crate::thumbnails::{is_image_extension, Thumbnails};
+use crate::time_format::format_time;
 
-pub fn list_explorer(app: &mut FileBrowserApp, mut ui: &mut Ui)
+pub fn list_explorer(app: &mut FileBrowserApp, ui: &mut Ui)
 {
-    let mut new_path = None;
+    let mut actions = EntryActions::default();
+    let modifiers = ui.input(|i| i.modifiers);
+    // Less the header row.
+    app.page_rows = page_rows(ui.available_height() - ROW_HEIGHT, ROW_HEIGHT);
+    let now = std::time::SystemTime::now();
+    // Taken out while the rows are built, which only read the rest of the app.
+    let mut renaming = app.renaming.take();
+    let search_root = app.search_root().map(Path::to_path_buf);
 
-    let combined_table = egui_extras::TableBuilder::new(&mut ui)
+    let mut combined_table = egui_extras::TableBuilder::new(ui)
         .striped(true)
         .resizable(true)
         .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
         .column(egui_extras::Column::initial(100.0).at_least(25.0))
+        .columns(egui_extras::Column::auto().at_least(80.0), usize::from(search_root.is_some()))
+        .column(egui_extras::Column::auto().at_least(80.0))
+        .column(egui_extras::Column::auto().at_least(80.0))
+        .columns(egui_extras::Column::auto().at_least(80.0), detail_columns(app))
         .min_scrolled_height(0.0);
+    if let Some(cursor) = app.cursor.filter(|_| app.scroll_to_cursor) {
+        let row = cursor_row(cursor, app.new_folder.is_some());
+        combined_table = combined_table.scroll_to_row(row, None);
+        app.scroll_to_cursor = false;
+    }
 
     combined_table
-        .header(20.0, |mut header| {
+        .header(ROW_HEIGHT, |mut header| {
             header.col(|ui| {
                 ui.strong("Name");
             });
+            if search_root.is_some() {
+                header.col(|ui| {
+                    ui.strong("Location");
+                });
+            }
+            header.col(|ui| {
+                ui.strong("Modified");
+            });
+            header.col(|ui| {
+                ui.strong("Created");
+            });
+            if app.show_permissions_column {
+                header.col(|ui| {
+                    ui.strong("Permissions");
+                });
+            }
+            if app.show_owner_column {
+                header.col(|ui| {
+                    ui.strong("Owner");
+                });
+            }
         })
-        .body(|mut body| {
-            for directory in &mut app.directories {
-                body.row(20.0, |mut row| {
-                    row.col(|ui| {
-                        ui.label("📁");
-                        let dir = ui.button(&directory.name);
-
-                        if dir.clicked() {
-                            new_path = Some(directory.dir.clone());
+        .body(|body| {
+            let has_new_folder = app.new_folder.is_some();
+            let row_count = usize::from(has_new_folder) + app.entries.len();
+            // Only the visible rows are built, which keeps huge folders responsive.
+            body.rows(ROW_HEIGHT, row_count, |mut row| {
+                let position = row.index().saturating_sub(usize::from(has_new_folder));
+                match row_entry(row.index(), has_new_folder, &app.entries) {
+                    RowEntry::NewFolder => {
+                        let Some(new_folder) = &mut app.new_folder else {
+                            return;
+                        };
+                        row.col(|ui| {
+                            ui.label("📁");
+                            new_folder_field(ui, new_folder, &mut actions);
+                        });
+                        if search_root.is_some() {
+                            row.col(|_ui| {});
+                        }
+                        row.col(|ui| {
+                            if let Some(error) = &new_folder.error {
+                                ui.label(RichText::new(error).color(Color32::RED));
+                            }
+                        });
+                        row.col(|_ui| {});
+                        for _ in 0..detail_columns(app) {
+                            row.col(|_ui| {});
+                        }
+                    }
+                    RowEntry::Folder(index) => {
+                        row.set_selected(app.selection.contains(Path::new(&app.directories[index].dir)));
+                        row.col(|ui| {
+                            let directory = &app.directories[index];
+                            ui.label("📁");
+                            link_badge(ui, directory.link_target.as_deref());
+                            if let Some(rename) = renaming.as_mut().filter(|r| r.path == Path::new(&directory.dir)) {
+                                actions.rename = rename_field(ui, rename, true).or(actions.rename);
+                                return;
+                            }
+                            let dir = ui.add(Button::new(name_text(ui, &directory.name, app.highlight.as_ref())).sense(Sense::click_and_drag()));
+                            folder_entry(app, ui, index, position, &dir, modifiers, &mut actions);
+                        });
+                        if let Some(root) = &search_root {
+                            location_cell(&mut row, root, &app.directories[index].dir);
+                        }
+                        row.col(|ui| {
+                            ui.label(format_time(app.directories[index].modified, now));
+                        });
+                        row.col(|ui| {
+                            ui.label(format_time(app.directories[index].created, now));
+                        });
+                        let directory = &app.directories[index];
+                        detail_cells(app, &mut row, directory.permissions, directory.owner);
+                    }
+                    RowEntry::File(index) => {
+                        row.set_selected(app.selection.contains(Path::new(&app.files[index].dir)));
+                        row.col(|ui| {
+                            let file = &app.files[index];
+                            match file_icon(&mut app.thumbnails, &app.file_types, &app.icon_theme, file) {
+                                FileIcon::Thumbnail(texture) => {
+                                    ui.add(Image::new(&texture).fit_to_exact_size(Vec2::splat(18.0)));
+                                }
+                                FileIcon::Text(icon) => {
+                                    ui.label(icon);
+                                }
+                            }
+                            link_badge(ui, file.link_target.as_deref());
+                            if let Some(rename) = renaming.as_mut().filter(|r| r.path == Path::new(&file.dir)) {
+                                actions.rename = rename_field(ui, rename, false).or(actions.rename);
+                                return;
+                            }
+                            let file_btn = ui.add(Button::new(name_text(ui, &file.name, app.highlight.as_ref())).sense(Sense::click_and_drag()));
+                            file_entry(app, ui, index, position, &file_btn, &mut actions);
+                        });
+                        if let Some(root) = &search_root {
+                            location_cell(&mut row, root, &app.files[index].dir);
                         }
+                        row.col(|ui| {
+                            ui.label(format_time(app.files[index].modified, now));
+                        });
+                        row.col(|ui| {
+                            ui.label(format_time(app.files[index].created, now));
+                        });
+                        let file = &app.files[index];
+                        detail_cells(app, &mut row, file.permissions, file.owner);
+                    }
+                }
+            });
+        });
 
-                        let id = Id::new(format!("1 {}", &directory.name));
+    app.renaming = renaming;
+    actions.apply(app, ui, modifiers);
+}
 
-                        if dir.secondary_clicked() {
-                            ui.memory_mut(|mem| mem.toggle_popup(id));
-                        }
+/// The folder of the search result at `path`, relative to `root`, the folder searched in.
+fn location_cell(row: &mut TableRow<'_, '_>, root: &Path, path: &str) {
+    row.col(|ui| {
+        let location = result_location(root, Path::new(path));
+        let full = Path::new(path).parent().map(|parent| parent.display().to_string());
+        let label = ui.add(egui::Label::new(RichText::new(location).weak()).truncate());
+        if let Some(full) = full {
+            label.on_hover_text(full);
+        }
+    });
+}
 
-                        popup_above_or_below_widget(
-                            ui,
-                            id,
-                            &dir,
-                            AboveOrBelow::Above,
-                            PopupCloseBehavior::CloseOnClickOutside,
-                            |ui| {
-                                ui.set_min_width(100.0);
-                                let size = directory.size.lock().unwrap().clone();
-                                let calculating = *directory.calculating.lock().unwrap();
-                                let error = directory.error.lock().unwrap().clone();
-
-                                ui.vertical(|ui| {
-                                    ui.horizontal(|ui| {
-                                        ui.label("Name: ");
-                                        ui.strong(directory.clone().name);
-                                    });
-                                    if error.is_some() {
-                                        ui.horizontal(|ui| {
-                                            ui.label("Error: ");
-                                            ui.label(RichText::new("Something Went Wrong").color(Color32::RED));
-                                        });
-                                    } else if size.is_none() && !calculating {
-                                        ui.horizontal(|ui| {
-                                            ui.label("Size: ");
-                                            FileBrowserApp::directory_size(directory);
-                                        });
-                                    } else if calculating {
-                                        ui.horizontal(|ui| {
-                                            ui.label("Size: ");
-                                            ui.add(egui::Spinner::new());
-                                            ui.label("Calculating...");
-                                        });
-                                    } else {
-                                        ui.horizontal(|ui| {
-                                            ui.label("Size: ");
-                                            ui.label(FileBrowserApp::format_size(size));
-                                        });
-                                    }
-                                })
-                            },
-                        );
-                    });
-                });
+/// How many of the optional "Permissions" and "Owner" columns the table has.
+fn detail_columns(app: &FileBrowserApp) -> usize {
+    usize::from(app.show_permissions_column) + usize::from(app.show_owner_column)
+}
+
+/// The cells of the optional columns counted by [`detail_columns`].
+fn detail_cells(app: &FileBrowserApp, row: &mut TableRow<'_, '_>, permissions: Option<Permissions>, owner: Option<Owner>) {
+    if app.show_permissions_column {
+        row.col(|ui| {
+            if let Some(permissions) = permissions {
+                ui.monospace(permissions.short_label());
             }
+        });
+    }
+    if app.show_owner_column {
+        row.col(|ui| {
+            if let Some(owner) = owner {
+                ui.label(app.owner_names.user(owner.uid));
+            }
+        });
+    }
+}
 
-            for file in &app.files {
-                body.row(20.0, |mut row| {
-                    row.col(|ui| {
-                        let path = Path::new(&file.name);
-                        if path.extension() != None
-                        { ui.label(FileBrowserApp::extension_icon(path.extension().unwrap().to_str().unwrap()).unwrap().to_string()); }
-                        else { ui.label("❓"); }
-                        let file_btn = ui.button(&file.name);
-
-                        if file_btn.clicked() {
-                            //new_path = Some(file.dir.clone());
-                        }
+/// What was done to the entries while a view of them was built, applied once it's done.
+#[derive(Default)]
+pub struct EntryActions {
+    open: Option<String>,
+    open_file: Option<String>,
+    /// An index into [`FileBrowserApp::visible_order`].
+    clicked: Option<usize>,
+    create_folder: Option<String>,
+    cancel_new_folder: bool,
+    start_rename: Option<PathBuf>,
+    open_terminal: Option<PathBuf>,
+    open_in_editor: Option<PathBuf>,
+    edit_permissions: Option<(PathBuf, u32)>,
+    properties: Option<File>,
+    preview: Option<ArchivePath>,
+    extract: Option<ArchivePath>,
+    reveal: Option<PathBuf>,
+    pub rename: Option<RenameAction>,
+    delete: Option<String>,
+    copy: Option<(String, ClipboardMode)>,
+    bookmark: Option<PathBuf>,
+    drag: Option<String>,
+    dropped: Option<(Arc<DraggedEntries>, PathBuf)>,
+}
 
-                        let id = Id::new(format!("2 {}", &file.name));
+impl EntryActions {
+    pub fn apply(self, app: &mut FileBrowserApp, ui: &Ui, modifiers: Modifiers) {
+        if let Some(path) = self.delete {
+            app.request_delete(&path);
+        }
+        if let Some(path) = self.drag {
+            // Dragging part of the selection drags all of it.
+            DragAndDrop::set_payload(ui.ctx(), DraggedEntries(app.action_paths(&path)));
+        }
+        if let Some((dragged, dest_dir)) = self.dropped {
+            app.drop_into(&dragged.0, dest_dir);
+        }
 
-                        if file_btn.secondary_clicked() {
-                            ui.memory_mut(|mem| mem.toggle_popup(id));
-                        }
+        if let Some((path, mode)) = self.copy {
+            let paths = app.action_paths(&path);
+            app.copy_paths(ui.ctx(), paths, mode);
+        }
 
-                        popup_above_or_below_widget(
-                            ui,
-                            id,
-                            &file_btn,
-                            AboveOrBelow::Above,
-                            PopupCloseBehavior::CloseOnClickOutside,
-                            |ui| {
-                                ui.set_min_width(100.0);
-                                if let Some(size) = file.size {
-                                    ui.label(format!("Size: {}", FileBrowserApp::format_size(Some(size))));
-                                } else {
-                                    ui.label("Size unknown");
-                                }
-                            },
-                        );
-                    });
+        if let Some(path) = self.bookmark {
+            app.bookmarks.toggle(&path);
+        }
+
+        if let Some(path) = self.start_rename {
+            app.renaming = Some(Rename::new(path));
+        }
+        if let Some(dir) = self.open_terminal {
+            app.open_terminal(&dir);
+        }
+        if let Some(path) = self.open_in_editor {
+            app.open_in_editor(&path);
+        }
+        if let Some((path, mode)) = self.edit_permissions {
+            app.permissions_dialog.open(path, mode);
+        }
+        if let Some(file) = self.properties {
+            app.properties_dialog.open(file);
+        }
+        if let Some(entry) = self.preview {
+            app.preview_entry(entry);
+        }
+        if let Some(entry) = self.extract {
+            app.extract_entry(entry);
+        }
+        match self.rename {
+            Some(RenameAction::Commit { overwrite }) => app.commit_rename(overwrite),
+            Some(RenameAction::Cancel) => app.renaming = None,
+            None => {}
+        }
+
+        if self.cancel_new_folder {
+            app.new_folder = None;
+        } else if let Some(name) = self.create_folder {
+            app.create_folder(&name);
+        }
+
+        if let Some(index) = self.clicked {
+            app.click_select(index, modifiers.command, modifiers.shift);
+        }
+
+        if let Some(path) = self.open {
+            app.open_folder(Path::new(&path));
+        }
+        if let Some(path) = self.open_file {
+            app.open_file(Path::new(&path));
+        }
+        if let Some(path) = self.reveal {
+            app.reveal(&path);
+        }
+    }
+}
+
+/// The text field naming the new folder, focused until it's created (Enter) or canceled (Escape).
+pub fn new_folder_field(ui: &mut Ui, new_folder: &mut NewFolder, actions: &mut EntryActions) {
+    let response = ui.text_edit_singleline(&mut new_folder.name);
+    if !response.has_focus() && !response.lost_focus() {
+        response.request_focus();
+    }
+    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+        actions.create_folder = Some(new_folder.name.clone());
+    }
+    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+        actions.cancel_new_folder = true;
+    }
+}
+
+/// What the folder at `index` of [`FileBrowserApp::directories`], shown at `position` of
+/// [`FileBrowserApp::entries`], does when its `response` is clicked, dragged, dropped onto
+/// or right-clicked, in either view.
+pub fn folder_entry(
+    app: &FileBrowserApp,
+    ui: &Ui,
+    index: usize,
+    position: usize,
+    response: &Response,
+    modifiers: Modifiers,
+    actions: &mut EntryActions,
+) {
+    let directory = &app.directories[index];
+    if response.drag_started() {
+        actions.drag = Some(directory.dir.clone());
+    }
+    if let Some(dragged) = drop_target(ui, response) {
+        actions.dropped = Some((dragged, PathBuf::from(&directory.dir)));
+    }
+
+    if response.clicked() {
+        if modifiers.command || modifiers.shift {
+            actions.clicked = Some(position);
+        } else {
+            actions.open = Some(directory.dir.clone());
+        }
+    }
+
+    let id = entry_id("folder_popup", Path::new(&directory.dir));
+
+    if response.secondary_clicked() {
+        ui.memory_mut(|mem| mem.toggle_popup(id));
+    }
+
+    popup_above_or_below_widget(
+        ui,
+        id,
+        response,
+        AboveOrBelow::Above,
+        PopupCloseBehavior::CloseOnClickOutside,
+        |ui| folder_popup(app, ui, directory, actions),
+    );
+    // Nobody is waiting for the size once its popup is closed.
+    if !ui.memory(|mem| mem.is_popup_open(id)) {
+        directory.size.lock().unwrap().cancel(SizeState::NotCalculated);
+    }
+}
+
+fn folder_popup(app: &FileBrowserApp, ui: &mut Ui, directory: &Folder, actions: &mut EntryActions) {
+    let now = std::time::SystemTime::now();
+    ui.set_min_width(100.0);
+    let size = directory.size.lock().unwrap().clone();
+
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.label("Name: ");
+            ui.strong(directory.clone().name);
+        });
+        if let Some(entry) = parse_archive_path(&directory.dir) {
+            archive_buttons(ui, entry, false, actions);
+            copy_text_buttons(app, ui, &directory.dir, &directory.name);
+            ui.label(format!("Modified: {}", format_time(directory.modified, now)));
+            if let SizeState::Done(size) = size {
+                ui.label(format!("Size: {}", format_size_with_base(Some(size.bytes), app.size_base)));
+            }
+            return;
+        }
+        if ui.button("✏ Rename").clicked() {
+            actions.start_rename = Some(PathBuf::from(&directory.dir));
+            ui.memory_mut(|mem| mem.close_popup());
+        }
+        if ui.button("🗑 Delete").clicked() {
+            actions.delete = Some(directory.dir.clone());
+            ui.memory_mut(|mem| mem.close_popup());
+        }
+        if ui.button("📋 Copy").clicked() {
+            actions.copy = Some((directory.dir.clone(), ClipboardMode::Copy));
+            ui.memory_mut(|mem| mem.close_popup());
+        }
+        if ui.button("✂ Cut").clicked() {
+            actions.copy = Some((directory.dir.clone(), ClipboardMode::Cut));
+            ui.memory_mut(|mem| mem.close_popup());
+        }
+        if ui.button(">_ Open terminal here").clicked() {
+            actions.open_terminal = Some(PathBuf::from(&directory.dir));
+            ui.memory_mut(|mem| mem.close_popup());
+        }
+        reveal_button(app, ui, &directory.dir, actions);
+        copy_text_buttons(app, ui, &directory.dir, &directory.name);
+        link_target_label(ui, &directory.dir, directory.link_target.as_deref());
+        let label = if app.bookmarks.contains(Path::new(&directory.dir)) {
+            "★ Remove bookmark"
+        } else {
+            "☆ Bookmark"
+        };
+        if ui.button(label).clicked() {
+            actions.bookmark = Some(PathBuf::from(&directory.dir));
+            ui.memory_mut(|mem| mem.close_popup());
+        }
+        permissions_lines(app, ui, &directory.dir, directory.permissions, directory.owner, actions);
+        ui.label(format!("Modified: {}", format_time(directory.modified, now)));
+        ui.label(format!("Created: {}", format_time(directory.created, now)));
+        match size {
+            SizeState::NotCalculated => {
+                FileBrowserApp::directory_size(directory, &app.size_cache, false, app.follow_links, app.size_mode);
+            }
+            SizeState::Calculating(progress) => {
+                ui.horizontal(|ui| {
+                    ui.label("Size: ");
+                    ui.add(egui::Spinner::new());
+                    ui.label(format!(
+                        "{} so far, {} entries",
+                        format_size_with_base(Some(progress.bytes()), app.size_base),
+                        group_thousands(progress.entries() as u64)
+                    ));
+                    if ui.small_button("Cancel").clicked() {
+                        directory.size.lock().unwrap().cancel(SizeState::Canceled);
+                    }
+                });
+                let current = progress.current();
+                ui.add(egui::Label::new(RichText::new(current.display().to_string()).small().weak()).truncate());
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+            }
+            SizeState::Canceled => {
+                ui.horizontal(|ui| {
+                    ui.label("Size: canceled");
+                    if ui.small_button("⟳").on_hover_text("Calculate").clicked() {
+                        FileBrowserApp::directory_size(directory, &app.size_cache, false, app.follow_links, app.size_mode);
+                    }
                 });
             }
+            SizeState::Failed(err) => {
+                ui.horizontal(|ui| {
+                    ui.label("Size: ⚠ unknown").on_hover_text(err);
+                    if ui.small_button("⟳").on_hover_text("Try again").clicked() {
+                        FileBrowserApp::directory_size(directory, &app.size_cache, false, app.follow_links, app.size_mode);
+                    }
+                });
+            }
+            SizeState::Done(size) => {
+                ui.horizontal(|ui| {
+                    ui.label("Size: ");
+                    let text = format_size_with_base(Some(size.bytes), app.size_base);
+                    if size.incomplete {
+                        ui.label(format!("⚠ at least {text}"))
+                            .on_hover_text("Some of the folder couldn't be read");
+                    } else {
+                        ui.label(text);
+                    }
+                    ui.weak(format!("({})", size.mode.label().to_lowercase()));
+                    if ui.small_button("⟳").on_hover_text("Recalculate").clicked() {
+                        FileBrowserApp::directory_size(directory, &app.size_cache, true, app.follow_links, app.size_mode);
+                    }
+                });
+            }
+        }
+    });
+}
+
+/// What the file at `index` of [`FileBrowserApp::files`], shown at `position` of
+/// [`FileBrowserApp::entries`], does when its `response` is clicked, dragged or
+/// right-clicked, in either view.
+pub fn file_entry(
+    app: &FileBrowserApp,
+    ui: &Ui,
+    index: usize,
+    position: usize,
+    response: &Response,
+    actions: &mut EntryActions,
+) {
+    let file = &app.files[index];
+    if response.drag_started() {
+        actions.drag = Some(file.dir.clone());
+    }
+
+    if response.clicked() {
+        actions.clicked = Some(position);
+    }
+    if response.double_clicked() {
+        actions.open_file = Some(file.dir.clone());
+    }
+
+    let id = entry_id("file_popup", Path::new(&file.dir));
+
+    if response.secondary_clicked() {
+        ui.memory_mut(|mem| mem.toggle_popup(id));
+    }
+
+    popup_above_or_below_widget(
+        ui,
+        id,
+        response,
+        AboveOrBelow::Above,
+        PopupCloseBehavior::CloseOnClickOutside,
+        |ui| file_popup(app, ui, file, actions),
+    );
+}
+
+fn file_popup(app: &FileBrowserApp, ui: &mut Ui, file: &File, actions: &mut EntryActions) {
+    let now = std::time::SystemTime::now();
+    ui.set_min_width(100.0);
+    if let Some(entry) = parse_archive_path(&file.dir) {
+        archive_buttons(ui, entry, true, actions);
+        copy_text_buttons(app, ui, &file.dir, &file.name);
+        ui.label(format!("Size: {}", format_size_with_base(file.size, app.size_base)));
+        ui.label(format!("Modified: {}", format_time(file.modified, now)));
+        return;
+    }
+    let is_zip = Path::new(&file.name)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"));
+    if is_zip && ui.button("📦 Browse archive").clicked() {
+        actions.open_file = Some(file.dir.clone());
+        ui.memory_mut(|mem| mem.close_popup());
+    }
+    if ui.button("✏ Rename").clicked() {
+        actions.start_rename = Some(PathBuf::from(&file.dir));
+        ui.memory_mut(|mem| mem.close_popup());
+    }
+    if ui.button("🗑 Delete").clicked() {
+        actions.delete = Some(file.dir.clone());
+        ui.memory_mut(|mem| mem.close_popup());
+    }
+    if ui.button("📋 Copy").clicked() {
+        actions.copy = Some((file.dir.clone(), ClipboardMode::Copy));
+        ui.memory_mut(|mem| mem.close_popup());
+    }
+    if ui.button("✂ Cut").clicked() {
+        actions.copy = Some((file.dir.clone(), ClipboardMode::Cut));
+        ui.memory_mut(|mem| mem.close_popup());
+    }
+    if ui.button("📝 Open in editor").clicked() {
+        actions.open_in_editor = Some(PathBuf::from(&file.dir));
+        ui.memory_mut(|mem| mem.close_popup());
+    }
+    if ui.button("ℹ Properties").clicked() {
+        actions.properties = Some(file.clone());
+        ui.memory_mut(|mem| mem.close_popup());
+    }
+    reveal_button(app, ui, &file.dir, actions);
+    copy_text_buttons(app, ui, &file.dir, &file.name);
+    link_target_label(ui, &file.dir, file.link_target.as_deref());
+    if let Some(size) = file.size {
+        ui.label(format!("Size: {}", format_size_with_base(Some(size), app.size_base)));
+    } else {
+        ui.label("Size unknown");
+    }
+    file_type_line(app, ui, file);
+    permissions_lines(app, ui, &file.dir, file.permissions, file.owner, actions);
+    ui.label(format!("Modified: {}", format_time(file.modified, now)));
+    ui.label(format!("Created: {}", format_time(file.created, now)));
+}
+
+/// What can be done to an entry of an archive, which is only reading it: "Extract" it next
+/// to the archive, or "Preview" it if it's a file.
+fn archive_buttons(ui: &mut Ui, entry: ArchivePath, is_file: bool, actions: &mut EntryActions) {
+    if is_file && ui.button("👁 Preview").clicked() {
+        actions.preview = Some(entry.clone());
+        ui.memory_mut(|mem| mem.close_popup());
+    }
+    if ui.button("📤 Extract").on_hover_text("Next to the archive").clicked() {
+        actions.extract = Some(entry);
+        ui.memory_mut(|mem| mem.close_popup());
+    }
+    ui.label(RichText::new("🔒 Read-only").weak());
+}
+
+/// "Reveal in folder" for a search result, going to where the entry at `path` is.
+fn reveal_button(app: &FileBrowserApp, ui: &mut Ui, path: &str, actions: &mut EntryActions) {
+    if app.search_root().is_some() && ui.button("📂 Reveal in folder").clicked() {
+        actions.reveal = Some(PathBuf::from(path));
+        ui.memory_mut(|mem| mem.close_popup());
+    }
+}
+
+/// What `file` is going by its contents, and whether its extension says otherwise.
+pub fn file_type_line(app: &FileBrowserApp, ui: &mut Ui, file: &File) {
+    match app.file_types.sniff(ui.ctx(), Path::new(&file.dir), file.modified) {
+        Sniff::Sniffing => {
+            ui.horizontal(|ui| {
+                ui.label("Type: ");
+                ui.add(egui::Spinner::new());
+            });
+        }
+        Sniff::Done(Err(error)) => {
+            ui.label("Type unknown").on_hover_text(error);
+        }
+        Sniff::Done(Ok(None)) => {
+            ui.label("Type unknown");
+        }
+        Sniff::Done(Ok(Some(file_type))) => {
+            ui.label(format!("Type: {}", file_type.label()));
+            if is_mismatch(&file.name, file_type) {
+                ui.label(RichText::new("⚠ The extension says otherwise").color(Color32::YELLOW))
+                    .on_hover_text(format!("The contents are a {}, whatever the name ends in", file_type.label()));
+            }
+        }
+    }
+}
+
+/// What stands for a file next to (or above) its name.
+pub enum FileIcon<'a> {
+    Thumbnail(TextureHandle),
+    Text(&'a str),
+}
+
+/// The thumbnail of `file` if it's an image and has one, or else its icon in `theme`.
+pub fn file_icon<'a>(
+    thumbnails: &mut Thumbnails,
+    file_types: &FileTypes,
+    theme: &'a IconTheme,
+    file: &File,
+) -> FileIcon<'a> {
+    let path = Path::new(&file.name);
+    let thumbnail = path
+        .extension()
+        .filter(|extension| is_image_extension(&extension.to_string_lossy()))
+        .and_then(|_| thumbnails.get(Path::new(&file.dir), file.modified));
+    if let Some(texture) = thumbnail {
+        FileIcon::Thumbnail(texture.clone())
+    } else {
+        let sniffed = file_types.known(Path::new(&file.dir), file.modified);
+        FileIcon::Text(theme.icon(&file.name, sniffed))
+    }
+}
+
+/// The id of the `purpose` state of the entry at `path`, like its popup.
+///
+/// Keyed by the whole path, since names repeat: in search results, or across listings.
+pub fn entry_id(purpose: &str, path: &Path) -> Id {
+    Id::new((purpose, path))
+}
+
+/// Height of the header and of every row of the table.
+const ROW_HEIGHT: f32 = 20.0;
+
+/// How many whole rows of `row_height` fit in `height`; at least one, so paging always moves.
+pub fn page_rows(height: f32, row_height: f32) -> usize {
+    ((height / row_height).floor() as usize).max(1)
+}
+
+/// The table row showing the entry at `cursor` in [`FileBrowserApp::visible_order`],
+/// the inverse of [`row_entry`].
+fn cursor_row(cursor: usize, has_new_folder: bool) -> usize {
+    cursor + usize::from(has_new_folder)
+}
+
+/// What the "Copy path" and "Copy name" popup buttons put on the clipboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CopyText {
+    Path,
+    Name,
+}
+
+/// The text `what` copies for the entry at `dir` (its full path) called `name`, quoted for
+/// the shell if `quote`.
+fn copy_text(what: CopyText, dir: &str, name: &str, quote: bool) -> String {
+    let text = match what {
+        CopyText::Path => native_path(dir),
+        CopyText::Name => name.to_owned(),
+    };
+    if quote {
+        shell_quote(&text, QuoteStyle::native()).into_owned()
+    } else {
+        text
+    }
+}
+
+/// What "Copy paths" puts on the clipboard: one path per line.
+fn copy_paths_text(paths: &[PathBuf], quote: bool) -> String {
+    let lines: Vec<_> = paths
+        .iter()
+        .map(|path| copy_text(CopyText::Path, &path.to_string_lossy(), "", quote))
+        .collect();
+    lines.join("\n")
+}
+
+/// The "Copy path" and "Copy name" buttons of an entry's popup, and "Copy paths" if the
+/// entry is part of a selection of several.
+fn copy_text_buttons(app: &FileBrowserApp, ui: &mut Ui, dir: &str, name: &str) {
+    let quote = app.quote_copied_paths;
+    let mut copied = None;
+    for (label, what) in [("🔗 Copy path", CopyText::Path), ("Copy name", CopyText::Name)] {
+        if ui.button(label).clicked() {
+            copied = Some(copy_text(what, dir, name, quote));
+        }
+    }
+    let paths = app.action_paths(dir);
+    if paths.len() > 1 && ui.button(format!("Copy {} paths (one per line)", paths.len())).clicked() {
+        copied = Some(copy_paths_text(&paths, quote));
+    }
+    if let Some(text) = copied {
+        ui.output_mut(|o| o.copied_text = text);
+        ui.memory_mut(|mem| mem.close_popup());
+    }
+}
+
+/// `name` for its button, with the part `highlight` matches drawn over the selection color.
+///
+/// Only called for the rows on screen, so long search results stay cheap.
+pub fn name_text(ui: &Ui, name: &str, highlight: Option<&Matcher>) -> WidgetText {
+    let ranges = highlight.map(|matcher| matcher.find_ranges(name)).unwrap_or_default();
+    if ranges.is_empty() {
+        return name.into();
+    }
+    let font_id = TextStyle::Button.resolve(ui.style());
+    let mut job = LayoutJob::default();
+    for (segment, matched) in split_matches(name, &ranges) {
+        // The placeholder color is replaced by the button's own, so hovering still shows.
+        let mut format = TextFormat::simple(font_id.clone(), Color32::PLACEHOLDER);
+        if matched {
+            format.background = ui.visuals().selection.bg_fill;
+        }
+        job.append(segment, 0.0, format);
+    }
+    job.into()
+}
+
+/// A 🔗 after the icon of an entry that's a symbolic link to `target`.
+fn link_badge(ui: &mut Ui, target: Option<&Path>) {
+    if let Some(target) = target {
+        ui.label("🔗").on_hover_text(format!("Link to {}", target.display()));
+    }
+}
+
+/// Where the symbolic link at `path` leads, for its popup.
+fn link_target_label(ui: &mut Ui, path: &str, target: Option<&Path>) {
+    let Some(target) = target else {
+        return;
+    };
+    // `exists` follows the link, so it's false for a broken one.
+    if Path::new(path).exists() {
+        ui.label(format!("Link to: {}", target.display()));
+    } else {
+        ui.label(RichText::new(format!("Broken link to: {}", target.display())).color(Color32::RED));
+    }
+}
+
+/// The permissions, mode, owner and group lines of an entry's popup, with the button to edit them.
+fn permissions_lines(
+    app: &FileBrowserApp,
+    ui: &mut Ui,
+    path: &str,
+    permissions: Option<Permissions>,
+    owner: Option<Owner>,
+    actions: &mut EntryActions,
+) {
+    match permissions {
+        Some(permissions) => ui.label(format!("Permissions: {}", permissions.label())),
+        None => ui.label("Permissions unknown"),
+    };
+    if let Some(mode) = permissions.and_then(Permissions::mode) {
+        ui.horizontal(|ui| {
+            ui.label(format!("Mode: {mode:o}"));
+            if ui.small_button("🔒 Edit").on_hover_text("Edit permissions").clicked() {
+                actions.edit_permissions = Some((PathBuf::from(path), mode));
+                ui.memory_mut(|mem| mem.close_popup());
+            }
         });
+    }
+    if let Some(owner) = owner {
+        ui.label(format!("Owner: {} ({})", app.owner_names.user(owner.uid), owner.uid));
+        ui.label(format!("Group: {} ({})", app.owner_names.group(owner.gid), owner.gid));
+    }
+}
+
+/// What a row of the table shows.
+#[derive(Debug, PartialEq, Eq)]
+enum RowEntry {
+    /// The inline row for naming a new folder.
+    NewFolder,
+
+    /// An index into [`FileBrowserApp::directories`].
+    Folder(usize),
+
+    /// An index into [`FileBrowserApp::files`].
+    File(usize),
+}
+
+/// Maps a row of the table to what it shows: the new folder row (if any) comes first,
+/// then the `entries` in order.
+fn row_entry(row: usize, has_new_folder: bool, entries: &[Entry]) -> RowEntry {
+    let row = if has_new_folder {
+        match row.checked_sub(1) {
+            Some(row) => row,
+            None => return RowEntry::NewFolder,
+        }
+    } else {
+        row
+    };
+    match entries[row] {
+        Entry::Folder(index) => RowEntry::Folder(index),
+        Entry::File(index) => RowEntry::File(index),
+    }
+}
+
+/// The drag-and-drop payload of rows being dragged.
+pub struct DraggedEntries(pub Vec<PathBuf>);
+
+/// Lets rows be dropped onto `response`, highlighting it while they hover it.
+///
+/// Returns what was dropped this frame, if anything.
+pub fn drop_target(ui: &Ui, response: &Response) -> Option<Arc<DraggedEntries>> {
+    if response.dnd_hover_payload::<DraggedEntries>().is_some() {
+        let stroke = ui.visuals().selection.stroke;
+        ui.painter().rect_stroke(response.rect.expand(2.0), 2.0, stroke);
+    }
+    response.dnd_release_payload::<DraggedEntries>()
+}
+
+#[derive(Clone, Copy)]
+pub enum RenameAction {
+    Commit { overwrite: bool },
+    Cancel,
+}
+
+/// The text field replacing the name of the entry being renamed, with the overwrite prompt.
+pub fn rename_field(ui: &mut Ui, rename: &mut Rename, is_dir: bool) -> Option<RenameAction> {
+    let id = entry_id("rename", &rename.path);
+    let response = ui.add(TextEdit::singleline(&mut rename.name).id(id).desired_width(150.0));
+
+    if rename.select_on_focus {
+        rename.select_on_focus = false;
+        response.request_focus();
+        if let Some(mut state) = TextEdit::load_state(ui.ctx(), id) {
+            let end = initial_selection(&rename.name, is_dir);
+            state.cursor.set_char_range(Some(CCursorRange::two(CCursor::new(0), CCursor::new(end))));
+            TextEdit::store_state(ui.ctx(), id, state);
+        }
+    }
+    if response.changed() {
+        rename.confirm_overwrite = false;
+    }
+
+    let mut action = None;
+    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+        action = Some(RenameAction::Commit { overwrite: false });
+    }
+    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+        action = Some(RenameAction::Cancel);
+    }
+
+    if rename.confirm_overwrite {
+        ui.label(RichText::new(format!("\"{}\" exists. Replace it?", rename.name)).color(Color32::RED));
+        if ui.button("Replace").clicked() {
+            action = Some(RenameAction::Commit { overwrite: true });
+        }
+        if ui.button("Cancel").clicked() {
+            action = Some(RenameAction::Cancel);
+        }
+    }
+
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::visible_rows;
+
+    /// `folders` folders followed by `files` files, as when folders are grouped first.
+    fn folders_then_files(folders: usize, files: usize) -> Vec<Entry> {
+        (0..folders).map(Entry::Folder).chain((0..files).map(Entry::File)).collect()
+    }
+
+    #[test]
+    fn rows_map_to_folders_then_files() {
+        let entries = folders_then_files(2, 3);
+        let rows: Vec<_> = (0..5).map(|row| row_entry(row, false, &entries)).collect();
+        assert_eq!(
+            rows,
+            [
+                RowEntry::Folder(0),
+                RowEntry::Folder(1),
+                RowEntry::File(0),
+                RowEntry::File(1),
+                RowEntry::File(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn rows_follow_mixed_entries() {
+        let entries = [Entry::File(1), Entry::Folder(0), Entry::File(0)];
+        let rows: Vec<_> = (0..4).map(|row| row_entry(row, true, &entries)).collect();
+        assert_eq!(
+            rows,
+            [RowEntry::NewFolder, RowEntry::File(1), RowEntry::Folder(0), RowEntry::File(0)]
+        );
+    }
+
+    #[test]
+    fn new_folder_row_comes_first() {
+        let entries = folders_then_files(2, 3);
+        assert_eq!(row_entry(0, true, &entries), RowEntry::NewFolder);
+        assert_eq!(row_entry(1, true, &entries), RowEntry::Folder(0));
+        assert_eq!(row_entry(2, true, &entries), RowEntry::Folder(1));
+        assert_eq!(row_entry(3, true, &entries), RowEntry::File(0));
+    }
+
+    #[test]
+    fn rows_without_folders_are_all_files() {
+        assert_eq!(row_entry(0, false, &folders_then_files(0, 1)), RowEntry::File(0));
+        assert_eq!(row_entry(0, true, &[]), RowEntry::NewFolder);
+        assert_eq!(row_entry(1, true, &folders_then_files(0, 1)), RowEntry::File(0));
+        let entries = folders_then_files(10_000, 40_000);
+        assert_eq!(row_entry(49_999, false, &entries), RowEntry::File(39_999));
+    }
+
+    #[test]
+    fn cursor_rows_skip_the_new_folder_row() {
+        for has_new_folder in [false, true] {
+            for cursor in 0..5 {
+                let expected = if cursor < 2 {
+                    RowEntry::Folder(cursor)
+                } else {
+                    RowEntry::File(cursor - 2)
+                };
+                let row = cursor_row(cursor, has_new_folder);
+                assert_eq!(row_entry(row, has_new_folder, &folders_then_files(2, 3)), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn only_rows_in_view_are_built() {
+        // 100k entries, scrolled anywhere: a frame only builds what fits the viewport.
+        let (directory_count, file_count) = (30_000, 70_000);
+        let entries = folders_then_files(directory_count, file_count);
+        let row_count = 1 + entries.len();
+        let viewport = 600.0;
+        let bound = page_rows(viewport, ROW_HEIGHT) + 2;
+
+        let total_height = row_count as f32 * ROW_HEIGHT;
+        for top in [0.0, 10.0, 299_990.0, 599_995.0, total_height - viewport, total_height] {
+            let rows = visible_rows(top, top + viewport, ROW_HEIGHT, row_count);
+            assert!(rows.len() <= bound, "{rows:?} built at {top}");
+            // Every built row is an entry, without walking the others.
+            for row in rows {
+                match row_entry(row, true, &entries) {
+                    RowEntry::NewFolder => assert_eq!(row, 0),
+                    RowEntry::Folder(index) => assert!(index < directory_count),
+                    RowEntry::File(index) => assert!(index < file_count, "{row}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pages_are_whole_rows() {
+        assert_eq!(page_rows(200.0, 20.0), 10);
+        assert_eq!(page_rows(219.0, 20.0), 10);
+        assert_eq!(page_rows(5.0, 20.0), 1);
+        assert_eq!(page_rows(-20.0, 20.0), 1);
+    }
+
+    #[test]
+    fn copy_text_picks_the_path_or_the_name() {
+        let file = File {
+            dir: "/home/me/notes.txt".to_owned(),
+            name: "notes.txt".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(copy_text(CopyText::Path, &file.dir, &file.name, false), "/home/me/notes.txt");
+        assert_eq!(copy_text(CopyText::Name, &file.dir, &file.name, false), "notes.txt");
+
+        let folder = Folder {
+            dir: "/home/me/projects".to_owned(),
+            name: "projects".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(copy_text(CopyText::Path, &folder.dir, &folder.name, false), "/home/me/projects");
+        assert_eq!(copy_text(CopyText::Name, &folder.dir, &folder.name, false), "projects");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn copied_text_can_be_quoted() {
+        let (dir, name) = ("/home/me/it's $5.txt", "it's $5.txt");
+        assert_eq!(copy_text(CopyText::Path, dir, name, true), r"'/home/me/it'\''s $5.txt'");
+        assert_eq!(copy_text(CopyText::Name, dir, name, true), r"'it'\''s $5.txt'");
+        assert_eq!(copy_text(CopyText::Name, dir, "plain.txt", true), "plain.txt");
+
+        let paths = [PathBuf::from("/tmp/a b"), PathBuf::from("/tmp/c")];
+        assert_eq!(copy_paths_text(&paths, false), "/tmp/a b\n/tmp/c");
+        assert_eq!(copy_paths_text(&paths, true), "'/tmp/a b'\n/tmp/c");
+    }
 
-    if let Some(path) = new_path {
-        app.current_path = path;
-        app.search = "".to_string();
-        app.update_directory_list(&app.current_path.clone());
+    #[test]
+    fn entries_with_the_same_name_have_their_own_ids() {
+        let first = entry_id("file_popup", Path::new("/projects/a/README.md"));
+        let second = entry_id("file_popup", Path::new("/projects/b/README.md"));
+        assert_ne!(first, second);
+        assert_eq!(first, entry_id("file_popup", Path::new("/projects/a/README.md")));
+        // Another purpose of the same entry.
+        assert_ne!(first, entry_id("rename", Path::new("/projects/a/README.md")));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,212 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+
+/// Hands results of background work back to the UI thread, dropping any from superseded requests.
+///
+/// Each [`Self::start`] bumps a generation counter; results sent for an older generation
+/// (e.g. from a folder the user has already navigated away from) are ignored by [`Self::poll`].
+/// A job may send any number of results before it finishes, so they can be shown as they arrive.
+pub struct Loader<T> {
+    generation: u64,
+    loading: bool,
+    received: bool,
+    canceled: Arc<AtomicBool>,
+    tx: Sender<(u64, Message<T>)>,
+    rx: Receiver<(u64, Message<T>)>,
+}
+
+enum Message<T> {
+    Result(T),
+    Finished,
+}
+
+/// Where a background job sends its results.
+pub struct LoaderSender<T> {
+    generation: u64,
+    canceled: Arc<AtomicBool>,
+    tx: Sender<(u64, Message<T>)>,
+}
+
+impl<T> LoaderSender<T> {
+    pub fn send(&self, result: T) {
+        // The app may have been closed; nobody is waiting then.
+        self.tx
+            .send((self.generation, Message::Result(result)))
+            .ok();
+    }
+
+    /// Marks the request as done. Dropping the sender without calling this leaves it loading.
+    pub fn finish(self) {
+        self.tx.send((self.generation, Message::Finished)).ok();
+    }
+
+    /// Set once the request has been superseded or canceled; the job should stop early.
+    pub fn canceled(&self) -> &AtomicBool {
+        &self.canceled
+    }
+}
+
+/// What [`Loader::poll`] got since the last call.
+pub struct Update<T> {
+    pub results: Vec<T>,
+
+    /// This is the first update for the current request, so whatever the previous one
+    /// showed should be replaced rather than added to.
+    pub is_first: bool,
+}
+
+impl<T> Default for Loader<T> {
+    fn default() -> Self {
+        let (tx, rx) = channel();
+        Self {
+            generation: 0,
+            loading: false,
+            received: false,
+            canceled: Arc::new(AtomicBool::new(false)),
+            tx,
+            rx,
+        }
+    }
+}
+
+impl<T> Loader<T> {
+    /// Starts a new request, superseding (and canceling) any that is still running.
+    pub fn start(&mut self) -> LoaderSender<T> {
+        self.cancel();
+        self.loading = true;
+        self.received = false;
+        LoaderSender {
+            generation: self.generation,
+            canceled: self.canceled.clone(),
+            tx: self.tx.clone(),
+        }
+    }
+
+    /// Stops the current request. Anything it still sends will be ignored.
+    pub fn cancel(&mut self) {
+        self.canceled.store(true, Ordering::Relaxed);
+        self.canceled = Arc::new(AtomicBool::new(false));
+        self.generation += 1;
+        self.loading = false;
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading
+    }
+
+    /// Results of the current request that have arrived since the last call, if any. Never blocks.
+    pub fn poll(&mut self) -> Option<Update<T>> {
+        let mut results = Vec::new();
+        let mut finished = false;
+        while let Ok((generation, message)) = self.rx.try_recv() {
+            if generation != self.generation || !self.loading {
+                continue;
+            }
+            match message {
+                Message::Result(result) => results.push(result),
+                Message::Finished => finished = true,
+            }
+        }
+
+        if finished {
+            self.loading = false;
+        }
+        if results.is_empty() && !finished {
+            return None;
+        }
+        let is_first = !self.received;
+        self.received = true;
+        Some(Update { results, is_first })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results<T>(update: Option<Update<T>>) -> Option<(Vec<T>, bool)> {
+        update.map(|update| (update.results, update.is_first))
+    }
+
+    #[test]
+    fn returns_result_of_current_request() {
+        let mut loader = Loader::default();
+        assert!(!loader.is_loading());
+
+        let sender = loader.start();
+        assert!(loader.is_loading());
+        assert!(loader.poll().is_none());
+
+        sender.send("listing");
+        sender.finish();
+        assert_eq!(results(loader.poll()), Some((vec!["listing"], true)));
+        assert!(!loader.is_loading());
+        assert!(loader.poll().is_none());
+    }
+
+    #[test]
+    fn streams_results_until_finished() {
+        let mut loader = Loader::default();
+        let sender = loader.start();
+
+        sender.send(1);
+        sender.send(2);
+        assert_eq!(results(loader.poll()), Some((vec![1, 2], true)));
+        assert!(loader.is_loading());
+
+        sender.send(3);
+        assert_eq!(results(loader.poll()), Some((vec![3], false)));
+
+        sender.finish();
+        assert_eq!(results(loader.poll()), Some((vec![], false)));
+        assert!(!loader.is_loading());
+    }
+
+    #[test]
+    fn finishing_without_results_is_an_update() {
+        let mut loader = Loader::<()>::default();
+        loader.start().finish();
+        assert_eq!(results(loader.poll()), Some((vec![], true)));
+    }
+
+    #[test]
+    fn discards_superseded_results() {
+        let mut loader = Loader::default();
+        let first = loader.start();
+        let second = loader.start();
+        assert!(first.canceled().load(Ordering::Relaxed));
+        assert!(!second.canceled().load(Ordering::Relaxed));
+
+        first.send("old");
+        first.finish();
+        assert!(loader.poll().is_none());
+        assert!(loader.is_loading());
+
+        second.send("new");
+        assert_eq!(results(loader.poll()), Some((vec!["new"], true)));
+    }
+
+    #[test]
+    fn superseded_result_arriving_late_is_discarded() {
+        let mut loader = Loader::default();
+        let first = loader.start();
+        let second = loader.start();
+
+        second.send("new");
+        first.send("old");
+        assert_eq!(results(loader.poll()), Some((vec!["new"], true)));
+    }
+
+    #[test]
+    fn canceled_request_is_discarded() {
+        let mut loader = Loader::default();
+        let sender = loader.start();
+        loader.cancel();
+        assert!(!loader.is_loading());
+        assert!(sender.canceled().load(Ordering::Relaxed));
+
+        sender.send("late");
+        assert!(loader.poll().is_none());
+    }
+}
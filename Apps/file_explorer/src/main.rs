@@ -1,22 +1,69 @@
 use eframe::egui;
-use crate::explorer::load_style_from_file;
 
+mod address_bar;
+mod archive;
+mod bookmarks;
+mod checksum;
+mod content_search;
+mod delete;
+mod devices;
 mod explorer;
+mod file_type;
+mod grid;
+mod hidden;
+mod history;
+mod icons;
+mod launch;
 mod list;
+mod loader;
+mod new_folder;
+mod paste;
+mod permissions;
+mod properties;
+mod read_errors;
+mod recent;
+mod rename;
+mod search;
+mod selection;
+mod settings;
+mod size_cache;
+mod size_format;
+mod size_mode;
+mod size_state;
+mod sort;
+mod statistics;
+mod status;
+mod style_file;
+#[cfg(test)]
+mod temp_dir;
+mod thumbnails;
+mod time_format;
+mod toasts;
+mod type_ahead;
+mod type_filter;
+mod watcher;
 // Import the file_browser module
 
 fn main() -> eframe::Result {
+    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+
+    // See the `profiling` feature in Cargo.toml.
+    #[cfg(feature = "profiling")]
+    let _puffin_server = eframe::start_puffin_server(8585)
+        .map_err(|err| log::warn!("Failed to start puffin server: {err}"))
+        .ok();
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([960.0, 640.0]),
         ..Default::default()
     };
 
     eframe::run_native(
-        "File Browser",
+        explorer::APP_ID,
         options,
         Box::new(|cc| {
-            cc.egui_ctx.set_visuals(egui::Visuals::light());
-            Ok(Box::<explorer::FileBrowserApp>::default())
+            cc.egui_ctx.set_style(style_file::default_style());
+            Ok(Box::new(explorer::FileBrowserApp::new(cc)))
         }),
     )
 }
@@ -0,0 +1,114 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// The name suggested for a new folder.
+pub const DEFAULT_NAME: &str = "New Folder";
+
+/// The inline row for naming a folder that's about to be created.
+pub struct NewFolder {
+    pub name: String,
+    pub error: Option<String>,
+}
+
+impl NewFolder {
+    /// Starts with a name that's not taken in `parent` yet.
+    pub fn new(parent: &Path) -> Self {
+        Self {
+            name: unique_name(DEFAULT_NAME, |name| parent.join(name).exists()),
+            error: None,
+        }
+    }
+}
+
+/// Checks that `name` can be used for a single new folder.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("The name can't be empty".to_owned());
+    }
+    if name.contains(['/', '\\']) {
+        return Err("The name can't contain / or \\".to_owned());
+    }
+    if name == "." || name == ".." {
+        return Err(format!("\"{name}\" is not a valid name"));
+    }
+    Ok(())
+}
+
+/// `base`, or the first of "`base` (2)", "`base` (3)"… that isn't `taken`.
+pub fn unique_name(base: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(base) {
+        return base.to_owned();
+    }
+    (2..)
+        .map(|n| format!("{base} ({n})"))
+        .find(|name| !taken(name))
+        .unwrap()
+}
+
+/// Creates the folder `name` in `parent`, returning its path.
+pub fn create(parent: &Path, name: &str) -> Result<PathBuf, String> {
+    validate_name(name)?;
+    let path = parent.join(name);
+    fs::create_dir(&path).map_err(|err| match err.kind() {
+        ErrorKind::AlreadyExists => {
+            let suggestion = unique_name(name, |name| parent.join(name).exists());
+            format!("\"{name}\" already exists, try \"{suggestion}\"")
+        }
+        ErrorKind::PermissionDenied => {
+            format!("No permission to create a folder in {}", parent.display())
+        }
+        _ => format!("Can't create {}: {err}", path.display()),
+    })?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explorer::FileBrowserApp;
+    use crate::temp_dir::TempDir;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn rejects_empty_and_nested_names() {
+        assert!(validate_name("Projects").is_ok());
+        assert!(validate_name(" spaced out ").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("   ").is_err());
+        assert!(validate_name("a/b").is_err());
+        assert!(validate_name("a\\b").is_err());
+        assert!(validate_name("..").is_err());
+    }
+
+    #[test]
+    fn suggests_the_first_free_suffix() {
+        let taken = ["New Folder", "New Folder (2)", "New Folder (4)"];
+        let is_taken = |name: &str| taken.contains(&name);
+        assert_eq!(unique_name("New Folder", is_taken), "New Folder (3)");
+        assert_eq!(unique_name("Other", is_taken), "Other");
+    }
+
+    #[test]
+    fn created_folder_appears_in_the_refreshed_listing() {
+        let dir = TempDir::new("new_folder");
+
+        let mut app = FileBrowserApp::default();
+        app.current_path = dir.to_string_lossy().into_owned();
+        app.create_folder(DEFAULT_NAME);
+        assert!(app.new_folder.is_none());
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while app.loader.is_loading() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+            app.poll_directory_list();
+        }
+
+        let created = dir.join(DEFAULT_NAME);
+        assert!(app.directories.iter().any(|folder| Path::new(&folder.dir) == created));
+        assert!(app.selection.contains(&created));
+
+        let error = create(&dir, DEFAULT_NAME).unwrap_err();
+        assert!(error.contains("New Folder (2)"), "{error}");
+    }
+}
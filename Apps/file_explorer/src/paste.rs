@@ -0,0 +1,621 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::delete::remove;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipboardMode {
+    Copy,
+
+    /// Move the entries when pasting.
+    Cut,
+}
+
+/// Entries copied or cut in the explorer, waiting to be pasted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clipboard {
+    pub paths: Vec<PathBuf>,
+    pub mode: ClipboardMode,
+}
+
+/// What to do with a pasted entry whose name is already taken in the destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Collision {
+    Skip,
+    Overwrite,
+
+    /// Paste under a free name like `"notes (copy).txt"`.
+    Rename,
+}
+
+/// Where a pasted entry ends up.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Target {
+    Skip,
+    Create(PathBuf),
+
+    /// Remove what's at the path first.
+    Replace(PathBuf),
+}
+
+/// Where to paste `source` into `dest_dir`.
+///
+/// Pasting an entry into the folder it's already in makes a renamed copy, or does nothing for a cut.
+pub fn resolve_target(
+    source: &Path,
+    dest_dir: &Path,
+    mode: ClipboardMode,
+    collision: Collision,
+) -> Target {
+    let Some(name) = source.file_name() else {
+        return Target::Skip;
+    };
+    let target = dest_dir.join(name);
+    if target.symlink_metadata().is_err() {
+        return Target::Create(target);
+    }
+
+    let name = name.to_string_lossy();
+    if target == source {
+        return match mode {
+            ClipboardMode::Copy => Target::Create(free_name(dest_dir, &name)),
+            ClipboardMode::Cut => Target::Skip,
+        };
+    }
+    match collision {
+        Collision::Skip => Target::Skip,
+        Collision::Overwrite => Target::Replace(target),
+        Collision::Rename => Target::Create(free_name(dest_dir, &name)),
+    }
+}
+
+/// The names of `paths` that are already taken in `dest_dir`, not counting entries pasted
+/// where they already are (see [`resolve_target`]).
+pub fn collisions(paths: &[PathBuf], dest_dir: &Path) -> Vec<String> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_name()?;
+            let target = dest_dir.join(name);
+            (target != *path && target.symlink_metadata().is_ok())
+                .then(|| name.to_string_lossy().into_owned())
+        })
+        .collect()
+}
+
+/// The first of `"name (copy).ext"`, `"name (copy 2).ext"`, … that doesn't exist in `dir`.
+///
+/// Dotfiles like `".bashrc"` become `".bashrc (copy)"`.
+pub fn free_name(dir: &Path, name: &str) -> PathBuf {
+    let (stem, extension) = match name.rfind('.') {
+        Some(0) | None => (name, ""),
+        Some(dot) => name.split_at(dot),
+    };
+    (1..)
+        .map(|n| match n {
+            1 => dir.join(format!("{stem} (copy){extension}")),
+            n => dir.join(format!("{stem} (copy {n}){extension}")),
+        })
+        .find(|path| path.symlink_metadata().is_err())
+        .expect("ran out of numbers")
+}
+
+/// Is `path` somewhere inside `folder`? A folder isn't inside itself.
+///
+/// Compares whole components, so `/a/bc` isn't inside `/a/b`.
+pub fn is_descendant(path: &Path, folder: &Path) -> bool {
+    path != folder && path.starts_with(folder)
+}
+
+/// Which of the dragged `paths` to move when they're dropped onto `dest_dir`.
+///
+/// Entries dropped onto themselves or onto the folder they're already in are left out,
+/// and dropping a folder into one of its own subfolders is an error.
+pub fn drop_moves(paths: &[PathBuf], dest_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    if let Some(ancestor) = paths.iter().find(|path| is_descendant(dest_dir, path)) {
+        return Err(format!(
+            "Can't move {} into one of its own subfolders",
+            ancestor.display()
+        ));
+    }
+    Ok(paths
+        .iter()
+        .filter(|path| *path != dest_dir && path.parent() != Some(dest_dir))
+        .cloned()
+        .collect())
+}
+
+/// Did `fs::rename` fail because source and target are on different devices?
+///
+/// Moves then have to copy and delete instead.
+pub fn is_cross_device(err: &io::Error) -> bool {
+    // `EXDEV` on Unix, `ERROR_NOT_SAME_DEVICE` on Windows.
+    let code = if cfg!(windows) { 17 } else { 18 };
+    err.raw_os_error() == Some(code)
+}
+
+/// What a running paste sends back to the UI.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PasteUpdate {
+    /// `current` is being copied; `done` of `total` files are.
+    Progress {
+        done: usize,
+        total: usize,
+        current: PathBuf,
+    },
+
+    /// A message for each entry that couldn't be pasted.
+    Finished { errors: Vec<String> },
+}
+
+struct Progress<'a> {
+    done: usize,
+    total: usize,
+    canceled: &'a AtomicBool,
+    report: &'a mut dyn FnMut(PasteUpdate),
+}
+
+impl Progress<'_> {
+    fn start(&mut self, path: &Path) -> Result<(), String> {
+        if self.canceled.load(Ordering::Relaxed) {
+            return Err("Canceled".to_owned());
+        }
+        (self.report)(PasteUpdate::Progress {
+            done: self.done,
+            total: self.total,
+            current: path.to_owned(),
+        });
+        Ok(())
+    }
+}
+
+/// Copies or moves the entries of `clipboard` into `dest_dir`, reporting progress per file.
+///
+/// Folders are copied recursively; symlinks are copied as links, not followed.
+/// Keeps going after a failure and stops soon after `canceled` is set.
+/// Returns a message for each entry that couldn't be pasted.
+pub fn paste(
+    clipboard: &Clipboard,
+    dest_dir: &Path,
+    collision: Collision,
+    canceled: &AtomicBool,
+    report: &mut dyn FnMut(PasteUpdate),
+) -> Vec<String> {
+    let total = clipboard.paths.iter().map(|path| count_files(path)).sum();
+    let mut progress = Progress {
+        done: 0,
+        total,
+        canceled,
+        report,
+    };
+
+    let mut errors = Vec::new();
+    for source in &clipboard.paths {
+        if canceled.load(Ordering::Relaxed) {
+            break;
+        }
+        if let Err(err) = paste_one(source, dest_dir, clipboard.mode, collision, &mut progress) {
+            errors.push(format!("{}: {err}", source.display()));
+        }
+    }
+    errors
+}
+
+fn paste_one(
+    source: &Path,
+    dest_dir: &Path,
+    mode: ClipboardMode,
+    collision: Collision,
+    progress: &mut Progress<'_>,
+) -> Result<(), String> {
+    let is_dir = fs::symlink_metadata(source)
+        .map_err(|err| err.to_string())?
+        .is_dir();
+    if is_dir && (dest_dir == source || is_descendant(dest_dir, source)) {
+        return Err("Can't paste a folder into itself".to_owned());
+    }
+
+    let target = match resolve_target(source, dest_dir, mode, collision) {
+        Target::Skip => {
+            progress.done += count_files(source);
+            return Ok(());
+        }
+        Target::Create(target) => target,
+        Target::Replace(target) => {
+            // Replacing a folder the entry is in would delete the entry first.
+            if is_descendant(source, &target) {
+                return Err(format!(
+                    "Can't replace {}, which contains it",
+                    target.display()
+                ));
+            }
+            remove(&target)?;
+            target
+        }
+    };
+
+    match mode {
+        ClipboardMode::Copy => copy_tree(source, &target, progress),
+        ClipboardMode::Cut => match fs::rename(source, &target) {
+            Ok(()) => {
+                progress.done += count_files(&target);
+                Ok(())
+            }
+            Err(err) if is_cross_device(&err) => {
+                copy_tree(source, &target, progress)?;
+                remove(source)
+            }
+            Err(err) => Err(err.to_string()),
+        },
+    }
+}
+
+/// How many files (and symlinks) are under `path`, for the progress bar.
+fn count_files(path: &Path) -> usize {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| count_files(&entry.path()))
+                    .sum()
+            })
+            .unwrap_or(0),
+        _ => 1,
+    }
+}
+
+fn copy_tree(source: &Path, target: &Path, progress: &mut Progress<'_>) -> Result<(), String> {
+    let meta = fs::symlink_metadata(source).map_err(|err| err.to_string())?;
+    if meta.is_dir() {
+        fs::create_dir(target).map_err(|err| format!("{}: {err}", target.display()))?;
+        for entry in fs::read_dir(source).map_err(|err| err.to_string())? {
+            let entry = entry.map_err(|err| err.to_string())?;
+            copy_tree(&entry.path(), &target.join(entry.file_name()), progress)?;
+        }
+        return Ok(());
+    }
+
+    progress.start(source)?;
+    if meta.file_type().is_symlink() {
+        copy_symlink(source, target)
+    } else {
+        fs::copy(source, target).map(|_| ())
+    }
+    .map_err(|err| format!("{}: {err}", source.display()))?;
+    progress.done += 1;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, target)
+}
+
+#[cfg(windows)]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    let link = fs::read_link(source)?;
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(link, target)
+    } else {
+        std::os::windows::fs::symlink_file(link, target)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    fs::copy(source, target).map(|_| ())
+}
+
+/// The paste window, from resolving name collisions to reporting what went wrong.
+#[derive(Debug, Default)]
+pub enum PasteDialog {
+    #[default]
+    Closed,
+
+    /// Some names are taken in `dest_dir`; waiting for the user to pick a [`Collision`].
+    Collisions {
+        clipboard: Clipboard,
+        dest_dir: PathBuf,
+        names: Vec<String>,
+    },
+
+    Running {
+        started: Instant,
+        done: usize,
+        total: usize,
+        current: PathBuf,
+    },
+
+    /// Some entries couldn't be copied or moved; the others were.
+    Failed { errors: Vec<String> },
+}
+
+impl PasteDialog {
+    pub fn close(&mut self) {
+        *self = Self::Closed;
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self, Self::Running { .. })
+    }
+
+    /// The user picked how to handle the collisions: returns what to paste where.
+    pub fn resolve(&mut self) -> Option<(Clipboard, PathBuf)> {
+        match std::mem::take(self) {
+            Self::Collisions {
+                clipboard,
+                dest_dir,
+                ..
+            } => Some((clipboard, dest_dir)),
+            other => {
+                *self = other;
+                None
+            }
+        }
+    }
+
+    pub fn start(&mut self) {
+        *self = Self::Running {
+            started: Instant::now(),
+            done: 0,
+            total: 0,
+            current: PathBuf::new(),
+        };
+    }
+
+    /// Shows the errors of a finished paste, if there were any.
+    pub fn finish(&mut self, errors: Vec<String>) {
+        *self = if errors.is_empty() {
+            Self::Closed
+        } else {
+            Self::Failed { errors }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    fn run(clipboard: &Clipboard, dest_dir: &Path, collision: Collision) -> Vec<String> {
+        paste(
+            clipboard,
+            dest_dir,
+            collision,
+            &AtomicBool::new(false),
+            &mut |_| {},
+        )
+    }
+
+    #[test]
+    fn free_names_keep_the_extension() {
+        let dir = TempDir::new("paste_free_name");
+        fs::write(dir.join("notes (copy).txt"), "").unwrap();
+
+        assert_eq!(free_name(&dir, "notes.txt"), dir.join("notes (copy 2).txt"));
+        assert_eq!(free_name(&dir, "a.tar.gz"), dir.join("a.tar (copy).gz"));
+        assert_eq!(free_name(&dir, ".bashrc"), dir.join(".bashrc (copy)"));
+        assert_eq!(free_name(&dir, "Makefile"), dir.join("Makefile (copy)"));
+    }
+
+    #[test]
+    fn collisions_resolve_by_policy() {
+        let dir = TempDir::new("paste_resolve");
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/a.txt"), "").unwrap();
+        fs::write(dir.join("src/b.txt"), "").unwrap();
+        fs::write(dir.join("a.txt"), "").unwrap();
+
+        let a = dir.join("src/a.txt");
+        let b = dir.join("src/b.txt");
+        let copy = ClipboardMode::Copy;
+        assert_eq!(
+            resolve_target(&b, &dir, copy, Collision::Skip),
+            Target::Create(dir.join("b.txt"))
+        );
+        assert_eq!(
+            resolve_target(&a, &dir, copy, Collision::Skip),
+            Target::Skip
+        );
+        assert_eq!(
+            resolve_target(&a, &dir, copy, Collision::Overwrite),
+            Target::Replace(dir.join("a.txt"))
+        );
+        assert_eq!(
+            resolve_target(&a, &dir, copy, Collision::Rename),
+            Target::Create(dir.join("a (copy).txt"))
+        );
+        assert_eq!(collisions(&[a.clone(), b.clone()], &dir), ["a.txt"]);
+
+        // Pasting into the same folder: copies get a new name, cuts stay put.
+        let same = dir.join("a.txt");
+        assert_eq!(collisions(&[same.clone()], &dir), Vec::<String>::new());
+        assert_eq!(
+            resolve_target(&same, &dir, copy, Collision::Overwrite),
+            Target::Create(dir.join("a (copy).txt"))
+        );
+        assert_eq!(
+            resolve_target(&same, &dir, ClipboardMode::Cut, Collision::Overwrite),
+            Target::Skip
+        );
+    }
+
+    #[test]
+    fn descendants_compare_whole_components() {
+        assert!(is_descendant(Path::new("/a/b/c"), Path::new("/a/b")));
+        assert!(is_descendant(Path::new("/a/b/c"), Path::new("/")));
+        assert!(!is_descendant(Path::new("/a/b"), Path::new("/a/b")));
+        assert!(!is_descendant(Path::new("/a/bc"), Path::new("/a/b")));
+        assert!(!is_descendant(Path::new("/a"), Path::new("/a/b")));
+    }
+
+    #[test]
+    fn drops_skip_no_ops_and_reject_moving_into_a_subfolder() {
+        let paths = |paths: &[&str]| paths.iter().map(PathBuf::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            drop_moves(&paths(&["/a/x.txt", "/b/y.txt", "/c"]), Path::new("/c")),
+            Ok(paths(&["/a/x.txt", "/b/y.txt"]))
+        );
+        // Already there.
+        assert_eq!(
+            drop_moves(&paths(&["/c/x.txt"]), Path::new("/c")),
+            Ok(Vec::new())
+        );
+        assert_eq!(
+            drop_moves(&paths(&["/x.txt", "/c"]), Path::new("/c/d")),
+            Err("Can't move /c into one of its own subfolders".to_owned())
+        );
+    }
+
+    #[test]
+    fn only_cross_device_errors_fall_back_to_copying() {
+        let code = if cfg!(windows) { 17 } else { 18 };
+        assert!(is_cross_device(&io::Error::from_raw_os_error(code)));
+        assert!(!is_cross_device(&io::Error::from_raw_os_error(2)));
+        assert!(!is_cross_device(&io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "denied"
+        )));
+    }
+
+    #[test]
+    fn copies_nested_folders_with_progress() {
+        let dir = TempDir::new("paste_nested");
+        fs::create_dir_all(dir.join("src/folder/nested/empty")).unwrap();
+        fs::write(dir.join("src/folder/top.txt"), "top").unwrap();
+        fs::write(dir.join("src/folder/nested/deep.txt"), "deep").unwrap();
+        fs::write(dir.join("src/file.txt"), "file").unwrap();
+        fs::create_dir_all(dir.join("dest")).unwrap();
+
+        let clipboard = Clipboard {
+            paths: vec![dir.join("src/folder"), dir.join("src/file.txt")],
+            mode: ClipboardMode::Copy,
+        };
+        let mut updates = Vec::new();
+        let errors = paste(
+            &clipboard,
+            &dir.join("dest"),
+            Collision::Skip,
+            &AtomicBool::new(false),
+            &mut |update| updates.push(update),
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+        let read = |path: &str| fs::read_to_string(dir.join(path)).unwrap();
+        assert_eq!(read("dest/folder/top.txt"), "top");
+        assert_eq!(read("dest/folder/nested/deep.txt"), "deep");
+        assert_eq!(read("dest/file.txt"), "file");
+        assert!(dir.join("dest/folder/nested/empty").is_dir());
+        assert!(dir.join("src/file.txt").exists());
+
+        assert_eq!(updates.len(), 3);
+        assert!(updates.iter().enumerate().all(|(i, update)| matches!(
+            update,
+            PasteUpdate::Progress { done, total: 3, .. } if *done == i
+        )));
+    }
+
+    #[test]
+    fn cut_moves_and_overwrites() {
+        let dir = TempDir::new("paste_cut");
+        fs::create_dir_all(dir.join("src/folder")).unwrap();
+        fs::write(dir.join("src/folder/inner.txt"), "new").unwrap();
+        fs::create_dir_all(dir.join("dest/folder")).unwrap();
+        fs::write(dir.join("dest/folder/old.txt"), "old").unwrap();
+
+        let clipboard = Clipboard {
+            paths: vec![dir.join("src/folder")],
+            mode: ClipboardMode::Cut,
+        };
+        assert!(run(&clipboard, &dir.join("dest"), Collision::Skip).is_empty());
+        assert!(dir.join("src/folder").exists());
+        assert!(dir.join("dest/folder/old.txt").exists());
+
+        assert!(run(&clipboard, &dir.join("dest"), Collision::Overwrite).is_empty());
+        assert!(!dir.join("src/folder").exists());
+        assert!(!dir.join("dest/folder/old.txt").exists());
+        assert!(dir.join("dest/folder/inner.txt").exists());
+    }
+
+    #[test]
+    fn refuses_to_paste_a_folder_into_itself() {
+        let dir = TempDir::new("paste_into_itself");
+        fs::create_dir_all(dir.join("folder/child")).unwrap();
+
+        let clipboard = Clipboard {
+            paths: vec![dir.join("folder")],
+            mode: ClipboardMode::Copy,
+        };
+        let errors = run(&clipboard, &dir.join("folder/child"), Collision::Rename);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("into itself"), "{errors:?}");
+        assert!(!dir.join("folder/child/folder").exists());
+    }
+
+    #[test]
+    fn refuses_to_replace_a_folder_with_something_inside_it() {
+        let dir = TempDir::new("paste_replace_parent");
+        fs::create_dir_all(dir.join("proj/proj")).unwrap();
+        fs::write(dir.join("proj/proj/file.txt"), "").unwrap();
+
+        let clipboard = Clipboard {
+            paths: vec![dir.join("proj/proj")],
+            mode: ClipboardMode::Copy,
+        };
+        let errors = run(&clipboard, &dir, Collision::Overwrite);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("contains it"), "{errors:?}");
+        assert!(dir.join("proj/proj/file.txt").exists());
+    }
+
+    #[test]
+    fn canceled_paste_stops() {
+        let dir = TempDir::new("paste_canceled");
+        fs::write(dir.join("file.txt"), "").unwrap();
+        fs::create_dir_all(dir.join("dest")).unwrap();
+
+        let clipboard = Clipboard {
+            paths: vec![dir.join("file.txt")],
+            mode: ClipboardMode::Copy,
+        };
+        paste(
+            &clipboard,
+            &dir.join("dest"),
+            Collision::Skip,
+            &AtomicBool::new(true),
+            &mut |_| {},
+        );
+        assert!(!dir.join("dest/file.txt").exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn copies_symlinks_as_links() {
+        let dir = TempDir::new("paste_symlink");
+        fs::create_dir_all(dir.join("src/target")).unwrap();
+        fs::write(dir.join("src/target/file.txt"), "").unwrap();
+        // A link back up the tree would recurse forever if it were followed.
+        std::os::unix::fs::symlink("..", dir.join("src/target/up")).unwrap();
+        std::os::unix::fs::symlink("target/file.txt", dir.join("src/link")).unwrap();
+        fs::create_dir_all(dir.join("dest")).unwrap();
+
+        let clipboard = Clipboard {
+            paths: vec![dir.join("src/target"), dir.join("src/link")],
+            mode: ClipboardMode::Copy,
+        };
+        assert!(run(&clipboard, &dir.join("dest"), Collision::Skip).is_empty());
+
+        let up = dir.join("dest/target/up");
+        assert!(up.symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_link(&up).unwrap(), Path::new(".."));
+        let link = dir.join("dest/link");
+        assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_link(&link).unwrap(), Path::new("target/file.txt"));
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Who may do what with an entry, as far as the platform tells.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permissions {
+    // Each platform only reads its own kind, but both can be labeled (and tested) anywhere.
+    /// The mode bits, like `0o755`.
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Unix(u32),
+    #[cfg_attr(unix, allow(dead_code))]
+    Windows { read_only: bool, hidden: bool },
+}
+
+impl Permissions {
+    #[cfg(unix)]
+    pub fn of(meta: &Metadata) -> Self {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        Self::Unix(meta.permissions().mode())
+    }
+
+    #[cfg(not(unix))]
+    pub fn of(meta: &Metadata) -> Self {
+        Self::Windows {
+            read_only: meta.permissions().readonly(),
+            hidden: has_hidden_attribute(meta),
+        }
+    }
+
+    /// Like "rwxr-xr-x (755)" on Unix, or "Read-only, hidden" on Windows.
+    pub fn label(self) -> String {
+        match self {
+            Self::Unix(mode) => format!("{} ({:o})", mode_string(mode), mode & 0o7777),
+            Self::Windows { read_only, hidden } => match (read_only, hidden) {
+                (false, false) => "Read and write".to_owned(),
+                (true, false) => "Read-only".to_owned(),
+                (false, true) => "Hidden".to_owned(),
+                (true, true) => "Read-only, hidden".to_owned(),
+            },
+        }
+    }
+
+    /// What the "Permissions" column shows: "rwxr-xr-x" on Unix, the attributes on Windows.
+    pub fn short_label(self) -> String {
+        match self {
+            Self::Unix(mode) => mode_string(mode),
+            Self::Windows { .. } => self.label(),
+        }
+    }
+
+    /// The whole Unix mode, file type bits included, like `0o100755`.
+    pub fn mode(self) -> Option<u32> {
+        match self {
+            Self::Unix(mode) => Some(mode),
+            Self::Windows { .. } => None,
+        }
+    }
+}
+
+/// Who an entry belongs to, on Unix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Owner {
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl Owner {
+    #[cfg(unix)]
+    #[allow(clippy::unnecessary_wraps)] // `None` on other platforms
+    pub fn of(meta: &Metadata) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt as _;
+
+        Some(Self {
+            uid: meta.uid(),
+            gid: meta.gid(),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn of(_meta: &Metadata) -> Option<Self> {
+        None
+    }
+}
+
+/// The names of the users and groups on this machine, read once from `/etc/passwd` and `/etc/group`.
+///
+/// Users only known to a directory service (e.g. LDAP) aren't in there, and show as their id.
+#[derive(Debug, Default)]
+pub struct OwnerNames {
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+impl OwnerNames {
+    pub fn load() -> Self {
+        let read = |path| {
+            fs::read_to_string(path)
+                .map(|text| id_names(&text))
+                .unwrap_or_default()
+        };
+        Self {
+            users: read("/etc/passwd"),
+            groups: read("/etc/group"),
+        }
+    }
+
+    /// Like "ali", or "1000" if the user has no name here.
+    pub fn user(&self, uid: u32) -> String {
+        self.users
+            .get(&uid)
+            .cloned()
+            .unwrap_or_else(|| uid.to_string())
+    }
+
+    /// Like "staff", or "20" if the group has no name here.
+    pub fn group(&self, gid: u32) -> String {
+        self.groups
+            .get(&gid)
+            .cloned()
+            .unwrap_or_else(|| gid.to_string())
+    }
+}
+
+/// Maps ids to names in the lines of `/etc/passwd` or `/etc/group`, which both start
+/// with `name:password:id:`. Comments and broken lines are skipped.
+pub fn id_names(text: &str) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    for line in text.lines().filter(|line| !line.starts_with('#')) {
+        let mut fields = line.split(':');
+        let (Some(name), Some(_), Some(id)) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        if let Ok(id) = id.parse() {
+            // The first name for an id wins, like `ls` shows it.
+            names.entry(id).or_insert_with(|| name.to_owned());
+        }
+    }
+    names
+}
+
+/// The Unix permissions being edited in the popup of an entry.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub enum PermissionsDialog {
+    #[default]
+    Closed,
+
+    Editing {
+        path: PathBuf,
+        mode: u32,
+        /// The permissions as typed, like [`mode_string`] writes them.
+        text: String,
+        /// Why the typed permissions or the last Apply didn't work.
+        error: Option<String>,
+    },
+}
+
+impl PermissionsDialog {
+    pub fn open(&mut self, path: PathBuf, mode: u32) {
+        *self = Self::Editing {
+            path,
+            mode,
+            text: mode_string(mode),
+            error: None,
+        };
+    }
+
+    pub fn close(&mut self) {
+        *self = Self::Closed;
+    }
+}
+
+/// Gives the entry at `path` the permission bits of `mode`, like `chmod`.
+#[cfg(unix)]
+pub fn set_mode(path: &Path, mode: u32) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o7777))
+        .map_err(|err| chmod_error(path, &err))
+}
+
+#[cfg(not(unix))]
+pub fn set_mode(_path: &Path, _mode: u32) -> Result<(), String> {
+    Err("Permission bits can only be changed on Unix".to_owned())
+}
+
+/// Why the permissions of `path` couldn't be changed, in words.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn chmod_error(path: &Path, err: &io::Error) -> String {
+    // EPERM: only the owner (or root) may chmod.
+    if err.kind() == io::ErrorKind::PermissionDenied {
+        format!(
+            "Only the owner of {} can change its permissions",
+            path.display()
+        )
+    } else {
+        format!(
+            "Couldn't change the permissions of {}: {err}",
+            path.display()
+        )
+    }
+}
+
+#[cfg(windows)]
+fn has_hidden_attribute(meta: &Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt as _;
+
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    meta.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0
+}
+
+#[cfg(not(any(unix, windows)))]
+fn has_hidden_attribute(_meta: &Metadata) -> bool {
+    false
+}
+
+/// The shift of the owner, group and others bits, and the setuid, setgid and sticky bit
+/// that shows in the place of their `x`, as `(bit, when executable, when not)`.
+const CLASSES: [(u32, (u32, char, char)); 3] = [
+    (6, (0o4000, 's', 'S')),
+    (3, (0o2000, 's', 'S')),
+    (0, (0o1000, 't', 'T')),
+];
+
+/// The permission bits of `mode` the way `ls -l` shows them, like "rwxr-xr-x" or "rwsr-xr-t".
+pub fn mode_string(mode: u32) -> String {
+    let mut string = String::with_capacity(9);
+    for (shift, (special, executable, not_executable)) in CLASSES {
+        let bits = mode >> shift;
+        string.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        string.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        string.push(match (mode & special != 0, bits & 0o1 != 0) {
+            (true, true) => executable,
+            (true, false) => not_executable,
+            (false, true) => 'x',
+            (false, false) => '-',
+        });
+    }
+    string
+}
+
+/// Reads permissions written like [`mode_string`] writes them back into mode bits.
+pub fn parse_mode(text: &str) -> Result<u32, String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() != 9 {
+        return Err(format!("\"{text}\" isn't 9 characters like rwxr-xr-x"));
+    }
+    let mut mode = 0;
+    for ((shift, (special, executable, not_executable)), class) in
+        CLASSES.into_iter().zip(chars.chunks(3))
+    {
+        let bits = match class {
+            [r, w, x] => {
+                let flag = |c: char, set: char, bit: u32| match c {
+                    '-' => Ok(0),
+                    c if c == set => Ok(bit),
+                    c => Err(format!("Unexpected '{c}' in \"{text}\"")),
+                };
+                let x = match *x {
+                    c if c == executable => Ok((0o1 << shift) | special),
+                    c if c == not_executable => Ok(special),
+                    c => flag(c, 'x', 0o1 << shift),
+                };
+                flag(*r, 'r', 0o4 << shift)? | flag(*w, 'w', 0o2 << shift)? | x?
+            }
+            _ => unreachable!("9 characters make 3 classes of 3"),
+        };
+        mode |= bits;
+    }
+    Ok(mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn modes_read_like_ls() {
+        assert_eq!(mode_string(0o755), "rwxr-xr-x");
+        assert_eq!(mode_string(0o644), "rw-r--r--");
+        assert_eq!(mode_string(0o600), "rw-------");
+        assert_eq!(mode_string(0o777), "rwxrwxrwx");
+        assert_eq!(mode_string(0o000), "---------");
+        // The file type bits above the permissions don't show.
+        assert_eq!(mode_string(0o100_640), "rw-r-----");
+    }
+
+    #[test]
+    fn special_bits_show_in_place_of_x() {
+        assert_eq!(mode_string(0o4755), "rwsr-xr-x");
+        assert_eq!(mode_string(0o4644), "rwSr--r--");
+        assert_eq!(mode_string(0o2755), "rwxr-sr-x");
+        assert_eq!(mode_string(0o2745), "rwxr-Sr-x");
+        assert_eq!(mode_string(0o1777), "rwxrwxrwt");
+        assert_eq!(mode_string(0o1776), "rwxrwxrwT");
+        assert_eq!(mode_string(0o7777), "rwsrwsrwt");
+    }
+
+    #[test]
+    fn modes_parse_back() {
+        for mode in [
+            0o755, 0o644, 0o000, 0o777, 0o4755, 0o4644, 0o2755, 0o2745, 0o1777, 0o1776, 0o7000,
+            0o7777,
+        ] {
+            assert_eq!(parse_mode(&mode_string(mode)), Ok(mode), "{mode:o}");
+        }
+        assert_eq!(parse_mode("rwxr-x---"), Ok(0o750));
+        assert_eq!(parse_mode("--S--S--T"), Ok(0o7000));
+    }
+
+    #[test]
+    fn bad_modes_dont_parse() {
+        assert!(parse_mode("rwxr-xr-").is_err());
+        assert!(parse_mode("rwxr-xr-xx").is_err());
+        assert!(parse_mode("wrxr-xr-x").is_err());
+        // The sticky bit only goes last, setuid and setgid not there.
+        assert!(parse_mode("rwtr-xr-x").is_err());
+        assert!(parse_mode("rwxr-xr-s").is_err());
+        assert!(parse_mode("rwxr-xr-?").is_err());
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(Permissions::Unix(0o40_755).label(), "rwxr-xr-x (755)");
+        assert_eq!(Permissions::Unix(0o100_4755).label(), "rwsr-xr-x (4755)");
+        assert_eq!(Permissions::Unix(0o40_1777).short_label(), "rwxrwxrwt");
+        assert_eq!(Permissions::Unix(0o100_644).mode(), Some(0o100_644));
+        let windows = |read_only, hidden| Permissions::Windows { read_only, hidden }.label();
+        assert_eq!(windows(false, false), "Read and write");
+        assert_eq!(windows(true, true), "Read-only, hidden");
+    }
+
+    #[test]
+    fn names_come_from_passwd_and_group_lines() {
+        let passwd = "# comment\nroot:x:0:0:root:/root:/bin/bash\nali:x:1000:1000::/home/ali:/bin/sh\nbroken\ntoor:x:0:0::/:/bin/sh\n";
+        let names = id_names(passwd);
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[&0], "root");
+        assert_eq!(names[&1000], "ali");
+
+        let owners = OwnerNames {
+            users: names,
+            groups: id_names("staff:x:20:ali\n"),
+        };
+        assert_eq!(owners.user(1000), "ali");
+        assert_eq!(owners.user(1001), "1001");
+        assert_eq!(owners.group(20), "staff");
+        assert_eq!(owners.group(0), "0");
+    }
+
+    #[test]
+    fn denied_changes_say_who_may() {
+        let denied = chmod_error(
+            Path::new("/etc/passwd"),
+            &io::Error::from(io::ErrorKind::PermissionDenied),
+        );
+        assert_eq!(
+            denied,
+            "Only the owner of /etc/passwd can change its permissions"
+        );
+        let missing = chmod_error(
+            Path::new("/gone"),
+            &io::Error::from(io::ErrorKind::NotFound),
+        );
+        assert!(missing.starts_with("Couldn't change the permissions of /gone: "));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn set_mode_changes_the_bits() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let dir = TempDir::new("chmod");
+        let script = dir.join("script.sh");
+        fs::write(&script, "#!/bin/sh\n").unwrap();
+
+        set_mode(&script, 0o100_750).unwrap();
+        assert_eq!(
+            fs::metadata(&script).unwrap().permissions().mode() & 0o7777,
+            0o750
+        );
+        assert!(set_mode(&dir.join("missing"), 0o644).is_err());
+    }
+}
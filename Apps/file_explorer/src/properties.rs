@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use egui::Context;
+
+use crate::checksum::{checksum_file, ChecksumProgress, Checksums};
+use crate::explorer::File;
+
+/// Where the checksums of the file in the [`PropertiesDialog`] are at.
+#[derive(Clone, Debug, Default)]
+pub enum ChecksumState {
+    #[default]
+    NotCalculated,
+    Calculating(Arc<ChecksumProgress>),
+    Canceled,
+    /// Why the file couldn't be read.
+    Failed(String),
+    Done(Checksums),
+}
+
+impl ChecksumState {
+    /// Whether `progress` is the hashing still running, rather than one canceled since.
+    pub fn is_calculating(&self, progress: &Arc<ChecksumProgress>) -> bool {
+        matches!(self, Self::Calculating(running) if Arc::ptr_eq(running, progress))
+    }
+
+    /// Stops hashing, if it's running, leaving the state as `after`.
+    pub fn cancel(&mut self, after: Self) {
+        if let Self::Calculating(progress) = self {
+            progress.cancel();
+            *self = after;
+        }
+    }
+}
+
+/// The "Properties" window of a file, opened from its popup.
+#[derive(Debug, Default)]
+pub enum PropertiesDialog {
+    #[default]
+    Closed,
+
+    Open {
+        file: File,
+        /// Shared with the hashing running in the background, if any.
+        checksums: Arc<Mutex<ChecksumState>>,
+        /// A digest pasted to compare with the ones calculated.
+        expected: String,
+    },
+}
+
+impl PropertiesDialog {
+    pub fn open(&mut self, file: File) {
+        self.close();
+        *self = Self::Open {
+            file,
+            checksums: Arc::default(),
+            expected: String::new(),
+        };
+    }
+
+    /// Closes the window, and stops hashing the file if it's running.
+    pub fn close(&mut self) {
+        if let Self::Open { checksums, .. } = self {
+            checksums.lock().unwrap().cancel(ChecksumState::Canceled);
+        }
+        *self = Self::Closed;
+    }
+}
+
+/// Hashes the file at `path` on a background thread, keeping `state` up to date.
+pub fn calculate_checksums(ctx: &Context, path: PathBuf, state: &Arc<Mutex<ChecksumState>>) {
+    let progress = Arc::new(ChecksumProgress::default());
+    {
+        let mut state = state.lock().unwrap();
+        state.cancel(ChecksumState::NotCalculated);
+        *state = ChecksumState::Calculating(progress.clone());
+    }
+    let state = state.clone();
+    let ctx = ctx.clone();
+    thread::spawn(move || {
+        let result = checksum_file(&path, &progress);
+
+        // Whoever canceled it already said what the state is now.
+        let mut state = state.lock().unwrap();
+        if !state.is_calculating(&progress) {
+            return;
+        }
+        *state = match result {
+            Ok(Some(checksums)) => ChecksumState::Done(checksums),
+            Ok(None) => ChecksumState::Canceled,
+            Err(err) => ChecksumState::Failed(err.to_string()),
+        };
+        ctx.request_repaint();
+    });
+}
@@ -0,0 +1,97 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A location that couldn't be read while listing or searching, e.g. a folder of another user.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReadError {
+    pub path: PathBuf,
+    pub kind: io::ErrorKind,
+}
+
+impl ReadError {
+    pub fn new(path: &Path, err: &io::Error) -> Self {
+        Self {
+            path: path.to_owned(),
+            kind: err.kind(),
+        }
+    }
+}
+
+/// The locations that couldn't be read, each once, in the order they were found.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReadErrors(pub Vec<ReadError>);
+
+impl ReadErrors {
+    /// Adds `error`, unless its location is already known to be unreadable.
+    pub fn push(&mut self, error: ReadError) {
+        if !self.0.iter().any(|known| known.path == error.path) {
+            self.0.push(error);
+        }
+    }
+
+    pub fn extend(&mut self, errors: Self) {
+        for error in errors.0 {
+            self.push(error);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Like "3 locations could not be read", if any couldn't.
+    pub fn summary(&self) -> Option<String> {
+        match self.0.len() {
+            0 => None,
+            1 => Some("1 location could not be read".to_owned()),
+            n => Some(format!("{n} locations could not be read")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(path: &str, kind: io::ErrorKind) -> ReadError {
+        ReadError::new(Path::new(path), &io::Error::from(kind))
+    }
+
+    #[test]
+    fn each_location_is_reported_once() {
+        let mut errors = ReadErrors::default();
+        errors.push(error("/root", io::ErrorKind::PermissionDenied));
+        errors.push(error("/lost+found", io::ErrorKind::PermissionDenied));
+        errors.push(error("/root", io::ErrorKind::NotFound));
+
+        let mut more = ReadErrors::default();
+        more.push(error("/lost+found", io::ErrorKind::PermissionDenied));
+        more.push(error("/proc/1/fd", io::ErrorKind::PermissionDenied));
+        errors.extend(more);
+
+        assert_eq!(
+            errors.0,
+            [
+                error("/root", io::ErrorKind::PermissionDenied),
+                error("/lost+found", io::ErrorKind::PermissionDenied),
+                error("/proc/1/fd", io::ErrorKind::PermissionDenied),
+            ]
+        );
+    }
+
+    #[test]
+    fn summaries() {
+        let mut errors = ReadErrors::default();
+        assert_eq!(errors.summary(), None);
+        errors.push(error("/root", io::ErrorKind::PermissionDenied));
+        assert_eq!(errors.summary().unwrap(), "1 location could not be read");
+        errors.push(error("/lost+found", io::ErrorKind::PermissionDenied));
+        assert_eq!(errors.summary().unwrap(), "2 locations could not be read");
+        errors.clear();
+        assert!(errors.is_empty());
+    }
+}
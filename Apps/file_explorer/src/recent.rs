@@ -0,0 +1,97 @@
+/// How many folders [`crate::explorer::FileBrowserApp::recent_folders`] remembers.
+pub const MAX_RECENT_FOLDERS: usize = 20;
+
+/// Things used lately, most recent first and each only once, like the recently visited
+/// folders. Using one again moves it to the front; past `capacity`, the oldest are forgotten.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recent<T> {
+    items: Vec<T>,
+    capacity: usize,
+}
+
+impl<T: PartialEq> Recent<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            items: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// What [`Self::items`] were saved, most recent first. Repeats after the first are dropped.
+    pub fn from_items(items: Vec<T>, capacity: usize) -> Self {
+        let mut recent = Self::new(capacity);
+        for item in items.into_iter().rev() {
+            recent.add(item);
+        }
+        recent
+    }
+
+    /// Puts `item` first, moving it there if it's already in the list.
+    pub fn add(&mut self, item: T) {
+        self.items.retain(|recent| *recent != item);
+        self.items.insert(0, item);
+        self.items.truncate(self.capacity);
+    }
+
+    pub fn remove(&mut self, item: &T) {
+        self.items.retain(|recent| recent != item);
+    }
+
+    /// Most recent first.
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_latest_comes_first() {
+        let mut recent = Recent::new(5);
+        assert!(recent.items().is_empty());
+        recent.add("a");
+        recent.add("b");
+        recent.add("c");
+        assert_eq!(recent.items(), ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn revisits_move_to_the_front_once() {
+        let mut recent = Recent::new(5);
+        for item in ["a", "b", "c", "a"] {
+            recent.add(item);
+        }
+        assert_eq!(recent.items(), ["a", "c", "b"]);
+        // Already first.
+        recent.add("a");
+        assert_eq!(recent.items(), ["a", "c", "b"]);
+    }
+
+    #[test]
+    fn the_oldest_are_forgotten() {
+        let mut recent = Recent::new(3);
+        for item in 0..10 {
+            recent.add(item);
+        }
+        assert_eq!(recent.items(), [9, 8, 7]);
+        recent.add(7);
+        assert_eq!(recent.items(), [7, 9, 8]);
+    }
+
+    #[test]
+    fn items_can_be_removed() {
+        let mut recent = Recent::from_items(vec!["a", "b", "c"], 5);
+        recent.remove(&"b");
+        recent.remove(&"missing");
+        assert_eq!(recent.items(), ["a", "c"]);
+    }
+
+    #[test]
+    fn saved_items_are_restored_in_order() {
+        let recent = Recent::from_items(vec!["a", "b", "a", "c", "d"], 3);
+        assert_eq!(recent.items(), ["a", "b", "c"]);
+        assert_eq!(Recent::from_items(recent.items().to_vec(), 3), recent);
+    }
+}
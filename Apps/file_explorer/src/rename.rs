@@ -0,0 +1,186 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::new_folder::validate_name;
+
+/// A file or folder whose name is being edited in place.
+pub struct Rename {
+    /// The entry as it is on disk.
+    pub path: PathBuf,
+    pub name: String,
+
+    /// The new name is taken; renaming again will replace what's there.
+    pub confirm_overwrite: bool,
+
+    /// Whether the text field still needs its initial selection.
+    pub select_on_focus: bool,
+}
+
+impl Rename {
+    pub fn new(path: PathBuf) -> Self {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self {
+            path,
+            name,
+            confirm_overwrite: false,
+            select_on_focus: true,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RenameError {
+    /// Something else is called that already; rename with `overwrite` to replace it.
+    Exists(PathBuf),
+    Failed(String),
+}
+
+/// How many characters to select initially: all of a folder's name,
+/// and a file's name without its extension.
+///
+/// `"report.tar.gz"` selects `"report.tar"`, and dotfiles like `".bashrc"` are selected whole.
+pub fn initial_selection(name: &str, is_dir: bool) -> usize {
+    let whole = name.chars().count();
+    if is_dir {
+        return whole;
+    }
+    match name.rfind('.') {
+        Some(0) | None => whole,
+        Some(dot) => name[..dot].chars().count(),
+    }
+}
+
+/// Renames `path` to `new_name` in the same folder, returning the new path.
+///
+/// Won't replace an existing entry unless `overwrite` is set. Changing only the case of a name
+/// works on case-insensitive file systems too.
+pub fn rename(path: &Path, new_name: &str, overwrite: bool) -> Result<PathBuf, RenameError> {
+    validate_name(new_name).map_err(RenameError::Failed)?;
+    let parent = path
+        .parent()
+        .ok_or_else(|| RenameError::Failed(format!("Can't rename {}", path.display())))?;
+    let target = parent.join(new_name);
+    if target == path {
+        return Ok(target);
+    }
+
+    if !overwrite && target.symlink_metadata().is_ok() && !same_entry(path, &target) {
+        return Err(RenameError::Exists(target));
+    }
+
+    fs::rename(path, &target).map_err(|err| {
+        RenameError::Failed(match err.kind() {
+            ErrorKind::NotFound => format!("{} no longer exists", path.display()),
+            ErrorKind::PermissionDenied => format!("No permission to rename {}", path.display()),
+            _ => format!("Can't rename {}: {err}", path.display()),
+        })
+    })?;
+    Ok(target)
+}
+
+/// Do both paths lead to the same file, e.g. `a.txt` and `A.TXT` on a case-insensitive file system?
+fn same_entry(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explorer::FileBrowserApp;
+    use crate::temp_dir::TempDir;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn selects_name_without_extension() {
+        assert_eq!(initial_selection("notes.txt", false), 5);
+        assert_eq!(initial_selection("report.tar.gz", false), 10);
+        assert_eq!(initial_selection(".bashrc", false), 7);
+        assert_eq!(initial_selection("Makefile", false), 8);
+        assert_eq!(initial_selection("photos.2024", true), 11);
+        assert_eq!(initial_selection("überblick.md", false), 9);
+    }
+
+    #[test]
+    fn rejects_names_outside_the_folder() {
+        let path = Path::new("/tmp/whatever.txt");
+        assert!(matches!(
+            rename(path, "a/b", false),
+            Err(RenameError::Failed(_))
+        ));
+        assert!(matches!(
+            rename(path, "  ", false),
+            Err(RenameError::Failed(_))
+        ));
+        assert!(matches!(
+            rename(path, "..", false),
+            Err(RenameError::Failed(_))
+        ));
+    }
+
+    #[test]
+    fn renames_in_a_temp_folder() {
+        let dir = TempDir::new("rename");
+        let a = dir.join("a.txt");
+        let b = dir.join("b.txt");
+        fs::write(&a, "a").unwrap();
+        fs::write(&b, "b").unwrap();
+
+        // Taken: ask first, then replace.
+        assert_eq!(
+            rename(&a, "b.txt", false),
+            Err(RenameError::Exists(b.clone()))
+        );
+        assert!(a.exists());
+        assert_eq!(rename(&a, "b.txt", true), Ok(b.clone()));
+        assert!(!a.exists());
+        assert_eq!(fs::read_to_string(&b).unwrap(), "a");
+
+        // Free.
+        let c = dir.join("c.txt");
+        assert_eq!(rename(&b, "c.txt", false), Ok(c.clone()));
+        assert!(c.exists() && !b.exists());
+
+        // Vanished.
+        let error = rename(&b, "d.txt", false).unwrap_err();
+        assert!(
+            matches!(&error, RenameError::Failed(message) if message.contains("no longer exists")),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn renamed_file_is_selected_in_the_refreshed_listing() {
+        let dir = TempDir::new("rename_flow");
+        fs::write(dir.join("draft.txt"), "").unwrap();
+        fs::write(dir.join("final.txt"), "").unwrap();
+
+        let mut app = FileBrowserApp::default();
+        app.current_path = dir.to_string_lossy().into_owned();
+        app.renaming = Some(Rename::new(dir.join("draft.txt")));
+        app.renaming.as_mut().unwrap().name = "final.txt".to_owned();
+
+        app.commit_rename(false);
+        assert!(app.renaming.as_ref().unwrap().confirm_overwrite);
+
+        app.commit_rename(true);
+        assert!(app.renaming.is_none());
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while app.loader.is_loading() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+            app.poll_directory_list();
+        }
+
+        let renamed = dir.join("final.txt");
+        let names: Vec<_> = app.files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, ["final.txt"]);
+        assert!(app.selection.contains(&renamed));
+    }
+}
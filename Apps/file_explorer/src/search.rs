@@ -0,0 +1,601 @@
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+use regex::{Regex, RegexBuilder};
+
+use crate::hidden::is_hidden_entry;
+use crate::read_errors::{ReadError, ReadErrors};
+
+/// Matches are handed over in batches of about this many.
+pub const BATCH_SIZE: usize = 50;
+
+/// How long typing in the search box has to pause before the search starts over.
+pub const SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Holds back searching while the query is being typed, so every keystroke doesn't start
+/// (and cancel) a walk of the whole tree.
+#[derive(Debug, Default)]
+pub struct SearchDebounce {
+    /// When the query was last edited, if it hasn't been searched for since.
+    last_edit: Option<Instant>,
+}
+
+impl SearchDebounce {
+    pub fn edited(&mut self, now: Instant) {
+        self.last_edit = Some(now);
+    }
+
+    /// Should the search start? True once, when [`SEARCH_DEBOUNCE`] has passed since the
+    /// last edit.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        match self.last_edit {
+            Some(last_edit) if now.duration_since(last_edit) >= SEARCH_DEBOUNCE => {
+                self.last_edit = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// How long until [`Self::poll`] is due, if an edit is waiting.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.last_edit
+            .map(|last_edit| SEARCH_DEBOUNCE.saturating_sub(now.duration_since(last_edit)))
+    }
+
+    /// Forgets the waiting edit, e.g. because it was searched for right away.
+    pub fn clear(&mut self) {
+        self.last_edit = None;
+    }
+}
+
+/// How the search box is interpreted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Case-insensitive substring.
+    #[default]
+    Plain,
+
+    /// `*` matches any run of characters and `?` any single one; case-insensitive.
+    Glob,
+
+    /// A regular expression, searched for anywhere in the name.
+    Regex,
+}
+
+impl SearchMode {
+    pub const ALL: [Self; 3] = [Self::Plain, Self::Glob, Self::Regex];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Plain => "Text",
+            Self::Glob => "Glob",
+            Self::Regex => "Regex",
+        }
+    }
+}
+
+/// Decides which file names match a query. Built once per search.
+#[derive(Clone)]
+pub enum Matcher {
+    /// The lowercased query.
+    Plain(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    /// An empty query matches everything, whatever the mode.
+    pub fn new(mode: SearchMode, query: &str) -> Result<Self, String> {
+        if query.is_empty() {
+            return Ok(Self::Plain(String::new()));
+        }
+        match mode {
+            SearchMode::Plain => Ok(Self::Plain(query.to_lowercase())),
+            SearchMode::Glob => RegexBuilder::new(&glob_to_regex(query))
+                .case_insensitive(true)
+                .build()
+                .map(Self::Regex)
+                .map_err(|err| err.to_string()),
+            SearchMode::Regex => Regex::new(query)
+                .map(Self::Regex)
+                .map_err(|err| err.to_string()),
+        }
+    }
+
+    pub fn is_match(&self, name: &str) -> bool {
+        match self {
+            Self::Plain(query) => query.is_empty() || name.to_lowercase().contains(query.as_str()),
+            Self::Regex(regex) => regex.is_match(name),
+        }
+    }
+
+    /// Where in `name` the query matches, as byte ranges, in order and not overlapping.
+    pub fn find_ranges(&self, name: &str) -> Vec<Range<usize>> {
+        match self {
+            Self::Plain(query) if query.is_empty() => Vec::new(),
+            Self::Plain(query) => {
+                let mut ranges = Vec::new();
+                let mut start = 0;
+                while start < name.len() {
+                    match match_len(&name[start..], query) {
+                        Some(len) => {
+                            ranges.push(start..start + len);
+                            start += len;
+                        }
+                        None => start += name[start..].chars().next().map_or(1, char::len_utf8),
+                    }
+                }
+                ranges
+            }
+            Self::Regex(regex) => regex
+                .find_iter(name)
+                .map(|m| m.range())
+                .filter(|range| !range.is_empty())
+                .collect(),
+        }
+    }
+}
+
+/// How many bytes at the start of `haystack` match the lowercase `query`, ignoring case.
+///
+/// Compared char by char, since lowercasing can change how many bytes a char takes.
+fn match_len(haystack: &str, query: &str) -> Option<usize> {
+    let mut query = query.chars().peekable();
+    for (i, c) in haystack.char_indices() {
+        for lower in c.to_lowercase() {
+            if query.next() != Some(lower) {
+                return None;
+            }
+        }
+        if query.peek().is_none() {
+            return Some(i + c.len_utf8());
+        }
+    }
+    None
+}
+
+/// Splits `name` into pieces that are inside `ranges` (`true`) or between them (`false`),
+/// e.g. to highlight the part of a name that matched the search.
+pub fn split_matches<'a>(name: &'a str, ranges: &[Range<usize>]) -> Vec<(&'a str, bool)> {
+    let mut segments = Vec::new();
+    let mut end = 0;
+    for range in ranges {
+        if range.start > end {
+            segments.push((&name[end..range.start], false));
+        }
+        segments.push((&name[range.clone()], true));
+        end = range.end;
+    }
+    if end < name.len() {
+        segments.push((&name[end..], false));
+    }
+    segments
+}
+
+/// Where the search result at `path` is, as the path of its folder relative to `root`,
+/// the folder searched in: "." for results right in `root`.
+///
+/// Paths outside `root` keep their whole folder path.
+pub fn result_location(root: &Path, path: &Path) -> String {
+    let Some(parent) = path.parent() else {
+        return String::new();
+    };
+    match parent.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_owned(),
+        Ok(relative) => relative.display().to_string(),
+        Err(_) => parent.display().to_string(),
+    }
+}
+
+/// A regular expression matching whole names against `glob`.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+struct Search<'a, F> {
+    matcher: &'a Matcher,
+    show_hidden: bool,
+    cancel: &'a AtomicBool,
+    visited: AtomicUsize,
+    errors: Mutex<ReadErrors>,
+    batch: Mutex<Vec<PathBuf>>,
+    emit: F,
+}
+
+/// What [`search_streaming`] went through, besides what it found.
+#[derive(Debug, Default)]
+pub struct SearchOutcome {
+    /// How many entries were looked at.
+    pub visited: usize,
+    /// The folders that couldn't be listed, which may hold matches nobody will see.
+    pub errors: ReadErrors,
+}
+
+/// Recursively searches `dir` for entries whose names match,
+/// calling `emit` with batches of matches as they are found.
+///
+/// Folders that match are reported but not searched further, so a matcher for an empty query
+/// lists `dir` itself. Hidden entries are skipped (and hidden folders not searched)
+/// unless `show_hidden` is set.
+///
+/// Stops soon after `cancel` is set.
+pub fn search_streaming(
+    dir: &Path,
+    matcher: &Matcher,
+    show_hidden: bool,
+    cancel: &AtomicBool,
+    emit: impl Fn(Vec<PathBuf>) + Sync,
+) -> SearchOutcome {
+    let search = Search {
+        matcher,
+        show_hidden,
+        cancel,
+        visited: AtomicUsize::new(0),
+        errors: Mutex::new(ReadErrors::default()),
+        batch: Mutex::new(Vec::new()),
+        emit,
+    };
+
+    search.visit(dir);
+
+    let rest = std::mem::take(&mut *search.batch.lock().unwrap());
+    if !rest.is_empty() && !cancel.load(Ordering::Relaxed) {
+        (search.emit)(rest);
+    }
+    SearchOutcome {
+        visited: search.visited.into_inner(),
+        errors: search.errors.into_inner().unwrap(),
+    }
+}
+
+impl<F: Fn(Vec<PathBuf>) + Sync> Search<'_, F> {
+    fn visit(&self, dir: &Path) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => return self.failed(dir, &err),
+        };
+        let entries: Vec<_> = entries
+            .filter_map(|entry| entry.map_err(|err| self.failed(dir, &err)).ok())
+            .filter(|entry| self.show_hidden || !is_hidden_entry(entry))
+            .collect();
+
+        entries.par_iter().for_each(|entry| {
+            if self.cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            self.visited.fetch_add(1, Ordering::Relaxed);
+
+            let path = entry.path();
+            let matches = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map_or(false, |name| self.matcher.is_match(name));
+
+            if matches {
+                self.found(path);
+            } else if path.is_dir() {
+                self.visit(&path);
+            }
+        });
+    }
+
+    fn failed(&self, dir: &Path, err: &std::io::Error) {
+        if !self.cancel.load(Ordering::Relaxed) {
+            self.errors.lock().unwrap().push(ReadError::new(dir, err));
+        }
+    }
+
+    fn found(&self, path: PathBuf) {
+        let full_batch = {
+            let mut batch = self.batch.lock().unwrap();
+            batch.push(path);
+            (batch.len() >= BATCH_SIZE).then(|| std::mem::take(&mut *batch))
+        };
+        if let Some(batch) = full_batch {
+            (self.emit)(batch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    const NAMES: [&str; 6] = [
+        "Downloads",
+        "main.rs",
+        "lib.rs",
+        "README.md",
+        "Ärger.txt",
+        "rs_notes.txt",
+    ];
+
+    fn matching(mode: SearchMode, query: &str) -> Vec<&'static str> {
+        let matcher = Matcher::new(mode, query).unwrap();
+        NAMES
+            .into_iter()
+            .filter(|name| matcher.is_match(name))
+            .collect()
+    }
+
+    fn segments<'a>(mode: SearchMode, query: &str, name: &'a str) -> Vec<(&'a str, bool)> {
+        let matcher = Matcher::new(mode, query).unwrap();
+        split_matches(name, &matcher.find_ranges(name))
+    }
+
+    #[test]
+    fn splits_names_into_matched_segments() {
+        assert_eq!(
+            segments(SearchMode::Plain, "rs", "rs_notes.RS"),
+            [("rs", true), ("_notes.", false), ("RS", true)]
+        );
+        assert_eq!(
+            segments(SearchMode::Plain, "me", "README.md"),
+            [("READ", false), ("ME", true), (".md", false)]
+        );
+        assert_eq!(segments(SearchMode::Plain, "x", "main.rs"), [("main.rs", false)]);
+        assert_eq!(segments(SearchMode::Plain, "", "main.rs"), [("main.rs", false)]);
+        assert_eq!(segments(SearchMode::Plain, "main.rs", "main.rs"), [("main.rs", true)]);
+    }
+
+    #[test]
+    fn matched_segments_ignore_case_beyond_ascii() {
+        assert_eq!(
+            segments(SearchMode::Plain, "ärg", "ÄRGER.txt"),
+            [("ÄRG", true), ("ER.txt", false)]
+        );
+        // 'İ' lowercases to two chars, so byte offsets of the lowercased name would be off.
+        assert_eq!(
+            segments(SearchMode::Plain, "x", "İx"),
+            [("İ", false), ("x", true)]
+        );
+        assert_eq!(segments(SearchMode::Plain, "aa", "aaa"), [("aa", true), ("a", false)]);
+    }
+
+    #[test]
+    fn regex_and_glob_segments() {
+        assert_eq!(
+            segments(SearchMode::Regex, "[0-9]+", "v12_final3"),
+            [("v", false), ("12", true), ("_final", false), ("3", true)]
+        );
+        assert_eq!(segments(SearchMode::Regex, "x*", "ab"), [("ab", false)]);
+        assert_eq!(segments(SearchMode::Glob, "*.rs", "main.rs"), [("main.rs", true)]);
+    }
+
+    #[test]
+    fn plain_mode_ignores_case() {
+        assert_eq!(matching(SearchMode::Plain, "downloads"), ["Downloads"]);
+        assert_eq!(matching(SearchMode::Plain, "READ"), ["README.md"]);
+        assert_eq!(matching(SearchMode::Plain, "äRGER"), ["Ärger.txt"]);
+        assert_eq!(matching(SearchMode::Plain, "*.rs"), Vec::<&str>::new());
+        assert_eq!(matching(SearchMode::Plain, "").len(), NAMES.len());
+    }
+
+    #[test]
+    fn glob_mode_matches_whole_names() {
+        assert_eq!(matching(SearchMode::Glob, "*.rs"), ["main.rs", "lib.rs"]);
+        assert_eq!(matching(SearchMode::Glob, "???.rs"), ["lib.rs"]);
+        assert_eq!(matching(SearchMode::Glob, "readme*"), ["README.md"]);
+        assert_eq!(matching(SearchMode::Glob, "ä*"), ["Ärger.txt"]);
+        assert_eq!(matching(SearchMode::Glob, "rs"), Vec::<&str>::new());
+        // Regex syntax is taken literally.
+        assert_eq!(matching(SearchMode::Glob, "main.r[s]"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn regex_mode() {
+        assert_eq!(matching(SearchMode::Regex, r"\.rs$"), ["main.rs", "lib.rs"]);
+        assert_eq!(
+            matching(SearchMode::Regex, "^[A-Z]"),
+            ["Downloads", "README.md"]
+        );
+        assert_eq!(matching(SearchMode::Regex, "(?i)^ärger"), ["Ärger.txt"]);
+        assert!(Matcher::new(SearchMode::Regex, "(unclosed").is_err());
+    }
+
+    #[test]
+    fn locations_are_relative_to_the_search_root() {
+        let location = |root: &str, path: &str| result_location(Path::new(root), Path::new(path));
+        assert_eq!(location("/home/me", "/home/me/notes.txt"), ".");
+        assert_eq!(location("/home/me", "/home/me/a/b/c/notes.txt"), "a/b/c");
+        // A trailing slash on the root makes no difference.
+        assert_eq!(location("/home/me/", "/home/me/a/notes.txt"), "a");
+        assert_eq!(location("/home/me", "/home/mel/notes.txt"), "/home/mel");
+    }
+
+    #[test]
+    fn locations_under_the_filesystem_root() {
+        let location = |path: &str| result_location(Path::new("/"), Path::new(path));
+        assert_eq!(location("/hosts"), ".");
+        assert_eq!(location("/etc/hosts"), "etc");
+        assert_eq!(location("/usr/share/doc/README"), "usr/share/doc");
+        assert_eq!(location("/"), "");
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn locations_under_a_drive() {
+        let location = |path: &str| result_location(Path::new("C:\\"), Path::new(path));
+        assert_eq!(location("C:\\pagefile.sys"), ".");
+        assert_eq!(location("C:\\Users\\me\\notes.txt"), "Users\\me");
+        assert_eq!(location("D:\\Games\\save.dat"), "D:\\Games");
+        assert_eq!(
+            result_location(Path::new("C:\\Users"), Path::new("C:\\Users\\me\\a.txt")),
+            "me"
+        );
+    }
+
+    /// `width` folders of `width` folders of `width` files, all with an "f" in their name.
+    fn synthetic_tree(name: &str, width: usize) -> (TempDir, usize) {
+        let root = TempDir::new(name);
+        for a in 0..width {
+            for b in 0..width {
+                let dir = root.join(format!("a{a}")).join(format!("b{b}"));
+                fs::create_dir_all(&dir).unwrap();
+                for c in 0..width {
+                    fs::write(dir.join(format!("f{c}")), "").unwrap();
+                }
+            }
+        }
+        (root, width + width * width + width * width * width)
+    }
+
+    #[test]
+    fn finds_everything_in_batches() {
+        let (root, total) = synthetic_tree("search_all", 6);
+        let found = Mutex::new(Vec::new());
+        let matcher = Matcher::new(SearchMode::Plain, "f").unwrap();
+        let outcome = search_streaming(&root, &matcher, true, &AtomicBool::new(false), |batch| {
+            assert!(batch.len() <= BATCH_SIZE);
+            found.lock().unwrap().extend(batch);
+        });
+
+        assert_eq!(outcome.visited, total);
+        assert!(outcome.errors.is_empty());
+        assert_eq!(found.into_inner().unwrap().len(), 6 * 6 * 6);
+    }
+
+    #[test]
+    fn cancellation_halts_traversal() {
+        let (root, total) = synthetic_tree("search_cancel", 12);
+        let cancel = AtomicBool::new(false);
+        let batches = AtomicUsize::new(0);
+        let matcher = Matcher::new(SearchMode::Plain, "f").unwrap();
+        let visited = search_streaming(&root, &matcher, true, &cancel, |_batch| {
+            batches.fetch_add(1, Ordering::Relaxed);
+            cancel.store(true, Ordering::Relaxed);
+        })
+        .visited;
+
+        assert!(batches.load(Ordering::Relaxed) >= 1);
+        assert!(visited < total / 2, "visited {visited} of {total}");
+    }
+
+    #[test]
+    fn cancellation_stops_deep_recursion() {
+        let root = TempDir::new("search_deep");
+        let depth = 8 * BATCH_SIZE;
+        let mut dir = root.to_path_buf();
+        for level in 0..depth {
+            dir.push("d");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join(format!("f{level}")), "").unwrap();
+        }
+
+        let cancel = AtomicBool::new(false);
+        let matcher = Matcher::new(SearchMode::Plain, "f").unwrap();
+        let visited = search_streaming(&root, &matcher, true, &cancel, |_batch| {
+            cancel.store(true, Ordering::Relaxed);
+        })
+        .visited;
+
+        let total = 2 * depth;
+        assert!(visited < total / 2, "visited {visited} of {total}");
+    }
+
+    #[test]
+    fn searches_wait_for_typing_to_pause() {
+        let start = Instant::now();
+        let mut debounce = SearchDebounce::default();
+        assert!(!debounce.poll(start));
+        assert_eq!(debounce.remaining(start), None);
+
+        debounce.edited(start);
+        debounce.edited(start + SEARCH_DEBOUNCE / 2);
+        assert!(!debounce.poll(start + SEARCH_DEBOUNCE));
+        assert_eq!(
+            debounce.remaining(start + SEARCH_DEBOUNCE),
+            Some(SEARCH_DEBOUNCE / 2)
+        );
+        assert!(debounce.poll(start + SEARCH_DEBOUNCE * 3 / 2));
+        // Only once per pause.
+        assert!(!debounce.poll(start + SEARCH_DEBOUNCE * 2));
+
+        debounce.edited(start);
+        debounce.clear();
+        assert!(!debounce.poll(start + SEARCH_DEBOUNCE));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn skips_hidden_entries_unless_asked() {
+        let (root, _) = synthetic_tree("search_hidden", 2);
+        fs::create_dir_all(root.join(".git").join("objects")).unwrap();
+        fs::write(root.join(".git").join("objects").join("f0"), "").unwrap();
+        fs::write(root.join(".fhidden"), "").unwrap();
+
+        let matcher = Matcher::new(SearchMode::Plain, "f").unwrap();
+        let count = |show_hidden| {
+            let found = AtomicUsize::new(0);
+            search_streaming(
+                &root,
+                &matcher,
+                show_hidden,
+                &AtomicBool::new(false),
+                |batch| {
+                    found.fetch_add(batch.len(), Ordering::Relaxed);
+                },
+            );
+            found.into_inner()
+        };
+
+        assert_eq!(count(false), 2 * 2 * 2);
+        assert_eq!(count(true), 2 * 2 * 2 + 2);
+    }
+
+    #[test]
+    fn canceled_search_visits_nothing() {
+        let (root, _) = synthetic_tree("search_precanceled", 2);
+        let matcher = Matcher::new(SearchMode::Plain, "f").unwrap();
+        let outcome = search_streaming(&root, &matcher, true, &AtomicBool::new(true), |_| {
+            panic!("nothing should be found");
+        });
+        assert_eq!(outcome.visited, 0);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn reports_folders_that_cannot_be_read() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let (root, _) = synthetic_tree("search_locked", 2);
+        let locked = root.join("a1");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        if fs::read_dir(&locked).is_ok() {
+            // Running as root, which can read anything.
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let found = AtomicUsize::new(0);
+        let matcher = Matcher::new(SearchMode::Plain, "f").unwrap();
+        let outcome = search_streaming(&root, &matcher, true, &AtomicBool::new(false), |batch| {
+            found.fetch_add(batch.len(), Ordering::Relaxed);
+        });
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(found.into_inner(), 2 * 2);
+        assert_eq!(
+            outcome.errors.0,
+            [ReadError {
+                path: locked,
+                kind: std::io::ErrorKind::PermissionDenied,
+            }]
+        );
+    }
+}
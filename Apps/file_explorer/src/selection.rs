@@ -0,0 +1,120 @@
+/// Returns the entries between `anchor` and the one at `clicked` (both inclusive), in display order.
+///
+/// `order` is what's displayed now, so the anchor may have been filtered out (or moved)
+/// since it was clicked. If it's gone, or there is none, only the clicked entry is selected.
+/// An index past the end of `order` is clamped to the last entry.
+pub fn range_select<T: Clone + PartialEq>(
+    order: &[T],
+    anchor: Option<&T>,
+    clicked: usize,
+) -> Vec<T> {
+    if order.is_empty() {
+        return Vec::new();
+    }
+
+    let last = order.len() - 1;
+    let clicked = clicked.min(last);
+    let anchor = anchor
+        .and_then(|anchor| order.iter().position(|entry| entry == anchor))
+        .unwrap_or(clicked);
+    let start = anchor.min(clicked);
+    let end = anchor.max(clicked);
+
+    order[start..=end].to_vec()
+}
+
+/// Moves the keyboard cursor by `delta` rows in a list of `len` rows, stopping at either end.
+///
+/// Without a cursor, moving down starts at the first row and moving up at the last.
+pub fn step_cursor(cursor: Option<usize>, delta: isize, len: usize) -> Option<usize> {
+    let last = len.checked_sub(1)?;
+    let index = match cursor {
+        Some(cursor) => cursor.saturating_add_signed(delta),
+        None if delta < 0 => last,
+        None => 0,
+    };
+    Some(index.min(last))
+}
+
+/// Keeps the keyboard cursor on a row after the list changed to `len` rows, e.g. when
+/// entries were deleted from the end of it.
+pub fn clamp_cursor(cursor: Option<usize>, len: usize) -> Option<usize> {
+    Some(cursor?.min(len.checked_sub(1)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order() -> Vec<String> {
+        ["/a", "/b", "/c", "/d", "/e"].map(String::from).to_vec()
+    }
+
+    #[test]
+    fn range_select_forward_and_backward() {
+        let order = order();
+        assert_eq!(range_select(&order, Some(&order[1]), 3), ["/b", "/c", "/d"]);
+        assert_eq!(range_select(&order, Some(&order[3]), 1), ["/b", "/c", "/d"]);
+        assert_eq!(range_select(&order, Some(&order[2]), 2), ["/c"]);
+    }
+
+    #[test]
+    fn range_select_clamps_out_of_bounds() {
+        let order = order();
+        assert_eq!(range_select(&order, Some(&order[3]), 10), ["/d", "/e"]);
+        assert!(range_select::<String>(&[], None, 3).is_empty());
+    }
+
+    #[test]
+    fn range_select_without_anchor_selects_the_clicked_entry() {
+        assert_eq!(range_select(&order(), None, 2), ["/c"]);
+    }
+
+    #[test]
+    fn range_select_follows_the_filtered_order() {
+        // "/b" and "/d" were filtered out (e.g. by a search) after "/a" was clicked.
+        let filtered: Vec<String> = ["/a", "/c", "/e"].map(String::from).to_vec();
+        let anchor = "/a".to_owned();
+        assert_eq!(
+            range_select(&filtered, Some(&anchor), 2),
+            ["/a", "/c", "/e"]
+        );
+
+        // The anchor itself was filtered out.
+        let anchor = "/b".to_owned();
+        assert_eq!(range_select(&filtered, Some(&anchor), 1), ["/c"]);
+
+        // The order changed (e.g. re-sorted): ranges go by where the anchor is now.
+        let resorted: Vec<String> = ["/e", "/d", "/c", "/b", "/a"].map(String::from).to_vec();
+        let anchor = "/d".to_owned();
+        assert_eq!(
+            range_select(&resorted, Some(&anchor), 3),
+            ["/d", "/c", "/b"]
+        );
+    }
+
+    #[test]
+    fn step_cursor_stops_at_the_ends() {
+        assert_eq!(step_cursor(Some(2), 1, 5), Some(3));
+        assert_eq!(step_cursor(Some(2), -1, 5), Some(1));
+        assert_eq!(step_cursor(Some(4), 1, 5), Some(4));
+        assert_eq!(step_cursor(Some(0), -1, 5), Some(0));
+    }
+
+    #[test]
+    fn step_cursor_starts_at_an_end() {
+        assert_eq!(step_cursor(None, 1, 5), Some(0));
+        assert_eq!(step_cursor(None, -1, 5), Some(4));
+        assert_eq!(step_cursor(None, 1, 0), None);
+        assert_eq!(step_cursor(Some(3), 1, 0), None);
+    }
+
+    #[test]
+    fn clamp_cursor_when_the_list_shrinks() {
+        assert_eq!(clamp_cursor(Some(7), 10), Some(7));
+        assert_eq!(clamp_cursor(Some(7), 3), Some(2));
+        assert_eq!(clamp_cursor(Some(0), 1), Some(0));
+        assert_eq!(clamp_cursor(Some(7), 0), None);
+        assert_eq!(clamp_cursor(None, 10), None);
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::grid::ViewMode;
+use crate::launch::{default_editor_command, default_terminal_command};
+use crate::size_format::SizeBase;
+use crate::size_mode::SizeMode;
+
+/// [`eframe::Storage`] key of [`ExplorerSettings`].
+pub const SETTINGS_KEY: &str = "settings";
+
+/// The version of [`ExplorerSettings`] this build saves.
+///
+/// Bump it when a field changes meaning, and convert what older versions saved in
+/// [`ExplorerSettings::migrate`]. Fields that are only added need neither: they start out
+/// at their default.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The options of the explorer, kept between sessions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExplorerSettings {
+    /// What version of this struct was saved. Version 0 is the separate keys saved before
+    /// there was one.
+    pub schema_version: u32,
+    /// The folder open when the app was closed.
+    pub current_path: Option<String>,
+    pub show_hidden: bool,
+    pub size_base: SizeBase,
+    pub size_mode: SizeMode,
+    pub follow_links: bool,
+    pub show_permissions_column: bool,
+    pub show_owner_column: bool,
+    pub view_mode: ViewMode,
+    /// Folders before files, rather than mixed in by name.
+    pub group_folders_first: bool,
+    /// Run by "Open terminal here", see [`crate::launch::command_args`].
+    pub terminal_command: String,
+    /// Run by "Open in editor", see [`crate::launch::command_args`].
+    pub editor_command: String,
+    /// Quote copied paths and names that a shell would split or expand.
+    pub quote_copied_paths: bool,
+    /// The user's icons for files, by lowercase extension, see [`crate::icons::IconTheme`].
+    pub icon_overrides: BTreeMap<String, String>,
+    pub dark_mode: bool,
+}
+
+impl Default for ExplorerSettings {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            current_path: None,
+            show_hidden: false,
+            size_base: SizeBase::default(),
+            size_mode: SizeMode::default(),
+            follow_links: false,
+            show_permissions_column: false,
+            show_owner_column: false,
+            view_mode: ViewMode::default(),
+            group_folders_first: true,
+            terminal_command: default_terminal_command(),
+            editor_command: default_editor_command(),
+            quote_copied_paths: false,
+            icon_overrides: BTreeMap::new(),
+            dark_mode: false,
+        }
+    }
+}
+
+impl ExplorerSettings {
+    /// What was saved in `storage`, brought up to [`SCHEMA_VERSION`]; the defaults if nothing was.
+    pub fn load(storage: &dyn eframe::Storage) -> Self {
+        match eframe::get_value::<Self>(storage, SETTINGS_KEY) {
+            Some(settings) => settings.migrate(),
+            None => Self::from_separate_keys(storage),
+        }
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SETTINGS_KEY, self);
+    }
+
+    fn migrate(self) -> Self {
+        // Nothing has changed meaning since version 1 yet.
+        Self {
+            schema_version: SCHEMA_VERSION,
+            ..self
+        }
+    }
+
+    /// Version 0: every setting under a key of its own.
+    fn from_separate_keys(storage: &dyn eframe::Storage) -> Self {
+        let defaults = Self::default();
+        Self {
+            schema_version: SCHEMA_VERSION,
+            current_path: eframe::get_value(storage, "current_path"),
+            show_hidden: eframe::get_value(storage, "show_hidden").unwrap_or(defaults.show_hidden),
+            size_base: eframe::get_value(storage, "size_base").unwrap_or(defaults.size_base),
+            follow_links: eframe::get_value(storage, "follow_links")
+                .unwrap_or(defaults.follow_links),
+            show_permissions_column: eframe::get_value(storage, "show_permissions_column")
+                .unwrap_or(defaults.show_permissions_column),
+            show_owner_column: eframe::get_value(storage, "show_owner_column")
+                .unwrap_or(defaults.show_owner_column),
+            view_mode: eframe::get_value(storage, "view_mode").unwrap_or(defaults.view_mode),
+            ..defaults
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::Storage as _;
+    use std::collections::HashMap;
+
+    /// An [`eframe::Storage`] that only lives as long as the test.
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_owned(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    #[test]
+    fn nothing_saved_loads_the_defaults() {
+        assert_eq!(
+            ExplorerSettings::load(&MemoryStorage::default()),
+            ExplorerSettings::default()
+        );
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let settings = ExplorerSettings {
+            current_path: Some("/home/me/projects".to_owned()),
+            show_hidden: true,
+            size_base: SizeBase::Decimal,
+            size_mode: SizeMode::DiskUsage,
+            follow_links: true,
+            show_permissions_column: true,
+            show_owner_column: false,
+            view_mode: ViewMode::Grid,
+            group_folders_first: false,
+            terminal_command: "kitty --directory {path}".to_owned(),
+            editor_command: r#""/opt/My Editor/editor" {path}"#.to_owned(),
+            quote_copied_paths: true,
+            icon_overrides: BTreeMap::from([("rs".to_owned(), "🦀".to_owned())]),
+            dark_mode: true,
+            ..Default::default()
+        };
+        let mut storage = MemoryStorage::default();
+        settings.save(&mut storage);
+        assert_eq!(ExplorerSettings::load(&storage), settings);
+    }
+
+    #[test]
+    fn missing_fields_are_defaults() {
+        let mut storage = MemoryStorage::default();
+        storage.set_string(
+            SETTINGS_KEY,
+            "(schema_version: 1, show_hidden: true)".to_owned(),
+        );
+        let settings = ExplorerSettings::load(&storage);
+        assert!(settings.show_hidden);
+        assert_eq!(settings.current_path, None);
+        assert_eq!(settings.view_mode, ViewMode::List);
+        assert!(settings.group_folders_first);
+    }
+
+    #[test]
+    fn separate_keys_are_migrated() {
+        let mut storage = MemoryStorage::default();
+        eframe::set_value(&mut storage, "current_path", &"/tmp".to_owned());
+        eframe::set_value(&mut storage, "show_hidden", &true);
+        eframe::set_value(&mut storage, "size_base", &SizeBase::Decimal);
+        eframe::set_value(&mut storage, "view_mode", &ViewMode::Grid);
+
+        let settings = ExplorerSettings::load(&storage);
+        assert_eq!(settings.schema_version, SCHEMA_VERSION);
+        assert_eq!(settings.current_path.as_deref(), Some("/tmp"));
+        assert!(settings.show_hidden);
+        assert_eq!(settings.size_base, SizeBase::Decimal);
+        assert_eq!(settings.view_mode, ViewMode::Grid);
+        assert!(!settings.follow_links);
+
+        // Once saved, the struct wins over what's left of the separate keys.
+        let saved = ExplorerSettings {
+            show_hidden: false,
+            ..settings
+        };
+        saved.save(&mut storage);
+        assert_eq!(ExplorerSettings::load(&storage), saved);
+    }
+
+    #[test]
+    fn older_versions_are_brought_up_to_date() {
+        let mut storage = MemoryStorage::default();
+        let old = ExplorerSettings {
+            schema_version: 0,
+            follow_links: true,
+            ..Default::default()
+        };
+        old.save(&mut storage);
+
+        let settings = ExplorerSettings::load(&storage);
+        assert_eq!(settings.schema_version, SCHEMA_VERSION);
+        assert!(settings.follow_links);
+    }
+}
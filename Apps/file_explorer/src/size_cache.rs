@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+/// A cached size is recalculated after this long even if the folder looks unchanged:
+/// a folder's modification time only changes when entries are added to or removed from it,
+/// not when something deeper down changes.
+pub const MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// At most this many sizes are saved between sessions.
+pub const MAX_SAVED_ENTRIES: usize = 2000;
+
+/// The size of a folder, as it was calculated.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedSize {
+    pub size: u64,
+    pub computed_at: SystemTime,
+
+    /// The folder's modification time when its size was calculated.
+    pub dir_modified: Option<SystemTime>,
+}
+
+impl CachedSize {
+    /// Can this still be shown for a folder last modified at `dir_modified`?
+    pub fn is_fresh(&self, dir_modified: Option<SystemTime>, now: SystemTime) -> bool {
+        let age = now.duration_since(self.computed_at).unwrap_or_default();
+        // Without a modification time there's no telling whether the folder changed.
+        dir_modified.is_some() && self.dir_modified == dir_modified && age < MAX_AGE
+    }
+}
+
+/// Folder sizes by canonical path, so opening the size of the same folder again is instant.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeCache {
+    entries: HashMap<PathBuf, CachedSize>,
+}
+
+impl SizeCache {
+    /// The size of the folder at `path`, unless it's missing or stale.
+    pub fn get(
+        &self,
+        path: &Path,
+        dir_modified: Option<SystemTime>,
+        now: SystemTime,
+    ) -> Option<u64> {
+        self.entries
+            .get(path)
+            .filter(|entry| entry.is_fresh(dir_modified, now))
+            .map(|entry| entry.size)
+    }
+
+    pub fn insert(
+        &mut self,
+        path: PathBuf,
+        size: u64,
+        dir_modified: Option<SystemTime>,
+        now: SystemTime,
+    ) {
+        let entry = CachedSize {
+            size,
+            computed_at: now,
+            dir_modified,
+        };
+        self.entries.insert(path, entry);
+    }
+
+    /// The `max_entries` most recently calculated sizes, which is what gets saved.
+    pub fn bounded(&self, max_entries: usize) -> Self {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.computed_at));
+        entries.truncate(max_entries);
+        Self {
+            entries: entries
+                .into_iter()
+                .map(|(path, entry)| (path.clone(), entry.clone()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn sizes_stay_fresh_until_the_folder_changes() {
+        let mut cache = SizeCache::default();
+        let path = Path::new("/usr");
+        cache.insert(path.to_owned(), 42, Some(at(100)), at(1000));
+
+        assert_eq!(cache.get(path, Some(at(100)), at(1000)), Some(42));
+        assert_eq!(
+            cache.get(path, Some(at(100)), at(1000) + MAX_AGE / 2),
+            Some(42)
+        );
+        assert_eq!(cache.get(path, Some(at(200)), at(1000)), None);
+        assert_eq!(
+            cache.get(Path::new("/usr/lib"), Some(at(100)), at(1000)),
+            None
+        );
+    }
+
+    #[test]
+    fn sizes_go_stale_with_age() {
+        let mut cache = SizeCache::default();
+        let path = Path::new("/usr");
+        cache.insert(path.to_owned(), 42, Some(at(100)), at(1000));
+
+        assert_eq!(cache.get(path, Some(at(100)), at(1000) + MAX_AGE), None);
+        // A clock that went backwards doesn't make it stale.
+        assert_eq!(cache.get(path, Some(at(100)), at(500)), Some(42));
+    }
+
+    #[test]
+    fn sizes_without_modification_time_are_never_fresh() {
+        let mut cache = SizeCache::default();
+        cache.insert(PathBuf::from("/proc"), 0, None, at(1000));
+        assert_eq!(cache.get(Path::new("/proc"), None, at(1000)), None);
+    }
+
+    #[test]
+    fn recalculating_replaces_the_size() {
+        let mut cache = SizeCache::default();
+        let path = Path::new("/usr");
+        cache.insert(path.to_owned(), 42, Some(at(100)), at(1000));
+        cache.insert(path.to_owned(), 43, Some(at(200)), at(2000));
+        assert_eq!(cache.get(path, Some(at(200)), at(2000)), Some(43));
+        assert_eq!(cache.entries.len(), 1);
+    }
+
+    #[test]
+    fn saving_keeps_the_most_recent_sizes() {
+        let mut cache = SizeCache::default();
+        for i in 0..5 {
+            cache.insert(PathBuf::from(format!("/{i}")), i, Some(at(1)), at(1000 + i));
+        }
+
+        let saved = cache.bounded(2);
+        assert_eq!(saved.entries.len(), 2);
+        assert_eq!(saved.get(Path::new("/4"), Some(at(1)), at(1004)), Some(4));
+        assert_eq!(saved.get(Path::new("/3"), Some(at(1)), at(1004)), Some(3));
+        assert_eq!(saved.get(Path::new("/2"), Some(at(1)), at(1004)), None);
+
+        assert_eq!(cache.bounded(10), cache);
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+/// Whether sizes are counted in powers of 1024 or of 1000.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SizeBase {
+    /// KiB, MiB, GiB: what most file systems and Windows Explorer count in.
+    #[default]
+    Binary,
+
+    /// KB, MB, GB: what drive makers and macOS Finder count in.
+    Decimal,
+}
+
+impl SizeBase {
+    pub const ALL: [Self; 2] = [Self::Binary, Self::Decimal];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Binary => "1024 (KiB, MiB, GiB)",
+            Self::Decimal => "1000 (KB, MB, GB)",
+        }
+    }
+
+    fn step(self) -> f64 {
+        match self {
+            Self::Binary => 1024.0,
+            Self::Decimal => 1000.0,
+        }
+    }
+
+    fn units(self) -> [&'static str; 3] {
+        match self {
+            Self::Binary => ["KiB", "MiB", "GiB"],
+            Self::Decimal => ["KB", "MB", "GB"],
+        }
+    }
+}
+
+/// Formats `size` in bytes like "1.5 KiB", counting in `base`. `None` is shown as "Unknown".
+pub fn format_size_with_base(size: Option<u64>, base: SizeBase) -> String {
+    let Some(bytes) = size else {
+        return "Unknown".to_owned();
+    };
+
+    let step = base.step();
+    if (bytes as f64) < step {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / step;
+    let units = base.units();
+    let mut unit = units[0];
+    for next in &units[1..] {
+        if value < step {
+            break;
+        }
+        value /= step;
+        unit = next;
+    }
+    format!("{value:.1} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fifteen_hundred_bytes_in_both_bases() {
+        assert_eq!(
+            format_size_with_base(Some(1500), SizeBase::Binary),
+            "1.5 KiB"
+        );
+        assert_eq!(
+            format_size_with_base(Some(1500), SizeBase::Decimal),
+            "1.5 KB"
+        );
+    }
+
+    #[test]
+    fn units_switch_at_the_base() {
+        let binary = |bytes| format_size_with_base(Some(bytes), SizeBase::Binary);
+        assert_eq!(binary(1023), "1023 B");
+        assert_eq!(binary(1024), "1.0 KiB");
+        assert_eq!(binary(1024 * 1024), "1.0 MiB");
+        assert_eq!(binary(3 * 1024 * 1024 * 1024), "3.0 GiB");
+        assert_eq!(binary(2048 * 1024 * 1024 * 1024), "2048.0 GiB");
+
+        let decimal = |bytes| format_size_with_base(Some(bytes), SizeBase::Decimal);
+        assert_eq!(decimal(999), "999 B");
+        assert_eq!(decimal(1000), "1.0 KB");
+        assert_eq!(decimal(1024), "1.0 KB");
+        assert_eq!(decimal(2_500_000), "2.5 MB");
+        assert_eq!(decimal(7_000_000_000), "7.0 GB");
+    }
+
+    #[test]
+    fn unknown_sizes() {
+        assert_eq!(format_size_with_base(None, SizeBase::Decimal), "Unknown");
+    }
+}
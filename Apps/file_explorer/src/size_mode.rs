@@ -0,0 +1,162 @@
+use std::collections::HashSet;
+use std::fs::Metadata;
+
+use serde::{Deserialize, Serialize};
+
+/// What a file's size is taken to be when adding up folders.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SizeMode {
+    /// How many bytes are in the file, like `du --apparent-size`.
+    #[default]
+    Apparent,
+
+    /// How much of the disk the file takes up, like `du`: whole blocks, and nothing for the
+    /// holes in sparse files.
+    DiskUsage,
+}
+
+impl SizeMode {
+    pub const ALL: [Self; 2] = [Self::Apparent, Self::DiskUsage];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Apparent => "Apparent size",
+            Self::DiskUsage => "Disk usage",
+        }
+    }
+
+    /// The size of a file with `meta`. Where the disk usage isn't known, it's the apparent size.
+    pub fn bytes(self, meta: &FileMeta) -> u64 {
+        match (self, meta.blocks) {
+            (Self::DiskUsage, Some(blocks)) => blocks * 512,
+            _ => meta.len,
+        }
+    }
+}
+
+/// What's needed of a file's [`Metadata`] to count its size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileMeta {
+    pub len: u64,
+    /// How many 512-byte blocks are allocated for the file, where the platform says.
+    pub blocks: Option<u64>,
+    /// The device and inode, which all hard links to a file share, where the platform says.
+    pub id: Option<(u64, u64)>,
+    /// How many hard links the file has.
+    pub links: u64,
+}
+
+impl FileMeta {
+    #[cfg(unix)]
+    pub fn new(meta: &Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        Self {
+            len: meta.len(),
+            blocks: Some(meta.blocks()),
+            id: Some((meta.dev(), meta.ino())),
+            links: meta.nlink(),
+        }
+    }
+
+    // The allocated size and file index need a handle to the file on Windows, which the
+    // standard library doesn't give metadata from yet.
+    #[cfg(not(unix))]
+    pub fn new(meta: &Metadata) -> Self {
+        Self {
+            len: meta.len(),
+            blocks: None,
+            id: None,
+            links: 1,
+        }
+    }
+}
+
+/// The hard-linked files counted so far, so a file with several links is only counted once.
+#[derive(Debug, Default)]
+pub struct HardLinks {
+    counted: HashSet<(u64, u64)>,
+    found: usize,
+}
+
+impl HardLinks {
+    /// Should the file with `meta` be counted, being the first of its links found?
+    /// Files with a single link always are.
+    pub fn count_once(&mut self, meta: &FileMeta) -> bool {
+        match meta.id {
+            Some(id) if meta.links > 1 => {
+                self.found += 1;
+                self.counted.insert(id)
+            }
+            _ => true,
+        }
+    }
+
+    /// How many files with more than one link have been found so far, counted or not.
+    ///
+    /// A folder with any of those under it has a size that depends on what else was counted.
+    pub fn found(&self) -> usize {
+        self.found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(len: u64, blocks: Option<u64>, inode: u64, links: u64) -> FileMeta {
+        FileMeta {
+            len,
+            blocks,
+            id: Some((1, inode)),
+            links,
+        }
+    }
+
+    #[test]
+    fn disk_usage_is_whole_blocks() {
+        let small = meta(10, Some(8), 1, 1);
+        assert_eq!(SizeMode::Apparent.bytes(&small), 10);
+        assert_eq!(SizeMode::DiskUsage.bytes(&small), 4096);
+
+        // Sparse: far more bytes than blocks.
+        let sparse = meta(1 << 30, Some(0), 2, 1);
+        assert_eq!(SizeMode::Apparent.bytes(&sparse), 1 << 30);
+        assert_eq!(SizeMode::DiskUsage.bytes(&sparse), 0);
+    }
+
+    #[test]
+    fn unknown_disk_usage_is_the_apparent_size() {
+        let unknown = meta(1234, None, 1, 1);
+        assert_eq!(SizeMode::DiskUsage.bytes(&unknown), 1234);
+    }
+
+    #[test]
+    fn hard_links_are_counted_once() {
+        let mut links = HardLinks::default();
+        let first = meta(100, Some(8), 7, 2);
+        assert!(links.count_once(&first));
+        assert!(!links.count_once(&first));
+        // The same inode on another device is another file.
+        assert!(links.count_once(&FileMeta {
+            id: Some((2, 7)),
+            ..first
+        }));
+        assert_eq!(links.found(), 3);
+    }
+
+    #[test]
+    fn single_links_are_always_counted() {
+        let mut links = HardLinks::default();
+        let single = meta(100, Some(8), 7, 1);
+        assert!(links.count_once(&single));
+        assert!(links.count_once(&single));
+        let unknown = FileMeta {
+            id: None,
+            links: 3,
+            ..single
+        };
+        assert!(links.count_once(&unknown));
+        assert!(links.count_once(&unknown));
+        assert_eq!(links.found(), 0);
+    }
+}
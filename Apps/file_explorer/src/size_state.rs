@@ -0,0 +1,250 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::size_mode::SizeMode;
+
+/// What [`crate::explorer::FileBrowserApp::calculate_size`] counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FolderSize {
+    pub bytes: u64,
+    /// Some of the folder couldn't be read, so there's more to it than [`Self::bytes`].
+    pub incomplete: bool,
+    /// What the files' sizes were taken to be.
+    pub mode: SizeMode,
+}
+
+/// How far a running size calculation has got, shared between it and the UI.
+///
+/// Each calculation has its own, so canceling one doesn't stop another.
+#[derive(Debug, Default)]
+pub struct SizeProgress {
+    entries: AtomicUsize,
+    bytes: AtomicU64,
+    current: Mutex<PathBuf>,
+    cancel: AtomicBool,
+}
+
+impl SizeProgress {
+    /// How many files and folders have been counted so far.
+    pub fn entries(&self) -> usize {
+        self.entries.load(Ordering::Relaxed)
+    }
+
+    /// The size of what has been counted so far.
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// The folder being counted right now.
+    pub fn current(&self) -> PathBuf {
+        self.current.lock().unwrap().clone()
+    }
+
+    /// Stops the calculation at the next entry it looks at.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    pub fn enter(&self, folder: &Path) {
+        folder.clone_into(&mut self.current.lock().unwrap());
+    }
+
+    pub fn count(&self, entries: usize, bytes: u64) {
+        self.entries.fetch_add(entries, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+/// Where the size of a folder is at.
+#[derive(Clone, Debug, Default)]
+pub enum SizeState {
+    #[default]
+    NotCalculated,
+    Calculating(Arc<SizeProgress>),
+    Canceled,
+    /// Couldn't be calculated, and why.
+    Failed(String),
+    Done(FolderSize),
+}
+
+impl SizeState {
+    /// The size, once it's been calculated.
+    pub fn bytes(&self) -> Option<u64> {
+        match self {
+            Self::Done(size) => Some(size.bytes),
+            _ => None,
+        }
+    }
+
+    /// Stops the calculation, if there is one, leaving the state as `after`.
+    pub fn cancel(&mut self, after: Self) {
+        if let Self::Calculating(progress) = self {
+            progress.cancel();
+            *self = after;
+        }
+    }
+
+    /// Is this the calculation `progress` belongs to?
+    pub fn is_calculating(&self, progress: &Arc<SizeProgress>) -> bool {
+        matches!(self, Self::Calculating(running) if Arc::ptr_eq(running, progress))
+    }
+
+    /// Starts a new calculation, canceling any that's running, and returns its progress for
+    /// the worker to count into.
+    pub fn start(&mut self) -> Arc<SizeProgress> {
+        self.cancel(Self::NotCalculated);
+        let progress = Arc::new(SizeProgress::default());
+        *self = Self::Calculating(progress.clone());
+        progress
+    }
+
+    /// Ends the calculation `progress` belongs to with its `result`, unless it's been canceled
+    /// or started again since: whoever did that already said what the state is now.
+    pub fn finish(&mut self, progress: &Arc<SizeProgress>, result: Result<FolderSize, String>) {
+        if !self.is_calculating(progress) {
+            return;
+        }
+        *self = match result {
+            Ok(size) => Self::Done(size),
+            Err(_) if progress.is_canceled() => Self::Canceled,
+            Err(err) => Self::Failed(err),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::thread;
+
+    /// A worker that counts `bytes` into `progress` a byte at a time, then finishes.
+    fn fake_worker(
+        state: Arc<Mutex<SizeState>>,
+        progress: Arc<SizeProgress>,
+        bytes: u64,
+    ) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            for _ in 0..bytes {
+                if progress.is_canceled() {
+                    state
+                        .lock()
+                        .unwrap()
+                        .finish(&progress, Err("Canceled".to_owned()));
+                    return;
+                }
+                progress.count(1, 1);
+                thread::yield_now();
+            }
+            let size = FolderSize {
+                bytes: progress.bytes(),
+                ..FolderSize::default()
+            };
+            state.lock().unwrap().finish(&progress, Ok(size));
+        })
+    }
+
+    #[test]
+    fn a_calculation_goes_from_started_to_done() {
+        let state = Arc::new(Mutex::new(SizeState::default()));
+        let progress = state.lock().unwrap().start();
+        assert!(state.lock().unwrap().is_calculating(&progress));
+
+        fake_worker(state.clone(), progress, 1000).join().unwrap();
+        assert_eq!(state.lock().unwrap().bytes(), Some(1000));
+    }
+
+    #[test]
+    fn errors_end_the_calculation() {
+        let mut state = SizeState::default();
+        let progress = state.start();
+        state.finish(&progress, Err("Permission denied".to_owned()));
+        assert!(matches!(&state, SizeState::Failed(err) if err == "Permission denied"));
+        assert_eq!(state.bytes(), None);
+
+        // A failed calculation can be started again.
+        let progress = state.start();
+        assert!(state.is_calculating(&progress));
+    }
+
+    #[test]
+    fn only_the_latest_calculation_finishes() {
+        let state = Arc::new(Mutex::new(SizeState::default()));
+        let first = state.lock().unwrap().start();
+        let second = state.lock().unwrap().start();
+        assert!(first.is_canceled());
+
+        // Even finishing with a size, the superseded one can't overwrite the new one.
+        state
+            .lock()
+            .unwrap()
+            .finish(&first, Ok(FolderSize::default()));
+        assert!(state.lock().unwrap().is_calculating(&second));
+
+        fake_worker(state.clone(), second, 10).join().unwrap();
+        assert_eq!(state.lock().unwrap().bytes(), Some(10));
+    }
+
+    #[test]
+    fn canceled_calculations_stay_canceled() {
+        let state = Arc::new(Mutex::new(SizeState::default()));
+        let progress = state.lock().unwrap().start();
+        let worker = fake_worker(state.clone(), progress, 1_000_000);
+        state.lock().unwrap().cancel(SizeState::Canceled);
+        worker.join().unwrap();
+        assert!(matches!(*state.lock().unwrap(), SizeState::Canceled));
+    }
+
+    #[test]
+    fn observers_only_see_whole_states() {
+        let state = Arc::new(Mutex::new(SizeState::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let observer = {
+            let (state, stop) = (state.clone(), stop.clone());
+            thread::spawn(move || {
+                let mut seen = Vec::new();
+                while !stop.load(Ordering::Relaxed) {
+                    let bytes = match &*state.lock().unwrap() {
+                        SizeState::NotCalculated => None,
+                        // Still counting, so the size can't be done yet, whatever the progress.
+                        SizeState::Calculating(progress) => {
+                            assert!(progress.bytes() <= 500);
+                            None
+                        }
+                        SizeState::Canceled => panic!("nobody canceled it"),
+                        SizeState::Failed(err) => panic!("nothing failed: {err}"),
+                        SizeState::Done(size) => Some(size.bytes),
+                    };
+                    if let Some(bytes) = bytes {
+                        seen.push(bytes);
+                    }
+                    thread::yield_now();
+                }
+                seen
+            })
+        };
+
+        for _ in 0..20 {
+            // Restarted halfway, like recalculating does.
+            let first = state.lock().unwrap().start();
+            let worker = fake_worker(state.clone(), first.clone(), 500);
+            while first.bytes() < 100 {
+                thread::yield_now();
+            }
+            let second = state.lock().unwrap().start();
+            worker.join().unwrap();
+            fake_worker(state.clone(), second, 500).join().unwrap();
+        }
+        stop.store(true, Ordering::Relaxed);
+
+        // A canceled count never passes for the size.
+        let seen = observer.join().unwrap();
+        assert!(seen.iter().all(|&bytes| bytes == 500), "{seen:?}");
+        assert_eq!(state.lock().unwrap().bytes(), Some(500));
+    }
+}
@@ -0,0 +1,250 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// An entry of the listing: an index into [`crate::explorer::FileBrowserApp::directories`]
+/// or [`crate::explorer::FileBrowserApp::files`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Entry {
+    Folder(usize),
+    File(usize),
+}
+
+/// Sorts `entries` by name (see [`compare_names`]), with all folders before all files if
+/// `folders_first` is set. Entries with the same name keep their order.
+///
+/// Cheap when `entries` is sorted already but for some appended at the end, as it is while
+/// a listing streams in.
+pub fn sort_entries<'a>(
+    entries: &mut [Entry],
+    name: impl Fn(Entry) -> &'a str,
+    folders_first: bool,
+) {
+    let group = |entry: Entry| folders_first && matches!(entry, Entry::File(_));
+    entries.sort_by(|&a, &b| {
+        group(a)
+            .cmp(&group(b))
+            .then_with(|| compare_names(name(a), name(b)))
+    });
+}
+
+/// Orders names the way people expect, rather than by their bytes:
+///
+/// - Case is ignored, and so are accents on Latin letters: "Ärende" goes with the A's.
+/// - Runs of digits compare as numbers: "img9" comes before "img10".
+/// - Leading dots are ignored: ".config" goes with the C's, right after "config".
+///
+/// Names that only differ in those ways still have a fixed order, so sorting is stable
+/// from one listing to the next.
+pub fn compare_names(a: &str, b: &str) -> Ordering {
+    let (a_bare, b_bare) = (a.trim_start_matches('.'), b.trim_start_matches('.'));
+    compare_natural(a_bare, b_bare)
+        .then_with(|| (a.len() - a_bare.len()).cmp(&(b.len() - b_bare.len())))
+        .then_with(|| a.cmp(b))
+}
+
+fn compare_natural(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let ordering = match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                compare_numbers(&digit_run(&mut a), &digit_run(&mut b))
+            }
+            (Some(x), Some(y)) => {
+                a.next();
+                b.next();
+                fold(x).cmp(&fold(y))
+            }
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+fn digit_run(chars: &mut Peekable<Chars<'_>>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
+/// Compares runs of digits by their value, however long they are. Leading zeros don't
+/// count, except to tell "01" after "1".
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let (a_value, b_value) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+    a_value
+        .len()
+        .cmp(&b_value.len())
+        .then_with(|| a_value.cmp(b_value))
+        .then_with(|| a.len().cmp(&b.len()))
+}
+
+/// `c` in lowercase and without an accent, for comparing.
+fn fold(c: char) -> char {
+    let c = c.to_lowercase().next().unwrap_or(c);
+    match c {
+        'à'..='å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'ď' | 'đ' => 'd',
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'ĥ' | 'ħ' => 'h',
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => 'i',
+        'ĵ' => 'j',
+        'ķ' => 'k',
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => 'l',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => 'o',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'ß' | 'ś' | 'ŝ' | 'ş' | 'š' => 's',
+        'ţ' | 'ť' | 'ŧ' => 't',
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'ŵ' => 'w',
+        'ý' | 'ÿ' | 'ŷ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<_> = names.iter().map(|&name| name.to_owned()).collect();
+        names.sort_by(|a, b| compare_names(a, b));
+        names
+    }
+
+    #[test]
+    fn case_is_ignored() {
+        assert_eq!(
+            sorted(&["b", "A", "a", "C", "B"]),
+            ["A", "a", "B", "b", "C"]
+        );
+        assert_eq!(
+            sorted(&["readme.md", "Cargo.toml", "src", "README.md"]),
+            ["Cargo.toml", "README.md", "readme.md", "src"]
+        );
+        assert_eq!(compare_names("ABC", "abd"), Ordering::Less);
+    }
+
+    #[test]
+    fn numbers_compare_by_value() {
+        assert_eq!(
+            sorted(&["img10", "img9", "img1", "img100", "img2"]),
+            ["img1", "img2", "img9", "img10", "img100"]
+        );
+        assert_eq!(
+            sorted(&["v1.10.0", "v1.9.2", "v1.9.10", "v1.9"]),
+            ["v1.9", "v1.9.2", "v1.9.10", "v1.10.0"]
+        );
+        assert_eq!(
+            sorted(&["a01", "a1", "a001", "a2"]),
+            ["a1", "a01", "a001", "a2"]
+        );
+        assert_eq!(sorted(&["10", "9", "x", "1x"]), ["1x", "9", "10", "x"]);
+        // Longer than any integer type.
+        assert_eq!(
+            compare_names("n123456789012345678901234567890", "n99"),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn leading_dots_are_ignored() {
+        assert_eq!(
+            sorted(&[
+                ".profile",
+                "bin",
+                ".config",
+                "config",
+                ".bashrc",
+                "Documents"
+            ]),
+            [
+                ".bashrc",
+                "bin",
+                "config",
+                ".config",
+                "Documents",
+                ".profile"
+            ]
+        );
+        assert_eq!(sorted(&["..a", ".a", "a"]), ["a", ".a", "..a"]);
+        assert_eq!(compare_names(".", ".."), Ordering::Less);
+    }
+
+    #[test]
+    fn accents_sort_with_their_letter() {
+        assert_eq!(
+            sorted(&["Zebra", "Ärende", "Bok", "apple", "Åsa", "école", "Eagle", "ñu", "Nube"]),
+            ["apple", "Ärende", "Åsa", "Bok", "Eagle", "école", "ñu", "Nube", "Zebra"]
+        );
+        assert_eq!(compare_names("Straße", "strasse"), Ordering::Less);
+        assert_eq!(compare_names("Łódź", "lodzia"), Ordering::Less);
+        assert_eq!(compare_names("Łódź", "locja"), Ordering::Greater);
+    }
+
+    #[test]
+    fn other_scripts_keep_their_order() {
+        assert_eq!(
+            sorted(&["日本", "Ωmega", "αλφα", "Ѐ", "zeta"]),
+            ["zeta", "αλφα", "Ωmega", "Ѐ", "日本"]
+        );
+    }
+
+    #[test]
+    fn the_order_is_total() {
+        let names = ["a", "A", "á", "Á", ".a", "a1", "a01", "A01", "b"];
+        for a in names {
+            assert_eq!(compare_names(a, a), Ordering::Equal);
+            for b in names {
+                assert_eq!(
+                    compare_names(a, b),
+                    compare_names(b, a).reverse(),
+                    "{a} {b}"
+                );
+                if a != b {
+                    assert_ne!(compare_names(a, b), Ordering::Equal, "{a} {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn folders_can_come_first_or_mix_in() {
+        let folders = ["src", "Docs"];
+        let files = ["build.rs", "README.md", "Cargo.toml"];
+        let name = |entry| match entry {
+            Entry::Folder(i) => folders[i],
+            Entry::File(i) => files[i],
+        };
+        let mut entries = vec![
+            Entry::Folder(0),
+            Entry::Folder(1),
+            Entry::File(0),
+            Entry::File(1),
+            Entry::File(2),
+        ];
+
+        sort_entries(&mut entries, name, true);
+        let names: Vec<_> = entries.iter().map(|&entry| name(entry)).collect();
+        assert_eq!(
+            names,
+            ["Docs", "src", "build.rs", "Cargo.toml", "README.md"]
+        );
+
+        sort_entries(&mut entries, name, false);
+        let names: Vec<_> = entries.iter().map(|&entry| name(entry)).collect();
+        assert_eq!(
+            names,
+            ["build.rs", "Cargo.toml", "Docs", "README.md", "src"]
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use egui::{Color32, Sense, Ui, Vec2};
+
+use crate::explorer::File;
+use crate::icons::{file_kind, FileKind, FALLBACK_ICON};
+use crate::size_format::{format_size_with_base, SizeBase};
+use crate::size_mode::{FileMeta, HardLinks, SizeMode};
+use crate::status::group_thousands;
+
+/// How wide the longest bar of the chart is.
+const BAR_WIDTH: f32 = 160.0;
+
+/// What the files of a folder are broken down into, by their extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category {
+    Images,
+    Audio,
+    Video,
+    Documents,
+    Archives,
+    Code,
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Self; 7] = [
+        Self::Images,
+        Self::Audio,
+        Self::Video,
+        Self::Documents,
+        Self::Archives,
+        Self::Code,
+        Self::Other,
+    ];
+
+    /// The category of the file called `name`, going by the same extensions as its icon.
+    pub fn of(name: &str) -> Self {
+        let extension = Path::new(name).extension().map(|e| e.to_string_lossy());
+        match extension.and_then(|extension| file_kind(&extension)) {
+            Some(FileKind::Image) => Self::Images,
+            Some(FileKind::Audio) => Self::Audio,
+            Some(FileKind::Video) => Self::Video,
+            Some(FileKind::Document | FileKind::Spreadsheet | FileKind::Presentation) => {
+                Self::Documents
+            }
+            Some(FileKind::Archive) => Self::Archives,
+            Some(FileKind::Code) => Self::Code,
+            Some(FileKind::Executable) | None => Self::Other,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Images => "Images",
+            Self::Audio => "Audio",
+            Self::Video => "Video",
+            Self::Documents => "Documents",
+            Self::Archives => "Archives",
+            Self::Code => "Code",
+            Self::Other => "Other",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Images => FileKind::Image.icon(),
+            Self::Audio => FileKind::Audio.icon(),
+            Self::Video => FileKind::Video.icon(),
+            Self::Documents => FileKind::Document.icon(),
+            Self::Archives => FileKind::Archive.icon(),
+            Self::Code => FileKind::Code.icon(),
+            Self::Other => FALLBACK_ICON,
+        }
+    }
+
+    /// The color of the category's bar in the chart.
+    pub fn color(self) -> Color32 {
+        match self {
+            Self::Images => Color32::LIGHT_BLUE,
+            Self::Audio => Color32::LIGHT_GREEN,
+            Self::Video => Color32::LIGHT_RED,
+            Self::Documents => Color32::LIGHT_YELLOW,
+            Self::Archives => Color32::KHAKI,
+            Self::Code => Color32::GOLD,
+            Self::Other => Color32::GRAY,
+        }
+    }
+}
+
+/// How many files of a category there are, and how much they take up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CategoryTotal {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// The files of a folder, added up by [`Category`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// In the order of [`Category::ALL`].
+    totals: [CategoryTotal; 7],
+    /// Files of unknown size, which are counted but add nothing to the sizes.
+    pub unknown_sizes: usize,
+    /// Some folders couldn't be read, so there's more to it than this.
+    pub incomplete: bool,
+}
+
+impl Statistics {
+    /// The statistics of `files`, without looking at the disk: what's in the listing is enough.
+    pub fn of<'a>(files: impl IntoIterator<Item = &'a File>) -> Self {
+        let mut statistics = Self::default();
+        for file in files {
+            statistics.add(&file.name, file.size);
+        }
+        statistics
+    }
+
+    pub fn add(&mut self, name: &str, size: Option<u64>) {
+        let total = &mut self.totals[Category::of(name) as usize];
+        total.files += 1;
+        match size {
+            Some(bytes) => total.bytes += bytes,
+            None => self.unknown_sizes += 1,
+        }
+    }
+
+    pub fn total(&self, category: Category) -> CategoryTotal {
+        self.totals[category as usize]
+    }
+
+    /// All the categories together.
+    pub fn sum(&self) -> CategoryTotal {
+        self.totals
+            .iter()
+            .fold(CategoryTotal::default(), |sum, total| CategoryTotal {
+                files: sum.files + total.files,
+                bytes: sum.bytes + total.bytes,
+            })
+    }
+}
+
+/// The [`Statistics`] of every file under `dir`, the way
+/// [`crate::explorer::FileBrowserApp::calculate_size`] counts them without following links:
+/// links count as themselves, and a file with several hard links once, as big as `mode` says.
+///
+/// `None` once `canceled` is set.
+pub fn folder_statistics(dir: &Path, mode: SizeMode, canceled: &AtomicBool) -> Option<Statistics> {
+    let mut statistics = Statistics::default();
+    let mut links = HardLinks::default();
+    let mut folders = vec![dir.to_owned()];
+    while let Some(folder) = folders.pop() {
+        let Ok(entries) = fs::read_dir(&folder) else {
+            statistics.incomplete = true;
+            continue;
+        };
+        for entry in entries {
+            if canceled.load(Ordering::Relaxed) {
+                return None;
+            }
+            let Ok(entry) = entry else {
+                statistics.incomplete = true;
+                continue;
+            };
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                folders.push(entry.path());
+                continue;
+            }
+            let meta = entry.metadata().ok().map(|meta| FileMeta::new(&meta));
+            if meta.is_some_and(|meta| !links.count_once(&meta)) {
+                continue;
+            }
+            let name = entry.file_name();
+            statistics.add(&name.to_string_lossy(), meta.map(|meta| mode.bytes(&meta)));
+        }
+    }
+    Some(statistics)
+}
+
+/// A bar for each category, as long as its share of the biggest one, with the exact numbers
+/// when hovered.
+pub fn chart_ui(ui: &mut Ui, statistics: &Statistics, base: SizeBase) {
+    let largest = Category::ALL
+        .iter()
+        .map(|&category| statistics.total(category).bytes)
+        .max()
+        .unwrap_or(0);
+    egui::Grid::new("statistics_chart")
+        .num_columns(3)
+        .show(ui, |ui| {
+            for category in Category::ALL {
+                let total = statistics.total(category);
+                ui.label(format!("{} {}", category.icon(), category.label()));
+
+                let height = ui.spacing().interact_size.y * 0.6;
+                let (rect, response) =
+                    ui.allocate_exact_size(Vec2::new(BAR_WIDTH, height), Sense::hover());
+                let mut bar = rect;
+                if largest > 0 {
+                    bar.set_width(rect.width() * (total.bytes as f64 / largest as f64) as f32);
+                } else {
+                    bar.set_width(0.0);
+                }
+                let color = if response.hovered() {
+                    category.color()
+                } else {
+                    category.color().gamma_multiply(0.8)
+                };
+                ui.painter()
+                    .rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+                ui.painter().rect_filled(bar, 2.0, color);
+                response.on_hover_text(format!(
+                    "{} files, {} bytes",
+                    group_thousands(total.files as u64),
+                    group_thousands(total.bytes)
+                ));
+
+                ui.label(format_size_with_base(Some(total.bytes), base));
+                ui.end_row();
+            }
+        });
+
+    let sum = statistics.sum();
+    ui.label(format!(
+        "{} files, total {}",
+        group_thousands(sum.files as u64),
+        format_size_with_base(Some(sum.bytes), base)
+    ));
+    if statistics.unknown_sizes > 0 {
+        ui.weak(format!(
+            "{} files of unknown size aren't in the sizes",
+            group_thousands(statistics.unknown_sizes as u64)
+        ));
+    }
+    if statistics.incomplete {
+        ui.label("⚠ Some folders couldn't be read");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    fn file(name: &str, size: Option<u64>) -> File {
+        File {
+            name: name.to_owned(),
+            size,
+            ..File::default()
+        }
+    }
+
+    #[test]
+    fn categories_go_by_the_icons_extensions() {
+        assert_eq!(Category::of("photo.JPG"), Category::Images);
+        assert_eq!(Category::of("song.opus"), Category::Audio);
+        assert_eq!(Category::of("clip.mkv"), Category::Video);
+        assert_eq!(Category::of("sheet.xlsx"), Category::Documents);
+        assert_eq!(Category::of("notes.md"), Category::Documents);
+        assert_eq!(Category::of("backup.tar.gz"), Category::Archives);
+        assert_eq!(Category::of("main.rs"), Category::Code);
+        assert_eq!(Category::of("setup.exe"), Category::Other);
+        assert_eq!(Category::of("Makefile"), Category::Other);
+        assert_eq!(Category::of(".bashrc"), Category::Other);
+    }
+
+    #[test]
+    fn files_are_added_up_by_category() {
+        let files = [
+            file("a.png", Some(100)),
+            file("b.jpg", Some(50)),
+            file("c.rs", Some(7)),
+            file("d", Some(1)),
+            file("e.mp4", None),
+        ];
+        let statistics = Statistics::of(&files);
+
+        assert_eq!(
+            statistics.total(Category::Images),
+            CategoryTotal {
+                files: 2,
+                bytes: 150
+            }
+        );
+        assert_eq!(
+            statistics.total(Category::Code),
+            CategoryTotal { files: 1, bytes: 7 }
+        );
+        assert_eq!(
+            statistics.total(Category::Other),
+            CategoryTotal { files: 1, bytes: 1 }
+        );
+        // Counted, but of no known size.
+        assert_eq!(
+            statistics.total(Category::Video),
+            CategoryTotal { files: 1, bytes: 0 }
+        );
+        assert_eq!(statistics.total(Category::Audio), CategoryTotal::default());
+        assert_eq!(statistics.unknown_sizes, 1);
+        assert_eq!(
+            statistics.sum(),
+            CategoryTotal {
+                files: 5,
+                bytes: 158
+            }
+        );
+    }
+
+    #[test]
+    fn nothing_adds_up_to_nothing() {
+        let statistics = Statistics::of(&[]);
+        assert_eq!(statistics.sum(), CategoryTotal::default());
+        assert_eq!(statistics.unknown_sizes, 0);
+    }
+
+    #[test]
+    fn subfolders_are_counted() {
+        let dir = TempDir::new("statistics");
+        fs::create_dir_all(dir.join("photos/2024")).unwrap();
+        fs::write(dir.join("readme.md"), [0; 10]).unwrap();
+        fs::write(dir.join("photos/a.png"), [0; 100]).unwrap();
+        fs::write(dir.join("photos/2024/b.png"), [0; 200]).unwrap();
+
+        let statistics =
+            folder_statistics(&dir, SizeMode::Apparent, &AtomicBool::new(false)).unwrap();
+        assert_eq!(
+            statistics.total(Category::Images),
+            CategoryTotal {
+                files: 2,
+                bytes: 300
+            }
+        );
+        assert_eq!(
+            statistics.total(Category::Documents),
+            CategoryTotal {
+                files: 1,
+                bytes: 10
+            }
+        );
+        assert!(!statistics.incomplete);
+
+        assert_eq!(
+            folder_statistics(&dir, SizeMode::Apparent, &AtomicBool::new(true)),
+            None
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_links_are_counted_once() {
+        let dir = TempDir::new("statistics_links");
+        fs::write(dir.join("song.mp3"), [0; 100]).unwrap();
+        fs::hard_link(dir.join("song.mp3"), dir.join("same.mp3")).unwrap();
+
+        let statistics =
+            folder_statistics(&dir, SizeMode::Apparent, &AtomicBool::new(false)).unwrap();
+        assert_eq!(
+            statistics.total(Category::Audio),
+            CategoryTotal {
+                files: 1,
+                bytes: 100
+            }
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use crate::explorer::{File, Folder};
+
+/// How many entries there are and how much they take up, for the status bar.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub folders: usize,
+    pub files: usize,
+
+    /// Files of unknown size, and folders whose size hasn't been calculated, don't count.
+    pub size: u64,
+}
+
+impl Summary {
+    pub fn of<'a>(
+        folders: impl IntoIterator<Item = &'a Folder>,
+        files: impl IntoIterator<Item = &'a File>,
+    ) -> Self {
+        let mut summary = Self::default();
+        for folder in folders {
+            summary.folders += 1;
+            summary.size += folder.size.lock().unwrap().bytes().unwrap_or(0);
+        }
+        for file in files {
+            summary.files += 1;
+            summary.size += file.size.unwrap_or(0);
+        }
+        summary
+    }
+}
+
+/// `n` with commas between groups of three digits, like "1,337".
+pub fn group_thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::size_state::{FolderSize, SizeState};
+
+    #[test]
+    fn thousands_are_grouped() {
+        assert_eq!(group_thousands(0), "0");
+        assert_eq!(group_thousands(999), "999");
+        assert_eq!(group_thousands(1337), "1,337");
+        assert_eq!(group_thousands(100_000), "100,000");
+        assert_eq!(group_thousands(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn sums_known_sizes_only() {
+        let file = |size| File {
+            size,
+            ..File::default()
+        };
+        let files = [file(Some(100)), file(None), file(Some(23)), file(Some(0))];
+
+        let calculated = Folder::default();
+        *calculated.size.lock().unwrap() = SizeState::Done(FolderSize {
+            bytes: 1000,
+            ..FolderSize::default()
+        });
+        let folders = [Folder::default(), calculated];
+
+        assert_eq!(
+            Summary::of(&folders, &files),
+            Summary {
+                folders: 2,
+                files: 4,
+                size: 1123,
+            }
+        );
+        let no_folders: [Folder; 0] = [];
+        let no_files: [File; 0] = [];
+        assert_eq!(Summary::of(&no_folders, &files).size, 123);
+        assert_eq!(Summary::of(&no_folders, &no_files), Summary::default());
+    }
+}
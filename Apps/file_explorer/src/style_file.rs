@@ -0,0 +1,213 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
+
+use egui::style::NumberFormatter;
+use egui::{Style, Visuals};
+
+use crate::explorer::APP_ID;
+
+/// How long the style has to stay the same before it's saved, so dragging a slider in the
+/// settings doesn't write the file every frame.
+pub const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Where the style is kept between sessions, next to eframe's own files.
+pub fn style_path() -> Option<PathBuf> {
+    eframe::storage_dir(APP_ID).map(|dir| dir.join("style.json"))
+}
+
+/// The style the app starts with, and goes back to on "Reset to default".
+pub fn default_style() -> Style {
+    Style {
+        visuals: Visuals::light(),
+        number_formatter: number_formatter(),
+        ..Style::default()
+    }
+}
+
+/// The number formatter of every style made or read here.
+///
+/// It isn't saved, and styles only compare equal with the very same one, so they share this.
+fn number_formatter() -> NumberFormatter {
+    static FORMATTER: OnceLock<NumberFormatter> = OnceLock::new();
+    FORMATTER
+        .get_or_init(|| Style::default().number_formatter)
+        .clone()
+}
+
+fn parse_style(json: &str) -> serde_json::Result<Style> {
+    let style: Style = serde_json::from_str(json)?;
+    Ok(Style {
+        number_formatter: number_formatter(),
+        ..style
+    })
+}
+
+pub fn write_style(path: &Path, style: &Style) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(style).map_err(io::Error::other)?;
+    fs::write(path, json)
+}
+
+/// Keeps the style in use and the saved one in step, without reading, applying or
+/// writing anything that didn't change.
+#[derive(Default)]
+pub struct StyleFile {
+    path: Option<PathBuf>,
+
+    /// The style as it was last read or written.
+    known: Option<Style>,
+
+    /// When the file was changed as of the last time it was read or written.
+    modified: Option<SystemTime>,
+
+    /// A style different from [`Self::known`], and since when it's been in use.
+    pending: Option<(Style, Instant)>,
+}
+
+impl StyleFile {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            ..Default::default()
+        }
+    }
+
+    /// The saved style, if the file changed since it was last read and has a different style.
+    ///
+    /// A missing file is ignored. One that can't be read or is broken is an error, once: the
+    /// style stays as it is until the file changes again.
+    pub fn reload(&mut self) -> Result<Option<Style>, String> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(None);
+        };
+        let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        if modified.is_none() || modified == self.modified {
+            return Ok(None);
+        }
+        self.modified = modified;
+        let error = |err: &dyn std::fmt::Display| {
+            format!("Couldn't load the style from {}: {err}", path.display())
+        };
+        let json = fs::read_to_string(path).map_err(|err| error(&err))?;
+        let style = parse_style(&json).map_err(|err| error(&err))?;
+        if self.known.as_ref() == Some(&style) {
+            return Ok(None);
+        }
+        self.known = Some(style.clone());
+        Ok(Some(style))
+    }
+
+    /// Notes `style` as the one in use, saving it once it's been the same for [`SAVE_DELAY`].
+    pub fn track(&mut self, style: &Style, now: Instant) -> Result<(), String> {
+        if self.known.as_ref() == Some(style) {
+            self.pending = None;
+            return Ok(());
+        }
+        match &self.pending {
+            Some((pending, since)) if pending == style => {
+                if now.duration_since(*since) < SAVE_DELAY {
+                    return Ok(());
+                }
+            }
+            _ => {
+                self.pending = Some((style.clone(), now));
+                return Ok(());
+            }
+        }
+
+        self.pending = None;
+        self.known = Some(style.clone());
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        write_style(path, style)
+            .map_err(|err| format!("Couldn't save the style to {}: {err}", path.display()))?;
+        // What was just written doesn't need reading back.
+        self.modified = fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        Ok(())
+    }
+
+    /// Is a changed style waiting to be saved?
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    /// The style saved at `path`, or `None` if there's none or it can't be read.
+    fn read_style(path: &Path) -> Option<Style> {
+        let json = fs::read_to_string(path).ok()?;
+        parse_style(&json).ok()
+    }
+
+    #[test]
+    fn styles_round_trip() {
+        let dir = TempDir::new("style_round_trip");
+        let path = dir.join("nested").join("style.json");
+        let mut style = default_style();
+        style.spacing.item_spacing.x = 13.0;
+        style.visuals.window_rounding = 2.0.into();
+
+        write_style(&path, &style).unwrap();
+        assert_eq!(read_style(&path), Some(style));
+    }
+
+    #[test]
+    fn missing_files_are_ignored_and_broken_ones_reported_once() {
+        let dir = TempDir::new("style_broken");
+        let path = dir.join("style.json");
+        assert_eq!(read_style(&path), None);
+        assert_eq!(StyleFile::new(Some(path.clone())).reload(), Ok(None));
+
+        fs::write(&path, "{ not json").unwrap();
+        assert_eq!(read_style(&path), None);
+        let mut file = StyleFile::new(Some(path.clone()));
+        let error = file.reload().unwrap_err();
+        assert!(error.contains("style.json"), "{error}");
+        assert_eq!(file.reload(), Ok(None));
+    }
+
+    #[test]
+    fn unchanged_styles_are_only_read_once() {
+        let dir = TempDir::new("style_reload");
+        let path = dir.join("style.json");
+        write_style(&path, &default_style()).unwrap();
+
+        let mut file = StyleFile::new(Some(path.clone()));
+        assert_eq!(file.reload(), Ok(Some(default_style())));
+        assert_eq!(file.reload(), Ok(None));
+    }
+
+    #[test]
+    fn saves_wait_until_the_style_settles() {
+        let dir = TempDir::new("style_debounce");
+        let path = dir.join("style.json");
+        let mut file = StyleFile::new(Some(path.clone()));
+        let start = Instant::now();
+        let mut style = default_style();
+
+        style.spacing.item_spacing.x = 1.0;
+        file.track(&style, start).unwrap();
+        // Still changing, like a slider being dragged.
+        style.spacing.item_spacing.x = 2.0;
+        file.track(&style, start + SAVE_DELAY / 2).unwrap();
+        file.track(&style, start + SAVE_DELAY).unwrap();
+        assert!(file.is_pending());
+        assert!(!path.exists());
+
+        file.track(&style, start + SAVE_DELAY * 2).unwrap();
+        assert!(!file.is_pending());
+        assert_eq!(read_style(&path), Some(style.clone()));
+        // What was saved isn't applied again.
+        assert_eq!(file.reload(), Ok(None));
+    }
+}
@@ -0,0 +1,356 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use egui::{ColorImage, Context, TextureHandle, TextureOptions};
+
+/// Width and height of a thumbnail, in pixels.
+pub const THUMBNAIL_SIZE: u32 = 32;
+
+/// How much the thumbnails on disk may take up before the least recently used are removed.
+pub const DEFAULT_CACHE_CAP: u64 = 20 * 1024 * 1024;
+
+/// Prune the disk cache after this many new thumbnails.
+const PRUNE_EVERY: usize = 64;
+
+/// Can a file with this extension be turned into a thumbnail?
+pub fn is_image_extension(extension: &str) -> bool {
+    matches!(
+        extension.to_lowercase().as_str(),
+        "png" | "jpg" | "jpeg" | "gif" | "bmp"
+    )
+}
+
+/// Thumbnails saved on disk, so revisiting a folder doesn't decode its images again.
+///
+/// There's one entry per image path; it remembers the image's modification time,
+/// so an entry for an image that changed since is a miss (and gets replaced).
+pub struct DiskCache {
+    dir: PathBuf,
+    cap: u64,
+    writes: AtomicUsize,
+}
+
+impl DiskCache {
+    pub fn new(dir: PathBuf, cap: u64) -> Self {
+        Self {
+            dir,
+            cap,
+            writes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Where the thumbnail of `path` goes.
+    pub fn entry_path(&self, path: &Path) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        self.dir.join(format!("{:016x}.thumb", hasher.finish()))
+    }
+
+    /// The thumbnail of `path` as it was when last modified at `modified`, if there is one.
+    ///
+    /// Marks the entry as used, for [`Self::prune`].
+    pub fn load(&self, path: &Path, modified: SystemTime) -> Option<ColorImage> {
+        let entry = self.entry_path(path);
+        let image = decode_entry(&fs::read(&entry).ok()?, modified)?;
+        if let Ok(file) = fs::File::options().write(true).open(&entry) {
+            file.set_modified(SystemTime::now()).ok();
+        }
+        Some(image)
+    }
+
+    pub fn store(&self, path: &Path, modified: SystemTime, image: &ColorImage) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.entry_path(path), encode_entry(modified, image))?;
+        if self.writes.fetch_add(1, Ordering::Relaxed) % PRUNE_EVERY == 0 {
+            self.prune()?;
+        }
+        Ok(())
+    }
+
+    /// Removes the least recently used thumbnails until the cache fits its cap.
+    pub fn prune(&self) -> io::Result<()> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            let last_used = meta.modified().unwrap_or(UNIX_EPOCH);
+            entries.push((entry.path(), meta.len(), last_used));
+        }
+        for path in entries_to_prune(entries, self.cap) {
+            fs::remove_file(path).ok();
+        }
+        Ok(())
+    }
+}
+
+/// Which of the cache `entries` (path, size and last use) to remove to get down to `cap` bytes:
+/// the least recently used ones.
+pub fn entries_to_prune(mut entries: Vec<(PathBuf, u64, SystemTime)>, cap: u64) -> Vec<PathBuf> {
+    let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+    entries.sort_by_key(|(_, _, last_used)| *last_used);
+
+    let mut pruned = Vec::new();
+    for (path, size, _) in entries {
+        if total <= cap {
+            break;
+        }
+        total -= size;
+        pruned.push(path);
+    }
+    pruned
+}
+
+/// A cache entry: the image's modification time, the size, and the RGBA pixels.
+fn encode_entry(modified: SystemTime, image: &ColorImage) -> Vec<u8> {
+    let modified = modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let mut bytes = Vec::with_capacity(20 + image.pixels.len() * 4);
+    bytes.extend_from_slice(&modified.as_secs().to_le_bytes());
+    bytes.extend_from_slice(&modified.subsec_nanos().to_le_bytes());
+    bytes.extend_from_slice(&(image.size[0] as u32).to_le_bytes());
+    bytes.extend_from_slice(&(image.size[1] as u32).to_le_bytes());
+    for pixel in &image.pixels {
+        bytes.extend_from_slice(&pixel.to_srgba_unmultiplied());
+    }
+    bytes
+}
+
+/// The image in a cache entry, unless it's for a different modification time or corrupt.
+fn decode_entry(bytes: &[u8], modified: SystemTime) -> Option<ColorImage> {
+    let u32_at = |i: usize| Some(u32::from_le_bytes(bytes.get(i..i + 4)?.try_into().ok()?));
+    let secs = u64::from_le_bytes(bytes.get(0..8)?.try_into().ok()?);
+    let stored = UNIX_EPOCH + Duration::new(secs, u32_at(8)?);
+    if stored != modified {
+        return None;
+    }
+
+    let size = [u32_at(12)? as usize, u32_at(16)? as usize];
+    let pixels = bytes.get(20..)?;
+    (pixels.len() == size[0] * size[1] * 4)
+        .then(|| ColorImage::from_rgba_unmultiplied(size, pixels))
+}
+
+/// Decodes the image at `path` and shrinks it to fit [`THUMBNAIL_SIZE`].
+fn make_thumbnail(path: &Path) -> Option<ColorImage> {
+    let image = image::open(path)
+        .ok()?
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Some(ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
+
+/// The thumbnail of `path`, from the disk cache if it's there and up to date.
+fn load_thumbnail(
+    cache: Option<&DiskCache>,
+    path: &Path,
+    modified: Option<SystemTime>,
+) -> Option<ColorImage> {
+    // Without a modification time there's no telling whether a cached thumbnail is stale.
+    let cache = cache.zip(modified);
+    if let Some((cache, modified)) = cache {
+        if let Some(image) = cache.load(path, modified) {
+            return Some(image);
+        }
+    }
+
+    let image = make_thumbnail(path)?;
+    if let Some((cache, modified)) = cache {
+        cache.store(path, modified, &image).ok(); // Only costs a decode next time.
+    }
+    Some(image)
+}
+
+struct Request {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+enum Thumbnail {
+    Loading,
+    Ready(TextureHandle),
+
+    /// Not an image we can decode after all; keep showing the icon.
+    Failed,
+}
+
+/// Thumbnails of the images in the listing, made in the background.
+///
+/// Requests are served newest first: rows are only built while they're visible, so the
+/// rows on screen now come before the ones that were scrolled past.
+pub struct Thumbnails {
+    cache: Option<Arc<DiskCache>>,
+    thumbnails: HashMap<PathBuf, (Option<SystemTime>, Thumbnail)>,
+    queue: Arc<Mutex<Vec<Request>>>,
+    tx: Sender<(PathBuf, Option<ColorImage>)>,
+    rx: Receiver<(PathBuf, Option<ColorImage>)>,
+}
+
+impl Thumbnails {
+    /// Caches thumbnails on disk in `cache_dir`, if given.
+    pub fn new(cache_dir: Option<PathBuf>) -> Self {
+        let cache = cache_dir.map(|dir| Arc::new(DiskCache::new(dir, DEFAULT_CACHE_CAP)));
+        if let Some(cache) = cache.clone() {
+            // Left over from the previous run.
+            std::thread::spawn(move || cache.prune().ok());
+        }
+        let (tx, rx) = channel();
+        Self {
+            cache,
+            thumbnails: HashMap::new(),
+            queue: Arc::new(Mutex::new(Vec::new())),
+            tx,
+            rx,
+        }
+    }
+
+    /// The thumbnail of the image at `path`, last modified at `modified`, once it's loaded.
+    ///
+    /// Starts loading it if it isn't yet.
+    pub fn get(&mut self, path: &Path, modified: Option<SystemTime>) -> Option<&TextureHandle> {
+        let up_to_date = matches!(self.thumbnails.get(path), Some((m, _)) if *m == modified);
+        if !up_to_date {
+            self.request(path, modified);
+        }
+        match self.thumbnails.get(path) {
+            Some((_, Thumbnail::Ready(texture))) => Some(texture),
+            _ => None,
+        }
+    }
+
+    fn request(&mut self, path: &Path, modified: Option<SystemTime>) {
+        self.thumbnails
+            .insert(path.to_owned(), (modified, Thumbnail::Loading));
+        self.queue.lock().unwrap().push(Request {
+            path: path.to_owned(),
+            modified,
+        });
+
+        let queue = self.queue.clone();
+        let cache = self.cache.clone();
+        let tx = self.tx.clone();
+        rayon::spawn(move || {
+            // Not necessarily the request pushed above: always the newest one.
+            let Some(request) = queue.lock().unwrap().pop() else {
+                return; // Cleared by `Self::clear`.
+            };
+            let image = load_thumbnail(cache.as_deref(), &request.path, request.modified);
+            tx.send((request.path, image)).ok();
+        });
+    }
+
+    /// Is anything still loading?
+    pub fn is_loading(&self) -> bool {
+        self.thumbnails
+            .values()
+            .any(|(_, thumbnail)| matches!(thumbnail, Thumbnail::Loading))
+    }
+
+    /// Turns the thumbnails made since the last call into textures.
+    pub fn poll(&mut self, ctx: &Context) {
+        while let Ok((path, image)) = self.rx.try_recv() {
+            let Some((_, thumbnail)) = self.thumbnails.get_mut(&path) else {
+                continue; // Cleared in the meantime.
+            };
+            *thumbnail = match image {
+                Some(image) => {
+                    let name = format!("thumbnail:{}", path.display());
+                    Thumbnail::Ready(ctx.load_texture(name, image, TextureOptions::LINEAR))
+                }
+                None => Thumbnail::Failed,
+            };
+        }
+    }
+
+    /// Forgets the thumbnails in memory and what's waiting to be loaded, e.g. when leaving a folder.
+    pub fn clear(&mut self) {
+        self.queue.lock().unwrap().clear();
+        self.thumbnails.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+    use egui::Color32;
+
+    fn image() -> ColorImage {
+        ColorImage::new([2, 3], Color32::from_rgb(10, 20, 30))
+    }
+
+    #[test]
+    fn entries_are_invalidated_by_a_new_modification_time() {
+        let modified = UNIX_EPOCH + Duration::new(1_700_000_000, 123);
+        let entry = encode_entry(modified, &image());
+
+        assert_eq!(decode_entry(&entry, modified), Some(image()));
+        assert_eq!(
+            decode_entry(&entry, modified + Duration::from_nanos(1)),
+            None
+        );
+        assert_eq!(decode_entry(&entry[..entry.len() - 1], modified), None);
+        assert_eq!(decode_entry(&[], modified), None);
+    }
+
+    #[test]
+    fn entries_are_keyed_by_path() {
+        let cache = DiskCache::new(PathBuf::from("/cache"), DEFAULT_CACHE_CAP);
+        let a = cache.entry_path(Path::new("/photos/a.jpg"));
+        assert_eq!(a, cache.entry_path(Path::new("/photos/a.jpg")));
+        assert_ne!(a, cache.entry_path(Path::new("/photos/b.jpg")));
+        assert!(a.starts_with("/cache"));
+    }
+
+    #[test]
+    fn cache_round_trip_on_disk() {
+        let dir = TempDir::new("thumbnail_cache");
+        let cache = DiskCache::new(dir.to_path_buf(), DEFAULT_CACHE_CAP);
+        let path = Path::new("/photos/a.jpg");
+        let modified = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        assert_eq!(cache.load(path, modified), None);
+        cache.store(path, modified, &image()).unwrap();
+        assert_eq!(cache.load(path, modified), Some(image()));
+
+        // The image was edited: the old thumbnail is stale.
+        let edited = modified + Duration::from_secs(60);
+        assert_eq!(cache.load(path, edited), None);
+    }
+
+    #[test]
+    fn prunes_least_recently_used_first() {
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+        let entries = vec![
+            (PathBuf::from("new"), 40, at(300)),
+            (PathBuf::from("oldest"), 40, at(100)),
+            (PathBuf::from("old"), 40, at(200)),
+        ];
+
+        assert!(entries_to_prune(entries.clone(), 120).is_empty());
+        assert_eq!(
+            entries_to_prune(entries.clone(), 100),
+            [PathBuf::from("oldest")]
+        );
+        assert_eq!(
+            entries_to_prune(entries.clone(), 40),
+            [PathBuf::from("oldest"), PathBuf::from("old")]
+        );
+        assert_eq!(entries_to_prune(entries, 0).len(), 3);
+    }
+
+    #[test]
+    fn only_known_image_extensions() {
+        assert!(is_image_extension("PNG"));
+        assert!(is_image_extension("jpeg"));
+        assert!(!is_image_extension("txt"));
+        assert!(!is_image_extension(""));
+    }
+}
@@ -0,0 +1,136 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Shown instead of a time that couldn't be read.
+pub const UNKNOWN_TIME: &str = "—";
+
+/// Formats `time` relative to `now` ("5 min ago"), or as a date once it is a week old.
+///
+/// `None` (the platform or file system didn't tell us) is shown as [`UNKNOWN_TIME`].
+pub fn format_time(time: Option<SystemTime>, now: SystemTime) -> String {
+    let Some(time) = time else {
+        return UNKNOWN_TIME.to_owned();
+    };
+
+    match now.duration_since(time) {
+        Ok(age) if age < Duration::from_secs(7 * 24 * 60 * 60) => format_age(age),
+        _ => format_date(time), // Old, or in the future because of clock skew
+    }
+}
+
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    if secs < 60 {
+        format!("{secs} s ago")
+    } else if secs < 60 * 60 {
+        format!("{} min ago", secs / 60)
+    } else if secs < 24 * 60 * 60 {
+        format!("{} h ago", secs / (60 * 60))
+    } else {
+        format!("{} d ago", secs / (24 * 60 * 60))
+    }
+}
+
+/// `time` as a UTC date like "2024-08-31".
+pub fn format_date(time: SystemTime) -> String {
+    let days = match time.duration_since(UNIX_EPOCH) {
+        Ok(since_epoch) => (since_epoch.as_secs() / (24 * 60 * 60)) as i64,
+        Err(before_epoch) => -(before_epoch.duration().as_secs().div_ceil(24 * 60 * 60) as i64),
+    };
+    let (year, month, day) = civil_from_days(days);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) date.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Converts a (year, month, day) date to days since 1970-01-01, the other way around from
+/// [`civil_from_days`].
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let day_of_year = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ago(secs: u64) -> String {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        format_time(Some(now - Duration::from_secs(secs)), now)
+    }
+
+    #[test]
+    fn relative_time_boundaries() {
+        assert_eq!(ago(0), "0 s ago");
+        assert_eq!(ago(59), "59 s ago");
+        assert_eq!(ago(61), "1 min ago");
+        assert_eq!(ago(59 * 60 + 59), "59 min ago");
+        assert_eq!(ago(23 * 60 * 60), "23 h ago");
+        assert_eq!(ago(25 * 60 * 60), "1 d ago");
+    }
+
+    #[test]
+    fn old_and_future_times_are_dates() {
+        assert_eq!(ago(30 * 24 * 60 * 60), "2023-10-15");
+
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(
+            format_time(Some(now + Duration::from_secs(60)), now),
+            "2023-11-14"
+        );
+    }
+
+    #[test]
+    fn unknown_time_is_a_dash() {
+        assert_eq!(format_time(None, SystemTime::now()), UNKNOWN_TIME);
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(format_date(UNIX_EPOCH), "1970-01-01");
+        assert_eq!(
+            format_date(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29"
+        );
+        assert_eq!(
+            format_date(UNIX_EPOCH - Duration::from_secs(1)),
+            "1969-12-31"
+        );
+    }
+
+    #[test]
+    fn days_and_dates_convert_both_ways() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        for days in [-800_000, -1, 0, 59, 11016, 19966, 2_000_000] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(
+                days_from_civil(year, month, day),
+                days,
+                "{year}-{month}-{day}"
+            );
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use egui::{Align2, Color32, Context, RichText, Vec2};
+
+/// How long a toast that isn't an error stays up.
+pub const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// How many toasts are shown at once; older ones make way for new ones.
+pub const MAX_TOASTS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    /// Stays up until it's dismissed, since it says something didn't happen.
+    Error,
+}
+
+impl Severity {
+    fn icon(self) -> &'static str {
+        match self {
+            Self::Warning => "⚠",
+            Self::Error => "✖",
+        }
+    }
+
+    fn color(self) -> Color32 {
+        match self {
+            Self::Warning => Color32::YELLOW,
+            Self::Error => Color32::RED,
+        }
+    }
+}
+
+/// A notice shown in the corner of the window for a while, without getting in the way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Toast {
+    pub severity: Severity,
+    pub message: String,
+    /// More than fits in a toast, like the error of every item that failed, to be copied.
+    pub details: Option<String>,
+    /// When it was shown, or last shown again.
+    pub created_at: Instant,
+}
+
+/// The toasts being shown, oldest first.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    toasts: VecDeque<Toast>,
+}
+
+impl Toasts {
+    /// Shows `message` as of `now`, or shows it for longer if it's already up, so an error
+    /// that happens over and over doesn't fill the corner.
+    pub fn push(
+        &mut self,
+        severity: Severity,
+        message: impl Into<String>,
+        details: Option<String>,
+        now: Instant,
+    ) {
+        let message = message.into();
+        self.toasts
+            .retain(|toast| toast.severity != severity || toast.message != message);
+        self.toasts.push_back(Toast {
+            severity,
+            message,
+            details,
+            created_at: now,
+        });
+        while self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Severity::Error, message, None, Instant::now());
+    }
+
+    /// An error about several things at once, each of which is in `details`.
+    pub fn error_with_details(&mut self, message: impl Into<String>, details: String) {
+        self.push(Severity::Error, message, Some(details), Instant::now());
+    }
+
+    pub fn warning(&mut self, message: impl Into<String>) {
+        self.push(Severity::Warning, message, None, Instant::now());
+    }
+
+    /// Takes down the toasts that have been up for [`TOAST_DURATION`] by `now`. Errors stay.
+    pub fn expire(&mut self, now: Instant) {
+        self.toasts.retain(|toast| {
+            toast.severity == Severity::Error
+                || now.duration_since(toast.created_at) < TOAST_DURATION
+        });
+    }
+
+    /// Takes down the toast at `index` of [`Self::iter`].
+    pub fn dismiss(&mut self, index: usize) {
+        self.toasts.remove(index);
+    }
+
+    /// How long until the next toast is taken down by [`Self::expire`], if any will be.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.toasts
+            .iter()
+            .filter(|toast| toast.severity != Severity::Error)
+            .map(|toast| TOAST_DURATION.saturating_sub(now.duration_since(toast.created_at)))
+            .min()
+    }
+
+    /// Shows the toasts stacked in the bottom right corner, newest at the bottom.
+    pub fn ui(&mut self, ctx: &Context) {
+        let now = Instant::now();
+        self.expire(now);
+        if let Some(remaining) = self.remaining(now) {
+            ctx.request_repaint_after(remaining);
+        }
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        let mut dismissed = None;
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(Align2::RIGHT_BOTTOM, Vec2::new(-8.0, -32.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.set_max_width(320.0);
+                for (index, toast) in self.toasts.iter().enumerate() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            let icon =
+                                RichText::new(toast.severity.icon()).color(toast.severity.color());
+                            ui.label(icon);
+                            ui.label(&toast.message);
+                            if ui.small_button("🗙").on_hover_text("Dismiss").clicked() {
+                                dismissed = Some(index);
+                            }
+                        });
+                        if let Some(details) = &toast.details {
+                            if ui.small_button("📋 Copy details").clicked() {
+                                ui.output_mut(|o| o.copied_text = details.clone());
+                            }
+                        }
+                    });
+                }
+            });
+        if let Some(index) = dismissed {
+            self.dismiss(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(toasts: &Toasts) -> Vec<&str> {
+        toasts
+            .toasts
+            .iter()
+            .map(|toast| toast.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn toasts_time_out_but_errors_stay() {
+        let start = Instant::now();
+        let mut toasts = Toasts::default();
+        toasts.push(Severity::Warning, "copied", None, start);
+        toasts.push(Severity::Error, "couldn't delete", None, start);
+        toasts.push(
+            Severity::Warning,
+            "read-only",
+            None,
+            start + TOAST_DURATION / 2,
+        );
+
+        toasts.expire(start + TOAST_DURATION / 2);
+        assert_eq!(
+            messages(&toasts),
+            ["copied", "couldn't delete", "read-only"]
+        );
+        toasts.expire(start + TOAST_DURATION);
+        assert_eq!(messages(&toasts), ["couldn't delete", "read-only"]);
+        toasts.expire(start + TOAST_DURATION * 10);
+        assert_eq!(messages(&toasts), ["couldn't delete"]);
+
+        toasts.dismiss(0);
+        assert!(toasts.toasts.is_empty());
+    }
+
+    #[test]
+    fn the_next_timeout_is_known() {
+        let start = Instant::now();
+        let mut toasts = Toasts::default();
+        assert_eq!(toasts.remaining(start), None);
+        toasts.push(Severity::Error, "failed", None, start);
+        assert_eq!(toasts.remaining(start), None);
+
+        toasts.push(Severity::Warning, "first", None, start);
+        toasts.push(
+            Severity::Warning,
+            "second",
+            None,
+            start + Duration::from_secs(1),
+        );
+        assert_eq!(
+            toasts.remaining(start + Duration::from_secs(1)),
+            Some(TOAST_DURATION - Duration::from_secs(1))
+        );
+        assert_eq!(
+            toasts.remaining(start + TOAST_DURATION * 2),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn repeats_are_shown_again_not_twice() {
+        let start = Instant::now();
+        let mut toasts = Toasts::default();
+        toasts.push(Severity::Warning, "can't watch", None, start);
+        toasts.push(Severity::Warning, "other", None, start);
+        toasts.push(
+            Severity::Warning,
+            "can't watch",
+            None,
+            start + TOAST_DURATION / 2,
+        );
+        assert_eq!(messages(&toasts), ["other", "can't watch"]);
+
+        // It's up for longer now.
+        toasts.expire(start + TOAST_DURATION);
+        assert_eq!(messages(&toasts), ["can't watch"]);
+    }
+
+    #[test]
+    fn the_oldest_make_way() {
+        let start = Instant::now();
+        let mut toasts = Toasts::default();
+        for n in 0..MAX_TOASTS + 2 {
+            toasts.push(Severity::Error, format!("error {n}"), None, start);
+        }
+        let expected: Vec<String> = (2..MAX_TOASTS + 2).map(|n| format!("error {n}")).collect();
+        assert_eq!(messages(&toasts), expected);
+    }
+
+    #[test]
+    fn details_are_kept() {
+        let mut toasts = Toasts::default();
+        let details = "a.txt: denied\nb.txt: denied".to_owned();
+        toasts.push(
+            Severity::Error,
+            "2 items couldn't be deleted",
+            Some(details.clone()),
+            Instant::now(),
+        );
+        assert_eq!(toasts.toasts[0].details, Some(details));
+    }
+}
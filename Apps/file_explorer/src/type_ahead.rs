@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+/// Typing again after this long starts a new prefix instead of extending the old one.
+pub const RESET_AFTER: Duration = Duration::from_secs(1);
+
+/// The characters typed in quick succession over the file list, to jump to the entry they start.
+#[derive(Debug, Default)]
+pub struct TypeAhead {
+    prefix: String,
+    last_typed: Option<Instant>,
+}
+
+impl TypeAhead {
+    /// Adds `text` typed at `now`, returning the prefix to look for.
+    pub fn push(&mut self, text: &str, now: Instant) -> &str {
+        let stale = self.last_typed.map_or(true, |last_typed| {
+            now.duration_since(last_typed) >= RESET_AFTER
+        });
+        if stale {
+            self.prefix.clear();
+        }
+        self.prefix.push_str(text);
+        self.last_typed = Some(now);
+        &self.prefix
+    }
+}
+
+/// The index of the first of `names` starting with `prefix`, ignoring case.
+pub fn find_prefix<'a>(names: impl IntoIterator<Item = &'a str>, prefix: &str) -> Option<usize> {
+    let prefix = prefix.to_lowercase();
+    names
+        .into_iter()
+        .position(|name| name.to_lowercase().starts_with(&prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 5] = ["Documents", "downloads", "Music", "notes.txt", "Notes.md"];
+
+    #[test]
+    fn finds_the_first_match_ignoring_case() {
+        assert_eq!(find_prefix(NAMES, "d"), Some(0));
+        assert_eq!(find_prefix(NAMES, "dow"), Some(1));
+        assert_eq!(find_prefix(NAMES, "NOTES"), Some(3));
+        assert_eq!(find_prefix(NAMES, "notes.m"), Some(4));
+        assert_eq!(find_prefix(NAMES, "x"), None);
+        assert_eq!(find_prefix([], "d"), None);
+    }
+
+    #[test]
+    fn quick_typing_extends_the_prefix() {
+        let mut type_ahead = TypeAhead::default();
+        let start = Instant::now();
+        assert_eq!(type_ahead.push("d", start), "d");
+        assert_eq!(type_ahead.push("o", start + RESET_AFTER / 2), "do");
+        assert_eq!(type_ahead.push("w", start + RESET_AFTER), "dow");
+    }
+
+    #[test]
+    fn pausing_starts_over() {
+        let mut type_ahead = TypeAhead::default();
+        let start = Instant::now();
+        type_ahead.push("do", start);
+        assert_eq!(type_ahead.push("m", start + RESET_AFTER), "m");
+    }
+}
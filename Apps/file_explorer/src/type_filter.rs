@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Kinds of files that go together in the type filter, and share an icon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileGroup {
+    Images,
+    Audio,
+    Documents,
+    Archives,
+}
+
+impl FileGroup {
+    pub const ALL: [Self; 4] = [Self::Images, Self::Audio, Self::Documents, Self::Archives];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Images => "Images",
+            Self::Audio => "Audio",
+            Self::Documents => "Documents",
+            Self::Archives => "Archives",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Images => "🖼",
+            Self::Audio => "🎵",
+            Self::Documents => "📄",
+            Self::Archives => "📦",
+        }
+    }
+}
+
+/// The group of files with `extension` (without the dot), if it belongs to one.
+pub fn file_group(extension: &str) -> Option<FileGroup> {
+    match extension.to_lowercase().as_str() {
+        "jpg" | "jpeg" | "png" | "gif" | "bmp" | "svg" | "webp" => Some(FileGroup::Images),
+        "mp3" | "wav" | "flac" | "ogg" | "m4a" => Some(FileGroup::Audio),
+        "txt" | "md" | "pdf" | "doc" | "docx" | "odt" | "rtf" => Some(FileGroup::Documents),
+        "zip" | "tar" | "gz" | "7z" | "rar" => Some(FileGroup::Archives),
+        _ => None,
+    }
+}
+
+/// The lowercased extension of the file called `name`, if it has one.
+fn extension(name: &str) -> Option<String> {
+    Path::new(name)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+}
+
+/// Which files the listing shows. Folders are always shown, so there's still somewhere to go.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TypeFilter {
+    #[default]
+    All,
+    Group(FileGroup),
+
+    /// Lowercase, without the dot.
+    Extension(String),
+}
+
+impl TypeFilter {
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Group(group) => extension(name).and_then(|e| file_group(&e)) == Some(*group),
+            Self::Extension(wanted) => extension(name).as_ref() == Some(wanted),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Self::All => "All files".to_owned(),
+            Self::Group(group) => group.label().to_owned(),
+            Self::Extension(extension) => format!(".{extension}"),
+        }
+    }
+}
+
+/// The extensions of the files called `names`, with how many files have each, in order.
+pub fn extension_counts<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<(String, usize)> {
+    let mut counts = BTreeMap::new();
+    for extension in names.into_iter().filter_map(extension) {
+        *counts.entry(extension).or_default() += 1;
+    }
+    counts.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{search_streaming, Matcher, SearchMode};
+    use crate::temp_dir::TempDir;
+    use std::fs;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Mutex;
+
+    #[test]
+    fn groups_by_extension_ignoring_case() {
+        assert_eq!(file_group("PNG"), Some(FileGroup::Images));
+        assert_eq!(file_group("jpeg"), Some(FileGroup::Images));
+        assert_eq!(file_group("mp3"), Some(FileGroup::Audio));
+        assert_eq!(file_group("pdf"), Some(FileGroup::Documents));
+        assert_eq!(file_group("zip"), Some(FileGroup::Archives));
+        assert_eq!(file_group("rs"), None);
+        assert_eq!(file_group(""), None);
+    }
+
+    #[test]
+    fn filters_match_names() {
+        let images = TypeFilter::Group(FileGroup::Images);
+        assert!(images.matches("photo.JPG"));
+        assert!(!images.matches("notes.txt"));
+        assert!(!images.matches("png")); // That's a name, not an extension.
+
+        let rust = TypeFilter::Extension("rs".to_owned());
+        assert!(rust.matches("main.rs"));
+        assert!(rust.matches("LIB.RS"));
+        assert!(!rust.matches("main.rs.bak"));
+        assert!(!rust.matches("Makefile"));
+
+        assert!(TypeFilter::All.matches("Makefile"));
+    }
+
+    #[test]
+    fn counts_extensions_in_order() {
+        let names = ["b.rs", "a.RS", "c.png", "Makefile", ".bashrc"];
+        assert_eq!(
+            extension_counts(names),
+            [("png".to_owned(), 1), ("rs".to_owned(), 2)]
+        );
+    }
+
+    #[test]
+    fn composes_with_hidden_files_and_search() {
+        let dir = TempDir::new("type_filter");
+        for name in ["cat.png", "cat.rs", ".cat.png", "dog.png", "cat notes.txt"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        // The listing has already been searched and stripped of hidden files when it's filtered.
+        let list = |show_hidden: bool, query: &str, filter: TypeFilter| {
+            let matcher = Matcher::new(SearchMode::Plain, query).unwrap();
+            let found = Mutex::new(Vec::new());
+            let cancel = AtomicBool::new(false);
+            search_streaming(&dir, &matcher, show_hidden, &cancel, |batch| {
+                found.lock().unwrap().extend(batch);
+            });
+            let mut names: Vec<String> = found
+                .into_inner()
+                .unwrap()
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .filter(|name| filter.matches(name))
+                .collect();
+            names.sort();
+            names
+        };
+
+        let images = TypeFilter::Group(FileGroup::Images);
+        assert_eq!(list(false, "", images.clone()), ["cat.png", "dog.png"]);
+        assert_eq!(
+            list(true, "", images.clone()),
+            [".cat.png", "cat.png", "dog.png"]
+        );
+        assert_eq!(list(false, "cat", images), ["cat.png"]);
+        assert_eq!(
+            list(false, "cat", TypeFilter::All),
+            ["cat notes.txt", "cat.png", "cat.rs"]
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+
+/// How long to wait after the last change before refreshing, so a burst of changes
+/// (e.g. extracting an archive) only refreshes once.
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Does `kind` change what's listed in a folder?
+pub fn is_relevant(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+    )
+}
+
+/// Watches the folder being shown for entries being created, removed or renamed outside the app.
+pub struct DirWatcher {
+    watcher: Option<RecommendedWatcher>,
+    watched: Option<PathBuf>,
+    tx: Sender<()>,
+    rx: Receiver<()>,
+
+    /// When the last change not yet refreshed for came in.
+    last_change: Option<Instant>,
+}
+
+impl Default for DirWatcher {
+    fn default() -> Self {
+        let (tx, rx) = channel();
+        Self {
+            watcher: None,
+            watched: None,
+            tx,
+            rx,
+            last_change: None,
+        }
+    }
+}
+
+impl DirWatcher {
+    pub fn watched(&self) -> Option<&Path> {
+        self.watched.as_deref()
+    }
+
+    /// Watches `path` instead of whatever was watched before.
+    ///
+    /// `wake` is called from the watcher's thread on every relevant change, e.g. to repaint
+    /// so [`Self::poll`] gets called. A folder that can't be watched (say, on a network drive)
+    /// just isn't refreshed automatically, which the error says.
+    pub fn watch(&mut self, path: &Path, wake: impl Fn() + Send + 'static) -> Result<(), String> {
+        self.watcher = None; // Stops watching the old folder.
+        self.watched = Some(path.to_owned());
+        self.last_change = None;
+        while self.rx.try_recv().is_ok() {}
+
+        let tx = self.tx.clone();
+        let handler = move |event: notify::Result<Event>| {
+            if event.is_ok_and(|event| is_relevant(&event.kind)) && tx.send(()).is_ok() {
+                wake();
+            }
+        };
+        let watcher = notify::recommended_watcher(handler)
+            .and_then(|mut watcher| {
+                watcher.watch(path, RecursiveMode::NonRecursive)?;
+                Ok(watcher)
+            })
+            .map_err(|err| {
+                format!(
+                    "Changes to {} won't show until you refresh: {err}",
+                    path.display()
+                )
+            })?;
+        self.watcher = Some(watcher);
+        Ok(())
+    }
+
+    /// Should the listing be refreshed? True once changes came in and [`DEBOUNCE`] has
+    /// passed since the last of them.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        while self.rx.try_recv().is_ok() {
+            self.last_change = Some(now);
+        }
+        match self.last_change {
+            Some(last_change) if now.duration_since(last_change) >= DEBOUNCE => {
+                self.last_change = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+    use std::fs;
+    use std::sync::mpsc;
+
+    #[test]
+    fn creating_a_file_signals_a_refresh() {
+        let dir = TempDir::new("watch");
+
+        let (woken_tx, woken) = mpsc::channel();
+        let mut watcher = DirWatcher::default();
+        watcher
+            .watch(&dir, move || {
+                woken_tx.send(()).ok();
+            })
+            .unwrap();
+        assert_eq!(watcher.watched(), Some(&*dir));
+
+        fs::write(dir.join("new.txt"), "").unwrap();
+        woken.recv_timeout(Duration::from_secs(5)).unwrap();
+        std::thread::sleep(Duration::from_millis(100)); // Any other events for the same file.
+
+        // Debounced: not right away, but once things have settled.
+        let now = Instant::now();
+        assert!(!watcher.poll(now));
+        assert!(watcher.poll(now + DEBOUNCE));
+        assert!(!watcher.poll(now + DEBOUNCE * 2));
+    }
+
+    #[test]
+    fn only_listing_changes_are_relevant() {
+        use notify::event::{AccessKind, CreateKind, DataChange, RemoveKind, RenameMode};
+
+        assert!(is_relevant(&EventKind::Create(CreateKind::File)));
+        assert!(is_relevant(&EventKind::Remove(RemoveKind::Folder)));
+        assert!(is_relevant(&EventKind::Modify(ModifyKind::Name(
+            RenameMode::Both
+        ))));
+        assert!(!is_relevant(&EventKind::Modify(ModifyKind::Data(
+            DataChange::Content
+        ))));
+        assert!(!is_relevant(&EventKind::Access(AccessKind::Read)));
+    }
+}
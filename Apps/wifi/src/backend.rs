@@ -0,0 +1,732 @@
+use std::fmt;
+use std::io::Read as _;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+use crate::scanner::{parse_wifi_scan_output, WifiNetwork};
+use crate::security::Security;
+
+/// Why a WiFi scan failed.
+#[derive(Debug)]
+pub enum ScanError {
+    /// The scan command could not be started (e.g. it is not installed).
+    Spawn {
+        command: String,
+        source: std::io::Error,
+    },
+
+    /// The scan command ran but reported an error.
+    Failed { command: String, stderr: String },
+
+    /// The command didn't finish in time (e.g. a wedged driver) and was killed.
+    TimedOut { command: String, timeout: Duration },
+
+    /// The backend can't do this on this platform.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spawn { command, source } => write!(f, "Failed to execute `{command}`: {source}"),
+            Self::Failed { command, stderr } => write!(f, "`{command}` failed: {}", stderr.trim()),
+            Self::TimedOut { command, timeout } => write!(
+                f,
+                "`{command}` did not finish within {} s and was stopped",
+                timeout.as_secs_f32()
+            ),
+            Self::Unsupported(what) => write!(f, "{what} is not supported by this backend"),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+/// A platform-specific way of listing the WiFi networks in range.
+pub trait WifiBackend: Send {
+    fn scan(&self) -> Result<Vec<WifiNetwork>, ScanError>;
+
+    /// Joins the network called `ssid`. `password` is `None` for open networks.
+    fn connect(&self, _ssid: &str, _password: Option<&str>) -> Result<(), ScanError> {
+        Err(ScanError::Unsupported("Connecting"))
+    }
+
+    /// The SSID of the network we are currently connected to, if any.
+    fn connected_ssid(&self) -> Option<String> {
+        None
+    }
+
+    /// Leaves the current network, without forgetting it.
+    fn disconnect(&self) -> Result<(), ScanError> {
+        Err(ScanError::Unsupported("Disconnecting"))
+    }
+
+    /// Deletes the saved connection (and password) of `ssid`, so it isn't joined again by itself.
+    fn forget(&self, _ssid: &str) -> Result<(), ScanError> {
+        Err(ScanError::Unsupported("Forgetting networks"))
+    }
+}
+
+/// Picks the backend for the platform we are running on.
+///
+/// On Linux, setting `WIFI_ADAPTER` (e.g. `wlp3s0`) scans that adapter with `iwlist` instead of `nmcli`.
+pub fn default_backend() -> Box<dyn WifiBackend> {
+    if let Ok(adapter) = std::env::var("WIFI_ADAPTER") {
+        Box::new(IwlistBackend { adapter })
+    } else if cfg!(target_os = "windows") {
+        Box::new(NetshBackend)
+    } else if cfg!(target_os = "macos") {
+        Box::new(AirportBackend)
+    } else {
+        Box::new(NmcliBackend)
+    }
+}
+
+/// `program` and `args` as a single line for error messages, with any password hidden.
+fn command_line(program: &str, args: &[&str]) -> String {
+    let mut line = program.to_owned();
+    let mut hide_next = false;
+    for arg in args {
+        line.push(' ');
+        line.push_str(if hide_next { "***" } else { arg });
+        hide_next = *arg == "password";
+    }
+    line
+}
+
+/// How long a scan (or connect) command may run before it is killed, in milliseconds.
+static COMMAND_TIMEOUT_MS: AtomicU64 = AtomicU64::new(30_000);
+
+pub fn command_timeout() -> Duration {
+    Duration::from_millis(COMMAND_TIMEOUT_MS.load(Ordering::Relaxed))
+}
+
+pub fn set_command_timeout(timeout: Duration) {
+    let millis = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+    COMMAND_TIMEOUT_MS.store(millis, Ordering::Relaxed);
+}
+
+/// Runs `program` with `args` and returns its stdout, giving up after [`command_timeout`].
+fn run_command(program: &str, args: &[&str]) -> Result<String, ScanError> {
+    run_command_with_timeout(program, args, command_timeout())
+}
+
+fn run_command_with_timeout(
+    program: &str,
+    args: &[&str],
+    timeout: Duration,
+) -> Result<String, ScanError> {
+    let command = command_line(program, args);
+    let spawn_error = |source| ScanError::Spawn {
+        command: command.clone(),
+        source,
+    };
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(spawn_error)?;
+
+    // Read the pipes on their own threads, so a chatty command can't block on a full pipe.
+    let read_to_end = |pipe: Option<Box<dyn std::io::Read + Send>>| {
+        thread::spawn(move || {
+            let mut bytes = Vec::new();
+            if let Some(mut pipe) = pipe {
+                pipe.read_to_end(&mut bytes).ok();
+            }
+            bytes
+        })
+    };
+    let stdout = read_to_end(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr = read_to_end(child.stderr.take().map(|p| Box::new(p) as _));
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(spawn_error)? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill().ok();
+            child.wait().ok();
+            return Err(ScanError::TimedOut { command, timeout });
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    if status.success() {
+        Ok(String::from_utf8_lossy(&stdout).into_owned())
+    } else {
+        Err(ScanError::Failed {
+            command,
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        })
+    }
+}
+
+/// Linux, through NetworkManager.
+pub struct NmcliBackend;
+
+/// The fields asked of `nmcli` when scanning, in the order it prints them.
+const NMCLI_FIELDS: &str = "BSSID,SSID,CHAN,SIGNAL,SECURITY,FREQ";
+
+impl WifiBackend for NmcliBackend {
+    fn scan(&self) -> Result<Vec<WifiNetwork>, ScanError> {
+        let output = run_command(
+            "nmcli",
+            &[
+                "--terse",
+                // The default in terse mode, but a user's config can't turn it off this way.
+                "--escape",
+                "yes",
+                "--fields",
+                NMCLI_FIELDS,
+                "device",
+                "wifi",
+                "list",
+            ],
+        )?;
+        Ok(parse_nmcli_output(&output))
+    }
+
+    fn connect(&self, ssid: &str, password: Option<&str>) -> Result<(), ScanError> {
+        let args = nmcli_connect_args(ssid, password);
+        run_command("nmcli", &args).map(|_| ())
+    }
+
+    fn connected_ssid(&self) -> Option<String> {
+        let output = run_command(
+            "nmcli",
+            &["--terse", "--fields", "ACTIVE,SSID", "device", "wifi"],
+        )
+        .ok()?;
+        output.lines().find_map(|line| {
+            let fields = split_terse_line(line);
+            match fields.as_slice() {
+                [active, ssid] if active == "yes" && !ssid.is_empty() => Some(ssid.clone()),
+                _ => None,
+            }
+        })
+    }
+
+    fn disconnect(&self) -> Result<(), ScanError> {
+        let output = run_command(
+            "nmcli",
+            &["--terse", "--fields", "DEVICE,TYPE,STATE", "device"],
+        )?;
+        let Some(device) = connected_wifi_device(&output) else {
+            return Err(ScanError::Failed {
+                command: "nmcli device".to_owned(),
+                stderr: "No WiFi device is connected".to_owned(),
+            });
+        };
+        run_command("nmcli", &nmcli_disconnect_args(&device)).map(|_| ())
+    }
+
+    fn forget(&self, ssid: &str) -> Result<(), ScanError> {
+        run_command("nmcli", &nmcli_forget_args(ssid)).map(|_| ())
+    }
+}
+
+/// The WiFi device that is connected, from `nmcli --terse --fields DEVICE,TYPE,STATE device`.
+fn connected_wifi_device(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let fields = split_terse_line(line);
+        match fields.as_slice() {
+            [device, kind, state] if kind == "wifi" && state == "connected" => Some(device.clone()),
+            _ => None,
+        }
+    })
+}
+
+/// The arguments for `nmcli` to disconnect `device` (e.g. `wlp3s0`).
+pub fn nmcli_disconnect_args(device: &str) -> Vec<&str> {
+    vec!["device", "disconnect", device]
+}
+
+/// The arguments for `nmcli` to delete the saved connection of `ssid`.
+///
+/// NetworkManager names the connections it makes when joining a network after the SSID.
+pub fn nmcli_forget_args(ssid: &str) -> Vec<&str> {
+    vec!["connection", "delete", ssid]
+}
+
+/// The arguments for `nmcli` to join a network, with a password unless it is open.
+pub fn nmcli_connect_args<'a>(ssid: &'a str, password: Option<&'a str>) -> Vec<&'a str> {
+    let mut args = vec!["device", "wifi", "connect", ssid];
+    if let Some(password) = password {
+        args.extend(["password", password]);
+    }
+    args
+}
+
+/// Parses the output of the scan of [`NmcliBackend`], see [`parse_nmcli_fields`].
+pub fn parse_nmcli_output(output: &str) -> Vec<WifiNetwork> {
+    parse_nmcli_fields(output, NMCLI_FIELDS)
+}
+
+/// Parses the output of `nmcli --terse --escape yes --fields <fields> device wifi list`.
+///
+/// Unlike the columns of the plain output, this doesn't depend on alignment or the locale.
+/// Values are found by the name of their field in `fields`, so any order works, and fields
+/// that weren't asked for are "Not found". Lines with more or fewer values are skipped.
+///
+/// Hidden networks (empty SSID) are reported as "Hidden".
+pub fn parse_nmcli_fields(output: &str, fields: &str) -> Vec<WifiNetwork> {
+    let names: Vec<&str> = fields.split(',').collect();
+
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let values = split_terse_line(line);
+            if values.len() != names.len() {
+                return None;
+            }
+            let mut network = WifiNetwork::not_found();
+            for (name, value) in names.iter().zip(values) {
+                match *name {
+                    "BSSID" => network.address = value,
+                    "SSID" if value.is_empty() => network.essid = "Hidden".to_owned(),
+                    "SSID" => network.essid = value,
+                    "CHAN" => network.channel = value,
+                    "FREQ" => network.frequency = value,
+                    "SIGNAL" => network.quality = format!("{value}/100"),
+                    "SECURITY" => {
+                        network.security = Security::from_description(&value);
+                        network.encryption_key = value;
+                    }
+                    _ => {}
+                }
+            }
+            Some(network)
+        })
+        .collect()
+}
+
+/// Splits a line of `nmcli --terse` output on the `:` separators.
+///
+/// In terse mode nmcli escapes literal colons as `\:` and backslashes as `\\`.
+fn split_terse_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    fields.last_mut().unwrap().push(escaped);
+                }
+            }
+            ':' => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// Windows, through `netsh`.
+pub struct NetshBackend;
+
+impl WifiBackend for NetshBackend {
+    fn scan(&self) -> Result<Vec<WifiNetwork>, ScanError> {
+        let output = run_command("netsh", &["wlan", "show", "networks", "mode=bssid"])?;
+        Ok(parse_netsh_output(&output))
+    }
+
+    /// `netsh` can only connect to networks it has a profile for, so one is added first.
+    fn connect(&self, ssid: &str, password: Option<&str>) -> Result<(), ScanError> {
+        #[allow(clippy::disallowed_methods)] // A file for netsh to read, removed right after
+        let profile_path =
+            std::env::temp_dir().join(format!("wifi-profile-{}.xml", std::process::id()));
+        std::fs::write(&profile_path, netsh_profile_xml(ssid, password)).map_err(|source| {
+            ScanError::Spawn {
+                command: format!("writing {}", profile_path.display()),
+                source,
+            }
+        })?;
+
+        let filename = format!("filename={}", profile_path.display());
+        let added = run_command("netsh", &["wlan", "add", "profile", &filename]);
+        std::fs::remove_file(&profile_path).ok(); // It contains the password
+        added?;
+
+        let name = format!("name={ssid}");
+        let ssid = format!("ssid={ssid}");
+        run_command("netsh", &["wlan", "connect", &name, &ssid]).map(|_| ())
+    }
+
+    fn connected_ssid(&self) -> Option<String> {
+        let output = run_command("netsh", &["wlan", "show", "interfaces"]).ok()?;
+        output.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == "SSID" && !value.trim().is_empty()).then(|| value.trim().to_owned())
+        })
+    }
+
+    fn disconnect(&self) -> Result<(), ScanError> {
+        run_command("netsh", &["wlan", "disconnect"]).map(|_| ())
+    }
+
+    fn forget(&self, ssid: &str) -> Result<(), ScanError> {
+        let name = format!("name={ssid}");
+        run_command("netsh", &["wlan", "delete", "profile", &name]).map(|_| ())
+    }
+}
+
+/// A WLAN profile for `netsh wlan add profile`, using WPA2-Personal unless the network is open.
+fn netsh_profile_xml(ssid: &str, password: Option<&str>) -> String {
+    let ssid = xml_escape(ssid);
+    let security = match password {
+        Some(password) => format!(
+            "<authEncryption><authentication>WPA2PSK</authentication><encryption>AES</encryption><useOneX>false</useOneX></authEncryption>\
+             <sharedKey><keyType>passPhrase</keyType><protected>false</protected><keyMaterial>{}</keyMaterial></sharedKey>",
+            xml_escape(password)
+        ),
+        None => "<authEncryption><authentication>open</authentication><encryption>none</encryption><useOneX>false</useOneX></authEncryption>".to_owned(),
+    };
+
+    format!(
+        r#"<?xml version="1.0"?>
+<WLANProfile xmlns="http://www.microsoft.com/networking/WLAN/profile/v1">
+    <name>{ssid}</name>
+    <SSIDConfig><SSID><name>{ssid}</name></SSID></SSIDConfig>
+    <connectionType>ESS</connectionType>
+    <connectionMode>manual</connectionMode>
+    <MSM><security>{security}</security></MSM>
+</WLANProfile>
+"#
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Parses the output of `netsh wlan show networks mode=bssid`.
+///
+/// Every BSSID becomes its own network, sharing the SSID and authentication of its group.
+pub fn parse_netsh_output(output: &str) -> Vec<WifiNetwork> {
+    let mut networks = Vec::new();
+    let mut ssid = String::new();
+    let mut authentication = String::new();
+
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = value.trim();
+
+        if key.starts_with("SSID") {
+            ssid = value.to_owned();
+        } else if key == "Authentication" {
+            authentication = value.to_owned();
+        } else if key.starts_with("BSSID") {
+            networks.push(WifiNetwork {
+                address: value.to_owned(),
+                essid: ssid.clone(),
+                encryption_key: authentication.clone(),
+                security: Security::from_description(&authentication),
+                ..WifiNetwork::not_found()
+            });
+        } else if let Some(network) = networks.last_mut() {
+            match key {
+                "Signal" => {
+                    network.quality = format!("{}/100", value.trim_end_matches('%'));
+                }
+                "Channel" => {
+                    network.channel = value.to_owned();
+                    if let Ok(channel) = value.parse() {
+                        network.frequency = frequency_from_channel(channel);
+                    }
+                }
+                "Radio type" => network.mode = value.to_owned(),
+                "Basic rates (Mbps)" => network.bit_rates = value.to_owned(),
+                _ => {}
+            }
+        }
+    }
+
+    networks
+}
+
+/// macOS, through the `airport` utility.
+pub struct AirportBackend;
+
+const AIRPORT_PATH: &str =
+    "/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport";
+
+impl WifiBackend for AirportBackend {
+    fn scan(&self) -> Result<Vec<WifiNetwork>, ScanError> {
+        let output = run_command(AIRPORT_PATH, &["-s"])?;
+        Ok(parse_airport_output(&output))
+    }
+}
+
+/// Parses the output of `airport -s`.
+///
+/// The SSID column is right-aligned and may contain spaces, so each line is split at its BSSID.
+pub fn parse_airport_output(output: &str) -> Vec<WifiNetwork> {
+    let bssid_re = Regex::new(r"([0-9a-fA-F]{2}:){5}[0-9a-fA-F]{2}").unwrap();
+
+    output
+        .lines()
+        .filter_map(|line| {
+            let bssid = bssid_re.find(line)?;
+            let mut rest = line[bssid.end()..].split_whitespace();
+            let signal_level = rest.next()?.to_owned();
+            let channel = rest.next()?.to_owned();
+            let _ht = rest.next();
+            let _country_code = rest.next();
+            let security = rest.collect::<Vec<_>>().join(" ");
+
+            let frequency = channel
+                .split(',')
+                .next()
+                .and_then(|c| c.parse().ok())
+                .map_or_else(|| "Not found".to_owned(), frequency_from_channel);
+
+            Some(WifiNetwork {
+                address: bssid.as_str().to_owned(),
+                essid: line[..bssid.start()].trim().to_owned(),
+                signal_level,
+                channel,
+                frequency,
+                security: Security::from_description(&security),
+                encryption_key: security,
+                ..WifiNetwork::not_found()
+            })
+        })
+        .collect()
+}
+
+/// Linux, through `iwlist`. Usually needs root to see more than the connected network.
+pub struct IwlistBackend {
+    pub adapter: String,
+}
+
+impl WifiBackend for IwlistBackend {
+    fn scan(&self) -> Result<Vec<WifiNetwork>, ScanError> {
+        let output = run_command("iwlist", &[&self.adapter, "scan"])?;
+        Ok(parse_wifi_scan_output(&output))
+    }
+
+    fn connected_ssid(&self) -> Option<String> {
+        // `--raw` prints only the ESSID, and nothing when the adapter isn't connected.
+        let output = run_command("iwgetid", &[&self.adapter, "--raw"]).ok()?;
+        let ssid = output.trim();
+        (!ssid.is_empty()).then(|| ssid.to_owned())
+    }
+}
+
+/// The center frequency of a WiFi channel, formatted like `iwlist` does (e.g. "2.412 GHz").
+fn frequency_from_channel(channel: u32) -> String {
+    let mhz = match channel {
+        14 => 2484,
+        1..=13 => 2407 + 5 * channel,
+        _ => 5000 + 5 * channel,
+    };
+    format!("{:.3} GHz", mhz as f32 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETSH_OUTPUT: &str = "
+Interface name : Wi-Fi
+There are 2 networks currently visible.
+
+SSID 1 : Home Network
+    Network type            : Infrastructure
+    Authentication          : WPA2-Personal
+    Encryption              : CCMP
+    BSSID 1                 : a0:b1:c2:d3:e4:f5
+         Signal             : 88%
+         Radio type         : 802.11ac
+         Channel            : 36
+         Basic rates (Mbps) : 6 12 24
+    BSSID 2                 : a0:b1:c2:d3:e4:f6
+         Signal             : 40%
+         Radio type         : 802.11n
+         Channel            : 6
+         Basic rates (Mbps) : 1 2 5.5 11
+
+SSID 2 : Cafe
+    Network type            : Infrastructure
+    Authentication          : Open
+    Encryption              : None
+    BSSID 1                 : 10:20:30:40:50:60
+         Signal             : 12%
+         Radio type         : 802.11n
+         Channel            : 11
+";
+
+    #[cfg(unix)]
+    #[test]
+    fn slow_commands_time_out() {
+        let start = Instant::now();
+        let result = run_command_with_timeout("sleep", &["10"], Duration::from_millis(200));
+        assert!(
+            matches!(result, Err(ScanError::TimedOut { .. })),
+            "{result:?}"
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fast_commands_return_their_output() {
+        let output = run_command_with_timeout("echo", &["hello"], Duration::from_secs(5));
+        assert_eq!(output.unwrap(), "hello\n");
+
+        let failed = run_command_with_timeout(
+            "sh",
+            &["-c", "echo oops >&2; exit 1"],
+            Duration::from_secs(5),
+        );
+        assert!(matches!(failed, Err(ScanError::Failed { stderr, .. }) if stderr == "oops\n"));
+    }
+
+    #[test]
+    fn nmcli_connect_args_only_pass_a_password_for_secured_networks() {
+        assert_eq!(
+            nmcli_connect_args("Cafe WiFi", None),
+            ["device", "wifi", "connect", "Cafe WiFi"]
+        );
+        assert_eq!(
+            nmcli_connect_args("Home", Some("hunter2")),
+            ["device", "wifi", "connect", "Home", "password", "hunter2"]
+        );
+    }
+
+    #[test]
+    fn nmcli_disconnects_the_device_and_forgets_by_ssid() {
+        assert_eq!(
+            nmcli_disconnect_args("wlp3s0"),
+            ["device", "disconnect", "wlp3s0"]
+        );
+        assert_eq!(
+            nmcli_forget_args("Cafe: Free WiFi"),
+            ["connection", "delete", "Cafe: Free WiFi"]
+        );
+    }
+
+    #[test]
+    fn finds_the_connected_wifi_device() {
+        let output = "lo:loopback:connected (externally)
+enp0s31f6:ethernet:unavailable
+p2p-dev-wlp3s0:wifi-p2p:disconnected
+wlp3s0:wifi:connected
+";
+        assert_eq!(connected_wifi_device(output).as_deref(), Some("wlp3s0"));
+        assert_eq!(connected_wifi_device("wlp3s0:wifi:disconnected\n"), None);
+    }
+
+    #[test]
+    fn command_line_hides_passwords() {
+        let args = nmcli_connect_args("Home", Some("hunter2"));
+        assert_eq!(
+            command_line("nmcli", &args),
+            "nmcli device wifi connect Home password ***"
+        );
+    }
+
+    #[test]
+    fn splits_terse_lines_on_unescaped_colons() {
+        assert_eq!(split_terse_line(r"a\:b:c d:"), ["a:b", "c d", ""]);
+        assert_eq!(split_terse_line(r"back\\slash:x"), [r"back\slash", "x"]);
+    }
+
+    #[test]
+    fn parses_nmcli_output_with_spaces_and_hidden_networks() {
+        let output = r"AA\:BB\:CC\:DD\:EE\:01:My Home Network:6:2437 MHz:72:WPA2
+AA\:BB\:CC\:DD\:EE\:02::11:2462 MHz:40:WPA1 WPA2
+AA\:BB\:CC\:DD\:EE\:03:Cafe\: Free WiFi:36:5180 MHz:15:
+";
+        let networks = parse_nmcli_fields(output, "BSSID,SSID,CHAN,FREQ,SIGNAL,SECURITY");
+        assert_eq!(networks.len(), 3);
+
+        assert_eq!(networks[0].address, "AA:BB:CC:DD:EE:01");
+        assert_eq!(networks[0].essid, "My Home Network");
+        assert_eq!(networks[0].channel, "6");
+        assert_eq!(networks[0].frequency, "2437 MHz");
+        assert_eq!(networks[0].quality, "72/100");
+        assert_eq!(networks[0].encryption_key, "WPA2");
+
+        assert_eq!(networks[1].essid, "Hidden");
+        assert_eq!(networks[1].encryption_key, "WPA1 WPA2");
+        assert_eq!(networks[1].security, Security::Wpa2);
+
+        assert_eq!(networks[2].essid, "Cafe: Free WiFi");
+        assert_eq!(networks[2].encryption_key, "");
+    }
+
+    #[test]
+    fn parses_the_fields_of_the_scan() {
+        let output = r"AA\:BB\:CC\:DD\:EE\:01:Office\: 2nd floor:1:64:WPA2 802.1X:2412 MHz
+AA\:BB\:CC\:DD\:EE\:02:a\:b\:c:149:99:WPA3:5745 MHz
+AA\:BB\:CC\:DD\:EE\:03:C\:\\Temp:6:30::2437 MHz
+AA\:BB\:CC\:DD\:EE\:04:Not enough fields
+";
+        let networks = parse_nmcli_output(output);
+        assert_eq!(networks.len(), 3);
+
+        assert_eq!(networks[0].address, "AA:BB:CC:DD:EE:01");
+        assert_eq!(networks[0].essid, "Office: 2nd floor");
+        assert_eq!(networks[0].channel, "1");
+        assert_eq!(networks[0].quality, "64/100");
+        assert_eq!(networks[0].encryption_key, "WPA2 802.1X");
+        assert_eq!(networks[0].frequency, "2412 MHz");
+
+        assert_eq!(networks[1].address, "AA:BB:CC:DD:EE:02");
+        assert_eq!(networks[1].essid, "a:b:c");
+        assert_eq!(networks[1].frequency, "5745 MHz");
+
+        assert_eq!(networks[2].essid, r"C:\Temp");
+        assert_eq!(networks[2].encryption_key, "");
+        assert_eq!(networks[2].frequency, "2437 MHz");
+    }
+
+    #[test]
+    fn fields_that_were_not_asked_for_are_not_found() {
+        let networks = parse_nmcli_fields(r"My Net:AA\:BB\:CC\:DD\:EE\:FF", "SSID,BSSID");
+        assert_eq!(networks.len(), 1);
+        assert_eq!(networks[0].essid, "My Net");
+        assert_eq!(networks[0].address, "AA:BB:CC:DD:EE:FF");
+        assert_eq!(networks[0].channel, "Not found");
+        assert_eq!(networks[0].quality, "Not found");
+    }
+
+    #[test]
+    fn parses_netsh_output() {
+        let networks = parse_netsh_output(NETSH_OUTPUT);
+        assert_eq!(networks.len(), 3);
+
+        assert_eq!(networks[0].essid, "Home Network");
+        assert_eq!(networks[0].address, "a0:b1:c2:d3:e4:f5");
+        assert_eq!(networks[0].quality, "88/100");
+        assert_eq!(networks[0].channel, "36");
+        assert_eq!(networks[0].frequency, "5.180 GHz");
+        assert_eq!(networks[0].encryption_key, "WPA2-Personal");
+
+        assert_eq!(networks[1].essid, "Home Network");
+        assert_eq!(networks[1].frequency, "2.437 GHz");
+
+        assert_eq!(networks[2].essid, "Cafe");
+        assert_eq!(networks[2].encryption_key, "Open");
+        assert_eq!(networks[2].quality, "12/100");
+    }
+}
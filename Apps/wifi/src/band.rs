@@ -0,0 +1,50 @@
+/// Parses a frequency like "2.412 GHz" or "2437 MHz" into GHz.
+///
+/// A number without a unit is taken to be GHz if it is small, and MHz otherwise.
+pub fn frequency_ghz(frequency: &str) -> Option<f64> {
+    let frequency = frequency.trim();
+    let number_end = frequency
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(frequency.len());
+    let value: f64 = frequency[..number_end].parse().ok()?;
+    let unit = frequency[number_end..].trim().to_ascii_lowercase();
+
+    match unit.as_str() {
+        "ghz" => Some(value),
+        "mhz" => Some(value / 1000.0),
+        "" => Some(if value < 100.0 { value } else { value / 1000.0 }),
+        _ => None,
+    }
+}
+
+/// The WiFi band a frequency (as shown in the scan results) belongs to.
+pub fn frequency_band(frequency: &str) -> &'static str {
+    match frequency_ghz(frequency) {
+        Some(ghz) if ghz < 3.0 => "2.4 GHz",
+        Some(ghz) if ghz < 6.0 => "5 GHz",
+        Some(_) => "6 GHz (WiFi 6E)",
+        None => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_bands() {
+        assert_eq!(frequency_band("2.412 GHz"), "2.4 GHz");
+        assert_eq!(frequency_band("2437 MHz"), "2.4 GHz");
+        assert_eq!(frequency_band("5.180 GHz"), "5 GHz");
+        assert_eq!(frequency_band(" 5745 MHz "), "5 GHz");
+        assert_eq!(frequency_band("6.115 GHz"), "6 GHz (WiFi 6E)");
+        assert_eq!(frequency_band("6135"), "6 GHz (WiFi 6E)");
+    }
+
+    #[test]
+    fn unparseable_frequencies_are_unknown() {
+        assert_eq!(frequency_band("Not found"), "Unknown");
+        assert_eq!(frequency_band(""), "Unknown");
+        assert_eq!(frequency_band("2.4 furlongs"), "Unknown");
+    }
+}
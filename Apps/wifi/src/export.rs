@@ -0,0 +1,162 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::scanner::WifiNetwork;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 2] = [Self::Csv, Self::Json];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Json => "JSON",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// The CSV header. `timestamp` is the time of the export, in seconds since the Unix epoch.
+pub const CSV_HEADER: [&str; 12] = [
+    "timestamp",
+    "address",
+    "essid",
+    "channel",
+    "frequency",
+    "quality",
+    "signal_level",
+    "encryption_key",
+    "security",
+    "bit_rates",
+    "mode",
+    "extra",
+];
+
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    timestamp: u64,
+    networks: &'a [WifiNetwork],
+}
+
+/// Seconds since the Unix epoch.
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// Writes `networks` to `path`, stamped with the current time.
+pub fn export_networks(
+    networks: &[WifiNetwork],
+    format: ExportFormat,
+    path: &Path,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let timestamp = unix_timestamp();
+    match format {
+        ExportFormat::Csv => write_csv(&mut writer, networks, timestamp)?,
+        ExportFormat::Json => write_json(&mut writer, networks, timestamp)?,
+    }
+    writer.flush()
+}
+
+/// One row per network, after a [`CSV_HEADER`] row.
+pub fn write_csv(writer: impl Write, networks: &[WifiNetwork], timestamp: u64) -> io::Result<()> {
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    csv_writer.write_record(CSV_HEADER)?;
+
+    let timestamp = timestamp.to_string();
+    for network in networks {
+        csv_writer.write_record([
+            timestamp.as_str(),
+            &network.address,
+            &network.essid,
+            &network.channel,
+            &network.frequency,
+            &network.quality,
+            &network.signal_level,
+            &network.encryption_key,
+            network.security.label(),
+            &network.bit_rates,
+            &network.mode,
+            &network.extra,
+        ])?;
+    }
+    csv_writer.flush()
+}
+
+/// A `{"timestamp": …, "networks": […]}` object.
+pub fn write_json(writer: impl Write, networks: &[WifiNetwork], timestamp: u64) -> io::Result<()> {
+    let export = JsonExport {
+        timestamp,
+        networks,
+    };
+    serde_json::to_writer_pretty(writer, &export)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn networks() -> Vec<WifiNetwork> {
+        vec![
+            WifiNetwork {
+                address: "11:22:33:44:55:66".to_owned(),
+                essid: "Home".to_owned(),
+                ..WifiNetwork::not_found()
+            },
+            WifiNetwork {
+                address: "22:33:44:55:66:77".to_owned(),
+                essid: "Café, upstairs".to_owned(),
+                ..WifiNetwork::not_found()
+            },
+        ]
+    }
+
+    #[test]
+    fn csv_has_header_and_one_row_per_network() {
+        let mut out = Vec::new();
+        write_csv(&mut out, &networks(), 1_700_000_000).unwrap();
+
+        let mut reader = csv::Reader::from_reader(out.as_slice());
+        assert_eq!(reader.headers().unwrap(), CSV_HEADER.as_slice());
+        let rows: Vec<_> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[0][0], "1700000000");
+        assert_eq!(&rows[1][2], "Café, upstairs");
+    }
+
+    #[test]
+    fn json_has_timestamp_and_networks() {
+        let mut out = Vec::new();
+        write_json(&mut out, &networks(), 1_700_000_000).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json["timestamp"], 1_700_000_000);
+        assert_eq!(json["networks"].as_array().unwrap().len(), 2);
+        assert_eq!(json["networks"][0]["essid"], "Home");
+        assert_eq!(json["networks"][0]["security"], "Unknown");
+    }
+
+    #[test]
+    fn write_errors_are_reported() {
+        let path = Path::new("/definitely/not/a/folder/scan.csv");
+        assert!(export_networks(&networks(), ExportFormat::Csv, path).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use crate::scanner::WifiNetwork;
+use crate::signal::compare_signal;
+
+/// One row of the networks table.
+pub struct NetworkGroup<'a> {
+    /// The access point shown in the row: the one with the strongest signal.
+    pub network: &'a WifiNetwork,
+
+    /// Every access point in the group, including `network`, in scan order.
+    pub members: Vec<&'a WifiNetwork>,
+}
+
+/// One group per network, i.e. no grouping.
+pub fn ungrouped(networks: &[WifiNetwork]) -> Vec<NetworkGroup<'_>> {
+    networks
+        .iter()
+        .map(|network| NetworkGroup {
+            network,
+            members: vec![network],
+        })
+        .collect()
+}
+
+/// Collapses access points sharing an ESSID (e.g. a mesh network) into one group each,
+/// in the order their ESSIDs first appear.
+///
+/// Networks without a name are never grouped, since they are not necessarily the same network.
+pub fn group_by_ssid(networks: &[WifiNetwork]) -> Vec<NetworkGroup<'_>> {
+    let mut groups: Vec<NetworkGroup<'_>> = Vec::new();
+    let mut index_of_essid: HashMap<&str, usize> = HashMap::new();
+
+    for network in networks {
+        let essid = network.essid.as_str();
+        let named = !essid.is_empty() && essid != "Hidden";
+
+        if let Some(&index) = index_of_essid.get(essid).filter(|_| named) {
+            let group = &mut groups[index];
+            group.members.push(network);
+            if compare_signal(network, group.network, true).is_lt() {
+                group.network = network;
+            }
+        } else {
+            if named {
+                index_of_essid.insert(essid, groups.len());
+            }
+            groups.push(NetworkGroup {
+                network,
+                members: vec![network],
+            });
+        }
+    }
+
+    groups
+}
+
+/// Is `network` the one we are connected to, going by its ESSID?
+pub fn is_connected(network: &WifiNetwork, connected_ssid: Option<&str>) -> bool {
+    connected_ssid.is_some_and(|ssid| !ssid.is_empty() && network.essid == ssid)
+}
+
+/// Moves the groups of the connected network to the top, keeping the order otherwise.
+pub fn connected_first(groups: &mut [NetworkGroup<'_>], connected_ssid: Option<&str>) {
+    groups.sort_by_key(|group| !is_connected(group.network, connected_ssid));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(essid: &str, address: &str, signal_level: &str) -> WifiNetwork {
+        WifiNetwork {
+            essid: essid.to_owned(),
+            address: address.to_owned(),
+            signal_level: signal_level.to_owned(),
+            ..WifiNetwork::not_found()
+        }
+    }
+
+    #[test]
+    fn same_essid_collapses_to_strongest() {
+        let networks = vec![
+            network("Office", "00:00:00:00:00:01", "-70"),
+            network("Guest", "00:00:00:00:00:02", "-60"),
+            network("Office", "00:00:00:00:00:03", "-45"),
+            network("Office", "00:00:00:00:00:04", "-80"),
+        ];
+
+        let groups = group_by_ssid(&networks);
+        assert_eq!(groups.len(), 2);
+
+        let office = &groups[0];
+        assert_eq!(office.network.address, "00:00:00:00:00:03");
+        let addresses: Vec<_> = office.members.iter().map(|n| n.address.as_str()).collect();
+        assert_eq!(
+            addresses,
+            [
+                "00:00:00:00:00:01",
+                "00:00:00:00:00:03",
+                "00:00:00:00:00:04"
+            ]
+        );
+
+        assert_eq!(groups[1].members.len(), 1);
+    }
+
+    #[test]
+    fn hidden_networks_are_not_grouped() {
+        let networks = vec![
+            network("", "00:00:00:00:00:01", "-70"),
+            network("", "00:00:00:00:00:02", "-60"),
+        ];
+        assert_eq!(group_by_ssid(&networks).len(), 2);
+        assert_eq!(ungrouped(&networks).len(), 2);
+    }
+
+    #[test]
+    fn the_connected_network_is_flagged_and_pinned() {
+        let networks = vec![
+            network("Guest", "00:00:00:00:00:01", "-40"),
+            network("Office", "00:00:00:00:00:02", "-70"),
+            network("Cafe", "00:00:00:00:00:03", "-50"),
+        ];
+        let flags: Vec<_> = networks
+            .iter()
+            .map(|network| is_connected(network, Some("Office")))
+            .collect();
+        assert_eq!(flags, [false, true, false]);
+
+        let mut groups = ungrouped(&networks);
+        connected_first(&mut groups, Some("Office"));
+        let essids: Vec<_> = groups.iter().map(|g| g.network.essid.as_str()).collect();
+        assert_eq!(essids, ["Office", "Guest", "Cafe"]);
+
+        // Not connected, or to a network out of range: nothing moves.
+        for connected_ssid in [None, Some("Home")] {
+            let mut groups = ungrouped(&networks);
+            connected_first(&mut groups, connected_ssid);
+            assert!(groups.iter().all(|g| !is_connected(g.network, connected_ssid)));
+            assert_eq!(groups[0].network.essid, "Guest");
+        }
+    }
+
+    #[test]
+    fn hidden_networks_are_never_connected() {
+        let hidden = network("", "00:00:00:00:00:01", "-40");
+        assert!(!is_connected(&hidden, Some("")));
+    }
+}
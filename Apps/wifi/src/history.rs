@@ -0,0 +1,81 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::scanner::WifiNetwork;
+use crate::signal::signal_percent;
+
+/// How many samples are kept per access point.
+pub const HISTORY_LEN: usize = 60;
+
+/// Recent signal strengths (in percent) of each access point, keyed by BSSID.
+#[derive(Default)]
+pub struct SignalHistory {
+    samples: HashMap<String, VecDeque<f32>>,
+}
+
+impl SignalHistory {
+    /// Adds a sample for every network of a completed scan.
+    pub fn record(&mut self, networks: &[WifiNetwork]) {
+        for network in networks {
+            if let Some(percent) = signal_percent(network) {
+                let samples = self.samples.entry(network.address.clone()).or_default();
+                push_capped(samples, percent, HISTORY_LEN);
+            }
+        }
+    }
+
+    /// Oldest first.
+    pub fn samples(&self, bssid: &str) -> Option<&VecDeque<f32>> {
+        self.samples.get(bssid)
+    }
+}
+
+/// Appends `value`, dropping the oldest values to keep at most `cap`.
+pub fn push_capped(samples: &mut VecDeque<f32>, value: f32, cap: usize) {
+    if cap == 0 {
+        samples.clear();
+        return;
+    }
+    while cap <= samples.len() {
+        samples.pop_front();
+    }
+    samples.push_back(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_drops_oldest_beyond_cap() {
+        let mut samples = VecDeque::new();
+        for value in 0..5 {
+            push_capped(&mut samples, value as f32, 3);
+        }
+        assert_eq!(samples, [2.0, 3.0, 4.0]);
+
+        push_capped(&mut samples, 5.0, 0);
+        assert!(samples.is_empty());
+    }
+
+    #[test]
+    fn records_by_bssid() {
+        let network = |address: &str, signal_level: &str| WifiNetwork {
+            address: address.to_owned(),
+            signal_level: signal_level.to_owned(),
+            ..WifiNetwork::not_found()
+        };
+
+        let mut history = SignalHistory::default();
+        history.record(&[network("aa", "-50"), network("bb", "-100")]);
+        history.record(&[network("aa", "-75"), network("bb", "Not found")]);
+
+        assert_eq!(history.samples("aa").unwrap(), &[100.0, 50.0]);
+        assert_eq!(history.samples("bb").unwrap(), &[0.0]);
+        assert!(history.samples("cc").is_none());
+
+        for _ in 0..2 * HISTORY_LEN {
+            history.record(&[network("aa", "-60")]);
+        }
+        assert_eq!(history.samples("aa").unwrap().len(), HISTORY_LEN);
+    }
+}
@@ -1,18 +1,57 @@
-use std::process::Command;
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use eframe::egui;
 use eframe::egui::{vec2, Button};
 
+mod backend;
+mod band;
+mod export;
+mod group;
+mod history;
+mod refresh;
 mod scanner;
+mod security;
+mod signal;
 
-use scanner::{display_wifi_networks, parse_wifi_scan_output, WifiNetwork};
+use backend::WifiBackend;
+use export::{export_networks, ExportFormat};
+use history::SignalHistory;
+use refresh::{AutoRefresh, REFRESH_INTERVALS};
+use scanner::{display_wifi_networks, TableOptions, WifiNetwork};
 
 pub struct WifiScannerApp {
     wifi_networks: Arc<Mutex<Vec<WifiNetwork>>>,
     scanning: Arc<Mutex<bool>>,
     scan_error: Arc<Mutex<Option<String>>>,
+    auto_refresh: AutoRefresh,
+    connected_ssid: Arc<Mutex<Option<String>>>,
+    /// What is being done to the connection (e.g. "Connecting..."), while it is.
+    connecting: Arc<Mutex<Option<&'static str>>>,
+    /// The outcome of the last connect, disconnect or forget, as a message for the user.
+    connect_status: Arc<Mutex<Option<Result<String, String>>>>,
+    password_prompt: Option<PasswordPrompt>,
+    /// The SSID whose saved connection the user was asked to confirm forgetting.
+    forget_prompt: Option<String>,
+    table_options: TableOptions,
+    export_dialog: Option<ExportDialog>,
+    /// Recorded after every scan while auto refresh is on.
+    signal_history: Arc<Mutex<SignalHistory>>,
+}
+
+/// The dialog for saving the current scan results to a file.
+struct ExportDialog {
+    path: String,
+    format: ExportFormat,
+    /// The outcome of the last export, as a message for the user.
+    status: Option<Result<String, String>>,
+}
+
+/// The dialog asking for the password of a secured network.
+struct PasswordPrompt {
+    ssid: String,
+    password: String,
 }
 
 impl Default for WifiScannerApp {
@@ -21,52 +60,331 @@ impl Default for WifiScannerApp {
             wifi_networks: Arc::new(Mutex::new(Vec::new())),
             scanning: Arc::new(Mutex::new(false)),
             scan_error: Arc::new(Mutex::new(None)),
+            auto_refresh: AutoRefresh::default(),
+            connected_ssid: Arc::new(Mutex::new(None)),
+            connecting: Arc::new(Mutex::new(None)),
+            connect_status: Arc::new(Mutex::new(None)),
+            password_prompt: None,
+            forget_prompt: None,
+            table_options: TableOptions::default(),
+            export_dialog: None,
+            signal_history: Arc::new(Mutex::new(SignalHistory::default())),
         }
     }
 }
 
 impl WifiScannerApp {
-    pub fn scan_wifi_networks(&self) {
+    /// Starts a scan in the background, unless one is already running.
+    pub fn scan_wifi_networks(&mut self, ctx: &egui::Context) {
+        {
+            let mut scanning = self.scanning.lock().unwrap();
+            if *scanning {
+                return;
+            }
+            *scanning = true;
+        }
+        self.auto_refresh.last_scan = Some(Instant::now());
+
         let wifi_networks = Arc::clone(&self.wifi_networks);
         let scanning = Arc::clone(&self.scanning);
         let scan_error = Arc::clone(&self.scan_error);
-
-        let wifi_adapter = "wlp3s0".to_string();
+        let connected_ssid = Arc::clone(&self.connected_ssid);
+        let signal_history = self
+            .auto_refresh
+            .enabled
+            .then(|| Arc::clone(&self.signal_history));
+        let ctx = ctx.clone();
 
         thread::spawn(move || {
-            *scanning.lock().unwrap() = true;
-            match Command::new("./wifi/test/src/sudo_wrapper.sh")
-                .arg("iwlist")
-                .arg(&wifi_adapter)
-                .arg("scan")
-                .output()
-            {
-                Ok(output) => {
-                    let output_str = String::from_utf8_lossy(&output.stdout);
-                    let networks = parse_wifi_scan_output(&output_str);
-
+            let backend = backend::default_backend();
+            *connected_ssid.lock().unwrap() = backend.connected_ssid();
+            match backend.scan() {
+                Ok(networks) => {
+                    if let Some(signal_history) = signal_history {
+                        signal_history.lock().unwrap().record(&networks);
+                    }
                     let mut wifi_networks = wifi_networks.lock().unwrap();
                     *wifi_networks = networks;
                     *scan_error.lock().unwrap() = None;
                 }
                 Err(e) => {
-                    *scan_error.lock().unwrap() = Some(format!("Failed to execute scan command: {}", e));
+                    *scan_error.lock().unwrap() = Some(e.to_string());
                     let mut wifi_networks = wifi_networks.lock().unwrap();
                     *wifi_networks = Vec::new(); // Clear the list on failure
                 }
             }
             *scanning.lock().unwrap() = false;
+            ctx.request_repaint();
+        });
+    }
+
+    fn auto_refresh_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.auto_refresh.enabled, "Auto refresh");
+        ui.add_enabled_ui(self.auto_refresh.enabled, |ui| {
+            egui::ComboBox::from_id_source("refresh_interval")
+                .selected_text(format!("{}s", self.auto_refresh.interval.as_secs()))
+                .show_ui(ui, |ui| {
+                    for interval in REFRESH_INTERVALS {
+                        ui.selectable_value(
+                            &mut self.auto_refresh.interval,
+                            interval,
+                            format!("{}s", interval.as_secs()),
+                        );
+                    }
+                });
+        });
+    }
+
+    /// Runs `change` on the backend in the background, unless another change is running,
+    /// then shows its outcome and the network connected to after it.
+    fn change_connection(
+        &self,
+        ctx: &egui::Context,
+        busy: &'static str,
+        change: impl FnOnce(&dyn WifiBackend) -> Result<String, String> + Send + 'static,
+    ) {
+        {
+            let mut connecting = self.connecting.lock().unwrap();
+            if connecting.is_some() {
+                return;
+            }
+            *connecting = Some(busy);
+        }
+
+        let connecting = Arc::clone(&self.connecting);
+        let connect_status = Arc::clone(&self.connect_status);
+        let connected_ssid = Arc::clone(&self.connected_ssid);
+        let ctx = ctx.clone();
+
+        thread::spawn(move || {
+            let backend = backend::default_backend();
+            let status = change(backend.as_ref());
+            *connected_ssid.lock().unwrap() = backend.connected_ssid();
+            *connect_status.lock().unwrap() = Some(status);
+            *connecting.lock().unwrap() = None;
+            ctx.request_repaint();
+        });
+    }
+
+    /// Joins `ssid` in the background. The password is never logged or shown.
+    fn connect_to_network(&self, ctx: &egui::Context, ssid: String, password: Option<String>) {
+        self.change_connection(ctx, "Connecting...", move |backend| {
+            match backend.connect(&ssid, password.as_deref()) {
+                Ok(()) => Ok(format!("Connected to {ssid}")),
+                Err(e) => Err(format!("Failed to connect to {ssid}: {e}")),
+            }
+        });
+    }
+
+    /// Leaves the current network in the background.
+    fn disconnect(&self, ctx: &egui::Context) {
+        self.change_connection(ctx, "Disconnecting...", |backend| {
+            match backend.disconnect() {
+                Ok(()) => Ok("Disconnected".to_owned()),
+                Err(e) => Err(format!("Failed to disconnect: {e}")),
+            }
+        });
+    }
+
+    /// Deletes the saved connection of `ssid` in the background.
+    fn forget_network(&self, ctx: &egui::Context, ssid: String) {
+        self.change_connection(ctx, "Forgetting...", move |backend| {
+            match backend.forget(&ssid) {
+                Ok(()) => Ok(format!("Forgot {ssid}")),
+                Err(e) => Err(format!("Failed to forget {ssid}: {e}")),
+            }
         });
     }
 
-    fn display_wifi_table(&self, ui: &mut egui::Ui) {
-        let wifi_networks = self.wifi_networks.lock().unwrap();
-        display_wifi_networks(ui, &wifi_networks);
+    /// The network connected to, with buttons to leave or forget it.
+    fn connection_ui(&mut self, ui: &mut egui::Ui) {
+        let connected_ssid = self.connected_ssid.lock().unwrap().clone();
+        let idle = self.connecting.lock().unwrap().is_none();
+        ui.horizontal(|ui| {
+            match &connected_ssid {
+                Some(ssid) => ui.label(format!("Connected to {ssid}")),
+                None => ui.label("Not connected"),
+            };
+            if ui
+                .add_enabled(idle && connected_ssid.is_some(), Button::new("Disconnect"))
+                .clicked()
+            {
+                self.disconnect(ui.ctx());
+            }
+            if ui
+                .add_enabled(idle && connected_ssid.is_some(), Button::new("Forget..."))
+                .on_hover_text("Delete the saved password, so it isn't joined again by itself")
+                .clicked()
+            {
+                self.forget_prompt = connected_ssid;
+            }
+        });
+    }
+
+    fn display_wifi_table(&mut self, ui: &mut egui::Ui) {
+        let connect_to = {
+            let wifi_networks = self.wifi_networks.lock().unwrap();
+            let connected_ssid = self.connected_ssid.lock().unwrap();
+            let signal_history = self.signal_history.lock().unwrap();
+            display_wifi_networks(
+                ui,
+                &wifi_networks,
+                connected_ssid.as_deref(),
+                &signal_history,
+                &mut self.table_options,
+            )
+        };
+
+        if let Some(network) = connect_to {
+            if network.is_open() {
+                self.connect_to_network(ui.ctx(), network.essid, None);
+            } else {
+                self.password_prompt = Some(PasswordPrompt {
+                    ssid: network.essid,
+                    password: String::new(),
+                });
+            }
+        }
+
+        if let Some(busy) = *self.connecting.lock().unwrap() {
+            ui.horizontal(|ui| {
+                ui.add(egui::Spinner::new());
+                ui.label(busy);
+            });
+        } else {
+            match &*self.connect_status.lock().unwrap() {
+                Some(Ok(message)) => {
+                    ui.label(egui::RichText::new(message).color(egui::Color32::GREEN));
+                }
+                Some(Err(message)) => {
+                    ui.label(egui::RichText::new(message).color(egui::Color32::RED));
+                }
+                None => {}
+            }
+        }
 
         if let Some(ref error) = *self.scan_error.lock().unwrap() {
             ui.label(egui::RichText::new(error).color(egui::Color32::RED));
         }
     }
+
+    fn export_dialog_ui(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.export_dialog else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new("Export")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for format in ExportFormat::ALL {
+                        if ui
+                            .radio_value(&mut dialog.format, format, format.label())
+                            .changed()
+                        {
+                            let path = std::path::Path::new(&dialog.path);
+                            dialog.path = path
+                                .with_extension(format.extension())
+                                .to_string_lossy()
+                                .into_owned();
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Path:");
+                    ui.text_edit_singleline(&mut dialog.path);
+                });
+
+                if ui.button("Save").clicked() {
+                    let networks = self.wifi_networks.lock().unwrap();
+                    let path = std::path::Path::new(&dialog.path);
+                    let count = networks.len();
+                    dialog.status = Some(match export_networks(&networks, dialog.format, path) {
+                        Ok(()) => Ok(format!("Saved {count} networks to {}", dialog.path)),
+                        Err(e) => Err(format!("Failed to write {}: {e}", dialog.path)),
+                    });
+                }
+
+                match &dialog.status {
+                    Some(Ok(message)) => {
+                        ui.label(egui::RichText::new(message).color(egui::Color32::GREEN));
+                    }
+                    Some(Err(message)) => {
+                        ui.label(egui::RichText::new(message).color(egui::Color32::RED));
+                    }
+                    None => {}
+                }
+            });
+
+        if !open {
+            self.export_dialog = None;
+        }
+    }
+
+    fn password_prompt_ui(&mut self, ctx: &egui::Context) {
+        let Some(prompt) = &mut self.password_prompt else {
+            return;
+        };
+
+        let mut open = true;
+        let mut submitted = false;
+        egui::Window::new(format!("Connect to {}", prompt.ssid))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Password:");
+                let response =
+                    ui.add(egui::TextEdit::singleline(&mut prompt.password).password(true));
+                response.request_focus();
+                let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Connect").clicked() || enter {
+                    submitted = true;
+                }
+            });
+
+        if submitted {
+            if let Some(prompt) = self.password_prompt.take() {
+                self.connect_to_network(ctx, prompt.ssid, Some(prompt.password));
+            }
+        } else if !open {
+            self.password_prompt = None;
+        }
+    }
+
+    fn forget_prompt_ui(&mut self, ctx: &egui::Context) {
+        let Some(ssid) = &self.forget_prompt else {
+            return;
+        };
+
+        let mut open = true;
+        let mut confirmed = false;
+        let mut canceled = false;
+        egui::Window::new("Forget network")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Forget {ssid}? Its saved password is deleted, and it has to be entered again to connect."
+                ));
+                ui.horizontal(|ui| {
+                    confirmed = ui.button("Forget").clicked();
+                    canceled = ui.button("Cancel").clicked();
+                });
+            });
+
+        if confirmed {
+            if let Some(ssid) = self.forget_prompt.take() {
+                self.forget_network(ctx, ssid);
+            }
+        } else if canceled || !open {
+            self.forget_prompt = None;
+        }
+    }
 }
 
 impl eframe::App for WifiScannerApp {
@@ -74,23 +392,69 @@ impl eframe::App for WifiScannerApp {
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("WiFi Scanner");
 
-            if *self.scanning.lock().unwrap() {
-                ui.horizontal(|ui| {
+            ui.horizontal(|ui| {
+                if *self.scanning.lock().unwrap() {
                     ui.add(egui::Spinner::new());
                     ui.label("Scanning...");
-                });
-            } else if ui.add_sized(vec2(50.0, 24.0), Button::new("🖧 Scan")).clicked() {
-                self.scan_wifi_networks();
-            }
+                } else if ui
+                    .add_sized(vec2(50.0, 24.0), Button::new("🖧 Scan"))
+                    .clicked()
+                {
+                    self.scan_wifi_networks(ctx);
+                }
+
+                ui.separator();
+                self.auto_refresh_ui(ui);
+
+                ui.separator();
+                ui.checkbox(&mut self.table_options.group_by_ssid, "Group by SSID");
 
+                ui.separator();
+                let mut timeout = backend::command_timeout().as_secs();
+                ui.label("Timeout:");
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut timeout)
+                            .range(1..=300)
+                            .suffix(" s"),
+                    )
+                    .changed()
+                {
+                    backend::set_command_timeout(Duration::from_secs(timeout));
+                }
+
+                ui.separator();
+                let has_results = !self.wifi_networks.lock().unwrap().is_empty();
+                if ui
+                    .add_enabled(has_results, Button::new("💾 Export"))
+                    .clicked()
+                {
+                    self.export_dialog = Some(ExportDialog {
+                        path: format!("wifi_scan.{}", ExportFormat::Csv.extension()),
+                        format: ExportFormat::Csv,
+                        status: None,
+                    });
+                }
+            });
+
+            self.connection_ui(ui);
             self.display_wifi_table(ui);
         });
 
-        ctx.request_repaint(); // Ensure the UI is constantly refreshed
+        self.password_prompt_ui(ctx);
+        self.forget_prompt_ui(ctx);
+        self.export_dialog_ui(ctx);
+
+        if let Some(remaining) = self.auto_refresh.time_until_due(Instant::now()) {
+            if remaining.is_zero() {
+                self.scan_wifi_networks(ctx);
+            } else {
+                ctx.request_repaint_after(remaining);
+            }
+        }
     }
 }
 
-
 fn main() -> eframe::Result {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([450.0, 450.0]),
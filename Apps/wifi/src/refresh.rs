@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+/// The intervals offered in the "Auto refresh" combo box.
+pub const REFRESH_INTERVALS: [Duration; 3] = [
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+];
+
+/// Periodically re-runs the scan while enabled.
+pub struct AutoRefresh {
+    pub enabled: bool,
+    pub interval: Duration,
+
+    /// When the last scan was started, if any.
+    pub last_scan: Option<Instant>,
+}
+
+impl Default for AutoRefresh {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: REFRESH_INTERVALS[1],
+            last_scan: None,
+        }
+    }
+}
+
+impl AutoRefresh {
+    /// How long until the next scan is due, or `None` if auto refresh is off.
+    ///
+    /// Returns [`Duration::ZERO`] if a scan is due now.
+    pub fn time_until_due(&self, now: Instant) -> Option<Duration> {
+        if !self.enabled {
+            return None;
+        }
+        Some(time_until_rescan(self.last_scan, self.interval, now))
+    }
+}
+
+/// How long until a rescan is due, given when the last scan started.
+///
+/// A scan is due right away if there never was one.
+pub fn time_until_rescan(last_scan: Option<Instant>, interval: Duration, now: Instant) -> Duration {
+    last_scan.map_or(Duration::ZERO, |last_scan| {
+        (last_scan + interval).saturating_duration_since(now)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rescan_is_due_once_the_interval_has_passed() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(10);
+
+        assert_eq!(time_until_rescan(None, interval, start), Duration::ZERO);
+        assert_eq!(
+            time_until_rescan(Some(start), interval, start + Duration::from_secs(4)),
+            Duration::from_secs(6)
+        );
+        assert_eq!(
+            time_until_rescan(Some(start), interval, start + interval),
+            Duration::ZERO
+        );
+        assert_eq!(
+            time_until_rescan(Some(start), interval, start + Duration::from_secs(60)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn disabled_auto_refresh_is_never_due() {
+        let auto_refresh = AutoRefresh::default();
+        assert_eq!(auto_refresh.time_until_due(Instant::now()), None);
+    }
+}
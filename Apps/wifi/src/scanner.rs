@@ -1,8 +1,16 @@
 use eframe::egui;
 use egui_extras::TableBuilder;
 use regex::Regex;
+use serde::Serialize;
 use eframe::egui::{popup_below_widget, vec2, Button, Id, PopupCloseBehavior};
 
+use crate::band::frequency_band;
+use crate::group::{connected_first, group_by_ssid, is_connected, ungrouped};
+use crate::history::SignalHistory;
+use crate::security::Security;
+use crate::signal::{compare_signal, signal_dbm, signal_percent, SignalBucket};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct WifiNetwork {
     pub address: String,
     pub channel: String,
@@ -10,12 +18,38 @@ pub struct WifiNetwork {
     pub quality: String,
     pub signal_level: String,
     pub encryption_key: String,
+    pub security: Security,
     pub essid: String,
     pub bit_rates: String,
     pub mode: String,
     pub extra: String,
 }
 
+impl WifiNetwork {
+    /// A network with every field set to "Not found", to be filled in by a parser.
+    pub fn not_found() -> Self {
+        let default = "Not found".to_owned();
+        Self {
+            address: default.clone(),
+            channel: default.clone(),
+            frequency: default.clone(),
+            quality: default.clone(),
+            signal_level: default.clone(),
+            encryption_key: default.clone(),
+            security: Security::Unknown,
+            essid: default.clone(),
+            bit_rates: default.clone(),
+            mode: default.clone(),
+            extra: default,
+        }
+    }
+
+    /// Can we join this network without a password?
+    pub fn is_open(&self) -> bool {
+        self.security == Security::Open
+    }
+}
+
 pub fn parse_wifi_scan_output(output: &str) -> Vec<WifiNetwork> {
     let mut networks = Vec::new();
     let cells = output.split("Cell").skip(1);
@@ -105,6 +139,7 @@ pub fn parse_wifi_scan_output(output: &str) -> Vec<WifiNetwork> {
             quality,
             signal_level,
             encryption_key,
+            security: Security::from_iwlist_cell(cell),
             essid,
             bit_rates,
             mode: mode.clone(),
@@ -116,32 +151,83 @@ pub fn parse_wifi_scan_output(output: &str) -> Vec<WifiNetwork> {
 }
 
 
-// Function to display WiFi networks using egui and return if the table is not empty
-pub fn display_wifi_networks(ui: &mut egui::Ui, networks: &[WifiNetwork]) -> bool {
+/// How the networks table is laid out.
+pub struct TableOptions {
+    pub strongest_first: bool,
+
+    /// Show access points with the same ESSID as a single row.
+    pub group_by_ssid: bool,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        Self {
+            strongest_first: true,
+            group_by_ssid: false,
+        }
+    }
+}
+
+// Function to display WiFi networks using egui and return the network whose "Connect" button was clicked
+pub fn display_wifi_networks(
+    ui: &mut egui::Ui,
+    networks: &[WifiNetwork],
+    connected_ssid: Option<&str>,
+    history: &SignalHistory,
+    options: &mut TableOptions,
+) -> Option<WifiNetwork> {
     if networks.is_empty() {
-        return false;
+        return None;
     }
 
+    let mut connect_to = None;
+    let mut groups = if options.group_by_ssid {
+        group_by_ssid(networks)
+    } else {
+        ungrouped(networks)
+    };
+    groups.sort_by(|a, b| compare_signal(a.network, b.network, options.strongest_first));
+    connected_first(&mut groups, connected_ssid);
+    let strongest_first = &mut options.strongest_first;
+
     let table = TableBuilder::new(ui)
         .striped(true)
         .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
         .min_scrolled_height(0.0);
 
     table
+        .column(egui_extras::Column::auto())
+        .column(egui_extras::Column::auto())
+        .column(egui_extras::Column::auto())
         .column(egui_extras::Column::auto())
         .column(egui_extras::Column::auto())
         .column(egui_extras::Column::auto())
         .header(20.0, |mut header| {
             header.col(|ui| { ui.strong("ESSID"); });
             header.col(|ui| { ui.strong("BSSID"); });
-            header.col(|ui| { ui.strong("Signal Level"); });
+            header.col(|ui| { ui.strong("Band"); });
+            header.col(|ui| { ui.strong("Security"); });
+            header.col(|ui| {
+                let arrow = if *strongest_first { "⏷" } else { "⏶" };
+                if ui.button(egui::RichText::new(format!("Signal Level {arrow}")).strong()).clicked() {
+                    *strongest_first = !*strongest_first;
+                }
+            });
+            header.col(|_ui| {});
         })
 
         .body(|mut body| {
-            for network in networks {
+            for group in groups {
+                let network = group.network;
                 body.row(20.0, |mut row| {
+                    let is_connected = is_connected(network, connected_ssid);
                     row.col(|ui| {
-                        let response =  ui.button(check_name(&network.essid));
+                        let name = check_name(&network.essid);
+                        let response = if is_connected {
+                            ui.button(egui::RichText::new(format!("✔ {name}")).strong()).on_hover_text("Connected")
+                        } else {
+                            ui.button(name)
+                        };
 
                         let popup_id = Id::new(format!("{}/{}", network.address, network.essid));
 
@@ -165,22 +251,46 @@ pub fn display_wifi_networks(ui: &mut egui::Ui, networks: &[WifiNetwork]) -> boo
                                     ui.separator();
                                     ui.label(format!("Encryption Key: {}", normalize_extra_text(&*network.encryption_key)));
                                     ui.separator();
+                                    ui.label(format!("Security: {}", network.security));
+                                    ui.separator();
                                     ui.label(format!("Channel: {}", normalize_extra_text(&*network.channel)));
                                     ui.separator();
                                     ui.label(format!("Bit Rates: {}", normalize_extra_text(&*network.bit_rates)));
                                     ui.separator();
                                     ui.label(format!("Extra: {}", normalize_extra_text(&*network.extra)));
+                                    ui.separator();
+                                    show_signal_history(ui, history.samples(&network.address));
+                                    if group.members.len() > 1 {
+                                        ui.separator();
+                                        ui.label("Access Points:");
+                                        for member in &group.members {
+                                            ui.label(format!("{} (channel {})", member.address, member.channel));
+                                        }
+                                    }
                                 })
                             },
                         );
                     });
-                    row.col(|ui| { ui.label(&network.address); });
-                    row.col(|ui| { ui.label(show_quality(&network.quality)); });
+                    row.col(|ui| {
+                        if group.members.len() > 1 {
+                            ui.label(format!("{} (+{})", network.address, group.members.len() - 1));
+                        } else {
+                            ui.label(&network.address);
+                        }
+                    });
+                    row.col(|ui| { ui.label(frequency_band(&network.frequency)); });
+                    row.col(|ui| { ui.label(network.security.label()); });
+                    row.col(|ui| { show_signal(ui, network); });
+                    row.col(|ui| {
+                        if !is_connected && ui.button("Connect").clicked() {
+                            connect_to = Some(network.clone());
+                        }
+                    });
                 });
             }
         });
 
-    true
+    connect_to
 }
 
 fn normalize_extra_text(extra: &str) -> String {
@@ -199,20 +309,66 @@ fn normalize_extra_text(extra: &str) -> String {
     result
 }
 
-fn show_quality(input: &str) -> String {
-    let parts: Vec<&str> = input.split('/').collect();
-    if let Some(first_part) = parts.get(0) {
-        if let Ok(value) = first_part.trim().parse::<f32>() {
-            if value > 52.5 { "📶 Excellent".to_string() }
-            else if value > 35.0 { "📶 Good".to_string() }
-            else if value > 17.5 { "📶 Fair".to_string() }
-            else if value > 0.0 { "📶 Poor".to_string() }
-            else { "📶 No Signal".to_string() }
-        } else {
-            "📶 Invalid Input".to_string()
-        }
-    } else {
-        "📶 No Signal".to_string()
+fn show_signal(ui: &mut egui::Ui, network: &WifiNetwork) {
+    let Some(percent) = signal_percent(network) else {
+        ui.label("📶 Invalid Input");
+        return;
+    };
+
+    let bucket = SignalBucket::from_percent(percent);
+    let color = signal_color(bucket);
+    ui.add(
+        egui::ProgressBar::new(percent / 100.0)
+            .desired_width(40.0)
+            .desired_height(8.0)
+            .fill(color),
+    );
+    let hover = match signal_dbm(network) {
+        Some((dbm, true)) => format!("{percent:.0}%, about {dbm:.0} dBm"),
+        Some((dbm, false)) => format!("{percent:.0}%, {dbm:.0} dBm"),
+        None => format!("{percent:.0}%"),
+    };
+    ui.label(egui::RichText::new(format!("📶 {}", bucket.label())).color(color))
+        .on_hover_text(hover);
+}
+
+/// A sparkline of the recent signal samples of an access point, 0% at the bottom and 100% at the top.
+fn show_signal_history(ui: &mut egui::Ui, samples: Option<&std::collections::VecDeque<f32>>) {
+    let Some(samples) = samples.filter(|samples| 2 <= samples.len()) else {
+        ui.label("Signal history: turn on auto refresh to record it");
+        return;
+    };
+
+    ui.label(format!("Signal history (last {} scans):", samples.len()));
+    let (rect, _) = ui.allocate_exact_size(vec2(200.0, 40.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let step = rect.width() / (samples.len() - 1) as f32;
+    let points: Vec<egui::Pos2> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, percent)| {
+            egui::pos2(
+                rect.left() + i as f32 * step,
+                rect.bottom() - rect.height() * percent / 100.0,
+            )
+        })
+        .collect();
+
+    let latest = *samples.back().unwrap_or(&0.0);
+    let color = signal_color(SignalBucket::from_percent(latest));
+    painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+}
+
+/// Red for a poor signal through yellow to green for an excellent one.
+fn signal_color(bucket: SignalBucket) -> egui::Color32 {
+    match bucket {
+        SignalBucket::Excellent => egui::Color32::from_rgb(60, 180, 75),
+        SignalBucket::Good => egui::Color32::from_rgb(160, 200, 50),
+        SignalBucket::Fair => egui::Color32::from_rgb(230, 190, 40),
+        SignalBucket::Poor => egui::Color32::from_rgb(220, 60, 50),
+        SignalBucket::NoSignal => egui::Color32::GRAY,
     }
 }
 
@@ -223,4 +379,27 @@ fn check_name(name: &str) -> String
     } else {
         name.to_string()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_goes_by_the_parsed_security() {
+        let network = |security| WifiNetwork {
+            security,
+            ..WifiNetwork::not_found()
+        };
+        assert!(network(Security::Open).is_open());
+        assert!(!network(Security::Wpa2).is_open());
+        assert!(!network(Security::Unknown).is_open());
+
+        // What the raw field says doesn't matter once it's been parsed.
+        let off = WifiNetwork {
+            encryption_key: "off".to_owned(),
+            ..network(Security::Wep)
+        };
+        assert!(!off.is_open());
+    }
+}
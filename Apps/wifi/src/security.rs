@@ -0,0 +1,284 @@
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+/// What it takes to join a network, from weakest to strongest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Security {
+    Open,
+
+    /// Opportunistic Wireless Encryption ("Enhanced Open"): no password, but encrypted.
+    Owe,
+    Wep,
+    Wpa,
+    Wpa2,
+
+    /// WPA3 transition mode: accepts both WPA2 (PSK) and WPA3 (SAE) clients.
+    Wpa2Wpa3,
+    Wpa3,
+    Unknown,
+}
+
+impl Security {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Open => "Open",
+            Self::Owe => "OWE",
+            Self::Wep => "WEP",
+            Self::Wpa => "WPA",
+            Self::Wpa2 => "WPA2",
+            Self::Wpa2Wpa3 => "WPA2/WPA3",
+            Self::Wpa3 => "WPA3",
+            Self::Unknown => "Unknown",
+        }
+    }
+
+    /// Classifies a free-form security description, as reported by `nmcli` ("WPA2 WPA3"),
+    /// `netsh` ("WPA3-Personal") or `airport` ("WPA2(PSK/AES/AES) WPA3(SAE/AES/AES)").
+    pub fn from_description(description: &str) -> Self {
+        let description = description.to_ascii_uppercase();
+        let has = |name: &str| description.contains(name);
+
+        if description.trim().is_empty()
+            || matches!(description.trim(), "--" | "NONE" | "OPEN" | "OFF")
+        {
+            Self::Open
+        } else if has("OWE") {
+            Self::Owe
+        } else if has("WPA3") || has("SAE") {
+            if has("WPA2") || has("PSK") {
+                Self::Wpa2Wpa3
+            } else {
+                Self::Wpa3
+            }
+        } else if has("WPA2") || has("RSN") {
+            Self::Wpa2
+        } else if has("WPA") {
+            Self::Wpa
+        } else if has("WEP") {
+            Self::Wep
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// Classifies an `iwlist scan` cell from its "Encryption key" line and its
+    /// WPA / RSN ("IEEE 802.11i/WPA2") information elements.
+    ///
+    /// `iwlist` predates WPA3 and prints the SAE and OWE key management suites as
+    /// "unknown (8)" and "unknown (18)"; newer builds may print their names.
+    pub fn from_iwlist_cell(cell: &str) -> Self {
+        let mut encryption = None;
+        let mut has_wpa = false;
+        let mut has_rsn = false;
+        let mut in_rsn = false;
+        let mut rsn_suites = Vec::new();
+
+        for line in cell.lines().map(str::trim) {
+            if let Some(key) = line.strip_prefix("Encryption key:") {
+                encryption = Some(key.trim() == "on");
+            } else if let Some(element) = line.strip_prefix("IE:") {
+                let element = element.trim();
+                in_rsn = element.starts_with("IEEE 802.11i/WPA2");
+                has_rsn |= in_rsn;
+                has_wpa |= element.starts_with("WPA Version");
+            } else if in_rsn {
+                if let Some((key, suites)) = line.split_once(':') {
+                    if key.trim().starts_with("Authentication Suites") {
+                        rsn_suites.extend(parse_suites(suites));
+                    }
+                }
+            }
+        }
+
+        match encryption {
+            None => return Self::Unknown,
+            Some(false) => return Self::Open,
+            Some(true) => {}
+        }
+
+        if has_rsn {
+            let sae = rsn_suites.iter().any(|suite| suite.is_sae());
+            let legacy = rsn_suites.iter().any(|suite| suite.is_legacy());
+            if rsn_suites.contains(&Suite::Owe) {
+                Self::Owe
+            } else if sae && legacy {
+                Self::Wpa2Wpa3
+            } else if sae {
+                Self::Wpa3
+            } else {
+                Self::Wpa2
+            }
+        } else if has_wpa {
+            Self::Wpa
+        } else {
+            Self::Wep
+        }
+    }
+}
+
+impl fmt::Display for Security {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Serialized as its [`Security::label`], e.g. "WPA2/WPA3".
+impl Serialize for Security {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.label())
+    }
+}
+
+/// An authentication and key management (AKM) suite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Suite {
+    Psk,
+    Ieee8021x,
+    Sae,
+    Owe,
+    Other(u8),
+}
+
+impl Suite {
+    fn is_sae(self) -> bool {
+        // 8: SAE, 24: SAE with group-dependent hash, 12/13: Suite B (WPA3-Enterprise).
+        matches!(self, Self::Sae | Self::Other(12 | 13 | 24))
+    }
+
+    fn is_legacy(self) -> bool {
+        matches!(self, Self::Psk | Self::Ieee8021x | Self::Other(6))
+    }
+}
+
+/// Parses e.g. "PSK unknown (8)" into its suites.
+fn parse_suites(suites: &str) -> Vec<Suite> {
+    let mut parsed = Vec::new();
+    let mut words = suites.split_whitespace();
+    while let Some(word) = words.next() {
+        let suite = match word.to_ascii_uppercase().as_str() {
+            "PSK" => Suite::Psk,
+            "802.1X" => Suite::Ieee8021x,
+            "SAE" => Suite::Sae,
+            "OWE" => Suite::Owe,
+            "UNKNOWN" => {
+                let number = words
+                    .next()
+                    .and_then(|n| n.trim_matches(|c| c == '(' || c == ')').parse().ok());
+                match number {
+                    Some(8) => Suite::Sae,
+                    Some(18) => Suite::Owe,
+                    Some(number) => Suite::Other(number),
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+        parsed.push(suite);
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WPA2_ONLY: &str = r#" 01 - Address: 11:22:33:44:55:66
+                    Channel:6
+                    Frequency:2.437 GHz (Channel 6)
+                    Quality=60/70  Signal level=-50 dBm
+                    Encryption key:on
+                    ESSID:"Home"
+                    Mode:Master
+                    IE: IEEE 802.11i/WPA2 Version 1
+                        Group Cipher : CCMP
+                        Pairwise Ciphers (1) : CCMP
+                        Authentication Suites (1) : PSK
+                    IE: Unknown: DD180050F2020101000003A4000027A4000042435E0062322F00
+"#;
+
+    const WPA3_ONLY: &str = r#" 02 - Address: 22:33:44:55:66:77
+                    Channel:36
+                    Frequency:5.18 GHz (Channel 36)
+                    Quality=50/70  Signal level=-60 dBm
+                    Encryption key:on
+                    ESSID:"Secure"
+                    IE: IEEE 802.11i/WPA2 Version 1
+                        Group Cipher : CCMP
+                        Pairwise Ciphers (1) : CCMP
+                        Authentication Suites (1) : unknown (8)
+"#;
+
+    const TRANSITION: &str = r#" 03 - Address: 33:44:55:66:77:88
+                    Encryption key:on
+                    ESSID:"Mixed"
+                    IE: IEEE 802.11i/WPA2 Version 1
+                        Group Cipher : CCMP
+                        Pairwise Ciphers (1) : CCMP
+                        Authentication Suites (2) : PSK unknown (8)
+"#;
+
+    const OWE: &str = r#" 04 - Address: 44:55:66:77:88:99
+                    Encryption key:on
+                    ESSID:"Cafe"
+                    IE: IEEE 802.11i/WPA2 Version 1
+                        Group Cipher : CCMP
+                        Pairwise Ciphers (1) : CCMP
+                        Authentication Suites (1) : unknown (18)
+"#;
+
+    const LEGACY_WPA: &str = r#" 05 - Address: 55:66:77:88:99:AA
+                    Encryption key:on
+                    ESSID:"Old"
+                    IE: WPA Version 1
+                        Group Cipher : TKIP
+                        Pairwise Ciphers (1) : TKIP
+                        Authentication Suites (1) : PSK
+"#;
+
+    #[test]
+    fn classifies_iwlist_cells() {
+        assert_eq!(Security::from_iwlist_cell(WPA2_ONLY), Security::Wpa2);
+        assert_eq!(Security::from_iwlist_cell(WPA3_ONLY), Security::Wpa3);
+        assert_eq!(Security::from_iwlist_cell(TRANSITION), Security::Wpa2Wpa3);
+        assert_eq!(Security::from_iwlist_cell(OWE), Security::Owe);
+        assert_eq!(Security::from_iwlist_cell(LEGACY_WPA), Security::Wpa);
+    }
+
+    #[test]
+    fn classifies_iwlist_cells_without_information_elements() {
+        assert_eq!(
+            Security::from_iwlist_cell("Encryption key:off\nESSID:\"Free\""),
+            Security::Open
+        );
+        assert_eq!(
+            Security::from_iwlist_cell("Encryption key:on\nESSID:\"Ancient\""),
+            Security::Wep
+        );
+        assert_eq!(Security::from_iwlist_cell("ESSID:\"?\""), Security::Unknown);
+    }
+
+    #[test]
+    fn wpa_element_does_not_hide_rsn() {
+        let both = format!(
+            "{WPA2_ONLY}{}",
+            &LEGACY_WPA[LEGACY_WPA.find("IE:").unwrap()..]
+        );
+        assert_eq!(Security::from_iwlist_cell(&both), Security::Wpa2);
+    }
+
+    #[test]
+    fn classifies_descriptions() {
+        assert_eq!(Security::from_description(""), Security::Open);
+        assert_eq!(Security::from_description("--"), Security::Open);
+        assert_eq!(Security::from_description("WPA1 WPA2"), Security::Wpa2);
+        assert_eq!(Security::from_description("WPA2 WPA3"), Security::Wpa2Wpa3);
+        assert_eq!(Security::from_description("WPA3-Personal"), Security::Wpa3);
+        assert_eq!(Security::from_description("OWE"), Security::Owe);
+        assert_eq!(
+            Security::from_description("WPA2(PSK/AES/AES) WPA3(SAE/AES/AES)"),
+            Security::Wpa2Wpa3
+        );
+        assert_eq!(Security::from_description("WEP"), Security::Wep);
+    }
+}
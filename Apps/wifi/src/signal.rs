@@ -0,0 +1,203 @@
+use std::cmp::Ordering;
+
+use crate::scanner::WifiNetwork;
+
+/// Signal strength as a percentage (0-100), so that networks from any backend can be compared.
+///
+/// Uses the `signal_level` in dBm if there is one, and the `quality` ratio otherwise:
+/// `iwlist` reports both, `nmcli` and `netsh` only a quality out of 100.
+pub fn signal_percent(network: &WifiNetwork) -> Option<f32> {
+    dbm_percent(&network.signal_level).or_else(|| quality_percent(&network.quality))
+}
+
+/// Signal strength in dBm: the `signal_level` if there is one, or else roughly what the
+/// `quality` comes to, see [`percent_dbm`]. The `bool` is whether it's only roughly.
+pub fn signal_dbm(network: &WifiNetwork) -> Option<(f32, bool)> {
+    match parse_dbm(&network.signal_level) {
+        Some(dbm) => Some((dbm, false)),
+        None => quality_percent(&network.quality).map(|percent| (percent_dbm(percent), true)),
+    }
+}
+
+/// `percent = 2 × (dBm + 100)`, so -100 dBm or weaker is 0%, -50 dBm or stronger is 100%.
+///
+/// This is the mapping NetworkManager uses for its SIGNAL, which makes percentages from
+/// `nmcli` and from dBm agree.
+fn dbm_percent(signal_level: &str) -> Option<f32> {
+    let dbm = parse_dbm(signal_level)?;
+    Some((2.0 * (dbm + 100.0)).clamp(0.0, 100.0))
+}
+
+/// The inverse of [`dbm_percent`]: `dBm = percent / 2 - 100`. Anything under -100 dBm or
+/// over -50 dBm has been clamped away, so it's only approximate.
+pub fn percent_dbm(percent: f32) -> f32 {
+    percent.clamp(0.0, 100.0) / 2.0 - 100.0
+}
+
+/// "-67" or "-67 dBm".
+fn parse_dbm(signal_level: &str) -> Option<f32> {
+    let dbm: f32 = signal_level
+        .trim()
+        .trim_end_matches("dBm")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(dbm)
+}
+
+/// `percent = 100 × value / max` of a quality like "70/100" (`nmcli`, `netsh`) or "35/70"
+/// (`iwlist`, whose drivers mostly count to 70), so both come out of 100.
+pub fn quality_percent(quality: &str) -> Option<f32> {
+    let (value, max) = quality.split_once('/')?;
+    let value: f32 = value.trim().parse().ok()?;
+    let max: f32 = max.trim().parse().ok()?;
+    (max > 0.0).then(|| (100.0 * value / max).clamp(0.0, 100.0))
+}
+
+/// How good a signal is, for labels and colors.
+///
+/// The thresholds are those `iwlist` quality ratios out of 70 were shown with: 52.5, 35 and 17.5.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignalBucket {
+    Excellent,
+    Good,
+    Fair,
+    Poor,
+    NoSignal,
+}
+
+impl SignalBucket {
+    pub fn from_percent(percent: f32) -> Self {
+        if percent > 75.0 {
+            Self::Excellent
+        } else if percent > 50.0 {
+            Self::Good
+        } else if percent > 25.0 {
+            Self::Fair
+        } else if percent > 0.0 {
+            Self::Poor
+        } else {
+            Self::NoSignal
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Excellent => "Excellent",
+            Self::Good => "Good",
+            Self::Fair => "Fair",
+            Self::Poor => "Poor",
+            Self::NoSignal => "No Signal",
+        }
+    }
+}
+
+/// Orders by signal strength, strongest first if `descending`.
+///
+/// Networks without a readable signal always come last.
+pub fn compare_signal(a: &WifiNetwork, b: &WifiNetwork, descending: bool) -> Ordering {
+    match (signal_percent(a), signal_percent(b)) {
+        (Some(a), Some(b)) => {
+            let ordering = a.total_cmp(&b);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_signal(signal_level: &str) -> WifiNetwork {
+        WifiNetwork {
+            signal_level: signal_level.to_owned(),
+            ..WifiNetwork::not_found()
+        }
+    }
+
+    fn sorted(mut networks: Vec<WifiNetwork>, descending: bool) -> Vec<String> {
+        networks.sort_by(|a, b| compare_signal(a, b, descending));
+        networks.into_iter().map(|n| n.signal_level).collect()
+    }
+
+    #[test]
+    fn stronger_signal_sorts_first_and_unknown_last() {
+        let networks = vec![
+            with_signal("Not found"),
+            with_signal("-80"),
+            with_signal("-40"),
+        ];
+        assert_eq!(sorted(networks.clone(), true), ["-40", "-80", "Not found"]);
+        assert_eq!(sorted(networks, false), ["-80", "-40", "Not found"]);
+    }
+
+    #[test]
+    fn buckets_match_the_old_quality_thresholds() {
+        let bucket = |quality: &str| {
+            let network = WifiNetwork {
+                quality: quality.to_owned(),
+                ..WifiNetwork::not_found()
+            };
+            SignalBucket::from_percent(signal_percent(&network).unwrap())
+        };
+
+        assert_eq!(bucket("70/70"), SignalBucket::Excellent);
+        assert_eq!(bucket("53/70"), SignalBucket::Excellent);
+        assert_eq!(bucket("52/70"), SignalBucket::Good);
+        assert_eq!(bucket("30/70"), SignalBucket::Fair);
+        assert_eq!(bucket("10/70"), SignalBucket::Poor);
+        assert_eq!(bucket("0/70"), SignalBucket::NoSignal);
+
+        assert_eq!(SignalBucket::from_percent(90.0), SignalBucket::Excellent);
+        assert_eq!(SignalBucket::from_percent(60.0), SignalBucket::Good);
+        assert_eq!(SignalBucket::from_percent(40.0), SignalBucket::Fair);
+        assert_eq!(SignalBucket::from_percent(5.0), SignalBucket::Poor);
+    }
+
+    #[test]
+    fn falls_back_to_quality() {
+        let network = WifiNetwork {
+            quality: "35/70".to_owned(),
+            ..WifiNetwork::not_found()
+        };
+        assert_eq!(signal_percent(&network), Some(50.0));
+        assert_eq!(signal_percent(&with_signal("-75 dBm")), Some(50.0));
+        assert_eq!(signal_percent(&WifiNetwork::not_found()), None);
+    }
+
+    #[test]
+    fn qualities_of_any_scale_are_percentages() {
+        assert_eq!(quality_percent("70/70"), Some(100.0));
+        let percent = quality_percent("50/70").unwrap();
+        assert!((percent - 71.43).abs() < 0.01, "{percent}");
+        assert_eq!(quality_percent("72/100"), Some(72.0));
+        assert_eq!(quality_percent("80/70"), Some(100.0));
+        assert_eq!(quality_percent("5/0"), None);
+        assert_eq!(quality_percent("Not found"), None);
+    }
+
+    #[test]
+    fn dbm_is_approximated_from_quality() {
+        assert_eq!(signal_dbm(&with_signal("-67 dBm")), Some((-67.0, false)));
+        let network = WifiNetwork {
+            quality: "70/100".to_owned(),
+            ..WifiNetwork::not_found()
+        };
+        assert_eq!(signal_dbm(&network), Some((-65.0, true)));
+        assert_eq!(signal_dbm(&WifiNetwork::not_found()), None);
+
+        for dbm in [-100.0, -80.0, -65.0, -50.0] {
+            let percent = dbm_percent(&dbm.to_string()).unwrap();
+            assert_eq!(percent_dbm(percent), dbm);
+        }
+        assert_eq!(percent_dbm(100.0), -50.0);
+        assert_eq!(percent_dbm(0.0), -100.0);
+    }
+}
@@ -1,4 +0,0 @@
-#!/bin/bash
-echo "ka1823" | sudo -S "$@"
-
-#chmod +x ./wifi/test/src/sudo_wrapper.sh
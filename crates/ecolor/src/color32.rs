@@ -1,5 +1,6 @@
 use crate::{
-    fast_round, gamma_u8_from_linear_f32, linear_f32_from_gamma_u8, linear_f32_from_linear_u8, Rgba,
+    fast_round, gamma_u8_from_linear_f32, linear_f32_from_gamma_u8, linear_f32_from_linear_u8,
+    Oklch, Rgba,
 };
 
 /// This format is used for space-efficient color representation (32 bits).
@@ -112,6 +113,41 @@ impl Color32 {
         }
     }
 
+    /// Creates an opaque `Color32` from hue (in degrees, wrapping around at 360),
+    /// saturation and lightness (both in `[0, 1]`), as in CSS `hsl()`.
+    ///
+    /// Like CSS, this works in gamma (sRGB) space.
+    #[inline]
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        Self::from_hsla(hue, saturation, lightness, 1.0)
+    }
+
+    /// Like [`Self::from_hsl`], with an unmultiplied `alpha` in `[0, 1]`.
+    pub fn from_hsla(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> Self {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = lightness - chroma / 2.0;
+
+        Self::from_rgba_unmultiplied(
+            fast_round((r + m) * 255.0),
+            fast_round((g + m) * 255.0),
+            fast_round((b + m) * 255.0),
+            fast_round(alpha.clamp(0.0, 1.0) * 255.0),
+        )
+    }
+
     #[inline]
     pub const fn from_gray(l: u8) -> Self {
         Self([l, l, l, 255])
@@ -220,6 +256,17 @@ impl Color32 {
         Rgba::from(self).multiply(factor).into()
     }
 
+    /// Multiplies the RGB components by a factor (in linear space) to darken the color,
+    /// e.g. for a hover state. Unlike [`Self::linear_multiply`], alpha is kept, so a
+    /// translucent color doesn't become more transparent.
+    #[inline]
+    pub fn dim(self, factor: f32) -> Self {
+        debug_assert!(0.0 <= factor && factor.is_finite());
+        // Scaling premultiplied channels scales the unmultiplied ones the same.
+        let [r, g, b, a] = Rgba::from(self).to_array();
+        Rgba::from_rgba_premultiplied(r * factor, g * factor, b * factor, a).into()
+    }
+
     /// Converts the color to floating point values in the range 0-1 without gamma correction.
     ///
     /// Use this method with caution; in most cases, you should convert to [`Rgba`] instead
@@ -235,6 +282,103 @@ impl Color32 {
         ]
     }
 
+    /// This color as Rust source, e.g. `Color32::from_rgba_unmultiplied(255, 0, 0, 255)`,
+    /// for pasting a palette into code.
+    ///
+    /// Additive colors can't be written unmultiplied, so they come out as
+    /// `Color32::from_rgba_premultiplied(r, g, b, 0)`.
+    pub fn to_rust_literal(&self) -> String {
+        if self.is_additive() {
+            let [r, g, b, a] = self.to_array();
+            format!("Color32::from_rgba_premultiplied({r}, {g}, {b}, {a})")
+        } else {
+            let [r, g, b, a] = self.to_srgba_unmultiplied();
+            format!("Color32::from_rgba_unmultiplied({r}, {g}, {b}, {a})")
+        }
+    }
+
+    /// This color as a GLSL `vec4`, e.g. `vec4(1.0, 0.0, 0.0, 1.0)`.
+    ///
+    /// The values are those of [`Rgba`]: linear and with premultiplied alpha, which is what
+    /// a shader blending in linear space wants.
+    pub fn to_glsl_vec4(&self) -> String {
+        let [r, g, b, a] = Rgba::from(*self).to_array();
+        format!("vec4({r:?}, {g:?}, {b:?}, {a:?})")
+    }
+
+    /// The classic "vintage photo" sepia tone, applied to the unmultiplied `sRGB` (gamma space)
+    /// channels. Alpha is kept.
+    pub fn sepia(self) -> Self {
+        let [r, g, b, a] = if self.is_additive() {
+            self.to_array()
+        } else {
+            self.to_srgba_unmultiplied()
+        };
+        let [r, g, b] = [r, g, b].map(f32::from);
+        let tone = |rw: f32, gw: f32, bw: f32| fast_round((rw * r + gw * g + bw * b).min(255.0));
+        let [r, g, b] = [
+            tone(0.393, 0.769, 0.189),
+            tone(0.349, 0.686, 0.168),
+            tone(0.272, 0.534, 0.131),
+        ];
+        if self.is_additive() {
+            Self::from_rgb_additive(r, g, b)
+        } else {
+            Self::from_rgba_unmultiplied(r, g, b, a)
+        }
+    }
+
+    /// Adds `delta` to each of the linear RGB channels, clamping them to `[0, 1]`. Alpha is kept.
+    ///
+    /// A `delta` of `0.0` leaves the color as it is.
+    pub fn brightness(self, delta: f32) -> Self {
+        self.map_linear_rgb(|c| c + delta)
+    }
+
+    /// Scales the distance of each linear RGB channel from mid-gray (`0.5`) by `factor`,
+    /// clamping them to `[0, 1]`. Alpha is kept.
+    ///
+    /// A `factor` of `1.0` leaves the color as it is, `0.0` turns it into mid-gray.
+    pub fn contrast(self, factor: f32) -> Self {
+        self.map_linear_rgb(|c| (c - 0.5) * factor + 0.5)
+    }
+
+    /// Adds `delta` to the OKLCH lightness, clamping it to `[0, 1]`. Unlike
+    /// [`Self::brightness`], the hue stays the same; the chroma too, unless it doesn't fit
+    /// in sRGB at the new lightness. Alpha is kept.
+    pub fn lighten_oklch(self, delta: f32) -> Self {
+        let mut oklch = Oklch::from(self);
+        oklch.l = (oklch.l + delta).clamp(0.0, 1.0);
+        oklch.into()
+    }
+
+    /// Subtracts `delta` from the OKLCH lightness, see [`Self::lighten_oklch`].
+    pub fn darken_oklch(self, delta: f32) -> Self {
+        self.lighten_oklch(-delta)
+    }
+
+    /// Applies `f` to the unmultiplied linear RGB channels.
+    fn map_linear_rgb(self, f: impl Fn(f32) -> f32) -> Self {
+        // Additive colors have nothing to unmultiply.
+        let [r, g, b, a] = Rgba::from(self).to_rgba_unmultiplied();
+        let [r, g, b] = [r, g, b].map(|c| f(c).clamp(0.0, 1.0));
+        if a == 0.0 {
+            Rgba::from_rgba_premultiplied(r, g, b, 0.0).into()
+        } else {
+            Rgba::from_rgba_unmultiplied(r, g, b, a).into()
+        }
+    }
+
+    /// This color drawn over `background`, blending in linear space.
+    ///
+    /// An additive color (alpha 0) adds its light to the background, clamped to white;
+    /// any other color covers the background as much as its alpha says ("source over").
+    /// With premultiplied alpha both are the same formula: `self + background * (1 - alpha)`.
+    pub fn composite_additive(self, background: Self) -> Self {
+        let (source, background) = (Rgba::from(self), Rgba::from(background));
+        (source + background * (1.0 - source.a())).clamp01().into()
+    }
+
     /// Linearly interpolates between this color and another color by `t` in gamma space.
     pub fn lerp_to_gamma(&self, other: Self, t: f32) -> Self {
         use emath::lerp;
@@ -246,4 +390,200 @@ impl Color32 {
             fast_round(lerp((self[3] as f32)..=(other[3] as f32), t)),
         )
     }
-}
\ No newline at end of file
+
+    /// `n` evenly spaced colors from `a` to `b`, interpolated in linear space, e.g. for a
+    /// lookup table.
+    ///
+    /// With two or more, the first is exactly `a` and the last exactly `b`.
+    /// One gives just `a`, and zero none.
+    pub fn ramp(a: Self, b: Self, n: usize) -> Vec<Self> {
+        match n {
+            0 => vec![],
+            1 => vec![a],
+            _ => {
+                let (start, end) = (Rgba::from(a), Rgba::from(b));
+                let last = (n - 1) as f32;
+                (0..n)
+                    .map(|i| match i {
+                        0 => a,
+                        i if i == n - 1 => b,
+                        i => {
+                            let t = i as f32 / last;
+                            (start * (1.0 - t) + end * t).into()
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[test]
+fn test_from_hsl() {
+    let fully_saturated = [
+        (0.0, Color32::RED),
+        (60.0, Color32::from_rgb(255, 255, 0)),
+        (120.0, Color32::from_rgb(0, 255, 0)),
+        (240.0, Color32::from_rgb(0, 0, 255)),
+        (360.0, Color32::RED),
+        (-120.0, Color32::from_rgb(0, 0, 255)),
+    ];
+    for (hue, expected) in fully_saturated {
+        assert_eq!(Color32::from_hsl(hue, 1.0, 0.5), expected, "hue {hue}");
+    }
+
+    // No saturation: a gray, whatever the hue.
+    for hue in [0.0, 90.0, 200.0] {
+        assert_eq!(Color32::from_hsl(hue, 0.0, 0.0), Color32::BLACK);
+        assert_eq!(Color32::from_hsl(hue, 0.0, 0.5), Color32::from_gray(128));
+        assert_eq!(Color32::from_hsl(hue, 0.0, 1.0), Color32::WHITE);
+    }
+
+    assert_eq!(
+        Color32::from_hsla(0.0, 1.0, 0.5, 0.5),
+        Color32::from_rgba_unmultiplied(255, 0, 0, 128)
+    );
+    assert_eq!(Color32::from_hsla(0.0, 1.0, 0.5, 0.0), Color32::TRANSPARENT);
+}
+
+#[test]
+fn test_sepia() {
+    let brown = Color32::from_gray(128).sepia();
+    assert_eq!(brown, Color32::from_rgb(173, 154, 120));
+    assert!(brown.r() > brown.g() && brown.g() > brown.b(), "{brown:?}");
+
+    // Bright colors saturate instead of wrapping around.
+    assert_eq!(Color32::WHITE.sepia(), Color32::from_rgb(255, 255, 239));
+
+    let translucent = Color32::from_rgba_unmultiplied(128, 128, 128, 100).sepia();
+    assert_eq!(translucent.a(), 100);
+}
+
+#[test]
+fn test_brightness_and_contrast() {
+    let colors = [
+        Color32::BLACK,
+        Color32::WHITE,
+        Color32::RED,
+        Color32::from_rgb(12, 200, 77),
+        Color32::from_gray(128),
+    ];
+    for color in colors {
+        assert_eq!(color.brightness(0.0), color);
+        assert_eq!(color.contrast(1.0), color);
+    }
+
+    assert_eq!(Color32::BLACK.brightness(1.0), Color32::WHITE);
+    assert_eq!(Color32::WHITE.brightness(-1.0), Color32::BLACK);
+    assert_eq!(Color32::RED.brightness(2.0), Color32::WHITE);
+
+    let gray = Color32::from(Rgba::from_gray(0.5));
+    assert_eq!(Color32::RED.contrast(0.0), gray);
+    assert_eq!(Color32::from_gray(100).contrast(100.0), Color32::BLACK);
+    assert_eq!(Color32::from_gray(200).contrast(100.0), Color32::WHITE);
+
+    let translucent = Color32::from_rgba_unmultiplied(40, 20, 10, 100);
+    assert_eq!(translucent.brightness(0.3).a(), 100);
+    assert_eq!(translucent.contrast(2.0).a(), 100);
+}
+
+#[test]
+fn test_ramp() {
+    let gray = Color32::from(Rgba::from_gray(0.5));
+    assert_eq!(
+        Color32::ramp(Color32::BLACK, Color32::WHITE, 3),
+        [Color32::BLACK, gray, Color32::WHITE]
+    );
+    // Half of the light, which is brighter than halfway between 0 and 255 in gamma space.
+    assert!(gray.r() > 128, "{gray:?}");
+
+    let a = Color32::from_rgb(12, 200, 77);
+    let b = Color32::from_rgb(250, 3, 140);
+    assert!(Color32::ramp(a, b, 0).is_empty());
+    assert_eq!(Color32::ramp(a, b, 1), [a]);
+    assert_eq!(Color32::ramp(a, b, 2), [a, b]);
+    let ramp = Color32::ramp(a, b, 7);
+    assert_eq!(ramp.len(), 7);
+    assert_eq!((ramp[0], ramp[6]), (a, b));
+}
+
+#[test]
+fn test_lighten_oklch() {
+    let blue = Oklch::from(Color32::BLUE);
+    let lighter = Oklch::from(Color32::BLUE.lighten_oklch(0.2));
+    assert!(lighter.l > blue.l + 0.15, "{lighter:?}");
+    assert!((lighter.h - blue.h).abs() < 2.0, "{lighter:?}");
+
+    let darker = Oklch::from(Color32::BLUE.darken_oklch(0.2));
+    assert!(darker.l < blue.l - 0.15, "{darker:?}");
+    assert!((darker.h - blue.h).abs() < 2.0, "{darker:?}");
+
+    assert_eq!(Color32::BLUE.lighten_oklch(1.0), Color32::WHITE);
+    assert_eq!(Color32::BLUE.darken_oklch(1.0), Color32::BLACK);
+    let translucent = Color32::from_rgba_unmultiplied(40, 20, 200, 100);
+    assert_eq!(translucent.lighten_oklch(0.1).a(), 100);
+}
+
+#[test]
+fn test_literals() {
+    assert_eq!(
+        Color32::RED.to_rust_literal(),
+        "Color32::from_rgba_unmultiplied(255, 0, 0, 255)"
+    );
+    assert_eq!(Color32::RED.to_glsl_vec4(), "vec4(1.0, 0.0, 0.0, 1.0)");
+
+    let translucent = Color32::from_rgba_unmultiplied(250, 3, 140, 100);
+    assert_eq!(
+        translucent.to_rust_literal(),
+        "Color32::from_rgba_unmultiplied(250, 3, 140, 100)"
+    );
+    assert_eq!(
+        Color32::from_rgba_premultiplied(10, 20, 30, 0).to_rust_literal(),
+        "Color32::from_rgba_premultiplied(10, 20, 30, 0)"
+    );
+    assert_eq!(
+        Color32::TRANSPARENT.to_glsl_vec4(),
+        "vec4(0.0, 0.0, 0.0, 0.0)"
+    );
+}
+
+#[test]
+fn test_composite_additive() {
+    let gray = Color32::from_gray(128);
+
+    // The red adds to the gray, and saturates.
+    let glow = Color32::RED.additive().composite_additive(gray);
+    assert_eq!(glow, Color32::from_rgb(255, 128, 128));
+    let faint = Color32::from_rgb(20, 0, 0)
+        .additive()
+        .composite_additive(gray);
+    assert!(faint.r() > 128 && faint.r() < 255, "{faint:?}");
+    assert_eq!((faint.g(), faint.b(), faint.a()), (128, 128, 255));
+
+    // Half covers the gray.
+    let over = Color32::from_rgba_unmultiplied(255, 0, 0, 128).composite_additive(gray);
+    assert_eq!(over, Color32::from_rgb(205, 92, 92));
+
+    assert_eq!(Color32::BLUE.composite_additive(gray), Color32::BLUE);
+    assert_eq!(Color32::TRANSPARENT.composite_additive(gray), gray);
+}
+
+#[test]
+fn test_dim() {
+    assert_eq!(Color32::RED.dim(1.0), Color32::RED);
+    assert_eq!(Color32::RED.dim(0.0), Color32::BLACK);
+    // Half of the light, like `linear_multiply`, but not half as opaque.
+    assert_eq!(Color32::WHITE.dim(0.5), Color32::from(Rgba::from_gray(0.5)));
+
+    let translucent = Color32::from_rgba_unmultiplied(200, 100, 50, 128);
+    let dimmed = translucent.dim(0.5);
+    assert_eq!(dimmed.a(), 128);
+    assert!(dimmed.r() < translucent.r(), "{dimmed:?}");
+    let faded = translucent.linear_multiply(0.5);
+    assert_eq!(faded.a(), 64);
+
+    // An additive color gives off less light, and stays additive.
+    let glow = Color32::from_rgb(200, 100, 50).additive().dim(0.5);
+    assert!(glow.is_additive());
+    assert!(glow.r() < 200, "{glow:?}");
+}
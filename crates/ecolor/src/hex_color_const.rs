@@ -0,0 +1,212 @@
+//! Parse hex colors in `const` contexts, without the `color-hex` proc-macro.
+
+use crate::Color32;
+
+impl Color32 {
+    /// Parses a `#rrggbb` or `#rrggbbaa` hex color (alpha unmultiplied) at compile time.
+    ///
+    /// Gives the same color as [`Self::from_hex`], but panics on malformed input,
+    /// which is a compile error when used in a `const`:
+    ///
+    /// ```
+    /// # use ecolor::Color32;
+    /// const ACCENT: Color32 = Color32::from_hex_const("#1e90ff");
+    /// assert_eq!(ACCENT, Color32::from_rgb(0x1e, 0x90, 0xff));
+    /// ```
+    pub const fn from_hex_const(hex: &str) -> Self {
+        let hex = hex.as_bytes();
+        assert!(
+            hex.len() == 7 || hex.len() == 9,
+            "expected a color like #rrggbb or #rrggbbaa"
+        );
+        assert!(hex[0] == b'#', "hex color is missing the leading '#'");
+
+        let r = hex_byte(hex, 1);
+        let g = hex_byte(hex, 3);
+        let b = hex_byte(hex, 5);
+        if hex.len() == 7 {
+            return Self::from_rgb(r, g, b);
+        }
+        match hex_byte(hex, 7) {
+            0 => Self::TRANSPARENT,
+            255 => Self::from_rgb(r, g, b),
+            a => Self::from_rgba_premultiplied(
+                premultiply(r, a),
+                premultiply(g, a),
+                premultiply(b, a),
+                a,
+            ),
+        }
+    }
+}
+
+const fn hex_byte(hex: &[u8], index: usize) -> u8 {
+    hex_digit(hex[index]) << 4 | hex_digit(hex[index + 1])
+}
+
+const fn hex_digit(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        b'A'..=b'F' => digit - b'A' + 10,
+        _ => panic!("invalid hex digit in color"),
+    }
+}
+
+/// Like [`Color32::from_rgba_unmultiplied`] does for a single channel: multiplies the gamma
+/// channel `c` by the alpha `a` in linear space. Floats aren't available in `const fn`,
+/// so this compares fixed-point linear values instead.
+const fn premultiply(c: u8, a: u8) -> u8 {
+    let linear = LINEAR[c as usize] * a as u64;
+    let mut gamma = 0;
+    while gamma < THRESHOLDS.len() && linear >= THRESHOLDS[gamma] * 255 {
+        gamma += 1;
+    }
+    gamma as u8
+}
+
+/// [`crate::linear_f32_from_gamma_u8`] of each gamma value, times `2^40` (which is exact).
+#[rustfmt::skip]
+const LINEAR: [u64; 256] = [
+    0, 333731424, 667462848, 1001194304, 1334925696, 1668657152,
+    2002388608, 2336120064, 2669851392, 3003582976, 3337314304, 3679554048,
+    4042361344, 4425222656, 4828440576, 5252314624, 5697137152, 6163192320,
+    6650761728, 7160118784, 7691534848, 8245272576, 8821593088, 9420752896,
+    10043004928, 10688591872, 11357760512, 12050750464, 12767798272, 13509135360,
+    14274993152, 15065600000, 15881174016, 16721938432, 17588109312, 18479904768,
+    19397531648, 20341204992, 21311131648, 22307510272, 23330547712, 24380442624,
+    25457393664, 26561595392, 27693246464, 28852529152, 30039638016, 31254759424,
+    32498081792, 33769785344, 35070054400, 36399075328, 37757018112, 39144058880,
+    40560381952, 42006155264, 43481546752, 44986736640, 46521888768, 48087171072,
+    49682755584, 51308802048, 52965478400, 54652944384, 56371359744, 58120888320,
+    59901689856, 61713932288, 63557746688, 65433300992, 67340750848, 69280251904,
+    71251943424, 73255993344, 75292540928, 77361741824, 79463735296, 81598676992,
+    83766706176, 85967970304, 88202633216, 90470801408, 92772630528, 95108268032,
+    97477853184, 99881517056, 102319415296, 104791662592, 107298422784, 109839810560,
+    112415965184, 115027034112, 117673140224, 120354430976, 123071012864, 125823033344,
+    128610615296, 131433897984, 134293004288, 137188065280, 140119212032, 143086567424,
+    146090262528, 149130412032, 152207163392, 155320614912, 158470946816, 161658208256,
+    164882546688, 168144093184, 171442962432, 174779285504, 178153177088, 181564768256,
+    185014124544, 188501458944, 192026820608, 195590340608, 199192182784, 202832396288,
+    206511112192, 210228461568, 213984542720, 217779503104, 221613424640, 225486422016,
+    229398642688, 233350152192, 237341097984, 241371578368, 245441691648, 249551568896,
+    253701324800, 257891041280, 262120849408, 266390863872, 270701166592, 275051872256,
+    279443177472, 283875049472, 288347652096, 292861083648, 297415475200, 302010892288,
+    306647465984, 311325327360, 316044541952, 320805208064, 325607456768, 330451386368,
+    335337062400, 340264648704, 345234210816, 350245847040, 355299688448, 360395800576,
+    365534281728, 370715262976, 375938842624, 381205086208, 386514124800, 391866056704,
+    397260947456, 402698928128, 408180162560, 413704585216, 419272392704, 424883650560,
+    430538489856, 436236976128, 441979240448, 447765348352, 453595398144, 459469488128,
+    465387716608, 471350149120, 477356949504, 483408150528, 489503850496, 495644180480,
+    501829173248, 508058992640, 514333671424, 520653340672, 527018065920, 533427945472,
+    539883077632, 546383527936, 552929460224, 559520874496, 566157967360, 572840738816,
+    579569254400, 586343645184, 593163976704, 600030380032, 606942986240, 613901729792,
+    620906872832, 627958415360, 635056422912, 642201026560, 649392291840, 656630284288,
+    663915134976, 671246974976, 678625738752, 686051688448, 693524758528, 701045145600,
+    708612849664, 716228001792, 723890733056, 731600977920, 739358998528, 747164794880,
+    755018498048, 762920108032, 770869690368, 778867441664, 786913361920, 795007516672,
+    803150102528, 811341119488, 819580633088, 827868708864, 836205543424, 844591136768,
+    853025554432, 861508927488, 870041255936, 878622736384, 887253368832, 895933218816,
+    904662482944, 913441095680, 922269188096, 931146891264, 940074270720, 949051326464,
+    958078189568, 967154991104, 976281862144, 985458606080, 994685550592, 1003962630144,
+    1013290041344, 1022667718656, 1032095924224, 1041574592512, 1051103854592, 1060683776000,
+    1070314422272, 1079995924480, 1089728282624, 1099511627776,
+];
+
+/// The smallest linear value (times `2^40`) that [`crate::gamma_u8_from_linear_f32`]
+/// turns into a gamma value greater than the index.
+#[rustfmt::skip]
+const THRESHOLDS: [u64; 255] = [
+    166865712, 500597152, 834328576, 1168060032, 1501791488, 1835522944,
+    2169254400, 2502985728, 2836717312, 3170448640, 3505573376, 3858470656,
+    4231266560, 4624268288, 5037777408, 5472089088, 5927493120, 6404270592,
+    6902700032, 7423052800, 7965596672, 8530593792, 9118302208, 9728975872,
+    10362865664, 11020213248, 11701263360, 12406252544, 13135416320, 13888985088,
+    14667191296, 15470252032, 16298390528, 17151831040, 18030786560, 18935472128,
+    19866095616, 20822872064, 21805998080, 22815680512, 23852122112, 24915519488,
+    26006071296, 27123976192, 28269416448, 29442588672, 30643681280, 31872878592,
+    33130369024, 34416336896, 35730960384, 37074415616, 38446886912, 39848550400,
+    41279574016, 42740133888, 44230406144, 45750554624, 47300751360, 48881164288,
+    50491957248, 52133298176, 53805350912, 55508271104, 57242222592, 59007369216,
+    60803878912, 62631878656, 64491544576, 66383028224, 68306481152, 70262063104,
+    72249917440, 74270195712, 76323045376, 78408630272, 80527073280, 82678546432,
+    84863172608, 87081123840, 89332514816, 91617492992, 93936214016, 96288808960,
+    98675417088, 101096177664, 103551229952, 106040721408, 108564774912, 111123529728,
+    113717125120, 116345692160, 119009386496, 121708298240, 124442583040, 127212363776,
+    130017779712, 132858961920, 135736025088, 138649124864, 141598359552, 144583868416,
+    147605766144, 150664216576, 153759301632, 156891185152, 160059949056, 163265724416,
+    166508658688, 169788850176, 173106446336, 176461529088, 179854262272, 183284727808,
+    186753105920, 190259429376, 193803862016, 197386518528, 201007513600, 204666961920,
+    208364961792, 212101660672, 215877173248, 219691597824, 223545049088, 227437707264,
+    231369555968, 235340775424, 239351398400, 243401687040, 247491674112, 251621474304,
+    255791185920, 260000940032, 264250834944, 268540985344, 272871473152, 277242445824,
+    281654001664, 286106189824, 290599206912, 295133085696, 299707990016, 304323985408,
+    308981170176, 313679708160, 318419632128, 323201073152, 328024129536, 332888932352,
+    337795645440, 342744203264, 347734802432, 352767508480, 357842452480, 362959765504,
+    368119480320, 373321728000, 378566639616, 383854280704, 389184749568, 394558144512,
+    399974563840, 405434105856, 410936901632, 416482983936, 422072516608, 427705565184,
+    433382195200, 439102570496, 444866723840, 450674786304, 456526856192, 462422999040,
+    468363313152, 474347929600, 480377012224, 486450462720, 492568444928, 498731122688,
+    504938496000, 511190728704, 517487886336, 523830067200, 530217369600, 536649859072,
+    543127666688, 549650825216, 556219498496, 562833719296, 569493553152, 576199196672,
+    582950584320, 589747978240, 596591378432, 603480850432, 610416525312, 617398468608,
+    624426745856, 631501488128, 638622826496, 645790695424, 653005488128, 660266876928,
+    667575189504, 674930491392, 682332848128, 689782325248, 697279053824, 704823099392,
+    712414527488, 720053469184, 727739924480, 735474089984, 743255965696, 751085617152,
+    758963240960, 766888837120, 774862471168, 782884274176, 790954311680, 799072649216,
+    807239417856, 815454683136, 823718510592, 832030965760, 840392114176, 848802086912,
+    857261146112, 865768964096, 874325868544, 882931924992, 891587198976, 900291690496,
+    909045661696, 917848981504, 926701912064, 935604387840, 944556605440, 953558564864,
+    962610397184, 971712102400, 980863877120, 990065721344, 999317700608, 1008619945984,
+    1017972457472, 1027375431680, 1036828803072, 1046332768256, 1055887327232, 1065492611072,
+    1075148685312, 1084855615488, 1094613467136,
+];
+
+#[test]
+fn test_from_hex_const() {
+    const ACCENT: Color32 = Color32::from_hex_const("#1E90ff");
+    assert_eq!(Ok(ACCENT), Color32::from_hex("#1e90ff"));
+
+    for hex in [
+        "#000000",
+        "#ffffff",
+        "#202122",
+        "#abcdef12",
+        "#ff000080",
+        "#00000000",
+    ] {
+        assert_eq!(
+            Ok(Color32::from_hex_const(hex)),
+            Color32::from_hex(hex),
+            "{hex}"
+        );
+    }
+
+    // Premultiplying must match the float math exactly, for every channel and alpha.
+    for c in 0..=255 {
+        for a in 0..=255 {
+            let hex = format!("#{c:02x}{c:02x}{c:02x}{a:02x}");
+            assert_eq!(
+                Color32::from_hex_const(&hex),
+                Color32::from_rgba_unmultiplied(c, c, c, a),
+                "{hex}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_tables_match_conversions() {
+    use crate::{gamma_u8_from_linear_f32, linear_f32_from_gamma_u8};
+
+    let fixed = |linear: f32| (linear as f64 * (1u64 << 40) as f64) as u64;
+    for (gamma, linear) in LINEAR.iter().enumerate() {
+        assert_eq!(*linear, fixed(linear_f32_from_gamma_u8(gamma as u8)));
+    }
+    for (gamma, threshold) in THRESHOLDS.iter().enumerate() {
+        let threshold = (*threshold as f64 / (1u64 << 40) as f64) as f32;
+        assert!(gamma_u8_from_linear_f32(threshold) as usize > gamma);
+        let below = f32::from_bits(threshold.to_bits() - 1);
+        assert!(gamma_u8_from_linear_f32(below) as usize <= gamma);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     gamma_u8_from_linear_f32, linear_f32_from_gamma_u8, linear_f32_from_linear_u8,
-    linear_u8_from_linear_f32, Color32, Rgba,
+    linear_u8_from_linear_f32, Color32, ParseHexColorError, Rgba,
 };
 
 /// Represents a color in the HSV (Hue, Saturation, Value) color space, including alpha.
@@ -115,6 +115,31 @@ impl Hsva {
 
     // ------------------------------------------------------------------------
 
+    /// Checks if the alpha value is negative (additive color).
+    #[inline]
+    pub fn is_additive(&self) -> bool {
+        self.a < 0.0
+    }
+
+    /// Makes the color additive by making alpha negative, like [`Color32::additive`].
+    ///
+    /// The size of alpha is kept, so [`Self::to_normal`] gives it back. A transparent color
+    /// has no negative alpha to keep, so it becomes an additive color at alpha `-1.0`.
+    #[inline]
+    pub fn to_additive(self) -> Self {
+        let a = if self.a == 0.0 { -1.0 } else { -self.a.abs() };
+        Self { a, ..self }
+    }
+
+    /// Makes an additive color normal again by making alpha positive.
+    #[inline]
+    pub fn to_normal(self) -> Self {
+        Self {
+            a: self.a.abs(),
+            ..self
+        }
+    }
+
     /// Converts the `Hsva` instance to opaque by setting alpha to 1.0.
     #[inline]
     pub fn to_opaque(self) -> Self {
@@ -142,8 +167,7 @@ impl Hsva {
     #[inline]
     pub fn to_rgba_premultiplied(&self) -> [f32; 4] {
         let [r, g, b, a] = self.to_rgba_unmultiplied();
-        let additive = a < 0.0;
-        if additive {
+        if self.is_additive() {
             [r, g, b, 0.0]  // For additive colors, alpha is set to 0.
         } else {
             [a * r, a * g, a * b, a]  // Apply alpha to RGB channels.
@@ -181,6 +205,37 @@ impl Hsva {
             linear_u8_from_linear_f32(a.abs()),
         ]
     }
+
+    /// Formats the color as an 8-digit hex string, like `"#ff8000ff"`, via [`Color32::to_hex`].
+    ///
+    /// The round-trip through [`Self::from_hex`] is not bit-exact: going through 8-bit sRGB
+    /// quantizes the color, so it only comes back close to what it was.
+    /// Grays (including black and white) lose their hue, and black its saturation too,
+    /// since RGB has nowhere to keep them.
+    pub fn to_hex(&self) -> String {
+        Color32::from(*self).to_hex()
+    }
+
+    /// Parses a color from a 3, 4, 6, or 8-digit hex string, via [`Color32::from_hex`].
+    ///
+    /// See [`Self::to_hex`] for why a round-trip isn't exact.
+    ///
+    /// # Errors
+    /// If `hex` isn't one of those formats.
+    pub fn from_hex(hex: &str) -> Result<Self, ParseHexColorError> {
+        Color32::from_hex(hex).map(Self::from)
+    }
+
+    /// Are all channels within `epsilon` of those in `other`?
+    ///
+    /// Hue wraps around, so a hue of `0.0` is considered equal to a hue of `1.0`.
+    #[inline]
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        hue_distance(self.h, other.h) <= epsilon
+            && (self.s - other.s).abs() <= epsilon
+            && (self.v - other.v).abs() <= epsilon
+            && (self.a - other.a).abs() <= epsilon
+    }
 }
 
 impl From<Hsva> for Rgba {
@@ -215,6 +270,13 @@ impl From<Color32> for Hsva {
     }
 }
 
+/// The shortest distance between two hues on the [0, 1] color wheel.
+#[inline]
+pub(crate) fn hue_distance(a: f32, b: f32) -> f32 {
+    let d = (a - b).rem_euclid(1.0);
+    d.min(1.0 - d)
+}
+
 /// Converts RGB values to HSV color space.
 ///
 /// All ranges are in [0, 1], and RGB is in linear space.
@@ -278,4 +340,64 @@ fn test_hsv_roundtrip() {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_hsva_hex_roundtrip() {
+    for color in [
+        Hsva::new(0.0, 1.0, 1.0, 1.0),
+        Hsva::new(1.0 / 3.0, 1.0, 0.5, 1.0),
+        Hsva::new(0.6, 0.9, 0.8, 1.0),
+        Hsva::new(0.85, 0.75, 0.6, 0.5),
+    ] {
+        let hex = color.to_hex();
+        let back = Hsva::from_hex(&hex).unwrap();
+        assert!(color.approx_eq(&back, 0.01), "{color:?} -> {hex} -> {back:?}");
+    }
+
+    assert_eq!(Hsva::new(0.0, 1.0, 1.0, 1.0).to_hex(), "#ff0000ff");
+    let green = Hsva::from_hex("#00ff00").unwrap();
+    assert!(green.approx_eq(&Hsva::new(1.0 / 3.0, 1.0, 1.0, 1.0), 1e-6));
+
+    // Grays lose their hue:
+    let gray = Hsva::from_hex(&Hsva::new(0.3, 0.0, 0.5, 1.0).to_hex()).unwrap();
+    assert_eq!((gray.h, gray.s), (0.0, 0.0));
+
+    assert_eq!(Hsva::from_hex("ff0000"), Err(ParseHexColorError::MissingHash));
+}
+
+#[test]
+fn test_hsva_approx_eq() {
+    let a = Hsva::new(0.25, 0.5, 0.75, 1.0);
+    assert!(a.approx_eq(&a, 0.0));
+
+    let b = Hsva::new(0.25, 0.5 + 5e-5, 0.75, 1.0);
+    assert!(a.approx_eq(&b, 1e-4));
+    assert!(!a.approx_eq(&b, 1e-8));
+
+    // Hue wraps around:
+    let hue = |h| Hsva::new(h, 1.0, 1.0, 1.0);
+    assert!(hue(0.0).approx_eq(&hue(1.0), 1e-8));
+    assert!(hue(0.99995).approx_eq(&hue(0.00004), 1e-4));
+    assert!(!hue(0.9).approx_eq(&hue(0.1), 1e-4));
+}
+
+#[test]
+fn test_hsva_additive() {
+    let glow = Hsva::from_additive_rgb([1.0, 0.5, 0.0]);
+    assert!(glow.is_additive());
+    let normal = glow.to_normal();
+    assert!(!normal.is_additive());
+    assert!(normal.a >= 0.0, "{normal:?}");
+    assert_eq!((normal.h, normal.s, normal.v), (glow.h, glow.s, glow.v));
+
+    let translucent = Hsva::new(0.25, 0.5, 0.75, 0.4);
+    assert!(!translucent.is_additive());
+    assert_eq!(translucent.to_additive().a, -0.4);
+    assert_eq!(translucent.to_additive().to_normal(), translucent);
+    assert_eq!(translucent.to_normal(), translucent);
+    assert_eq!(glow.to_additive(), glow);
+
+    assert!(Hsva::new(0.0, 0.0, 1.0, 0.0).to_additive().is_additive());
+    assert!(Hsva::from(Color32::RED.additive()).is_additive());
+}
@@ -1,4 +1,4 @@
-use crate::{gamma_from_linear, linear_from_gamma, Color32, Hsva, Rgba};
+use crate::{gamma_from_linear, hsva::hue_distance, linear_from_gamma, Color32, Hsva, Rgba};
 
 /// Represents a color in the HSV (Hue, Saturation, Value) color space with gamma-corrected brightness.
 /// All values are in the range [0, 1]. Alpha is not premultiplied.
@@ -18,6 +18,41 @@ pub struct HsvaGamma {
     pub a: f32,
 }
 
+impl HsvaGamma {
+    /// Are all channels within `epsilon` of those in `other`?
+    ///
+    /// Hue wraps around, so a hue of `0.0` is considered equal to a hue of `1.0`.
+    #[inline]
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        hue_distance(self.h, other.h) <= epsilon
+            && (self.s - other.s).abs() <= epsilon
+            && (self.v - other.v).abs() <= epsilon
+            && (self.a - other.a).abs() <= epsilon
+    }
+
+    /// Interpolates from `self` (at `t = 0`) to `other` (at `t = 1`), with `t` clamped to [0, 1].
+    ///
+    /// Hue takes the shorter way around the color wheel; saturation, value, and alpha are linear.
+    /// Since `v` is in gamma space, brightness ramps look perceptually even,
+    /// which is what `HsvaGamma` is for.
+    #[inline]
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |from: f32, to: f32| from + (to - from) * t;
+
+        // The signed hue difference, wrapped into [-0.5, 0.5]:
+        let dh = other.h - self.h;
+        let dh = dh - dh.round();
+
+        Self {
+            h: (self.h + dh * t).rem_euclid(1.0),
+            s: lerp(self.s, other.s),
+            v: lerp(self.v, other.v),
+            a: lerp(self.a, other.a),
+        }
+    }
+}
+
 impl From<HsvaGamma> for Rgba {
     /// Converts an `HsvaGamma` instance to an `Rgba` instance.
     #[inline]
@@ -76,4 +111,55 @@ impl From<Hsva> for HsvaGamma {
             a,
         }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_hsva_gamma_approx_eq() {
+    let a = HsvaGamma {
+        h: 1.0,
+        s: 0.5,
+        v: 0.75,
+        a: 1.0,
+    };
+    let b = HsvaGamma {
+        h: 0.0,
+        s: 0.5,
+        v: 0.75 + 5e-5,
+        a: 1.0,
+    };
+    assert!(a.approx_eq(&b, 1e-4));
+    assert!(!a.approx_eq(&b, 1e-8));
+}
+
+#[test]
+fn test_hsva_gamma_lerp() {
+    let black = HsvaGamma {
+        h: 0.0,
+        s: 0.0,
+        v: 0.0,
+        a: 1.0,
+    };
+    let white = HsvaGamma { v: 1.0, ..black };
+    assert_eq!(black.lerp(white, 0.5).v, 0.5);
+    // Half way in gamma space is much darker than half way in linear space:
+    assert!(Hsva::from(black.lerp(white, 0.5)).v < 0.25);
+
+    assert_eq!(black.lerp(white, 0.0), black);
+    assert_eq!(black.lerp(white, 1.0), white);
+    assert_eq!(black.lerp(white, -1.0), black);
+    assert_eq!(black.lerp(white, 2.0), white);
+
+    // Hue goes the short way around, through red rather than through green:
+    let hue = |h| HsvaGamma {
+        h,
+        s: 1.0,
+        v: 1.0,
+        a: 1.0,
+    };
+    assert!(hue(0.9).lerp(hue(0.1), 0.5).approx_eq(&hue(0.0), 1e-6));
+    assert!(hue(0.1).lerp(hue(0.9), 0.25).approx_eq(&hue(0.05), 1e-6));
+    assert!(hue(0.2).lerp(hue(0.4), 0.5).approx_eq(&hue(0.3), 1e-6));
+
+    let transparent = HsvaGamma { a: 0.0, ..white };
+    assert_eq!(white.lerp(transparent, 0.25).a, 0.75);
+}
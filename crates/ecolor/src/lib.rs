@@ -5,6 +5,7 @@
 //! Use [`Color32`] for a compact color representation.
 //! Use [`Rgba`] if you need to work with RGBA colors directly.
 //! Use [`HsvaGamma`] for manipulating colors in a way that is more intuitive for human perception of colors.
+//! Use [`Oklch`] for changing the lightness or chroma of a color without shifting its hue.
 //!
 //! ## Feature Flags
 #![cfg_attr(feature = "document-features", doc = document_features::document_features!())]
@@ -24,6 +25,9 @@ pub use hsva_gamma::*;
 mod hsva;
 pub use hsva::*;
 
+mod oklch;
+pub use oklch::*;
+
 #[cfg(feature = "color-hex")]
 mod hex_color_macro;
 #[cfg(feature = "color-hex")]
@@ -36,6 +40,8 @@ pub use rgba::*;
 mod hex_color_runtime;
 pub use hex_color_runtime::*;
 
+mod hex_color_const;
+
 // ----------------------------------------------------------------------------
 // Color Conversion Implementations:
 
@@ -141,25 +147,132 @@ pub fn gamma_from_linear(linear: f32) -> f32 {
 
 /// Adjusts a color towards a target color to create a "grayed out" effect.
 /// This is often used to indicate disabled or inactive states in UI elements.
+///
+/// Same as [`tint_color_towards_by`] with `t = 0.5`.
 pub fn tint_color_towards(color: Color32, target: Color32) -> Color32 {
-    let [mut r, mut g, mut b, mut a] = color.to_array();
-
-    if a == 0 {
-        r /= 2;
-        g /= 2;
-        b /= 2;
-    } else if a < 170 {
-        // A simple approximation for a grayed-out effect.
-        // Suitable for grid stripes and similar use cases.
-        let div = (2 * 255 / a as i32) as u8;
-        r = r / 2 + target.r() / div;
-        g = g / 2 + target.g() / div;
-        b = b / 2 + target.b() / div;
-        a /= 2;
-    } else {
-        r = r / 2 + target.r() / 2;
-        g = g / 2 + target.g() / 2;
-        b = b / 2 + target.b() / 2;
+    tint_color_towards_by(color, target, 0.5)
+}
+
+/// Blends `color` towards `target` by `t` (clamped to `[0, 1]`), in linear space.
+///
+/// `t = 0` returns `color` unchanged, and `t = 1` returns `target` for opaque colors.
+///
+/// The opacity of `color` is kept: the target is premultiplied by the alpha of `color`,
+/// so a half-transparent color is tinted into an equally transparent one,
+/// and a fully transparent color stays fully transparent.
+pub fn tint_color_towards_by(color: Color32, target: Color32, t: f32) -> Color32 {
+    let t = t.clamp(0.0, 1.0);
+    if t == 0.0 {
+        return color;
     }
-    Color32::from_rgba_premultiplied(r, g, b, a)
+
+    let color = Rgba::from(color);
+    let alpha = color.a();
+    let target = Rgba::from(target).to_opaque().multiply(alpha);
+    let blended = color * (1.0 - t) + target * t;
+    Color32::from(Rgba::from_rgba_premultiplied(
+        blended.r(),
+        blended.g(),
+        blended.b(),
+        alpha,
+    ))
+}
+
+#[test]
+fn test_tint_color_towards_by() {
+    let colors = [
+        Color32::RED,
+        Color32::from_rgb(12, 200, 77),
+        Color32::from_rgba_premultiplied(40, 20, 10, 100),
+        Color32::TRANSPARENT,
+    ];
+    for color in colors {
+        assert_eq!(tint_color_towards_by(color, Color32::GRAY, 0.0), color);
+        assert_eq!(tint_color_towards_by(color, Color32::GRAY, -1.0), color);
+    }
+
+    for color in [Color32::RED, Color32::from_rgb(12, 200, 77)] {
+        for target in [Color32::GRAY, Color32::BLACK, Color32::from_rgb(1, 2, 3)] {
+            assert_eq!(tint_color_towards_by(color, target, 1.0), target);
+            assert_eq!(tint_color_towards_by(color, target, 2.0), target);
+        }
+    }
+
+    // Opacity is kept.
+    let translucent = Color32::from_rgba_premultiplied(40, 20, 10, 100);
+    let tinted = tint_color_towards_by(translucent, Color32::WHITE, 0.7);
+    assert_eq!(tinted.a(), 100);
+    assert_eq!(
+        tint_color_towards_by(Color32::TRANSPARENT, Color32::WHITE, 1.0),
+        Color32::TRANSPARENT
+    );
+
+    // Halfway is somewhere in between.
+    let half = tint_color_towards_by(Color32::BLACK, Color32::WHITE, 0.5);
+    assert!(0 < half.r() && half.r() < 255);
+    assert_eq!(tint_color_towards(Color32::BLACK, Color32::WHITE), half);
+}
+
+/// Maps every pixel to the index of the nearest color in `palette`, e.g. for an indexed (retro) look.
+///
+/// There is no Lab color space in this crate (so no ΔE76), so "nearest" is the smallest
+/// squared distance in linear premultiplied [`Rgba`], alpha included.
+/// Ties go to the earlier palette entry.
+///
+/// Runs of identical pixels are only matched once, which makes flat areas of large images cheap.
+///
+/// # Panics
+/// If `palette` is empty or has more than 256 colors.
+pub fn quantize(pixels: &[Color32], palette: &[Color32]) -> Vec<u8> {
+    assert!(
+        !palette.is_empty() && palette.len() <= 256,
+        "The palette must have between 1 and 256 colors, not {}",
+        palette.len()
+    );
+    let palette: Vec<Rgba> = palette.iter().map(|&color| Rgba::from(color)).collect();
+    let nearest = |pixel: Color32| {
+        let pixel = Rgba::from(pixel);
+        let distance = |color: &Rgba| {
+            (0..4)
+                .map(|i| (color[i] - pixel[i]) * (color[i] - pixel[i]))
+                .sum::<f32>()
+        };
+        palette
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+            .map_or(0, |(index, _)| index as u8)
+    };
+
+    let mut previous: Option<(Color32, u8)> = None;
+    pixels
+        .iter()
+        .map(|&pixel| match previous {
+            Some((color, index)) if color == pixel => index,
+            _ => {
+                let index = nearest(pixel);
+                previous = Some((pixel, index));
+                index
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_quantize() {
+    let palette = [Color32::BLACK, Color32::WHITE];
+    let pixels = [
+        Color32::BLACK,
+        Color32::from_gray(30),
+        Color32::from_gray(30),
+        Color32::from_gray(220),
+        Color32::WHITE,
+        Color32::from_rgb(250, 10, 240),
+        Color32::from_rgb(20, 0, 40),
+    ];
+    assert_eq!(quantize(&pixels, &palette), [0, 0, 0, 1, 1, 1, 0]);
+
+    // Ties go to the first entry.
+    assert_eq!(quantize(&[Color32::RED], &[Color32::RED, Color32::RED]), [0]);
+    assert_eq!(quantize(&[], &palette), Vec::<u8>::new());
 }
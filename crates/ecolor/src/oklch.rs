@@ -0,0 +1,177 @@
+use crate::{Color32, Rgba};
+
+/// A color in the OKLCH color space, the polar form of OKLab, including alpha.
+///
+/// Unlike HSV, equal steps in lightness look like equal steps, and changing the lightness
+/// or chroma leaves the hue as it is. Alpha is not premultiplied.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Oklch {
+    /// Perceived lightness, from 0 (black) to 1 (white).
+    pub l: f32,
+
+    /// Chroma, how colorful the color is: 0 for grays, up to about 0.37 for sRGB colors.
+    pub c: f32,
+
+    /// Hue angle in degrees, in `[0, 360)`.
+    pub h: f32,
+
+    /// Alpha component (0 to 1). A negative value signifies an additive color.
+    pub a: f32,
+}
+
+impl Oklch {
+    /// Creates a new `Oklch` with the specified lightness, chroma, hue and alpha.
+    #[inline]
+    pub fn new(l: f32, c: f32, h: f32, a: f32) -> Self {
+        Self { l, c, h, a }
+    }
+
+    /// Converts from linear RGB, which may be out of `[0, 1]`.
+    pub fn from_rgb(rgb: [f32; 3]) -> Self {
+        let [l, a, b] = oklab_from_linear_rgb(rgb);
+        let h = b.atan2(a).to_degrees().rem_euclid(360.0);
+        Self {
+            l,
+            c: a.hypot(b),
+            h,
+            a: 1.0,
+        }
+    }
+
+    /// The linear RGB of this color, which may be out of `[0, 1]`.
+    pub fn to_rgb(&self) -> [f32; 3] {
+        let (sin, cos) = self.h.to_radians().sin_cos();
+        linear_rgb_from_oklab([self.l, self.c * cos, self.c * sin])
+    }
+
+    /// The linear RGB of this color, with the chroma lowered as little as needed to fit
+    /// in sRGB. Lightness and hue are kept.
+    pub fn to_rgb_in_gamut(&self) -> [f32; 3] {
+        let l = self.l.clamp(0.0, 1.0);
+        let fits = |c: f32| {
+            let rgb = Self { l, c, ..*self }.to_rgb();
+            // With a little leeway for rounding, or the edges of the gamut wouldn't fit.
+            rgb.iter().all(|x| (-1e-5..=1.0 + 1e-5).contains(x))
+        };
+        let chroma = if fits(self.c) {
+            self.c
+        } else {
+            let (mut fits_lo, mut too_high) = (0.0, self.c);
+            for _ in 0..20 {
+                let mid = (fits_lo + too_high) / 2.0;
+                if fits(mid) {
+                    fits_lo = mid;
+                } else {
+                    too_high = mid;
+                }
+            }
+            fits_lo
+        };
+        Self {
+            l,
+            c: chroma,
+            ..*self
+        }
+        .to_rgb()
+        .map(|x| x.clamp(0.0, 1.0))
+    }
+}
+
+impl From<Color32> for Oklch {
+    fn from(color: Color32) -> Self {
+        let rgba = Rgba::from(color);
+        if rgba.a() == 0.0 {
+            // Additive colors have nothing to unmultiply.
+            let [r, g, b, _] = rgba.to_array();
+            Self {
+                a: 0.0,
+                ..Self::from_rgb([r, g, b])
+            }
+        } else {
+            let [r, g, b, a] = rgba.to_rgba_unmultiplied();
+            Self {
+                a,
+                ..Self::from_rgb([r, g, b])
+            }
+        }
+    }
+}
+
+impl From<Oklch> for Color32 {
+    fn from(oklch: Oklch) -> Self {
+        let [r, g, b] = oklch.to_rgb_in_gamut();
+        if oklch.a <= 0.0 {
+            Rgba::from_rgba_premultiplied(r, g, b, 0.0).into()
+        } else {
+            Rgba::from_rgba_unmultiplied(r, g, b, oklch.a).into()
+        }
+    }
+}
+
+/// Converts linear sRGB to OKLab `[L, a, b]`.
+///
+/// See <https://bottosson.github.io/posts/oklab/>.
+pub fn oklab_from_linear_rgb([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = 0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b;
+    let m = 0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b;
+    let s = 0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b;
+    let [l, m, s] = [l, m, s].map(f32::cbrt);
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+/// Converts OKLab `[L, a, b]` to linear sRGB, which may be out of `[0, 1]`.
+pub fn linear_rgb_from_oklab([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let l_ = l + 0.396_337_78 * a + 0.215_803_76 * b;
+    let m_ = l - 0.105_561_346 * a - 0.063_854_17 * b;
+    let s_ = l - 0.089_484_18 * a - 1.291_485_5 * b;
+    let [l, m, s] = [l_, m_, s_].map(|x| x * x * x);
+    [
+        4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+        -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+        -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+    ]
+}
+
+#[test]
+fn test_oklch_reference_colors() {
+    let white = Oklch::from(Color32::WHITE);
+    assert!((white.l - 1.0).abs() < 1e-3 && white.c < 1e-3, "{white:?}");
+    let black = Oklch::from(Color32::BLACK);
+    assert!(black.l.abs() < 1e-3 && black.c < 1e-3, "{black:?}");
+
+    // Reference values from the CSS Color 4 specification.
+    let red = Oklch::from(Color32::RED);
+    assert!((red.l - 0.628).abs() < 1e-3, "{red:?}");
+    assert!((red.c - 0.258).abs() < 1e-3, "{red:?}");
+    assert!((red.h - 29.23).abs() < 0.1, "{red:?}");
+    let blue = Oklch::from(Color32::BLUE);
+    assert!((blue.l - 0.452).abs() < 1e-3, "{blue:?}");
+    assert!((blue.h - 264.05).abs() < 0.1, "{blue:?}");
+}
+
+#[test]
+fn test_oklch_roundtrip() {
+    for color in [
+        Color32::RED,
+        Color32::BLUE,
+        Color32::from_rgb(12, 200, 77),
+        Color32::from_gray(128),
+        Color32::from_rgba_unmultiplied(250, 3, 140, 100),
+    ] {
+        assert_eq!(Color32::from(Oklch::from(color)), color);
+    }
+}
+
+#[test]
+fn test_oklch_out_of_gamut() {
+    // Far more colorful than sRGB allows: the chroma gives, the hue and lightness don't.
+    let vivid = Oklch::new(0.7, 0.5, 145.0, 1.0);
+    let fitted = Oklch::from(Color32::from(vivid));
+    assert!(fitted.c < vivid.c, "{fitted:?}");
+    assert!((fitted.l - vivid.l).abs() < 0.01, "{fitted:?}");
+    assert!((fitted.h - vivid.h).abs() < 2.0, "{fitted:?}");
+}
@@ -218,6 +218,30 @@ impl Rgba {
             linear_u8_from_linear_f32(a.abs()),
         ]
     }
+
+    /// Are all four channels within `epsilon` of those in `other`?
+    #[inline]
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .all(|(a, b)| (a - b).abs() <= epsilon)
+    }
+
+    /// Clamp every channel, including alpha, to the range `[0, 1]`.
+    ///
+    /// Useful after computations that can overshoot, e.g. before converting to [`crate::Color32`].
+    /// NaN channels stay NaN, see [`Self::is_finite`].
+    #[inline]
+    pub fn clamp01(self) -> Self {
+        Self(self.0.map(|c| c.clamp(0.0, 1.0)))
+    }
+
+    /// Are all four channels neither NaN nor infinite?
+    #[inline]
+    pub fn is_finite(&self) -> bool {
+        self.0.iter().all(|c| c.is_finite())
+    }
 }
 
 impl std::ops::Add for Rgba {
@@ -275,3 +299,29 @@ impl std::ops::Mul<Rgba> for f32 {
         ])
     }
 }
+
+#[test]
+fn test_rgba_approx_eq() {
+    let a = Rgba::from_rgba_premultiplied(0.1, 0.2, 0.3, 0.4);
+    assert!(a.approx_eq(&a, 0.0));
+
+    let b = Rgba::from_rgba_premultiplied(0.1, 0.2 + 5e-5, 0.3, 0.4);
+    assert!(a.approx_eq(&b, 1e-4));
+    assert!(!a.approx_eq(&b, 1e-8));
+
+    let c = Rgba::from_rgba_premultiplied(0.1, 0.2, 0.3, 0.5);
+    assert!(!a.approx_eq(&c, 1e-4));
+}
+
+#[test]
+fn test_rgba_clamp01() {
+    let c = Rgba::from_rgba_premultiplied(1.5, -0.2, 0.5, 1.5).clamp01();
+    assert_eq!(c.to_array(), [1.0, 0.0, 0.5, 1.0]);
+
+    let a = Rgba::from_rgba_premultiplied(0.1, 0.2, 0.3, -0.2).clamp01();
+    assert_eq!(a.a(), 0.0);
+
+    assert!(c.is_finite());
+    assert!(!Rgba::from_rgba_premultiplied(f32::NAN, 0.0, 0.0, 1.0).is_finite());
+    assert!(!Rgba::from_rgba_premultiplied(0.0, 0.0, 0.0, f32::INFINITY).is_finite());
+}
@@ -31,7 +31,19 @@ pub use winit::{event_loop::EventLoopBuilder, window::WindowAttributes};
 /// done by `EFrame`.
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(any(feature = "glow", feature = "wgpu"))]
-pub type EventLoopBuilderHook = Box<dyn FnOnce(&mut EventLoopBuilder<UserEvent>)>;
+pub type EventLoopBuilderHook = Box<dyn EventLoopBuilderFn>;
+
+/// The closure in an [`EventLoopBuilderHook`].
+///
+/// Implemented for every `'static` closure taking the builder. Being [`std::any::Any`] lets
+/// a reused event loop tell whether a later hook is the one it was built with.
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(any(feature = "glow", feature = "wgpu"))]
+pub trait EventLoopBuilderFn: FnOnce(&mut EventLoopBuilder<UserEvent>) + std::any::Any {}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(any(feature = "glow", feature = "wgpu"))]
+impl<F: FnOnce(&mut EventLoopBuilder<UserEvent>) + std::any::Any> EventLoopBuilderFn for F {}
 
 /// Hook into the building of a the native window.
 ///
@@ -317,6 +329,11 @@ pub struct NativeOptions {
     /// event loop before it is run.
     ///
     /// Note: A [`NativeOptions`] clone will not include any `event_loop_builder` hook.
+    ///
+    /// Note: With [`Self::run_and_return`], the event loop is built once and reused by later
+    /// calls to [`crate::run_native`], so only the first call's hook takes effect.
+    /// A later call may pass the same hook again (the same closure in your code), but setting
+    /// a different one returns [`crate::Error::EventLoopAlreadyConfigured`].
     #[cfg(any(feature = "glow", feature = "wgpu"))]
     pub event_loop_builder: Option<EventLoopBuilderHook>,
 
@@ -355,6 +372,14 @@ pub struct NativeOptions {
     /// from [directories].
     pub persistence_path: Option<std::path::PathBuf>,
 
+    /// Exit gracefully on SIGTERM/SIGINT (Ctrl+C or console close on Windows),
+    /// calling [`App::save`] and flushing the storage before the process ends.
+    ///
+    /// Without this the process is killed by the signal and any unsaved state is lost.
+    ///
+    /// Default: `false`.
+    pub flush_on_signal: bool,
+
     /// Controls whether to apply dithering to minimize banding artifacts.
     ///
     /// Dithering assumes an sRGB output and thus will apply noise to any input value that lies between
@@ -422,6 +447,8 @@ impl Default for NativeOptions {
 
             persistence_path: None,
 
+            flush_on_signal: false,
+
             dithering: true,
         }
     }
@@ -682,6 +709,55 @@ impl Frame {
     pub fn wgpu_render_state(&self) -> Option<&egui_wgpu::RenderState> {
         self.wgpu_render_state.as_ref()
     }
+
+    /// Start or stop recording the events handled by the native event loop.
+    ///
+    /// Only the last [`crate::EVENT_TRACE_CAPACITY`] events are kept.
+    /// Turning tracing off discards the recorded events.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(any(feature = "glow", feature = "wgpu"))]
+    #[allow(clippy::unused_self)]
+    pub fn set_event_tracing(&mut self, enabled: bool) {
+        crate::native::event_trace::set_enabled(enabled);
+    }
+
+    /// Is event tracing turned on? See [`Self::set_event_tracing`].
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(any(feature = "glow", feature = "wgpu"))]
+    #[allow(clippy::unused_self)]
+    pub fn event_tracing(&self) -> bool {
+        crate::native::event_trace::is_enabled()
+    }
+
+    /// The events recorded since [`Self::set_event_tracing`] was turned on, oldest first.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(any(feature = "glow", feature = "wgpu"))]
+    #[allow(clippy::unused_self)]
+    pub fn event_trace(&self) -> Vec<crate::TraceEntry> {
+        crate::native::event_trace::entries()
+    }
+
+    /// Write the recorded events to a text file, one per line.
+    ///
+    /// # Errors
+    /// If the file could not be written.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(any(feature = "glow", feature = "wgpu"))]
+    #[allow(clippy::unused_self)]
+    pub fn write_event_trace(&self, path: &std::path::Path) -> std::io::Result<()> {
+        crate::native::event_trace::write_to_file(path)
+    }
+
+    /// Aggregate [`puffin`] stats over the recently profiled frames.
+    ///
+    /// Frames are collected from the first call to this (or to [`crate::start_puffin_server`]) onward,
+    /// and only while `puffin::set_scopes_on(true)`.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(feature = "profiling")]
+    #[allow(clippy::unused_self)]
+    pub fn profiler_frame_stats(&self) -> crate::ProfilerFrameStats {
+        crate::profiling::frame_stats()
+    }
 }
 
 /// Information about the web environment (if applicable).
@@ -179,6 +179,18 @@ mod native;
 #[cfg(feature = "persistence")]
 pub use native::file_storage::storage_dir;
 
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(any(feature = "glow", feature = "wgpu"))]
+pub use native::event_trace::{EventKind, TraceEntry, TracedResult, EVENT_TRACE_CAPACITY};
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "profiling")]
+mod profiling;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(feature = "profiling")]
+pub use profiling::{start_puffin_server, ProfilerFrameStats};
+
 #[cfg(not(target_arch = "wasm32"))]
 pub mod icon_data;
 
@@ -346,6 +358,11 @@ pub enum Error {
     #[cfg(not(target_arch = "wasm32"))]
     WinitEventLoop(winit::error::EventLoopError),
 
+    /// [`NativeOptions::event_loop_builder`] was set to a different hook on a later call to
+    /// [`run_native`], but the event loop had already been built by an earlier call and can't be rebuilt.
+    #[cfg(all(not(target_arch = "wasm32"), any(feature = "glow", feature = "wgpu")))]
+    EventLoopAlreadyConfigured,
+
     /// An error from [`glutin`] when using [`glow`].
     #[cfg(all(feature = "glow", not(target_arch = "wasm32")))]
     Glutin(glutin::error::Error),
@@ -420,6 +437,14 @@ impl std::fmt::Display for Error {
                 write!(f, "winit EventLoopError: {err}")
             }
 
+            #[cfg(all(not(target_arch = "wasm32"), any(feature = "glow", feature = "wgpu")))]
+            Self::EventLoopAlreadyConfigured => {
+                write!(
+                    f,
+                    "the event loop was already built by an earlier run_native call, so a different event_loop_builder can't be applied"
+                )
+            }
+
             #[cfg(all(feature = "glow", not(target_arch = "wasm32")))]
             Self::Glutin(err) => {
                 write!(f, "glutin error: {err}")
@@ -474,6 +499,23 @@ mod profiling_scopes {
         };
     }
     pub(crate) use profile_scope;
+
+    #[cfg(test)]
+    #[cfg(not(feature = "puffin"))]
+    mod tests {
+        /// Only compiles if the macros expand to nothing, since nothing else is allowed in a `const fn`.
+        const fn profiled() -> u32 {
+            crate::profile_function!();
+            crate::profile_scope!("scope");
+            42
+        }
+
+        #[test]
+        fn macros_are_free_without_puffin() {
+            const VALUE: u32 = profiled();
+            assert_eq!(VALUE, 42);
+        }
+    }
 }
 
 #[allow(unused_imports)]
@@ -0,0 +1,234 @@
+//! Runtime-toggleable tracing of the events handled by the native event loop.
+//!
+//! Turn it on with [`crate::Frame::set_event_tracing`] and read it back with
+//! [`crate::Frame::event_trace`]. This is useful for debugging repaint storms
+//! without having to recompile with `RUST_LOG=trace`.
+
+use std::{collections::VecDeque, time::Instant};
+
+use parking_lot::Mutex;
+use winit::window::WindowId;
+
+use super::winit_integration::EventResult;
+
+/// How many entries are kept before the oldest ones are dropped.
+pub const EVENT_TRACE_CAPACITY: usize = 1024;
+
+static EVENT_TRACE: Mutex<EventTraceBuffer> =
+    parking_lot::const_mutex(EventTraceBuffer::new(EVENT_TRACE_CAPACITY));
+
+/// What kind of event the native event loop handled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    /// The app was resumed.
+    Resumed,
+
+    /// The app was suspended.
+    Suspended,
+
+    /// A repaint request or an accessibility event.
+    UserEvent,
+
+    /// Raw input from a device.
+    DeviceEvent,
+
+    /// A window event, with a short description such as `"RedrawRequested"`.
+    WindowEvent(&'static str),
+}
+
+/// What the event loop decided to do after handling an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TracedResult {
+    /// Nothing to do until the next event.
+    Wait,
+
+    /// The window was repainted right away.
+    RepaintNow,
+
+    /// The window will be repainted on the next redraw.
+    RepaintNext,
+
+    /// The window will be repainted at the given time.
+    RepaintAt(Instant),
+
+    /// The app is exiting.
+    Exit,
+
+    /// Handling the event returned an error.
+    Error,
+}
+
+impl From<&crate::Result<EventResult>> for TracedResult {
+    fn from(result: &crate::Result<EventResult>) -> Self {
+        match result {
+            Ok(EventResult::Wait) => Self::Wait,
+            Ok(EventResult::RepaintNow(_)) => Self::RepaintNow,
+            Ok(EventResult::RepaintNext(_)) => Self::RepaintNext,
+            Ok(EventResult::RepaintAt(_, when)) => Self::RepaintAt(*when),
+            Ok(EventResult::Exit) => Self::Exit,
+            Err(_) => Self::Error,
+        }
+    }
+}
+
+/// One handled event, as recorded by the event trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// When the event was handled.
+    pub time: Instant,
+
+    /// The window the event was for, if any.
+    pub window_id: Option<WindowId>,
+
+    /// What kind of event it was.
+    pub kind: EventKind,
+
+    /// What the event loop decided to do about it.
+    pub result: TracedResult,
+}
+
+/// A bounded ring buffer of [`TraceEntry`].
+///
+/// Memory is reserved once when tracing is enabled, so recording never allocates.
+pub(crate) struct EventTraceBuffer {
+    enabled: bool,
+    capacity: usize,
+    entries: VecDeque<TraceEntry>,
+}
+
+impl EventTraceBuffer {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            enabled: false,
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled && !self.enabled {
+            self.entries.reserve_exact(self.capacity);
+        }
+        if !enabled {
+            self.entries = VecDeque::new();
+        }
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn push(&mut self, entry: TraceEntry) {
+        if !self.enabled || self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Oldest entry first.
+    pub fn to_vec(&self) -> Vec<TraceEntry> {
+        self.entries.iter().copied().collect()
+    }
+}
+
+/// Start or stop recording events. Stopping discards everything recorded so far.
+pub fn set_enabled(enabled: bool) {
+    EVENT_TRACE.lock().set_enabled(enabled);
+}
+
+pub fn is_enabled() -> bool {
+    EVENT_TRACE.lock().is_enabled()
+}
+
+/// Record a handled event. Does nothing (and allocates nothing) when tracing is off.
+pub(crate) fn record(
+    window_id: Option<WindowId>,
+    kind: EventKind,
+    result: &crate::Result<EventResult>,
+) {
+    let mut trace = EVENT_TRACE.lock();
+    if trace.is_enabled() {
+        trace.push(TraceEntry {
+            time: Instant::now(),
+            window_id,
+            kind,
+            result: result.into(),
+        });
+    }
+}
+
+/// All recorded entries, oldest first.
+pub fn entries() -> Vec<TraceEntry> {
+    EVENT_TRACE.lock().to_vec()
+}
+
+/// Write all recorded entries to a file, one per line, with times relative to the first entry.
+pub fn write_to_file(path: &std::path::Path) -> std::io::Result<()> {
+    use std::io::Write as _;
+
+    let entries = entries();
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let start = entries.first().map(|entry| entry.time);
+    for entry in &entries {
+        let elapsed = start.map_or(0.0, |start| (entry.time - start).as_secs_f64());
+        writeln!(
+            file,
+            "{elapsed:.6}s {:?} {:?} {:?}",
+            entry.window_id, entry.kind, entry.result
+        )?;
+    }
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: EventKind) -> TraceEntry {
+        TraceEntry {
+            time: Instant::now(),
+            window_id: None,
+            kind,
+            result: TracedResult::Wait,
+        }
+    }
+
+    #[test]
+    fn disabled_buffer_records_nothing() {
+        let mut buffer = EventTraceBuffer::new(4);
+        buffer.push(entry(EventKind::Resumed));
+        assert!(buffer.to_vec().is_empty());
+        assert_eq!(buffer.entries.capacity(), 0);
+    }
+
+    #[test]
+    fn ring_buffer_drops_oldest() {
+        let mut buffer = EventTraceBuffer::new(2);
+        buffer.set_enabled(true);
+        buffer.push(entry(EventKind::Resumed));
+        buffer.push(entry(EventKind::UserEvent));
+        buffer.push(entry(EventKind::Suspended));
+
+        let kinds: Vec<_> = buffer.to_vec().iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, [EventKind::UserEvent, EventKind::Suspended]);
+    }
+
+    #[test]
+    fn enabling_reserves_capacity_once() {
+        let mut buffer = EventTraceBuffer::new(8);
+        buffer.set_enabled(true);
+        let capacity = buffer.entries.capacity();
+        for _ in 0..100 {
+            buffer.push(entry(EventKind::DeviceEvent));
+        }
+        assert_eq!(buffer.entries.capacity(), capacity);
+        assert_eq!(buffer.to_vec().len(), 8);
+
+        buffer.set_enabled(false);
+        assert!(buffer.to_vec().is_empty());
+    }
+}
@@ -1,7 +1,9 @@
 mod app_icon;
 mod epi_integration;
 mod event_loop_context;
+pub(crate) mod event_trace;
 pub mod run;
+mod signal;
 
 /// File storage which can be used by native backends.
 #[cfg(feature = "persistence")]
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, time::Instant};
+use std::{any::TypeId, cell::RefCell, time::Instant};
 
 use winit::{
     application::ApplicationHandler,
@@ -11,7 +11,11 @@ use ahash::HashMap;
 use super::winit_integration::{UserEvent, WinitApp};
 use crate::{
     epi,
-    native::{event_loop_context, winit_integration::EventResult},
+    native::{
+        event_loop_context,
+        event_trace::{self, EventKind},
+        winit_integration::EventResult,
+    },
     Result,
 };
 
@@ -28,19 +32,50 @@ fn create_event_loop(native_options: &mut epi::NativeOptions) -> Result<EventLoo
     Ok(builder.build()?)
 }
 
-// Manage a thread-local event loop for reusability
+/// Exit gracefully (saving app state) on SIGTERM/SIGINT, if [`epi::NativeOptions::flush_on_signal`] is set.
+///
+/// Keep the returned handler alive for the run; dropping it restores the previous one.
+fn install_signal_handler(
+    native_options: &epi::NativeOptions,
+    event_loop: &EventLoop<UserEvent>,
+) -> Option<super::signal::SignalHandler> {
+    if !native_options.flush_on_signal {
+        return None;
+    }
+    super::signal::install(event_loop.create_proxy()).unwrap_or_else(|err| {
+        log::warn!("Failed to install signal handler: {err}");
+        None
+    })
+}
+
+// Manage a thread-local event loop for reusability.
+//
+// The event loop is built by the first call, and winit does not allow building a second one
+// in the same process. So an `event_loop_builder` hook passed to a later call can't be applied.
+// That's fine if it is the hook the loop was built with, which we tell by its closure type.
+// A different one is reported as [`crate::Error::EventLoopAlreadyConfigured`] instead of ignored.
 fn with_event_loop<R>(
     mut native_options: epi::NativeOptions,
     f: impl FnOnce(&mut EventLoop<UserEvent>, epi::NativeOptions) -> R,
 ) -> Result<R> {
-    thread_local!(static EVENT_LOOP: RefCell<Option<EventLoop<UserEvent>>> = RefCell::new(None));
+    thread_local!(static EVENT_LOOP: RefCell<Option<(EventLoop<UserEvent>, Option<TypeId>)>> = RefCell::new(None));
 
     EVENT_LOOP.with(|event_loop| {
         let mut event_loop_lock = event_loop.borrow_mut();
-        let event_loop = if let Some(event_loop) = &mut *event_loop_lock {
+        let hook = native_options
+            .event_loop_builder
+            .as_deref()
+            .map(|hook| hook.type_id());
+        let event_loop = if let Some((event_loop, built_with)) = &mut *event_loop_lock {
+            if hook.is_some() && hook != *built_with {
+                return Err(crate::Error::EventLoopAlreadyConfigured);
+            }
+            native_options.event_loop_builder = None;
             event_loop
         } else {
-            event_loop_lock.insert(create_event_loop(&mut native_options)?)
+            &mut event_loop_lock
+                .insert((create_event_loop(&mut native_options)?, hook))
+                .0
         };
         Ok(f(event_loop, native_options))
     })
@@ -68,11 +103,14 @@ impl<T: WinitApp> WinitAppWrapper<T> {
     fn handle_event_result(
         &mut self,
         event_loop: &ActiveEventLoop,
+        window_id: Option<WindowId>,
+        event_kind: EventKind,
         event_result: Result<EventResult>,
     ) {
         let mut exit = false;
 
         log::trace!("event_result: {event_result:?}");
+        event_trace::record(window_id, event_kind, &event_result);
 
         let combined_result = event_result.and_then(|event_result| {
             match event_result {
@@ -142,33 +180,29 @@ impl<T: WinitApp> WinitAppWrapper<T> {
 
     // Manage redraw requests for windows
     fn check_redraw_requests(&mut self, event_loop: &ActiveEventLoop) {
-        let mut next_repaint_time = self.windows_next_repaint_times.values().min().copied();
-
-        self.windows_next_repaint_times
-            .retain(|window_id, repaint_time| {
-                if Instant::now() < *repaint_time {
-                    return true; // Not yet time to repaint
-                };
-
-                next_repaint_time = None;
-                event_loop.set_control_flow(ControlFlow::Poll);
-
-                if let Some(window) = self.winit_app.window(*window_id) {
-                    log::trace!("Requesting redraw for {window_id:?}");
-                    let is_minimized = window.is_minimized().unwrap_or(false);
-                    if is_minimized {
-                        false
-                    } else {
-                        window.request_redraw();
-                        true
-                    }
+        let deadlines = self
+            .windows_next_repaint_times
+            .iter()
+            .map(|(window_id, repaint_time)| (*window_id, *repaint_time))
+            .collect();
+        let schedule = schedule_repaints(deadlines, Instant::now());
+
+        for window_id in &schedule.due {
+            if let Some(window) = self.winit_app.window(*window_id) {
+                log::trace!("Requesting redraw for {window_id:?}");
+                let is_minimized = window.is_minimized().unwrap_or(false);
+                if is_minimized {
+                    self.windows_next_repaint_times.remove(window_id);
                 } else {
-                    log::trace!("Window not found for {window_id:?}");
-                    false
+                    window.request_redraw();
                 }
-            });
+            } else {
+                log::trace!("Window not found for {window_id:?}");
+                self.windows_next_repaint_times.remove(window_id);
+            }
+        }
 
-        if let Some(next_repaint_time) = next_repaint_time {
+        if let Some(next_repaint_time) = schedule.next_wakeup {
             // iOS-specific handling
             #[cfg(target_os = "ios")]
             winit_app
@@ -180,7 +214,37 @@ impl<T: WinitApp> WinitAppWrapper<T> {
                 });
 
             event_loop.set_control_flow(ControlFlow::WaitUntil(next_repaint_time));
-        };
+        } else if !schedule.due.is_empty() {
+            event_loop.set_control_flow(ControlFlow::Poll);
+        }
+    }
+}
+
+/// Which windows to repaint right away, and when to wake up for the rest.
+#[derive(Debug, PartialEq, Eq)]
+struct RepaintSchedule<Id> {
+    /// Windows whose deadline has passed, most overdue first.
+    due: Vec<Id>,
+
+    /// The earliest deadline that has not passed yet.
+    next_wakeup: Option<Instant>,
+}
+
+/// Splits the repaint deadlines of all windows into the ones that are due at `now` and the next wake-up time.
+///
+/// Every window is judged on its own deadline, so a window that repaints continuously
+/// cannot push back the deadlines of the others.
+/// Windows with identical deadlines keep their relative order.
+fn schedule_repaints<Id>(mut deadlines: Vec<(Id, Instant)>, now: Instant) -> RepaintSchedule<Id> {
+    deadlines.sort_by_key(|(_, deadline)| *deadline);
+
+    let first_pending = deadlines.partition_point(|(_, deadline)| *deadline <= now);
+    let next_wakeup = deadlines.get(first_pending).map(|(_, deadline)| *deadline);
+    deadlines.truncate(first_pending);
+
+    RepaintSchedule {
+        due: deadlines.into_iter().map(|(id, _)| id).collect(),
+        next_wakeup,
     }
 }
 
@@ -199,18 +263,20 @@ impl<T: WinitApp> ApplicationHandler<UserEvent> for WinitAppWrapper<T> {
         // Ensure context is dropped after function returns
         event_loop_context::with_event_loop_context(event_loop, move || {
             let event_result = self.winit_app.resumed(event_loop);
-            self.handle_event_result(event_loop, event_result);
+            self.handle_event_result(event_loop, None, EventKind::Resumed, event_result);
         });
     }
 
     fn user_event(&mut self, event_loop: &ActiveEventLoop, event: UserEvent) {
         crate::profile_function!(match &event {
             UserEvent::RequestRepaint { .. } => "UserEvent::RequestRepaint",
+            UserEvent::Shutdown => "UserEvent::Shutdown",
             #[cfg(feature = "accesskit")]
             UserEvent::AccessKitActionRequest(_) => "UserEvent::AccessKitActionRequest",
         });
 
         event_loop_context::with_event_loop_context(event_loop, move || {
+            let mut window_id = None;
             let event_result = match event {
                 UserEvent::RequestRepaint {
                     when,
@@ -220,9 +286,8 @@ impl<T: WinitApp> ApplicationHandler<UserEvent> for WinitAppWrapper<T> {
                     let current_frame_nr = self.winit_app.frame_nr(viewport_id);
                     if current_frame_nr == frame_nr || current_frame_nr == frame_nr + 1 {
                         log::trace!("UserEvent::RequestRepaint scheduling repaint at {when:?}");
-                        if let Some(window_id) =
-                            self.winit_app.window_id_from_viewport_id(viewport_id)
-                        {
+                        window_id = self.winit_app.window_id_from_viewport_id(viewport_id);
+                        if let Some(window_id) = window_id {
                             Ok(EventResult::RepaintAt(window_id, when))
                         } else {
                             Ok(EventResult::Wait)
@@ -232,12 +297,16 @@ impl<T: WinitApp> ApplicationHandler<UserEvent> for WinitAppWrapper<T> {
                         Ok(EventResult::Wait) // Outdated request - repaint already occurred
                     }
                 }
+                UserEvent::Shutdown => {
+                    log::debug!("UserEvent::Shutdown - exiting");
+                    Ok(EventResult::Exit)
+                }
                 #[cfg(feature = "accesskit")]
                 UserEvent::AccessKitActionRequest(request) => {
                     self.winit_app.on_accesskit_event(request)
                 }
             };
-            self.handle_event_result(event_loop, event_result);
+            self.handle_event_result(event_loop, window_id, EventKind::UserEvent, event_result);
         });
     }
 
@@ -247,7 +316,8 @@ impl<T: WinitApp> ApplicationHandler<UserEvent> for WinitAppWrapper<T> {
         window_id: WindowId,
         event: winit::event::WindowEvent,
     ) {
-        crate::profile_function!(egui_winit::short_window_event_description(&event));
+        let event_description = egui_winit::short_window_event_description(&event);
+        crate::profile_function!(event_description);
 
         // Ensure context is dropped after function returns
         event_loop_context::with_event_loop_context(event_loop, move || {
@@ -259,7 +329,12 @@ impl<T: WinitApp> ApplicationHandler<UserEvent> for WinitAppWrapper<T> {
                 _ => self.winit_app.window_event(event_loop, window_id, event),
             };
 
-            self.handle_event_result(event_loop, event_result);
+            self.handle_event_result(
+                event_loop,
+                Some(window_id),
+                EventKind::WindowEvent(event_description),
+                event_result,
+            );
         });
     }
 
@@ -274,7 +349,7 @@ impl<T: WinitApp> ApplicationHandler<UserEvent> for WinitAppWrapper<T> {
         // Ensure context is dropped after function returns
         event_loop_context::with_event_loop_context(event_loop, move || {
             let event_result = self.winit_app.device_event(event_loop, device_id, event);
-            self.handle_event_result(event_loop, event_result);
+            self.handle_event_result(event_loop, None, EventKind::DeviceEvent, event_result);
         });
     }
 
@@ -283,7 +358,7 @@ impl<T: WinitApp> ApplicationHandler<UserEvent> for WinitAppWrapper<T> {
 
         event_loop_context::with_event_loop_context(event_loop, move || {
             let event_result = self.winit_app.suspended(event_loop);
-            self.handle_event_result(event_loop, event_result);
+            self.handle_event_result(event_loop, None, EventKind::Suspended, event_result);
         });
     }
 
@@ -332,12 +407,14 @@ pub fn run_glow(
     #[cfg(not(target_os = "ios"))]
     if native_options.run_and_return {
         return with_event_loop(native_options, |event_loop, native_options| {
+            let _signal_handler = install_signal_handler(&native_options, event_loop);
             let glow_eframe = GlowWinitApp::new(event_loop, app_name, native_options, app_creator);
             run_and_return(event_loop, glow_eframe)
         })?;
     }
 
     let event_loop = create_event_loop(&mut native_options)?;
+    let _signal_handler = install_signal_handler(&native_options, &event_loop);
     let glow_eframe = GlowWinitApp::new(&event_loop, app_name, native_options, app_creator);
     run_and_exit(event_loop, glow_eframe)
 }
@@ -356,12 +433,140 @@ pub fn run_wgpu(
     #[cfg(not(target_os = "ios"))]
     if native_options.run_and_return {
         return with_event_loop(native_options, |event_loop, native_options| {
+            let _signal_handler = install_signal_handler(&native_options, event_loop);
             let wgpu_eframe = WgpuWinitApp::new(event_loop, app_name, native_options, app_creator);
             run_and_return(event_loop, wgpu_eframe)
         })?;
     }
 
     let event_loop = create_event_loop(&mut native_options)?;
+    let _signal_handler = install_signal_handler(&native_options, &event_loop);
     let wgpu_eframe = WgpuWinitApp::new(&event_loop, app_name, native_options, app_creator);
     run_and_exit(event_loop, wgpu_eframe)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[cfg(all(target_os = "linux", feature = "x11"))]
+    #[test]
+    fn reused_event_loop_rejects_a_different_builder_hook() {
+        use winit::platform::x11::EventLoopBuilderExtX11 as _;
+
+        // Tests don't run on the main thread.
+        let any_thread = || -> Option<epi::EventLoopBuilderHook> {
+            Some(Box::new(|builder| {
+                builder.with_any_thread(true);
+            }))
+        };
+        let any_thread_on_x11 = || -> Option<epi::EventLoopBuilderHook> {
+            Some(Box::new(|builder| {
+                builder.with_any_thread(true).with_x11();
+            }))
+        };
+
+        let first = with_event_loop(
+            epi::NativeOptions {
+                event_loop_builder: any_thread(),
+                ..Default::default()
+            },
+            |_, _| (),
+        );
+        if first.is_err() {
+            eprintln!("Could not create an event loop (no display?) - skipping");
+            return;
+        }
+
+        // The hook the loop was built with is fine.
+        let same = with_event_loop(
+            epi::NativeOptions {
+                event_loop_builder: any_thread(),
+                ..Default::default()
+            },
+            |_, _| (),
+        );
+        assert!(same.is_ok());
+
+        let different = with_event_loop(
+            epi::NativeOptions {
+                event_loop_builder: any_thread_on_x11(),
+                ..Default::default()
+            },
+            |_, _| (),
+        );
+        assert!(matches!(
+            different,
+            Err(crate::Error::EventLoopAlreadyConfigured)
+        ));
+
+        // Without a hook the cached loop is reused as before.
+        assert!(with_event_loop(epi::NativeOptions::default(), |_, _| ()).is_ok());
+    }
+
+    #[test]
+    fn due_windows_are_sorted_by_deadline() {
+        let start = Instant::now();
+        let now = start + Duration::from_millis(50);
+        let schedule = schedule_repaints(
+            vec![
+                (1, start + Duration::from_millis(45)),
+                (2, start),
+                (3, now + Duration::from_millis(30)),
+                (4, now),
+            ],
+            now,
+        );
+        assert_eq!(schedule.due, [2, 1, 4]);
+        assert_eq!(schedule.next_wakeup, Some(now + Duration::from_millis(30)));
+    }
+
+    #[test]
+    fn hot_window_does_not_starve_others() {
+        let start = Instant::now();
+        let frame = Duration::from_millis(16);
+
+        // Window 1 wants to repaint every frame, window 2 once after 100 ms.
+        let mut hot_deadline = start;
+        let cold_deadline = start + Duration::from_millis(100);
+        let mut cold_repainted_at = None;
+
+        let mut now = start;
+        while now < start + Duration::from_millis(200) {
+            let schedule = schedule_repaints(vec![(1, hot_deadline), (2, cold_deadline)], now);
+            if schedule.due.contains(&2) && cold_repainted_at.is_none() {
+                cold_repainted_at = Some(now);
+            }
+            if schedule.due.contains(&1) {
+                hot_deadline = now + frame;
+            }
+            now += frame;
+        }
+
+        let cold_repainted_at = cold_repainted_at.expect("cold window was never repainted");
+        assert!(cold_repainted_at - cold_deadline < frame);
+    }
+
+    #[test]
+    fn identical_deadlines_are_both_due() {
+        let deadline = Instant::now();
+        let now = deadline + Duration::from_millis(1);
+        let schedule = schedule_repaints(vec![(7, deadline), (3, deadline)], now);
+        assert_eq!(schedule.due, [7, 3]);
+        assert_eq!(schedule.next_wakeup, None);
+
+        let deadline = now + Duration::from_millis(1);
+        let schedule = schedule_repaints(vec![(7, deadline), (3, deadline)], now);
+        assert!(schedule.due.is_empty());
+        assert_eq!(schedule.next_wakeup, Some(deadline));
+    }
+
+    #[test]
+    fn nothing_scheduled() {
+        let schedule = schedule_repaints(Vec::<(u32, Instant)>::new(), Instant::now());
+        assert!(schedule.due.is_empty());
+        assert_eq!(schedule.next_wakeup, None);
+    }
+}
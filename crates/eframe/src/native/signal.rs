@@ -0,0 +1,300 @@
+//! Turn SIGTERM/SIGINT (or a console Ctrl+C/close on Windows) into a graceful exit.
+//!
+//! The signal handler itself only writes to a pipe (Unix) or sets an atomic (Windows).
+//! A watcher thread picks that up and sends [`UserEvent::Shutdown`] to the event loop,
+//! which then exits through the normal [`super::winit_integration::EventResult::Exit`] path,
+//! saving the app state on the way out.
+//!
+//! The handler is only in place while the returned [`SignalHandler`] is alive, i.e. for one run.
+//! Dropping it restores whatever handled these signals before.
+//!
+//! Enabled with [`crate::NativeOptions::flush_on_signal`].
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use winit::event_loop::EventLoopProxy;
+
+use super::winit_integration::UserEvent;
+
+/// The handler is process-global, so only one can be installed at a time.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Keeps the signal handler installed. Dropping it restores the previous one.
+pub struct SignalHandler {
+    handler: Option<imp::Handler>,
+}
+
+impl Drop for SignalHandler {
+    fn drop(&mut self) {
+        drop(self.handler.take());
+        INSTALLED.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Install the signal handler, forwarding signals to the event loop behind `proxy`
+/// until the returned [`SignalHandler`] is dropped.
+///
+/// Returns `None` if one is already installed (e.g. by a run on another thread).
+pub fn install(proxy: EventLoopProxy<UserEvent>) -> std::io::Result<Option<SignalHandler>> {
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return Ok(None);
+    }
+
+    match imp::install(proxy) {
+        Ok(handler) => Ok(Some(SignalHandler {
+            handler: Some(handler),
+        })),
+        Err(err) => {
+            INSTALLED.store(false, Ordering::SeqCst);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(unix)]
+#[allow(unsafe_code)]
+mod imp {
+    use std::{
+        io::Read as _,
+        os::fd::FromRawFd as _,
+        sync::atomic::{AtomicI32, Ordering},
+        thread::JoinHandle,
+    };
+
+    use winit::event_loop::EventLoopProxy;
+
+    use super::UserEvent;
+
+    /// Write end of the self-pipe, or -1.
+    static PIPE_WRITE_FD: AtomicI32 = AtomicI32::new(-1);
+
+    extern "C" fn on_signal(_signal: libc::c_int) {
+        let fd = PIPE_WRITE_FD.load(Ordering::Relaxed);
+        if 0 <= fd {
+            let byte = 1_u8;
+            // SAFETY: `write` is async-signal-safe, and `byte` outlives the call.
+            unsafe {
+                libc::write(fd, std::ptr::addr_of!(byte).cast(), 1);
+            }
+        }
+    }
+
+    /// Undoes everything [`install`] did, also when it only got halfway.
+    pub struct Handler {
+        write_fd: libc::c_int,
+        watcher: Option<JoinHandle<()>>,
+
+        /// The actions we replaced, to put back.
+        previous: Vec<(libc::c_int, libc::sigaction)>,
+    }
+
+    impl Drop for Handler {
+        fn drop(&mut self) {
+            for (signal, previous) in self.previous.drain(..).rev() {
+                // SAFETY: `previous` is the action `sigaction` reported for `signal`.
+                unsafe {
+                    libc::sigaction(signal, &previous, std::ptr::null_mut());
+                }
+            }
+
+            PIPE_WRITE_FD.store(-1, Ordering::SeqCst);
+            // SAFETY: we created `write_fd` and nothing else closes it.
+            // The watcher then reads end-of-file and stops.
+            unsafe {
+                libc::close(self.write_fd);
+            }
+            if let Some(watcher) = self.watcher.take() {
+                watcher.join().ok();
+            }
+        }
+    }
+
+    pub fn install(proxy: EventLoopProxy<UserEvent>) -> std::io::Result<Handler> {
+        let mut fds = [0; 2];
+        // SAFETY: `fds` has room for the two file descriptors `pipe` writes.
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let [read_fd, write_fd] = fds;
+
+        // SAFETY: we just created `read_fd` and nothing else owns it.
+        let mut read_end = unsafe { std::fs::File::from_raw_fd(read_fd) };
+
+        let mut handler = Handler {
+            write_fd,
+            watcher: None,
+            previous: Vec::new(),
+        };
+
+        handler.watcher = Some(
+            std::thread::Builder::new()
+                .name("eframe_signal_watcher".to_owned())
+                .spawn(move || {
+                    // Every signal during the run asks for an exit, until the write end is closed.
+                    let mut byte = [0_u8; 1];
+                    loop {
+                        match read_end.read(&mut byte) {
+                            Ok(0) => break,
+                            Ok(_) => {
+                                log::debug!("Received termination signal - requesting exit");
+                                proxy.send_event(UserEvent::Shutdown).ok();
+                            }
+                            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                            Err(err) => {
+                                log::warn!("Failed to wait for signals: {err}");
+                                break;
+                            }
+                        }
+                    }
+                })?,
+        );
+
+        PIPE_WRITE_FD.store(write_fd, Ordering::SeqCst);
+
+        // SAFETY: all zeroes is a valid `sigaction`, and we fill in what matters below.
+        let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+        #[allow(clippy::fn_to_numeric_cast_any)] // `sigaction` takes the handler as an address.
+        {
+            action.sa_sigaction = on_signal as *const () as libc::sighandler_t;
+        }
+        action.sa_flags = libc::SA_RESTART;
+        // SAFETY: `sa_mask` is a valid `sigset_t` to write to.
+        unsafe {
+            libc::sigemptyset(&mut action.sa_mask);
+        }
+
+        for signal in [libc::SIGTERM, libc::SIGINT] {
+            // SAFETY: as above.
+            let mut previous: libc::sigaction = unsafe { std::mem::zeroed() };
+            // SAFETY: `on_signal` only does async-signal-safe work.
+            if unsafe { libc::sigaction(signal, &action, &mut previous) } != 0 {
+                // Dropping `handler` undoes what we did so far.
+                return Err(std::io::Error::last_os_error());
+            }
+            handler.previous.push((signal, previous));
+        }
+
+        Ok(handler)
+    }
+}
+
+#[cfg(windows)]
+#[allow(unsafe_code)]
+mod imp {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Condvar, Mutex, MutexGuard, PoisonError,
+        },
+        thread::JoinHandle,
+        time::{Duration, Instant},
+    };
+
+    use winapi::shared::minwindef::{BOOL, DWORD, FALSE, TRUE};
+    use winit::event_loop::EventLoopProxy;
+
+    use super::UserEvent;
+
+    /// How long a console control event waits for the app state to be saved.
+    ///
+    /// Windows ends the process 5 seconds after `CTRL_CLOSE_EVENT` regardless.
+    const SAVE_TIMEOUT: Duration = Duration::from_millis(4500);
+
+    static SIGNALLED: AtomicBool = AtomicBool::new(false);
+
+    /// Set when the run is over, with the app state saved.
+    static RUN_ENDED: Mutex<bool> = Mutex::new(false);
+    static RUN_ENDED_CHANGED: Condvar = Condvar::new();
+
+    fn run_ended() -> MutexGuard<'static, bool> {
+        RUN_ENDED.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    unsafe extern "system" fn on_console_ctrl(_ctrl_type: DWORD) -> BOOL {
+        SIGNALLED.store(true, Ordering::SeqCst);
+
+        // Windows calls this on a thread of its own, and ends the process as soon as we return
+        // from a close, logoff or shutdown event. So wait here until the state is saved.
+        let deadline = Instant::now() + SAVE_TIMEOUT;
+        let mut ended = run_ended();
+        while !*ended {
+            let Some(left) = deadline.checked_duration_since(Instant::now()) else {
+                log::warn!("Timed out waiting for the app state to be saved");
+                break;
+            };
+            ended = RUN_ENDED_CHANGED
+                .wait_timeout(ended, left)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        TRUE
+    }
+
+    /// Ends the run: unblocks [`on_console_ctrl`], removes it and stops the watcher.
+    pub struct Handler {
+        watcher: Option<JoinHandle<()>>,
+    }
+
+    impl Drop for Handler {
+        fn drop(&mut self) {
+            *run_ended() = true;
+            RUN_ENDED_CHANGED.notify_all();
+
+            // SAFETY: removes the handler we added in `install`.
+            unsafe {
+                winapi::um::consoleapi::SetConsoleCtrlHandler(Some(on_console_ctrl), FALSE);
+            }
+            if let Some(watcher) = self.watcher.take() {
+                watcher.join().ok();
+            }
+        }
+    }
+
+    pub fn install(proxy: EventLoopProxy<UserEvent>) -> std::io::Result<Handler> {
+        SIGNALLED.store(false, Ordering::SeqCst);
+        *run_ended() = false;
+
+        // SAFETY: `on_console_ctrl` has the signature Windows expects, and only waits on a condvar.
+        if unsafe { winapi::um::consoleapi::SetConsoleCtrlHandler(Some(on_console_ctrl), TRUE) }
+            == 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        let mut handler = Handler { watcher: None };
+        handler.watcher = Some(
+            std::thread::Builder::new()
+                .name("eframe_signal_watcher".to_owned())
+                .spawn(move || {
+                    let mut ended = run_ended();
+                    while !*ended {
+                        if SIGNALLED.swap(false, Ordering::SeqCst) {
+                            log::debug!("Received console control event - requesting exit");
+                            proxy.send_event(UserEvent::Shutdown).ok();
+                        }
+                        ended = RUN_ENDED_CHANGED
+                            .wait_timeout(ended, Duration::from_millis(50))
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .0;
+                    }
+                })?,
+        );
+
+        Ok(handler)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    use winit::event_loop::EventLoopProxy;
+
+    use super::UserEvent;
+
+    pub struct Handler;
+
+    #[allow(clippy::unnecessary_wraps, clippy::needless_pass_by_value)]
+    pub fn install(_proxy: EventLoopProxy<UserEvent>) -> std::io::Result<Handler> {
+        log::debug!("flush_on_signal is not supported on this platform");
+        Ok(Handler)
+    }
+}
@@ -46,6 +46,11 @@ pub enum UserEvent {
         frame_nr: u64,
     },
 
+    /// The process received SIGTERM/SIGINT, and should save its state and exit.
+    ///
+    /// Only sent when [`crate::NativeOptions::flush_on_signal`] is set.
+    Shutdown,
+
     /// A request related to [`accesskit`](https://accesskit.dev/).
     #[cfg(feature = "accesskit")]
     AccessKitActionRequest(accesskit_winit::Event),
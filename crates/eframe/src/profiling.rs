@@ -0,0 +1,101 @@
+//! A ready-made [`puffin`] sink, enabled by the `profiling` feature.
+//!
+//! The `profile_function!`/`profile_scope!` calls throughout eframe (and egui) already
+//! report to [`puffin`] when it is enabled. This module lets an app look at that data
+//! without wiring up puffin itself, either remotely with `puffin_viewer`
+//! (see [`start_puffin_server`]) or in-process (see [`crate::Frame::profiler_frame_stats`]).
+
+use std::{sync::OnceLock, time::Duration};
+
+/// Start a [`puffin_http`] server on `127.0.0.1:port` and turn on scope collection.
+///
+/// Connect to it with `cargo install puffin_viewer && puffin_viewer --url 127.0.0.1:<port>`.
+///
+/// The server stops when the returned value is dropped.
+pub fn start_puffin_server(port: u16) -> std::io::Result<puffin_http::Server> {
+    puffin::set_scopes_on(true);
+    frame_view(); // So that `profiler_frame_stats` covers the frames from now on.
+
+    puffin_http::Server::new(&format!("127.0.0.1:{port}"))
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err.to_string()))
+}
+
+/// Aggregate statistics over the recently profiled frames.
+///
+/// See [`crate::Frame::profiler_frame_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProfilerFrameStats {
+    /// Number of frames currently kept by the profiler.
+    pub frames: usize,
+
+    /// Memory used by the kept frames.
+    pub bytes_of_ram_used: usize,
+
+    /// Average duration of the recent frames.
+    pub mean_frame_time: Option<Duration>,
+
+    /// Duration of the slowest recent frame.
+    pub max_frame_time: Option<Duration>,
+}
+
+/// Collects every frame reported to the global profiler, from the first call onward.
+fn frame_view() -> &'static puffin::GlobalFrameView {
+    static FRAME_VIEW: OnceLock<puffin::GlobalFrameView> = OnceLock::new();
+    FRAME_VIEW.get_or_init(Default::default)
+}
+
+pub(crate) fn frame_stats() -> ProfilerFrameStats {
+    let view = frame_view().lock();
+    let stats = view.stats();
+
+    let durations: Vec<Duration> = view
+        .recent_frames()
+        .map(|frame| {
+            let (start, end) = frame.meta().range_ns;
+            Duration::from_nanos((end - start).max(0) as u64)
+        })
+        .collect();
+
+    ProfilerFrameStats {
+        frames: stats.frames(),
+        bytes_of_ram_used: stats.bytes_of_ram_used(),
+        mean_frame_time: (!durations.is_empty())
+            .then(|| durations.iter().sum::<Duration>() / durations.len() as u32),
+        max_frame_time: durations.iter().max().copied(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn scopes_nest() {
+        fn outer() {
+            crate::profile_function!();
+            inner();
+        }
+
+        fn inner() {
+            crate::profile_scope!("inner");
+        }
+
+        puffin::set_scopes_on(true);
+        let view = puffin::GlobalFrameView::default();
+
+        outer();
+        puffin::GlobalProfiler::lock().new_frame();
+
+        let frame = view.lock().latest_frame().expect("no frame was reported");
+        let Ok(frame) = frame.unpacked() else {
+            panic!("failed to unpack frame");
+        };
+        let thread_name = std::thread::current().name().unwrap_or_default().to_owned();
+        let (_, stream) = frame
+            .thread_streams
+            .iter()
+            .find(|(info, _)| info.name == thread_name)
+            .expect("no scopes from this thread");
+
+        assert_eq!(stream.num_scopes, 2);
+        assert_eq!(stream.depth, 2);
+    }
+}
@@ -0,0 +1,111 @@
+//! Checks that [`eframe::NativeOptions::flush_on_signal`] saves the app state on SIGTERM.
+//!
+//! The test re-runs its own executable as a helper process running a trivial app,
+//! waits for it to show a frame, sends it SIGTERM and then looks at the storage file.
+
+#![cfg(all(
+    target_os = "linux",
+    feature = "persistence",
+    any(feature = "glow", feature = "wgpu"),
+    any(feature = "x11", feature = "wayland")
+))]
+#![allow(unsafe_code)]
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+const HELPER_ENV: &str = "EFRAME_FLUSH_ON_SIGNAL_HELPER";
+const SAVED_KEY: &str = "flush_on_signal";
+
+struct HelperApp {
+    ready_path: PathBuf,
+}
+
+impl eframe::App for HelperApp {
+    fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
+        if !self.ready_path.exists() {
+            std::fs::write(&self.ready_path, "ready").unwrap();
+        }
+        ctx.request_repaint_after(Duration::from_millis(50));
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        storage.set_string(SAVED_KEY, "saved".to_owned());
+    }
+}
+
+fn run_helper(dir: &Path) {
+    let options = eframe::NativeOptions {
+        flush_on_signal: true,
+        persistence_path: Some(dir.join("app.ron")),
+        // The helper runs on a libtest thread, not the main one.
+        event_loop_builder: Some(Box::new(|builder| {
+            #[cfg(feature = "x11")]
+            winit::platform::x11::EventLoopBuilderExtX11::with_any_thread(builder, true);
+            #[cfg(feature = "wayland")]
+            winit::platform::wayland::EventLoopBuilderExtWayland::with_any_thread(builder, true);
+        })),
+        ..Default::default()
+    };
+    let ready_path = dir.join("ready");
+    eframe::run_native(
+        "flush_on_signal",
+        options,
+        Box::new(|_cc| Ok(Box::new(HelperApp { ready_path }))),
+    )
+    .unwrap();
+}
+
+fn wait_for(path: &Path, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if path.exists() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+#[test]
+fn sigterm_flushes_storage() {
+    if let Some(dir) = std::env::var_os(HELPER_ENV) {
+        run_helper(Path::new(&dir));
+        return;
+    }
+
+    if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        eprintln!("No display available - skipping");
+        return;
+    }
+
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join(format!("eframe_flush_on_signal_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["sigterm_flushes_storage", "--exact", "--nocapture"])
+        .env(HELPER_ENV, &dir)
+        .spawn()
+        .unwrap();
+
+    if !wait_for(&dir.join("ready"), Duration::from_secs(30)) {
+        child.kill().ok();
+        panic!("Helper app never showed a frame");
+    }
+
+    let pid = child.id() as libc::pid_t;
+    // SAFETY: `kill` has no memory safety requirements.
+    assert_eq!(unsafe { libc::kill(pid, libc::SIGTERM) }, 0);
+
+    let status = child.wait().unwrap();
+    assert!(status.success(), "Helper exited with {status}");
+
+    let ron = std::fs::read_to_string(dir.join("app.ron")).unwrap();
+    assert!(ron.contains(SAVED_KEY), "Storage was not flushed: {ron}");
+
+    std::fs::remove_dir_all(&dir).ok();
+}
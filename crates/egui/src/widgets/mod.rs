@@ -147,3 +147,59 @@ pub fn global_dark_light_mode_buttons(ui: &mut Ui) {
     visuals.light_dark_radio_buttons(ui);
     ui.ctx().set_visuals(visuals);
 }
+
+/// The visuals of dark or light mode, to start an app in the mode it was last left in.
+pub fn theme_visuals(is_dark: bool) -> Visuals {
+    if is_dark {
+        Visuals::dark()
+    } else {
+        Visuals::light()
+    }
+}
+
+/// Switch between dark and light mode (globally), flipping `is_dark` to match.
+///
+/// Keep `is_dark` in the app's storage, and set [`theme_visuals`] of it on startup.
+pub fn theme_toggle(ctx: &Context, is_dark: &mut bool) {
+    *is_dark = !*is_dark;
+    ctx.set_visuals(theme_visuals(*is_dark));
+}
+
+/// Show a button that calls [`theme_toggle`] when clicked.
+pub fn theme_toggle_button(ui: &mut Ui, is_dark: &mut bool) -> Response {
+    let (icon, hover) = if *is_dark {
+        ("☀", "Switch to light mode")
+    } else {
+        ("🌙", "Switch to dark mode")
+    };
+    let response = ui.button(icon).on_hover_text(hover);
+    if response.clicked() {
+        theme_toggle(ui.ctx(), is_dark);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_toggle_flips_the_mode() {
+        let ctx = Context::default();
+        let mut is_dark = false;
+
+        theme_toggle(&ctx, &mut is_dark);
+        assert!(is_dark);
+        assert!(ctx.style().visuals.dark_mode);
+
+        theme_toggle(&ctx, &mut is_dark);
+        assert!(!is_dark);
+        assert!(!ctx.style().visuals.dark_mode);
+    }
+
+    #[test]
+    fn theme_visuals_match_the_mode() {
+        assert!(theme_visuals(true).dark_mode);
+        assert!(!theme_visuals(false).dark_mode);
+    }
+}